@@ -0,0 +1,342 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Searching inside `.zip`, `.tar`, `.tar.gz` and `.tgz` archives
+//! without extracting them. Each file in an archive is named
+//! `archive.zip!inner/path`. Archives are streamed rather than read
+//! into memory: a tar is read through once as its files are searched
+//! in order, and a zip's files are found through its directory. Only
+//! the file being searched is held in memory, and only if it's within
+//! the limit given.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use tar;
+use zip::ZipArchive;
+
+/// Separates an archive from the path of a file inside it.
+pub const SEPARATOR: char = '!';
+
+/// The size of a tar block, which entries are padded to.
+const BLOCK: u64 = 512;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn kind(path: &str) -> Option<Kind> {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".zip") {
+        Some(Kind::Zip)
+    } else if lower.ends_with(".tar") {
+        Some(Kind::Tar)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(Kind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// Is `path` an archive whose files can be searched?
+pub fn is_archive(path: &str) -> bool {
+    kind(path).is_some() && Path::new(path).is_file()
+}
+
+/// The archive and inner path of a name made by `members`, or `None`
+/// for the name of a plain file.
+pub fn split(name: &str) -> Option<(&str, &str)> {
+    if Path::new(name).exists() {
+        return None;
+    }
+    name.match_indices(SEPARATOR)
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .find(|&(archive, _)| is_archive(archive))
+}
+
+/// An archive opened for reading its files.
+enum Opened {
+    Zip(ZipArchive<File>),
+    /// The tar stream, decompressed, at the header of the entry
+    /// after the last one read.
+    Tar(Box<dyn Read>),
+}
+
+thread_local! {
+    /// The archive last read from, kept open for its next file.
+    static OPEN: RefCell<Option<(String, Opened)>> = const { RefCell::new(None) };
+}
+
+fn open(path: &str) -> io::Result<Opened> {
+    let file = File::open(path)?;
+    Ok(match kind(path) {
+        Some(Kind::Zip) => Opened::Zip(ZipArchive::new(file)?),
+        Some(Kind::TarGz) => Opened::Tar(Box::new(BufReader::new(GzDecoder::new(file)))),
+        _ => Opened::Tar(Box::new(BufReader::new(file))),
+    })
+}
+
+/// Every file in the archive at `path`, named `path!inner/path`.
+pub fn members(path: &str) -> io::Result<Vec<String>> {
+    let mut names = vec![];
+    match open(path)? {
+        Opened::Zip(mut zip) => {
+            for i in 0..zip.len() {
+                let file = zip.by_index_raw(i)?;
+                if file.is_file() {
+                    names.push(file.name().to_owned());
+                }
+            }
+        }
+        Opened::Tar(reader) => {
+            for entry in tar::Archive::new(reader).entries()? {
+                let entry = entry?;
+                if entry.header().entry_type().is_file() {
+                    names.push(entry.path()?.display().to_string());
+                }
+            }
+        }
+    }
+    Ok(names.into_iter().map(|n| format!("{}{}{}", path, SEPARATOR, n)).collect())
+}
+
+/// A file read from an archive.
+#[derive(Debug, PartialEq)]
+pub enum Member {
+    Data(Vec<u8>),
+    /// It's over the limit, so it wasn't read.
+    TooBig,
+}
+
+/// Read up to `limit` bytes of `reader`, or `TooBig` past that.
+fn read_within<R: Read>(reader: R, limit: Option<u64>) -> io::Result<Member> {
+    let mut data = vec![];
+    // One byte past the limit is enough to know it's over
+    let most = limit.map_or(u64::MAX, |l| l.saturating_add(1));
+    reader.take(most).read_to_end(&mut data)?;
+    if limit.is_some_and(|l| data.len() as u64 > l) {
+        return Ok(Member::TooBig);
+    }
+    Ok(Member::Data(data))
+}
+
+/// Read on through the tar `reader` to the file `member`, leaving it
+/// at the entry after. `None` if the archive ends first.
+fn find_in_tar(reader: &mut dyn Read,
+               member: &str,
+               limit: Option<u64>)
+               -> io::Result<Option<Member>> {
+    let mut found = None;
+    // What's left of the entry found, and its padding, once the
+    // archive reading it is done
+    let mut skip = 0;
+    {
+        let mut archive = tar::Archive::new(&mut *reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() || entry.path()?.to_str() != Some(member) {
+                continue;
+            }
+            let size = entry.header().entry_size()?;
+            let padding = (BLOCK - size % BLOCK) % BLOCK;
+            if limit.is_some_and(|l| size > l) {
+                found = Some(Member::TooBig);
+                skip = size + padding;
+            } else {
+                found = Some(read_within(&mut entry, limit)?);
+                skip = padding;
+            }
+            break;
+        }
+    }
+    io::copy(&mut reader.take(skip), &mut io::sink())?;
+    Ok(found)
+}
+
+/// The contents of the file `member` of the archive at `path`, if
+/// it's no bigger than `limit`. Reading the files of a tar in order
+/// reads it through once.
+pub fn read(path: &str, member: &str, limit: Option<u64>) -> io::Result<Member> {
+    OPEN.with(|o| {
+        let mut o = o.borrow_mut();
+        let kept = o.take().filter(|o| o.0 == path).map(|o| o.1);
+        let mut opened = match kept {
+            Some(k) => k,
+            None => open(path)?,
+        };
+        let found = match opened {
+            Opened::Zip(ref mut zip) => {
+                let file = zip.by_name(member)?;
+                if limit.is_some_and(|l| file.size() > l) {
+                    Some(Member::TooBig)
+                } else {
+                    Some(read_within(file, limit)?)
+                }
+            }
+            Opened::Tar(ref mut reader) => {
+                match find_in_tar(reader, member, limit)? {
+                    Some(m) => Some(m),
+                    // Earlier in the archive than the last file read,
+                    // so start it over
+                    None => {
+                        opened = open(path)?;
+                        match opened {
+                            Opened::Tar(ref mut reader) => find_in_tar(reader, member, limit)?,
+                            Opened::Zip(_) => None,
+                        }
+                    }
+                }
+            }
+        };
+        // Left closed after an error, so the next read starts afresh
+        *o = Some((path.to_owned(), opened));
+        found.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, member.to_owned()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::{Cursor, Write};
+    use std::process;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    /// Write `bytes` to a file called `name` in the temporary directory.
+    fn temp(name: &str, bytes: &[u8]) -> String {
+        let path = env::temp_dir().join(format!("regtest-{}-{}", process::id(), name));
+        fs::write(&path, bytes).unwrap();
+        path.display().to_string()
+    }
+
+    fn data(text: &str) -> Member {
+        Member::Data(text.as_bytes().to_vec())
+    }
+
+    fn zip() -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        zip.add_directory("docs/", SimpleFileOptions::default()).unwrap();
+        zip.start_file("docs/a.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"alpha\nbeta\n").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar() -> Vec<u8> {
+        let long = format!("{}/b.txt", "x".repeat(120));
+        let mut tar = tar::Builder::new(vec![]);
+        for (name, text) in [("a.txt", "alpha\n"), (&long[..], "beta\n")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(text.len() as u64);
+            header.set_mode(0o644);
+            tar.append_data(&mut header, name, text.as_bytes()).unwrap();
+        }
+        tar.into_inner().unwrap()
+    }
+
+    #[test]
+    fn lists_and_reads_zip_files() {
+        let path = temp("ok.zip", &zip());
+        assert_eq!(members(&path).unwrap(), vec![format!("{}!docs/a.txt", path)]);
+        assert_eq!(read(&path, "docs/a.txt", None).unwrap(), data("alpha\nbeta\n"));
+        assert_eq!(read(&path, "docs/b.txt", None).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn lists_and_reads_tar_files_with_long_names() {
+        let path = temp("ok.tar", &tar());
+        let long = format!("{}/b.txt", "x".repeat(120));
+        assert_eq!(members(&path).unwrap(),
+                   vec![format!("{}!a.txt", path), format!("{}!{}", path, long)]);
+        assert_eq!(read(&path, &long, None).unwrap(), data("beta\n"));
+    }
+
+    #[test]
+    fn reads_tar_gz_files() {
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&tar()).unwrap();
+        let path = temp("ok.tgz", &gz.finish().unwrap());
+        assert_eq!(read(&path, "a.txt", None).unwrap(), data("alpha\n"));
+        let long = format!("{}/b.txt", "x".repeat(120));
+        assert_eq!(read(&path, &long, None).unwrap(), data("beta\n"));
+    }
+
+    #[test]
+    fn truncated_zip_is_an_error() {
+        let bytes = zip();
+        let path = temp("short.zip", &bytes[..bytes.len() - 10]);
+        assert!(members(&path).is_err());
+    }
+
+    #[test]
+    fn corrupt_zip_is_an_error() {
+        let path = temp("junk.zip", b"this is not a zip archive at all");
+        assert!(members(&path).is_err());
+    }
+
+    #[test]
+    fn truncated_tar_is_an_error() {
+        let path = temp("short.tar", &tar()[..700]);
+        assert!(members(&path).is_err());
+    }
+
+    #[test]
+    fn truncated_tar_gz_is_an_error() {
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&tar()).unwrap();
+        let bytes = gz.finish().unwrap();
+        let path = temp("short.tar.gz", &bytes[..bytes.len() / 2]);
+        assert!(members(&path).is_err());
+    }
+
+    #[test]
+    fn reads_tar_files_in_any_order() {
+        let path = temp("order.tar", &tar());
+        let long = format!("{}/b.txt", "x".repeat(120));
+        assert_eq!(read(&path, &long, None).unwrap(), data("beta\n"));
+        // Behind the last file read, so the archive is started over
+        assert_eq!(read(&path, "a.txt", None).unwrap(), data("alpha\n"));
+        assert_eq!(read(&path, &long, None).unwrap(), data("beta\n"));
+        assert_eq!(read(&path, "c.txt", None).unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(read(&path, "a.txt", None).unwrap(), data("alpha\n"));
+    }
+
+    #[test]
+    fn files_over_the_limit_are_not_read() {
+        let path = temp("limit.tar", &tar());
+        let long = format!("{}/b.txt", "x".repeat(120));
+        assert_eq!(read(&path, "a.txt", Some(5)).unwrap(), Member::TooBig);
+        // The file after it is still found
+        assert_eq!(read(&path, &long, Some(5)).unwrap(), data("beta\n"));
+
+        let path = temp("limit.zip", &zip());
+        assert_eq!(read(&path, "docs/a.txt", Some(10)).unwrap(), Member::TooBig);
+        assert_eq!(read(&path, "docs/a.txt", Some(11)).unwrap(), data("alpha\nbeta\n"));
+    }
+}
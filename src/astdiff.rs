@@ -0,0 +1,245 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Differences between two patterns in their syntax trees rather
+//! than their text: which parts were added, removed or changed.
+
+use std::slice;
+
+use regex_syntax::Parser as HirParser;
+use regex_syntax::ast::{Ast, GroupKind, Span};
+use regex_syntax::ast::parse::Parser;
+
+/// A part of a pattern.
+#[derive(Clone, Debug)]
+struct Node {
+    /// What sort of part it is, like "group" or "class".
+    kind: &'static str,
+    /// What sets it apart from others of its kind beyond its
+    /// children, like the operator of a repetition.
+    label: String,
+    text: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(pattern: &str, ast: &Ast) -> Node {
+        let text = |s: &Span| pattern[s.start.offset..s.end.offset].to_owned();
+        let (kind, label, children) = match *ast {
+            Ast::Empty(_) => ("empty", String::new(), vec![]),
+            Ast::Flags(_) => ("flags", text(ast.span()), vec![]),
+            Ast::Literal(_) => ("literal", text(ast.span()), vec![]),
+            Ast::Dot(_) => ("dot", text(ast.span()), vec![]),
+            Ast::Assertion(_) => ("assertion", text(ast.span()), vec![]),
+            Ast::Class(_) => ("class", text(ast.span()), vec![]),
+            Ast::Repetition(ref r) => {
+                ("repetition", text(&r.op.span), vec![Node::new(pattern, &r.ast)])
+            }
+            Ast::Group(ref g) => {
+                let label = match g.kind {
+                    GroupKind::CaptureIndex(_) => "(".to_owned(),
+                    GroupKind::CaptureName(ref n) => format!("(?P<{}>", n.name),
+                    GroupKind::NonCapturing(ref f) => format!("(?{}:", text(&f.span)),
+                };
+                ("group", label, vec![Node::new(pattern, &g.ast)])
+            }
+            Ast::Alternation(ref alt) => ("alternation", String::new(), nodes(pattern, &alt.asts)),
+            Ast::Concat(ref c) => ("sequence", String::new(), nodes(pattern, &c.asts)),
+        };
+        Node {
+            kind,
+            label,
+            text: text(ast.span()),
+            children,
+        }
+    }
+}
+
+fn nodes(pattern: &str, asts: &[Ast]) -> Vec<Node> {
+    asts.iter().map(|a| Node::new(pattern, a)).collect()
+}
+
+/// One difference between two patterns.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// A part of the new pattern, of a kind, that the old one lacks.
+    Added(&'static str, String),
+    Removed(&'static str, String),
+    /// A part of a kind written differently, and whether both ways
+    /// mean the same.
+    Changed {
+        kind: &'static str,
+        old: String,
+        new: String,
+        equivalent: bool,
+    },
+}
+
+/// The differences turning `old` into `new`, or the error of the
+/// one that doesn't parse.
+pub fn diff(old: &str, new: &str) -> Result<Vec<Change>, String> {
+    let a = Parser::new().parse(old).map_err(|e| e.to_string())?;
+    let b = Parser::new().parse(new).map_err(|e| e.to_string())?;
+    let mut out = vec![];
+    node(&Node::new(old, &a), &Node::new(new, &b), &mut out);
+    Ok(out)
+}
+
+/// Do `a` and `b` parse to the same expression?
+fn equivalent(a: &str, b: &str) -> bool {
+    match (HirParser::new().parse(a), HirParser::new().parse(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn node(a: &Node, b: &Node, out: &mut Vec<Change>) {
+    if a.text == b.text {
+        return;
+    }
+    // A pattern that became one of the alternatives of the other
+    // is compared as its only alternative
+    if a.kind != b.kind && (a.kind == "alternation" || b.kind == "alternation") {
+        return match a.kind {
+            "alternation" => children(&a.children, slice::from_ref(b), out),
+            _ => children(slice::from_ref(a), &b.children, out),
+        };
+    }
+    // Different kinds, or leaves written differently, are changed
+    // as a whole
+    if a.kind != b.kind || a.children.is_empty() || b.children.is_empty() {
+        out.push(Change::Changed {
+            kind: a.kind,
+            old: a.text.clone(),
+            new: b.text.clone(),
+            equivalent: equivalent(&a.text, &b.text),
+        });
+        return;
+    }
+    if a.label != b.label {
+        out.push(Change::Changed {
+            kind: a.kind,
+            old: a.label.clone(),
+            new: b.label.clone(),
+            equivalent: false,
+        });
+    }
+    children(&a.children, &b.children, out);
+}
+
+/// Line up `a` and `b` on the children written the same, then pair
+/// what's left between them by kind.
+fn children(a: &[Node], b: &[Node], out: &mut Vec<Change>) {
+    // lcs[i][j] is the most children of a[i..] and b[j..] in common
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i].text == b[j].text {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let (mut gone, mut came) = (vec![], vec![]);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i].text == b[j].text {
+            gap(&gone, &came, out);
+            gone.clear();
+            came.clear();
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            gone.push(&a[i]);
+            i += 1;
+        } else {
+            came.push(&b[j]);
+            j += 1;
+        }
+    }
+    gap(&gone, &came, out);
+}
+
+/// Children removed and added between two common ones: those of the
+/// same kind in the same order are changes, the rest removals and
+/// additions.
+fn gap(gone: &[&Node], came: &[&Node], out: &mut Vec<Change>) {
+    let mut came: Vec<Option<&Node>> = came.iter().map(|&n| Some(n)).collect();
+    for a in gone {
+        let partner = came.iter_mut().find(|b| b.is_some_and(|b| b.kind == a.kind));
+        match partner.and_then(|b| b.take()) {
+            Some(b) => node(a, b, out),
+            None => out.push(Change::Removed(a.kind, a.text.clone())),
+        }
+    }
+    for b in came.into_iter().flatten() {
+        out.push(Change::Added(b.kind, b.text.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(kind: &'static str, old: &str, new: &str, equivalent: bool) -> Change {
+        Change::Changed {
+            kind,
+            old: old.to_owned(),
+            new: new.to_owned(),
+            equivalent,
+        }
+    }
+
+    #[test]
+    fn same_patterns_have_no_differences() {
+        assert!(diff(r"a(b|c)+\d", r"a(b|c)+\d").unwrap().is_empty());
+        assert!(diff("(", "a").is_err());
+        assert!(diff("a", "[").is_err());
+    }
+
+    #[test]
+    fn finds_added_and_removed_parts() {
+        assert_eq!(diff("ab", "abc").unwrap(), [Change::Added("literal", "c".to_owned())]);
+        assert_eq!(diff(r"a\db", "ab").unwrap(), [Change::Removed("class", r"\d".to_owned())]);
+    }
+
+    #[test]
+    fn tells_equivalent_rewrites_apart() {
+        assert_eq!(diff(r"x[0-9]", r"x\d").unwrap(), [changed("class", "[0-9]", r"\d", false)]);
+        assert_eq!(diff("x[0-9]", "x[0123456789]").unwrap(),
+                   [changed("class", "[0-9]", "[0123456789]", true)]);
+        // Parts of different kinds aren't paired up
+        assert_eq!(diff("x[a]", "xa").unwrap(),
+                   [Change::Removed("class", "[a]".to_owned()),
+                    Change::Added("literal", "a".to_owned())]);
+    }
+
+    #[test]
+    fn compares_inside_groups_and_repetitions() {
+        assert_eq!(diff("(ab)+", "(ac)+").unwrap(), [changed("literal", "b", "c", false)]);
+        assert_eq!(diff("a+", "a*").unwrap(), [changed("repetition", "+", "*", false)]);
+        assert_eq!(diff("(a)", "(?:a)").unwrap(), [changed("group", "(", "(?:", false)]);
+    }
+
+    #[test]
+    fn a_pattern_can_become_one_alternative() {
+        assert_eq!(diff("cat", "cat|dog").unwrap(), [Change::Added("sequence", "dog".to_owned())]);
+        assert_eq!(diff("cat|dog", "dog").unwrap(),
+                   [Change::Removed("sequence", "cat".to_owned())]);
+    }
+}
@@ -0,0 +1,1643 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Commands that run once and exit, for use from scripts. Results
+//! go to stdout and the exit status tells whether things matched.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::io::{BufRead, BufReader, LineWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+
+use regex::Regex;
+use time;
+
+#[cfg(feature = "archives")]
+use archive;
+use bench::{self, Api};
+use checkpoint::Checkpoint;
+use describe;
+use diff;
+use engines::{self, Engine};
+use format::{self, Place};
+use grade::{self, Spec};
+use highlight;
+use hook::{Hook, Runner};
+use infer;
+use logging;
+use output::{self, Output};
+use settings;
+use pager::{Paged, Target};
+use records::{Delimiter, Records};
+use sample;
+#[cfg(feature = "sqlite")]
+use sqlite::Store;
+use offsets::Offsets;
+use suite::{self, Outcome, Suite};
+use template::{self, Resolved};
+use transform::Normalization;
+use watch::{self, Follower};
+use timestamp::{Bucket, TimeFilter};
+use window;
+
+/// Exit status when everything matched.
+pub const EXIT_MATCH: i32 = 0;
+/// Exit status when something failed to match.
+pub const EXIT_NO_MATCH: i32 = 1;
+/// Exit status when the pattern or input couldn't be used.
+pub const EXIT_ERROR: i32 = 2;
+
+/// Compile `pattern`, reporting the error on stderr.
+fn compile(pattern: &str) -> Option<Regex> {
+    let t1 = time::now();
+    let reg = Regex::new(pattern);
+    debug!("compiled {:?} with default flags on the {} engine in {:.3}ms",
+           pattern,
+           settings::ENGINE,
+           logging::ms(time::now() - t1));
+    match reg {
+        Ok(r) => Some(r),
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(ErrorCompiling, e));
+            None
+        }
+    }
+}
+
+/// Open `path` for reading line by line, or stdin if `None`,
+/// reporting any error on stderr.
+pub fn open(path: Option<&str>) -> Option<Box<dyn BufRead>> {
+    debug!("streaming {} line by line", path.unwrap_or("stdin"));
+    match path {
+        Some(p) => {
+            match File::open(p) {
+                Ok(f) => Some(Box::new(BufReader::new(f))),
+                Err(e) => {
+                    writeln!(io::stderr(), "{}", msg!(CantOpenFile, p, e));
+                    None
+                }
+            }
+        }
+        None => Some(Box::new(BufReader::new(io::stdin()))),
+    }
+}
+
+/// Like `open`, starting `offset` bytes into the file. Files of
+/// archives over `max_file` bytes are refused, as they're read into
+/// memory.
+fn open_at(path: Option<&str>, offset: u64, max_file: Option<u64>) -> Option<Box<dyn BufRead>> {
+    if let Some(opened) = path.and_then(|p| open_member(p, offset, max_file)) {
+        return opened;
+    }
+    let p = match path {
+        Some(p) if offset > 0 => p,
+        _ => return open(path),
+    };
+    debug!("streaming {} line by line from byte {}", p, offset);
+    match File::open(p).and_then(|mut f| f.seek(SeekFrom::Start(offset)).map(|_| f)) {
+        Ok(f) => Some(Box::new(BufReader::new(f))),
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantOpenFile, p, e));
+            None
+        }
+    }
+}
+
+/// The file `name` of an archive, named as `archive::members` does,
+/// from `offset` bytes in, or `None` if `name` isn't one. Inside is
+/// `None` if it's over `max_file` bytes or can't be read, which is
+/// reported on stderr.
+#[cfg(feature = "archives")]
+fn open_member(name: &str,
+               offset: u64,
+               max_file: Option<u64>)
+               -> Option<Option<Box<dyn BufRead>>> {
+    let (path, member) = archive::split(name)?;
+    Some(match archive::read(path, member, max_file) {
+        Ok(archive::Member::Data(data)) => {
+            let mut cursor = io::Cursor::new(data);
+            cursor.set_position(offset);
+            Some(Box::new(cursor))
+        }
+        Ok(archive::Member::TooBig) => {
+            let limit = settings::size_name(max_file.unwrap_or(0));
+            writeln!(io::stderr(), "{}", msg!(InputTooBig, name, limit));
+            None
+        }
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantOpenFile, name, e));
+            None
+        }
+    })
+}
+
+#[cfg(not(feature = "archives"))]
+fn open_member(_: &str, _: u64, _: Option<u64>) -> Option<Option<Box<dyn BufRead>>> {
+    None
+}
+
+/// The files in the archive `path` stands for, or `None` if it isn't
+/// one.
+#[cfg(feature = "archives")]
+fn archive_members(path: &str) -> Option<io::Result<Vec<String>>> {
+    if !archive::is_archive(path) {
+        return None;
+    }
+    Some(archive::members(path))
+}
+
+#[cfg(not(feature = "archives"))]
+fn archive_members(_: &str) -> Option<io::Result<Vec<String>>> {
+    None
+}
+
+/// Stands in for the `--sqlite` database in builds without SQLite,
+/// failing to open.
+#[cfg(not(feature = "sqlite"))]
+struct Store;
+
+#[cfg(not(feature = "sqlite"))]
+impl Store {
+    fn open(_: &str, _: &str) -> Result<Store, String> {
+        Err(msg!(NoSqlite).to_owned())
+    }
+
+    fn file(&mut self, _: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn input(&mut self, _: &output::Input) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// `paths`, with each directory replaced by the files under it in
+/// order if `recursive`, and each archive by the files in it. Links
+/// to directories aren't followed. Also whether a directory or an
+/// archive couldn't be read, which is reported on stderr.
+fn expand(paths: &[&str], recursive: bool) -> (Vec<String>, bool) {
+    fn walk(dir: &Path, out: &mut Vec<String>) -> bool {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantOpenFile, dir.display(), e));
+                return false;
+            }
+        };
+        let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+        let mut read = true;
+        for path in paths {
+            match fs::symlink_metadata(&path) {
+                Ok(ref m) if m.is_dir() => read &= walk(&path, out),
+                Ok(ref m) if m.file_type().is_symlink() && path.is_dir() => {}
+                _ => out.push(path.display().to_string()),
+            }
+        }
+        read
+    }
+    let mut files = vec![];
+    let mut read = true;
+    for &p in paths {
+        if recursive && Path::new(p).is_dir() {
+            read &= walk(Path::new(p), &mut files);
+        } else {
+            files.push(p.to_owned());
+        }
+    }
+    let mut out = vec![];
+    for file in files {
+        match archive_members(&file) {
+            Some(Ok(members)) => out.extend(members),
+            Some(Err(e)) => {
+                writeln!(io::stderr(), "{}", msg!(CantOpenFile, file, e));
+                read = false;
+            }
+            None => out.push(file),
+        }
+    }
+    (out, !read)
+}
+
+/// Test `input`, normalized to `normalization`, against `pattern`,
+/// printing the result and the captures of every match, the
+/// `format` template for each match, or a document in `output` with
+/// `columns` if given. Returns the exit status.
+pub fn run_match(pattern: &str,
+                 input: &str,
+                 normalization: Normalization,
+                 format: Option<&[format::Piece]>,
+                 output: Output,
+                 columns: Option<Vec<output::Column>>,
+                 accessible: bool)
+                 -> i32 {
+    let reg = match compile(pattern) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    let input = normalization.apply(input);
+    if let Some(pieces) = format {
+        let place = Place { file: "-", line: 1 };
+        for caps in reg.captures_iter(&input) {
+            writeln!(io::stdout(), "{}", format::render(pieces, &place, &caps));
+        }
+        return if reg.is_match(&input) { EXIT_MATCH } else { EXIT_NO_MATCH };
+    }
+    if output != Output::Text {
+        let mut stdout = io::stdout();
+        let columns = columns.unwrap_or_else(|| output::default_columns(&reg));
+        if let Err(e) = output::check_columns(&columns, &reg) {
+            writeln!(io::stderr(), "{}", e);
+            return EXIT_ERROR;
+        }
+        let mut doc = output::Writer::new(output, &mut stdout).columns(columns);
+        doc.input(&output::Input::new(&reg, "-", 1, &input));
+        doc.finish();
+        return if reg.is_match(&input) { EXIT_MATCH } else { EXIT_NO_MATCH };
+    }
+    if write_match(&mut io::stdout(), &reg, &input, accessible) {
+        EXIT_MATCH
+    } else {
+        EXIT_NO_MATCH
+    }
+}
+
+/// Write whether `reg` matches `input` and the captures of every
+/// match to `out`. Returns whether it matched.
+pub fn write_match(out: &mut dyn Write, reg: &Regex, input: &str, accessible: bool) -> bool {
+    if !reg.is_match(input) {
+        writeln!(out, "{}", msg!(FailedToMatch));
+        return false;
+    }
+
+    writeln!(out, "{}", msg!(Matched));
+    for (i, caps) in reg.captures_iter(input).enumerate() {
+        if accessible {
+            let text = describe::captures(reg, input, &caps);
+            writeln!(out, "{}", msg!(AccNthMatch, i + 1, text));
+            continue;
+        }
+        for (j, cap) in caps.iter().enumerate() {
+            writeln!(out,
+                     "{}:{}: {}",
+                     i,
+                     j,
+                     if let Some(c) = cap { c.as_str() } else { "None" });
+        }
+    }
+    true
+}
+
+/// Test every record of `path`, or stdin if `None`, against
+/// `pattern`, after normalizing it to `normalization`. Records end
+/// at `delimiter`. Prints each record's result, or only the failures
+/// if `failures_only`, then a summary. Returns the exit status.
+pub fn run_check(pattern: &str,
+                 path: Option<&str>,
+                 delimiter: Delimiter,
+                 normalization: Normalization,
+                 failures_only: bool)
+                 -> i32 {
+    let mut stdout = io::stdout();
+    let reg = match compile(pattern) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    let reader = match open(path) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+
+    let (mut total, mut matched) = (0, 0);
+    for (n, line) in Records::with_delimiter(reader, delimiter, None).enumerate() {
+        let line = match line {
+            Ok(l) => l.unwrap_or_default(),
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                return EXIT_ERROR;
+            }
+        };
+        let line = normalization.apply(line.trim_end_matches('\r'));
+        total += 1;
+        if reg.is_match(&line) {
+            matched += 1;
+            if !failures_only {
+                writeln!(stdout, "{}", msg!(CheckLineMatched, n + 1, line));
+            }
+        } else {
+            writeln!(stdout, "{}", msg!(CheckLineFailed, n + 1, line));
+        }
+    }
+
+    writeln!(stdout, "{}", msg!(CheckSummary, matched, total));
+    if matched == total {
+        EXIT_MATCH
+    } else {
+        EXIT_NO_MATCH
+    }
+}
+
+/// Print every line of `path`, or stdin if `None`, with the matches
+/// of each of `patterns` in its own color, after a legend on
+/// stderr. Returns the exit status.
+pub fn run_highlight(patterns: &[&str], path: Option<&str>, normalization: Normalization) -> i32 {
+    let mut stdout = io::stdout();
+    let regexes: Option<Vec<Regex>> = patterns.iter().map(|p| compile(p)).collect();
+    let regexes = match regexes {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    let regexes: Vec<&Regex> = regexes.iter().collect();
+    let reader = match open(path) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+
+    let color = diff::color_stdout();
+    for (i, p) in patterns.iter().enumerate() {
+        writeln!(io::stderr(), "{} {}", highlight::swatch(i, color), p);
+    }
+    let mut status = EXIT_NO_MATCH;
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                return EXIT_ERROR;
+            }
+        };
+        let line = normalization.apply(line.trim_end_matches('\r'));
+        if regexes.iter().any(|r| r.is_match(&line)) {
+            status = EXIT_MATCH;
+        }
+        writeln!(stdout, "{}", highlight::several(&line, &regexes, color));
+    }
+    status
+}
+
+/// The order matches extracted by `grep -o` are printed in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Sort {
+    /// As they're found.
+    Position,
+    /// Shortest first.
+    Length,
+    Text,
+}
+
+impl Sort {
+    pub fn from_name(name: &str) -> Option<Sort> {
+        match name {
+            "position" => Some(Sort::Position),
+            "length" => Some(Sort::Length),
+            "text" => Some(Sort::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Options for `grep`.
+pub struct GrepOptions {
+    /// Print lines that don't match instead.
+    pub invert: bool,
+    /// Prefix lines with their line number.
+    pub line_numbers: bool,
+    /// Print only the matched parts of each line.
+    pub only_matching: bool,
+    /// With `only_matching`, print each distinct match once.
+    pub unique: bool,
+    /// With `only_matching`, print the matches in this order once
+    /// every line has been read.
+    pub sort: Option<Sort>,
+    /// Extract this group by number or name instead of the whole
+    /// match.
+    pub group: Option<String>,
+    /// Instead of lines, print the N most common extracted values
+    /// and how often each was seen.
+    pub top: Option<usize>,
+    /// Print only this many characters either side of each match,
+    /// with the column it starts at.
+    pub window: Option<usize>,
+    /// With `--follow`, run for each match of the new lines.
+    pub on_match: Option<Hook>,
+    /// Run for each match instead of printing the lines.
+    pub exec: Option<Hook>,
+    /// The most `exec` commands running at once.
+    pub jobs: usize,
+    /// Skip lines longer than this many bytes.
+    pub max_line: Option<u64>,
+    /// Refuse files of archives bigger than this many bytes, as
+    /// they're read into memory.
+    pub max_file: Option<u64>,
+    /// What ends a line, which is also written after each one
+    /// printed.
+    pub delimiter: Delimiter,
+    /// Applied to each line before it's matched.
+    pub normalization: Normalization,
+    /// A `--format` template written for each match instead of the
+    /// line.
+    pub format: Option<Vec<format::Piece>>,
+    /// Write each line with its matches in this output.
+    pub output: Output,
+    /// Columns for `csv` output, or `None` for the defaults.
+    pub columns: Option<Vec<output::Column>>,
+    /// A database to also save the matches to.
+    pub sqlite: Option<String>,
+    /// Only keep lines whose time is in a range.
+    pub time: Option<TimeFilter>,
+    /// Instead of lines, print how many were kept per minute or
+    /// hour. Needs `time`.
+    pub bucket: Option<Bucket>,
+    /// Page output longer than the terminal.
+    pub pager: bool,
+    /// Write results to this file instead of stdout, line by line as
+    /// they're found, with a footer once the run is complete.
+    pub output_file: Option<String>,
+    /// Search the files under directories given.
+    pub recursive: bool,
+    /// Save how far the scan got to this file now and then.
+    pub checkpoint: Option<String>,
+    /// Start from the `checkpoint` saved, if there is one.
+    pub resume: bool,
+    /// Instead of lines, print how many lines and matches each file
+    /// had, and how many distinct matches there were in all.
+    pub summary: bool,
+    /// Print totals and how long the run took on stderr at the end.
+    pub stats: bool,
+    /// Stop reading after this many seconds.
+    pub max_time: Option<f64>,
+}
+
+/// Totals of a run for `--stats`.
+#[derive(Default)]
+struct Stats {
+    matches: usize,
+    lines: usize,
+    files_with_matches: usize,
+    files: usize,
+    binary: usize,
+    bytes: u64,
+}
+
+impl Stats {
+    /// Write the totals and the time since `started`.
+    fn write(&self, out: &mut dyn Write, started: u64) {
+        let seconds = (time::precise_time_ns() - started) as f64 / 1e9;
+        writeln!(out,
+                 "{}",
+                 msg!(Stats,
+                      self.matches,
+                      self.lines,
+                      self.files_with_matches,
+                      self.files,
+                      self.binary,
+                      self.bytes,
+                      format!("{:.6}", seconds)));
+    }
+}
+
+/// Does the start of `reader` hold a NUL byte, as binary files do?
+/// Nothing is consumed.
+fn looks_binary(reader: &mut dyn BufRead) -> bool {
+    reader.fill_buf().map(|b| b.contains(&0)).unwrap_or(false)
+}
+
+/// Output shared by several writers, like `Paged`, for results that
+/// may go to a file instead.
+#[derive(Clone)]
+struct Shared(Rc<RefCell<Box<dyn Write>>>);
+
+impl Shared {
+    fn new<W: Write + 'static>(out: W) -> Shared {
+        Shared(Rc::new(RefCell::new(Box::new(out))))
+    }
+}
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// Print the lines added to the file at `path` that match
+/// `pattern` as they're written, with the matches highlighted, until
+/// the program is stopped. Returns the exit status if the file can't
+/// be read.
+pub fn run_follow(pattern: &str, path: &str, opts: &GrepOptions) -> i32 {
+    let mut stdout = io::stdout();
+    let reg = match compile(pattern) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    let mut follower = match Follower::open(Path::new(path), opts.max_line) {
+        Ok(f) => f,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantOpenFile, path, e));
+            return EXIT_ERROR;
+        }
+    };
+    let color = diff::color_stdout();
+    let mut runner = opts.exec.clone().map(|h| Runner::new(h, opts.jobs));
+    loop {
+        let lines = match follower.lines() {
+            Ok(l) => l,
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                return EXIT_ERROR;
+            }
+        };
+        for (n, line) in lines {
+            let line = match line {
+                Some(l) => opts.normalization.apply(&l),
+                None => {
+                    let limit = settings::size_name(opts.max_line.unwrap_or(0));
+                    writeln!(io::stderr(), "{}", msg!(LineTooLong, path, n, limit));
+                    continue;
+                }
+            };
+            if reg.is_match(&line) == opts.invert {
+                continue;
+            }
+            if let Some(ref mut r) = runner {
+                let place = Place {
+                    file: path,
+                    line: n,
+                };
+                for caps in reg.captures_iter(&line) {
+                    r.spawn(&place, &caps, &mut io::stderr());
+                }
+                continue;
+            }
+            let prefix = if opts.line_numbers { format!("{}:", n) } else { String::new() };
+            if opts.invert {
+                writeln!(stdout, "{}{}", prefix, line);
+            } else if opts.only_matching {
+                for value in extract(&reg, &line, opts.group.as_ref().map(|g| &g[..])) {
+                    writeln!(stdout, "{}{}", prefix, value);
+                }
+            } else if let Some(context) = opts.window {
+                let spans: Vec<_> = reg.find_iter(&line).map(|m| (m.start(), m.end())).collect();
+                for w in window::windows(&line, &spans, context, true) {
+                    writeln!(stdout, "{}{}:{}", prefix, w.column, w.text);
+                }
+            } else if color {
+                writeln!(stdout, "{}{}", prefix, highlight::several(&line, &[&reg], true));
+            } else {
+                writeln!(stdout, "{}{}", prefix, line);
+            }
+            stdout.flush();
+            if let (Some(ref hook), false) = (&opts.on_match, opts.invert) {
+                let place = Place {
+                    file: path,
+                    line: n,
+                };
+                for caps in reg.captures_iter(&line) {
+                    if let Err(e) = hook.run(&place, &caps) {
+                        writeln!(io::stderr(), "{}", e);
+                    }
+                }
+            }
+        }
+        if !follower.behind() {
+            thread::sleep(watch::INTERVAL);
+        }
+    }
+}
+
+/// Print the lines of each file in `paths`, or stdin if empty,
+/// that match `pattern`. Returns the exit status.
+pub fn run_grep(pattern: &str, paths: &[&str], opts: &GrepOptions) -> i32 {
+    let started = time::precise_time_ns();
+    let reg = match compile(pattern) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    if let Some(Err(e)) = opts.columns.as_ref().map(|c| output::check_columns(c, &reg)) {
+        writeln!(io::stderr(), "{}", e);
+        return EXIT_ERROR;
+    }
+    let mut checkpoint = match opts.checkpoint {
+        Some(_) if opts.output == Output::Json || opts.output == Output::JsonCompact => {
+            writeln!(io::stderr(), "{}", msg!(CheckpointOutput));
+            return EXIT_ERROR;
+        }
+        Some(ref path) if opts.resume => {
+            match Checkpoint::resume(path, pattern) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    writeln!(io::stderr(), "{}", e);
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        Some(ref path) => Some(Checkpoint::new(path, pattern)),
+        None => None,
+    };
+    let resumed = checkpoint.as_ref().is_some_and(|c| c.files > 0);
+    // Results written to a file go out a line at a time, so they're
+    // kept if the run is cut short. A resumed run adds to them, after
+    // cutting off what was written since the checkpoint was saved.
+    let paged = match opts.output_file {
+        Some(ref path) => {
+            let saved = checkpoint.as_ref().and_then(|c| c.output).filter(|_| resumed);
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(!resumed)
+                .open(path)
+                .and_then(|mut f| {
+                    if let Some(len) = saved {
+                        f.set_len(len)?;
+                    }
+                    f.seek(SeekFrom::End(0))?;
+                    Ok(f)
+                });
+            match file {
+                Ok(f) => Shared::new(LineWriter::new(f)),
+                Err(e) => {
+                    writeln!(io::stderr(), "{}", msg!(CantWriteFile, path, e));
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        None => Shared::new(Paged::new(Target::Stdout, opts.pager)),
+    };
+    let mut stdout = paged.clone();
+    let (names, unreadable) = expand(paths, opts.recursive);
+    let sources: Vec<Option<&str>> = if paths.is_empty() {
+        vec![None]
+    } else {
+        names.iter().map(|p| Some(&p[..])).collect()
+    };
+
+    let mut doc_out = paged;
+    let columns = opts.columns.clone().unwrap_or_else(|| output::default_columns(&reg));
+    let mut doc = output::Writer::new(opts.output, &mut doc_out).columns(columns);
+    if resumed {
+        doc = doc.continued();
+    }
+    let mut store = match opts.sqlite {
+        Some(ref db) => {
+            match Store::open(db, pattern) {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    writeln!(io::stderr(), "{}", msg!(CantSave, e));
+                    return EXIT_ERROR;
+                }
+            }
+        }
+        None => None,
+    };
+    // Matches held back for --unique or --sort, with their prefixes
+    let hold = opts.only_matching && (opts.unique || opts.sort.is_some());
+    let mut runner = opts.exec.clone().map(|h| Runner::new(h, opts.jobs));
+    let mut extracted: Vec<(String, String)> = vec![];
+    // Times each value was seen, and when it was first seen
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    // Lines kept per --bucket, and matching lines without a time
+    let mut buckets: BTreeMap<String, usize> = BTreeMap::new();
+    let mut untimed = 0;
+    // Matching lines and matches per file for --summary, and every
+    // distinct match
+    let mut per_file: Vec<(&str, usize, usize)> = vec![];
+    let mut distinct: HashSet<String> = HashSet::new();
+    let deadline = opts.max_time.map(|t| started + (t * 1e9) as u64);
+    // Where the scan stopped at the deadline
+    let mut stopped: Option<(&str, usize)> = None;
+    let mut stats = Stats::default();
+    let (mut matched, mut files) = match checkpoint {
+        Some(ref c) => (c.lines, c.files),
+        None => (0, 0),
+    };
+    let end = opts.delimiter.terminator();
+    let mut status = if unreadable { EXIT_ERROR } else { EXIT_NO_MATCH };
+    for path in sources {
+        if let (Some(c), Some(p)) = (checkpoint.as_ref(), path) {
+            if c.is_done(p) {
+                continue;
+            }
+        }
+        let (offset, first_line) = match (checkpoint.as_ref(), path) {
+            (Some(c), Some(p)) => c.start(p),
+            _ => (0, 0),
+        };
+        let mut reader = match open_at(path, offset, opts.max_file) {
+            Some(r) => r,
+            None => {
+                status = EXIT_ERROR;
+                continue;
+            }
+        };
+        // Binary files are skipped, unless records end in NUL
+        if offset == 0 && opts.delimiter != Delimiter::Nul && looks_binary(&mut *reader) {
+            debug!("skipping binary file {}", path.unwrap_or("-"));
+            stats.binary += 1;
+            continue;
+        }
+        if offset == 0 {
+            files += 1;
+        }
+        let lines_before = matched;
+        if let Some(ref mut s) = store {
+            if let Err(e) = s.file(path.unwrap_or("-")) {
+                writeln!(io::stderr(), "{}", msg!(CantSave, e));
+                return EXIT_ERROR;
+            }
+        }
+        let mut records = Records::with_delimiter(reader, opts.delimiter.clone(), opts.max_line)
+            .starting_at(offset);
+        let mut next_line = first_line;
+        let mut complete = true;
+        loop {
+            if let (Some(c), Some(p)) = (checkpoint.as_mut(), path) {
+                if c.due() {
+                    c.lines = matched;
+                    c.files = files;
+                    let saved = output_length(&mut stdout, opts)
+                        .and_then(|l| {
+                            c.output = l;
+                            c.progress(p, records.offset(), next_line)
+                        });
+                    if let Err(e) = saved {
+                        let checkpoint = opts.checkpoint.as_ref().unwrap();
+                        writeln!(io::stderr(), "{}", msg!(CantWriteFile, checkpoint, e));
+                        return EXIT_ERROR;
+                    }
+                }
+            }
+            if deadline.is_some_and(|d| time::precise_time_ns() >= d) {
+                stopped = Some((path.unwrap_or("-"), next_line + 1));
+                complete = false;
+                break;
+            }
+            let line = match records.next() {
+                Some(l) => l,
+                None => break,
+            };
+            let n = next_line;
+            next_line += 1;
+            let line = match line {
+                Ok(Some(l)) => l,
+                Ok(None) => {
+                    let limit = settings::size_name(opts.max_line.unwrap_or(0));
+                    writeln!(io::stderr(),
+                             "{}",
+                             msg!(LineTooLong, path.unwrap_or("-"), n + 1, limit));
+                    continue;
+                }
+                Err(e) => {
+                    writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                    status = EXIT_ERROR;
+                    complete = false;
+                    break;
+                }
+            };
+            let line = opts.normalization.apply(line.trim_end_matches('\r'));
+            if reg.is_match(&line) == opts.invert {
+                continue;
+            }
+            if let Some(ref t) = opts.time {
+                match t.time_of(&reg, &line) {
+                    Some(s) if t.contains(s) => {
+                        if let Some(b) = opts.bucket {
+                            *buckets.entry(b.label(s)).or_insert(0) += 1;
+                        }
+                    }
+                    Some(_) => continue,
+                    None => {
+                        untimed += 1;
+                        continue;
+                    }
+                }
+            }
+            if status == EXIT_NO_MATCH {
+                status = EXIT_MATCH;
+            }
+            matched += 1;
+            if opts.stats && !opts.invert {
+                stats.matches += reg.find_iter(&line).count();
+            }
+            if let Some(ref mut s) = store {
+                if let Err(e) = s.input(&output::Input::new(&reg, "", n + 1, &line)) {
+                    writeln!(io::stderr(), "{}", msg!(CantSave, e));
+                    return EXIT_ERROR;
+                }
+            }
+            if let Some(ref mut r) = runner {
+                let place = Place {
+                    file: path.unwrap_or("-"),
+                    line: n + 1,
+                };
+                for caps in reg.captures_iter(&line) {
+                    r.spawn(&place, &caps, &mut io::stderr());
+                }
+                continue;
+            }
+            if opts.output != Output::Text {
+                doc.input(&output::Input::new(&reg, path.unwrap_or("-"), n + 1, &line));
+                continue;
+            }
+            if opts.bucket.is_some() {
+                continue;
+            }
+            if opts.summary {
+                let file = path.unwrap_or("-");
+                if per_file.last().is_none_or(|f| f.0 != file) {
+                    per_file.push((file, 0, 0));
+                }
+                let values = extract(&reg, &line, opts.group.as_ref().map(|g| &g[..]));
+                let entry = per_file.last_mut().unwrap();
+                entry.1 += 1;
+                entry.2 += values.len();
+                for value in values {
+                    if !distinct.contains(value) {
+                        distinct.insert(value.to_owned());
+                    }
+                }
+                continue;
+            }
+            if opts.top.is_some() {
+                for value in extract(&reg, &line, opts.group.as_ref().map(|g| &g[..])) {
+                    let first = counts.len();
+                    counts.entry(value.to_owned()).or_insert((0, first)).0 += 1;
+                }
+                continue;
+            }
+
+            let mut prefix = String::new();
+            // Name the file unless it's the only one and was given
+            let given = path.is_none_or(|p| paths.contains(&p));
+            if names.len() > 1 || opts.recursive || !given {
+                prefix.push_str(path.unwrap_or("-"));
+                prefix.push(':');
+            }
+            if opts.line_numbers {
+                prefix.push_str(&format!("{}:", n + 1));
+            }
+            if let (Some(ref pieces), false) = (&opts.format, opts.invert) {
+                let place = Place {
+                    file: path.unwrap_or("-"),
+                    line: n + 1,
+                };
+                for caps in reg.captures_iter(&line) {
+                    writeln!(stdout, "{}", format::render(pieces, &place, &caps));
+                }
+            } else if opts.only_matching && !opts.invert {
+                for value in extract(&reg, &line, opts.group.as_ref().map(|g| &g[..])) {
+                    if hold {
+                        extracted.push((prefix.clone(), value.to_owned()));
+                    } else {
+                        write!(stdout, "{}{}{}", prefix, value, end);
+                    }
+                }
+            } else if let (Some(context), false) = (opts.window, opts.invert) {
+                let spans: Vec<_> = reg.find_iter(&line).map(|m| (m.start(), m.end())).collect();
+                for w in window::windows(&line, &spans, context, true) {
+                    writeln!(stdout, "{}{}:{}", prefix, w.column, w.text);
+                }
+            } else {
+                write!(stdout, "{}{}{}", prefix, line, end);
+            }
+        }
+        stats.bytes += records.offset() - offset;
+        if matched > lines_before {
+            stats.files_with_matches += 1;
+        }
+        if let (Some(c), Some(p)) = (checkpoint.as_mut(), path) {
+            c.lines = matched;
+            c.files = files;
+            let saved = output_length(&mut stdout, opts).and_then(|l| {
+                c.output = l;
+                match (complete, stopped) {
+                    (true, _) => c.finish_file(p),
+                    (false, Some(_)) => c.progress(p, records.offset(), next_line),
+                    (false, None) => Ok(()),
+                }
+            });
+            if let Err(e) = saved {
+                let checkpoint = opts.checkpoint.as_ref().unwrap();
+                writeln!(io::stderr(), "{}", msg!(CantWriteFile, checkpoint, e));
+                return EXIT_ERROR;
+            }
+        }
+        if stopped.is_some() {
+            break;
+        }
+    }
+    doc.finish();
+
+    if opts.unique {
+        let mut seen = HashSet::new();
+        extracted.retain(|e| seen.insert(e.1.clone()));
+    }
+    match opts.sort {
+        Some(Sort::Length) => extracted.sort_by_key(|e| e.1.chars().count()),
+        Some(Sort::Text) => extracted.sort_by(|a, b| a.1.cmp(&b.1)),
+        Some(Sort::Position) | None => {}
+    }
+    for (prefix, text) in extracted {
+        write!(stdout, "{}{}{}", prefix, text, end);
+    }
+    if !buckets.is_empty() {
+        writeln!(stdout, "{:16}  {:>7}", msg!(BucketTime), msg!(BucketCount));
+        for (label, count) in buckets {
+            writeln!(stdout, "{}  {:>7}", label, count);
+        }
+    }
+    if opts.summary {
+        per_file.sort_by_key(|&(_, _, matches)| usize::MAX - matches);
+        writeln!(stdout,
+                 "{:>7}  {:>7}  {}",
+                 msg!(SummaryLines),
+                 msg!(SummaryMatches),
+                 msg!(SummaryFile));
+        for &(file, lines, matches) in &per_file {
+            writeln!(stdout, "{:>7}  {:>7}  {}", lines, matches, file);
+        }
+        let matches: usize = per_file.iter().map(|f| f.2).sum();
+        writeln!(stdout,
+                 "{}",
+                 msg!(SummaryTotals, files, per_file.len(), matched, matches, distinct.len()));
+    }
+    if let (Some(ref t), true) = (&opts.time, untimed > 0) {
+        writeln!(io::stderr(), "{}", msg!(NoTimestamp, untimed, t.format));
+    }
+    if let Some(n) = opts.top {
+        let mut table: Vec<_> = counts.into_iter().collect();
+        table.sort_by_key(|&(_, (count, first))| (usize::MAX - count, first));
+        writeln!(stdout, "{}", msg!(TopHeader));
+        for (value, (count, _)) in table.into_iter().take(n) {
+            writeln!(stdout, "{:>7}  {}", count, value);
+        }
+    }
+
+    if let Some(s) = store {
+        if let Err(e) = s.finish() {
+            writeln!(io::stderr(), "{}", msg!(CantSave, e));
+            return EXIT_ERROR;
+        }
+    }
+    if let Some(mut r) = runner {
+        if finish_exec(&mut r) {
+            return EXIT_ERROR;
+        }
+    }
+    match (checkpoint, stopped) {
+        // Kept to carry on from
+        (Some(_), Some(_)) => {}
+        (Some(c), None) => {
+            c.remove();
+        }
+        (None, _) => {}
+    }
+    if let Some((file, line)) = stopped {
+        writeln!(io::stderr(),
+                 "{}",
+                 msg!(MaxTimeStopped, file, line, files, names.len().max(1), stats.bytes));
+    }
+    if opts.stats {
+        stats.lines = matched;
+        stats.files = files;
+        stats.write(&mut io::stderr(), started);
+    }
+    if let Some(ref path) = opts.output_file {
+        // A scan cut short gets no footer, so it isn't taken for a
+        // complete one
+        let footer = output::footer(opts.output, matched, files).filter(|_| stopped.is_none());
+        if let Some(footer) = footer {
+            writeln!(stdout, "{}", footer);
+        }
+        if let Err(e) = stdout.flush() {
+            writeln!(io::stderr(), "{}", msg!(CantWriteFile, path, e));
+            return EXIT_ERROR;
+        }
+    }
+    status
+}
+
+/// The length of the `--output-file` with everything written to
+/// `out` so far, to save in a checkpoint.
+fn output_length<W: Write>(out: &mut W, opts: &GrepOptions) -> io::Result<Option<u64>> {
+    match opts.output_file {
+        Some(ref path) => {
+            out.flush()?;
+            Ok(Some(fs::metadata(path)?.len()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Wait for the `--exec` commands left and report how many failed.
+/// Returns true if any did.
+fn finish_exec(runner: &mut Runner) -> bool {
+    runner.finish(&mut io::stderr());
+    if runner.failed > 0 {
+        writeln!(io::stderr(), "{}", msg!(ExecFailures, runner.failed, runner.started));
+    }
+    runner.failed > 0
+}
+
+/// The text of each match of `reg` in `line`, or of `group` in each
+/// match where it took part.
+fn extract<'t>(reg: &Regex, line: &'t str, group: Option<&str>) -> Vec<&'t str> {
+    let group = match group {
+        Some(g) => g,
+        None => return reg.find_iter(line).map(|m| m.as_str()).collect(),
+    };
+    reg.captures_iter(line)
+        .filter_map(|caps| match group.parse::<usize>() {
+            Ok(i) => caps.get(i),
+            Err(_) => caps.name(group),
+        })
+        .map(|m| m.as_str())
+        .collect()
+}
+
+/// Options for `replace`.
+pub struct ReplaceOptions {
+    /// Matches replaced per line, 0 for all of them.
+    pub limit: usize,
+    /// Explain how each replacement was built.
+    pub preview: bool,
+    /// The unit spans are shown in by `preview`.
+    pub offsets: Offsets,
+    /// Color the diffs shown by `preview`.
+    pub color: bool,
+    /// Print totals and how long the run took on stderr at the end.
+    pub stats: bool,
+}
+
+/// Replace matches of `pattern` in each line of `path`, or stdin if
+/// `None`, with `template`. Lines are normalized to `normalization`
+/// first. With `opts.preview`, explain each replacement on stderr.
+/// Returns the exit status.
+pub fn run_replace(pattern: &str,
+                   template: &str,
+                   path: Option<&str>,
+                   normalization: Normalization,
+                   opts: &ReplaceOptions)
+                   -> i32 {
+    let started = time::precise_time_ns();
+    let mut stdout = io::stdout();
+    let reg = match compile(pattern) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+    let reader = match open(path) {
+        Some(r) => r,
+        None => return EXIT_ERROR,
+    };
+
+    let mut status = EXIT_NO_MATCH;
+    let mut stats = Stats {
+        files: 1,
+        ..Stats::default()
+    };
+    for line in reader.lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                return EXIT_ERROR;
+            }
+        };
+        stats.bytes += line.len() as u64 + 1;
+        let line = normalization.apply(line.trim_end_matches('\r'));
+        if reg.is_match(&line) {
+            status = EXIT_MATCH;
+            stats.lines += 1;
+            if opts.stats {
+                let found = reg.find_iter(&line).count();
+                stats.matches += if opts.limit == 0 { found } else { found.min(opts.limit) };
+            }
+        }
+        writeln!(stdout, "{}", reg.replacen(&line, opts.limit, template));
+        if opts.preview {
+            write_replacement(&mut io::stderr(), &reg, &line, template, opts);
+        }
+    }
+    if opts.stats {
+        stats.files_with_matches = if stats.lines > 0 { 1 } else { 0 };
+        stats.write(&mut io::stderr(), started);
+    }
+    status
+}
+
+/// Run the suite files in `paths`, printing each failing case
+/// and a summary. Returns the exit status.
+pub fn run_test(paths: &[&str]) -> i32 {
+    let mut stdout = io::stdout();
+    let (mut total, mut passed) = (0, 0);
+    let mut status = EXIT_MATCH;
+    for path in paths {
+        let suite = match suite::load(path) {
+            Ok(s) => s,
+            Err(e) => {
+                writeln!(io::stderr(), "{}", e);
+                status = EXIT_ERROR;
+                continue;
+            }
+        };
+        let outcomes = suite::run(&suite);
+        total += outcomes.len();
+        passed += write_failures(&mut stdout, "", path, &suite, &outcomes);
+    }
+
+    writeln!(stdout, "{}", msg!(SuiteSummary, passed, total));
+    if status == EXIT_MATCH && passed < total {
+        status = EXIT_NO_MATCH;
+    }
+    status
+}
+
+/// Write a line starting with `indent` for each case of `suite`
+/// that didn't pass. Returns how many passed.
+fn write_failures(out: &mut dyn Write,
+                  indent: &str,
+                  path: &str,
+                  suite: &Suite,
+                  outcomes: &[Outcome])
+                  -> usize {
+    let mut passed = 0;
+    for (case, outcome) in suite.cases.iter().zip(outcomes) {
+        let input = format!("{:?}", case.input);
+        let line = match *outcome {
+            Outcome::Pass => {
+                passed += 1;
+                continue;
+            }
+            Outcome::Fail if case.should_match => {
+                msg!(SuiteShouldMatch, path, case.line, case.pattern, input)
+            }
+            Outcome::Fail => msg!(SuiteShouldNotMatch, path, case.line, case.pattern, input),
+            Outcome::Error(ref e) => {
+                format!("{}:{}: {}", path, case.line, msg!(ErrorCompiling, e))
+            }
+        };
+        writeln!(out, "{}{}", indent, line);
+    }
+    passed
+}
+
+/// Run every `.suite` file under `dir`, spread over `jobs` threads,
+/// then print each suite's result with its failures and a summary.
+/// Returns the exit status.
+pub fn run_verify(dir: &str, jobs: usize) -> i32 {
+    let mut stdout = io::stdout();
+    let paths = match suite::find(Path::new(dir)) {
+        Ok(p) => p,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantOpenFile, dir, e));
+            return EXIT_ERROR;
+        }
+    };
+    if paths.is_empty() {
+        writeln!(io::stderr(), "{}", msg!(VerifyNoSuites, dir));
+        return EXIT_ERROR;
+    }
+
+    // Deal the suites out to the threads, then put the results
+    // back in path order
+    let jobs = jobs.max(1).min(paths.len());
+    let mut shares: Vec<Vec<(usize, PathBuf)>> = vec![vec![]; jobs];
+    for (i, path) in paths.into_iter().enumerate() {
+        shares[i % jobs].push((i, path));
+    }
+    let handles: Vec<_> = shares.into_iter()
+        .map(|share| {
+            thread::spawn(move || {
+                share.into_iter()
+                    .map(|(i, path)| {
+                        let path = path.display().to_string();
+                        let result = suite::load(&path).map(|s| {
+                            let outcomes = suite::run(&s);
+                            (s, outcomes)
+                        });
+                        (i, path, result)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let mut results: Vec<_> = handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+    results.sort_by_key(|r| r.0);
+
+    let (mut suites_passed, mut total, mut passed) = (0, 0, 0);
+    let mut status = EXIT_MATCH;
+    for (_, path, result) in &results {
+        let (suite, outcomes) = match *result {
+            Ok((ref s, ref o)) => (s, o),
+            Err(ref e) => {
+                writeln!(stdout, "{}", e);
+                status = EXIT_ERROR;
+                continue;
+            }
+        };
+        let ok = outcomes.iter().filter(|o| **o == Outcome::Pass).count();
+        writeln!(stdout, "{}: {}", path, msg!(SuiteSummary, ok, outcomes.len()));
+        write_failures(&mut stdout, "  ", path, suite, outcomes);
+        total += outcomes.len();
+        passed += ok;
+        if ok == outcomes.len() {
+            suites_passed += 1;
+        }
+    }
+
+    writeln!(stdout,
+             "{}",
+             msg!(VerifySummary, suites_passed, results.len(), passed, total));
+    if status == EXIT_MATCH && passed < total {
+        status = EXIT_NO_MATCH;
+    }
+    status
+}
+
+/// Grade every submission in `dir` against the spec at `spec` and
+/// print a scorecard for each student, with the cases they got
+/// wrong. Returns the exit status: no match means someone didn't
+/// get full marks.
+pub fn run_grade(spec: &str, dir: &str) -> i32 {
+    let mut stdout = io::stdout();
+    let spec = match Spec::load(spec) {
+        Ok(s) => s,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", e);
+            return EXIT_ERROR;
+        }
+    };
+    let paths = match grade::submissions(Path::new(dir)) {
+        Ok(p) => p,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantOpenFile, dir, e));
+            return EXIT_ERROR;
+        }
+    };
+    if paths.is_empty() {
+        writeln!(io::stderr(), "{}", msg!(GradeNoSubmissions, dir));
+        return EXIT_ERROR;
+    }
+
+    let total = spec.cases.len();
+    if !spec.title.is_empty() {
+        writeln!(stdout, "{}", spec.title);
+    }
+    let cards: Vec<grade::Scorecard> = paths.iter().map(|p| grade::grade(&spec, p)).collect();
+    let width = cards.iter().map(|c| c.student.chars().count()).max().unwrap_or(0);
+    let mut perfect = 0;
+    for card in &cards {
+        let score = card.score(total);
+        writeln!(stdout,
+                 "{:<width$}  {:>3}/{}  {:>3}%",
+                 card.student,
+                 score,
+                 total,
+                 score * 100 / total,
+                 width = width);
+        if let Some(ref e) = card.error {
+            writeln!(stdout, "  {}", e.replace('\n', "\n  "));
+        }
+        for &i in &card.failed {
+            let case = &spec.cases[i];
+            let input = format!("{:?}", case.input);
+            if case.should_match {
+                writeln!(stdout, "{}", msg!(GradeShouldMatch, input));
+            } else {
+                writeln!(stdout, "{}", msg!(GradeShouldNotMatch, input));
+            }
+        }
+        if score == total {
+            perfect += 1;
+        }
+    }
+    let points: usize = cards.iter().map(|c| c.score(total)).sum();
+    writeln!(stdout,
+             "{}",
+             msg!(GradeSummary, perfect, cards.len(), points * 100 / (total * cards.len())));
+    if perfect < cards.len() { EXIT_NO_MATCH } else { EXIT_MATCH }
+}
+
+/// The non-empty lines of `path`, reporting any error on stderr.
+fn read_lines(path: &str) -> Option<Vec<String>> {
+    let mut lines = vec![];
+    for line in open(Some(path))?.lines() {
+        match line {
+            Ok(l) => {
+                let l = l.trim_end_matches('\r');
+                if !l.is_empty() {
+                    lines.push(l.to_owned());
+                }
+            }
+            Err(e) => {
+                writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+                return None;
+            }
+        }
+    }
+    Some(lines)
+}
+
+/// Print patterns matching every line of `good` and no line of
+/// `bad`, shortest first, at most `limit` of them. Returns the
+/// exit status.
+pub fn run_infer(good: &str, bad: Option<&str>, limit: usize) -> i32 {
+    let mut stdout = io::stdout();
+    let good = match read_lines(good) {
+        Some(g) => g,
+        None => return EXIT_ERROR,
+    };
+    let bad = match bad.map(read_lines) {
+        Some(Some(b)) => b,
+        Some(None) => return EXIT_ERROR,
+        None => vec![],
+    };
+
+    let patterns = infer::infer(&good, &bad);
+    if patterns.is_empty() {
+        writeln!(io::stderr(), "{}", msg!(InferNothing));
+        return EXIT_NO_MATCH;
+    }
+    for p in patterns.iter().take(limit) {
+        writeln!(stdout, "{}", p);
+    }
+    EXIT_MATCH
+}
+
+/// Cross-check `engines` on `count` random patterns, from `seed` if
+/// given, and print every disagreement found, made as small as it
+/// goes. Returns the exit status: no match means none were found.
+pub fn run_fuzz_engines(engines: &[Engine], count: usize, seed: Option<u64>) -> i32 {
+    let mut stdout = io::stdout();
+    if engines.len() < 2 {
+        writeln!(io::stderr(), "{}", msg!(EnginesTooFew));
+        return EXIT_ERROR;
+    }
+    let seed = seed.unwrap_or_else(time::precise_time_ns);
+    let names: Vec<&str> = engines.iter().map(|e| e.name()).collect();
+    writeln!(io::stderr(), "{}", msg!(EnginesFuzzing, names.join(", "), seed));
+    let report = engines::fuzz(engines, count, &mut sample::Rng::new(seed));
+    if report.disagreements.is_empty() {
+        writeln!(stdout, "{}", msg!(EnginesAgree, report.cases));
+        return EXIT_NO_MATCH;
+    }
+    for d in &report.disagreements {
+        writeln!(stdout,
+                 "{}",
+                 msg!(EnginesCase, format!("{:?}", d.pattern), format!("{:?}", d.input)));
+        for &(engine, ref outcome) in &d.outcomes {
+            let result = match *outcome {
+                None => msg!(EngineRejected).to_owned(),
+                Some(ref o) if !o.matched => msg!(EngineNoMatch).to_owned(),
+                Some(engines::Outcome { spans: Some(ref spans), .. }) => {
+                    let matches: Vec<String> = spans.iter()
+                        .map(|groups| {
+                            let groups: Vec<String> = groups.iter()
+                                .map(|g| g.map_or("-".to_owned(), |(s, e)| format!("{}..{}", s, e)))
+                                .collect();
+                            format!("({})", groups.join(" "))
+                        })
+                        .collect();
+                    matches.join(" ")
+                }
+                Some(_) => msg!(EngineMatched).to_owned(),
+            };
+            writeln!(stdout, "  {:<15} {}", engine.name(), result);
+        }
+    }
+    writeln!(stdout, "{}", msg!(EnginesDisagree, report.disagreements.len(), report.cases));
+    EXIT_MATCH
+}
+
+/// Time `pattern` against the contents of `path`, or stdin if
+/// `None`, searching through `api`. Input over `max_file` bytes is
+/// refused rather than read into memory. Returns the exit status.
+pub fn run_bench(pattern: &str,
+                 path: Option<&str>,
+                 iterations: u32,
+                 api: Api,
+                 max_file: Option<u64>)
+                 -> i32 {
+    let mut stdout = io::stdout();
+    let mut haystack = String::new();
+    let read = match open(path) {
+        Some(r) => {
+            // One byte past the limit is enough to know it's over
+            let limit = max_file.map_or(u64::MAX, |m| m.saturating_add(1));
+            r.take(limit).read_to_string(&mut haystack)
+        }
+        None => return EXIT_ERROR,
+    };
+    if max_file.is_some_and(|m| haystack.len() as u64 > m) {
+        let limit = settings::size_name(max_file.unwrap_or(0));
+        writeln!(io::stderr(), "{}", msg!(InputTooBig, path.unwrap_or("-"), limit));
+        return EXIT_ERROR;
+    }
+    if let Err(e) = read {
+        writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+        return EXIT_ERROR;
+    }
+
+    let t = match bench::run(pattern, &haystack, iterations, api) {
+        Ok(t) => t,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(ErrorCompiling, e));
+            return EXIT_ERROR;
+        }
+    };
+    writeln!(stdout, "{}", msg!(BenchCompile, t.compile_ns, t.iterations));
+    writeln!(stdout,
+             "{}",
+             msg!(BenchSearch,
+                  api.name(),
+                  t.search_ns,
+                  haystack.len(),
+                  t.matches,
+                  format!("{:.1}", t.throughput(haystack.len()))));
+    EXIT_MATCH
+}
+
+/// Write `input` with the first `opts.limit` matches of `reg`, or
+/// all of them if it's 0, replaced by `template`. With
+/// `opts.preview`, show the result as a word diff of `input`, or
+/// `input` and the result on lines of their own when they're too
+/// long to diff, then what each match became and how every group
+/// reference in the template resolved.
+pub fn write_replacement(out: &mut dyn Write,
+                         reg: &Regex,
+                         input: &str,
+                         template: &str,
+                         opts: &ReplaceOptions) {
+    let replaced = reg.replacen(input, opts.limit, template);
+    if !opts.preview {
+        writeln!(out, "{}", msg!(Replaced, replaced));
+        return;
+    }
+    match diff::words(input, &replaced) {
+        Some(changes) => {
+            writeln!(out, "{}", msg!(Replaced, diff::render(&changes, opts.color)));
+        }
+        None => {
+            writeln!(out, "{}", msg!(ReplacedFrom, input));
+            writeln!(out, "{}", msg!(Replaced, replaced));
+        }
+    }
+
+    let pieces = template::parse(template);
+    let limit = if opts.limit == 0 { usize::MAX } else { opts.limit };
+    for (i, caps) in reg.captures_iter(input).take(limit).enumerate() {
+        let m = caps.get(0).unwrap();
+        let mut expanded = String::new();
+        caps.expand(template, &mut expanded);
+        writeln!(out,
+                 "{}",
+                 msg!(PreviewMatch,
+                      i + 1,
+                      format!("{:?}", m.as_str()),
+                      opts.offsets.span(input, m.start(), m.end()),
+                      format!("{:?}", expanded)));
+        for (written, name, resolved) in template::resolve(reg, &caps, &pieces) {
+            let line = match resolved {
+                Resolved::Text(t) => msg!(PreviewRef, written, format!("{:?}", t)),
+                Resolved::Unset => msg!(PreviewUnset, written, name),
+                Resolved::Missing => msg!(PreviewMissing, written, name),
+            };
+            writeln!(out, "  {}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn written<F: FnOnce(&mut Vec<u8>)>(f: F) -> String {
+        let mut out = vec![];
+        f(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_every_capture() {
+        let reg = Regex::new(r"(\d)(x)?").unwrap();
+        let text = written(|out| assert!(write_match(out, &reg, "1 2x", false)));
+        assert_eq!(text, "Matched\n0:0: 1\n0:1: 1\n0:2: None\n1:0: 2x\n1:1: 2\n1:2: x\n");
+        let text = written(|out| assert!(!write_match(out, &reg, "none", false)));
+        assert_eq!(text, "Failed to match\n");
+
+        let text = written(|out| assert!(write_match(out, &reg, "1", true)));
+        assert_eq!(text,
+                   "Matched\nMatch 1: match from column 1 to 1: \"1\"; group 1 from column 1 to \
+                    1: \"1\"; group 2 did not match\n");
+    }
+
+    fn replace_options(preview: bool) -> ReplaceOptions {
+        ReplaceOptions {
+            limit: 0,
+            preview,
+            offsets: Offsets::Bytes,
+            color: false,
+            stats: false,
+        }
+    }
+
+    #[test]
+    fn previews_each_replacement() {
+        let reg = Regex::new(r"(?P<n>\d+)(x)?").unwrap();
+        let opts = replace_options(false);
+        let text = written(|out| write_replacement(out, &reg, "a 12", "<$n$2>", &opts));
+        assert_eq!(text, "Replaced: a <12>\n");
+
+        let opts = replace_options(true);
+        let text = written(|out| write_replacement(out, &reg, "a 12", "<$n$2>", &opts));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Replaced: a {+<+}12{+>+}");
+        assert_eq!(lines[1], "Match 1: \"12\" at 2..4 becomes \"<12>\"");
+        assert_eq!(&lines[2..], ["  $n = \"12\"", "  $2 = \"\" (group 2 did not match)"]);
+    }
+
+    #[test]
+    fn extracts_matches_or_groups() {
+        let reg = Regex::new(r"(?P<key>\w+)=(\d+)?").unwrap();
+        assert_eq!(extract(&reg, "a=1 b= c=3", None), ["a=1", "b=", "c=3"]);
+        assert_eq!(extract(&reg, "a=1 b= c=3", Some("key")), ["a", "b", "c"]);
+        assert_eq!(extract(&reg, "a=1 b= c=3", Some("2")), ["1", "3"]);
+        assert!(extract(&reg, "a=1", Some("nope")).is_empty());
+    }
+
+    #[test]
+    fn sorts_by_name() {
+        assert_eq!(Sort::from_name("length"), Some(Sort::Length));
+        assert_eq!(Sort::from_name("position"), Some(Sort::Position));
+        assert_eq!(Sort::from_name("text"), Some(Sort::Text));
+        assert_eq!(Sort::from_name("nope"), None);
+    }
+
+    #[test]
+    fn spots_binary_input() {
+        let mut text: &[u8] = b"plain text\n";
+        assert!(!looks_binary(&mut text));
+        let mut binary: &[u8] = b"ELF\0\x01";
+        assert!(looks_binary(&mut binary));
+        assert_eq!(binary.len(), 5);
+    }
+
+    #[test]
+    fn expands_directories_in_order() {
+        let dir = env::temp_dir().join(format!("regtest-batch-{}", process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in &["b.txt", "a.txt", "sub/c.txt"] {
+            fs::write(dir.join(name), "x\n").unwrap();
+        }
+        let top = dir.display().to_string();
+        let (files, failed) = expand(&[&top], true);
+        let names: Vec<&str> = files.iter().map(|f| &f[top.len() + 1..]).collect();
+        assert_eq!(names, ["a.txt", "b.txt", "sub/c.txt"]);
+        assert!(!failed);
+        // Without recursing, a directory is left as it is
+        assert_eq!(expand(&[&top], false).0, [&top[..]]);
+        fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn writes_only_the_failures() {
+        let text = "pattern a\nmatch a\nmatch b\nnomatch a\npattern (\nmatch x\n";
+        let suite = suite::parse("s", text).unwrap();
+        let outcomes = suite::run(&suite);
+        let mut passed = 0;
+        let text = written(|out| passed = write_failures(out, "  ", "s.suite", &suite, &outcomes));
+        assert_eq!(passed, 1);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[..2],
+                   ["  s.suite:3: a should match \"b\"", "  s.suite:4: a should not match \"a\""]);
+        // The compile error goes on for a few lines
+        assert!(lines[2].starts_with("  s.suite:6: "));
+        assert_eq!(lines.iter().filter(|l| l.starts_with("  s.suite:")).count(), 3);
+    }
+}
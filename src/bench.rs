@@ -0,0 +1,212 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Rough timings for compiling a pattern and searching a haystack
+//! with it.
+
+use std::hint::black_box;
+
+use regex::Regex;
+use time;
+
+/// The API searches are timed through.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Api {
+    /// `find_iter`, spans of whole matches only.
+    Find,
+    /// `captures_iter`, allocating `Captures` for each match.
+    Captures,
+    /// `captures_read_at` into one reused `CaptureLocations`.
+    Locations,
+}
+
+/// Every API and its name.
+pub const APIS: &[(&str, Api)] = &[("find", Api::Find),
+                                   ("captures", Api::Captures),
+                                   ("locations", Api::Locations)];
+
+impl Api {
+    pub fn from_name(name: &str) -> Option<Api> {
+        APIS.iter().find(|&&(n, _)| n == name).map(|&(_, a)| a)
+    }
+
+    pub fn name(self) -> &'static str {
+        APIS.iter().find(|&&(_, a)| a == self).unwrap().0
+    }
+
+    /// Count the matches of `reg` in `haystack` through this API.
+    fn count(self, reg: &Regex, haystack: &str) -> usize {
+        match self {
+            Api::Find => reg.find_iter(haystack).count(),
+            Api::Captures => reg.captures_iter(haystack).count(),
+            Api::Locations => count_locations(reg, haystack),
+        }
+    }
+}
+
+/// Count matches the way `find_iter` does, but with
+/// `captures_read_at`, so an empty match right after another one
+/// is skipped and the search steps past empty matches.
+fn count_locations(reg: &Regex, haystack: &str) -> usize {
+    let mut locs = reg.capture_locations();
+    let next = |pos: usize| pos + haystack[pos..].chars().next().map_or(1, |c| c.len_utf8());
+    let (mut at, mut last_end, mut count) = (0, None, 0);
+    while at <= haystack.len() {
+        let m = match reg.captures_read_at(&mut locs, haystack, at) {
+            Some(m) => m,
+            None => break,
+        };
+        let empty = m.start() == m.end();
+        if empty && last_end == Some(m.end()) {
+            at = next(at);
+            continue;
+        }
+        count += 1;
+        last_end = Some(m.end());
+        at = if empty { next(m.end()) } else { m.end() };
+    }
+    count
+}
+
+/// Mean timings over a number of runs.
+pub struct Timings {
+    pub iterations: u32,
+    /// Nanoseconds per compile.
+    pub compile_ns: u64,
+    /// Nanoseconds per pass finding every match in the haystack.
+    pub search_ns: u64,
+    /// Matches found in one pass.
+    pub matches: usize,
+}
+
+impl Timings {
+    /// Search throughput in megabytes per second.
+    pub fn throughput(&self, haystack_len: usize) -> f64 {
+        if self.search_ns == 0 {
+            return 0.0;
+        }
+        haystack_len as f64 / self.search_ns as f64 * 1e9 / 1e6
+    }
+}
+
+/// Time `iterations` compiles of `pattern` and as many searches
+/// through `haystack` using `api`.
+pub fn run(pattern: &str, haystack: &str, iterations: u32, api: Api) -> Result<Timings, String> {
+    let iterations = iterations.max(1);
+    let start = time::precise_time_ns();
+    for _ in 0..iterations {
+        Regex::new(pattern).map_err(|e| e.to_string())?;
+    }
+    let compile_ns = (time::precise_time_ns() - start) / u64::from(iterations);
+
+    let reg = Regex::new(pattern).map_err(|e| e.to_string())?;
+    let mut matches = 0;
+    let start = time::precise_time_ns();
+    for _ in 0..iterations {
+        matches = api.count(&reg, haystack);
+    }
+    let search_ns = (time::precise_time_ns() - start) / u64::from(iterations);
+
+    Ok(Timings {
+        iterations,
+        compile_ns,
+        search_ns,
+        matches,
+    })
+}
+
+/// How long each API is timed for in `apis`, in nanoseconds.
+const APIS_TARGET_NS: u64 = 20_000_000;
+
+/// Mean nanoseconds of one `is_match`, `find` and `captures` of `reg`
+/// on `input`, named, and the runs they're the mean of. Runs double
+/// until `captures`, the slowest, takes long enough to time.
+pub fn apis(reg: &Regex, input: &str) -> (Vec<(&'static str, u64)>, u32) {
+    let time = |runs: u32, f: &dyn Fn() -> bool| {
+        let start = time::precise_time_ns();
+        for _ in 0..runs {
+            black_box(f());
+        }
+        time::precise_time_ns() - start
+    };
+    let is_match = || reg.is_match(black_box(input));
+    let find = || reg.find(black_box(input)).is_some();
+    let captures = || reg.captures(black_box(input)).is_some();
+    let mut runs = 1;
+    while runs < 1 << 20 && time(runs, &captures) < APIS_TARGET_NS / 4 {
+        runs *= 2;
+    }
+    let mean = |f: &dyn Fn() -> bool| time(runs, f) / u64::from(runs);
+    (vec![("is_match", mean(&is_match)), ("find", mean(&find)), ("captures", mean(&captures))],
+     runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_every_api() {
+        for &(name, api) in APIS {
+            assert_eq!(Api::from_name(name), Some(api));
+            assert_eq!(api.name(), name);
+        }
+        assert_eq!(Api::from_name("nope"), None);
+    }
+
+    #[test]
+    fn every_api_counts_the_same_matches() {
+        for &(pattern, haystack, n) in &[(r"\d+", "a1 22 333", 3),
+                                          ("a*", "baaac", 3),
+                                          ("", "héllo", 6),
+                                          ("x", "none", 0)] {
+            let reg = Regex::new(pattern).unwrap();
+            assert_eq!(reg.find_iter(haystack).count(), n, "{}", pattern);
+            for &(name, api) in APIS {
+                assert_eq!(api.count(&reg, haystack), n, "{} with {}", pattern, name);
+            }
+        }
+    }
+
+    #[test]
+    fn runs_at_least_once() {
+        let t = run(r"\w+", "two words", 0, Api::Find).unwrap();
+        assert_eq!((t.iterations, t.matches), (1, 2));
+        assert!(run("(", "", 1, Api::Find).is_err());
+    }
+
+    #[test]
+    fn throughput_is_megabytes_per_second() {
+        let t = Timings {
+            iterations: 1,
+            compile_ns: 0,
+            search_ns: 1_000_000,
+            matches: 0,
+        };
+        assert_eq!(t.throughput(2_000_000), 2000.0);
+        assert_eq!(Timings { search_ns: 0, ..t }.throughput(10), 0.0);
+    }
+
+    #[test]
+    fn times_each_api_by_name() {
+        let reg = Regex::new("b").unwrap();
+        let (times, runs) = apis(&reg, "abc");
+        let names: Vec<&str> = times.iter().map(|t| t.0).collect();
+        assert_eq!(names, ["is_match", "find", "captures"]);
+        assert!(runs >= 1);
+    }
+}
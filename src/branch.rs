@@ -0,0 +1,87 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Find out which branch of a top-level alternation matched by
+//! compiling a copy of the pattern with each branch wrapped in a
+//! hidden named group.
+
+use regex::Regex;
+use regex_syntax::Parser;
+use regex_syntax::ast::{self, Ast};
+use regex_syntax::hir::{Group, GroupKind, Hir, HirKind};
+
+const GROUP_PREFIX: &str = "__regtest_branch";
+
+/// An instrumented copy of a pattern with a top-level alternation.
+pub struct Branches {
+    regex: Regex,
+    /// The source text of each branch.
+    texts: Vec<String>,
+}
+
+impl Branches {
+    /// Returns `None` if `pattern` isn't a top-level alternation.
+    pub fn new(pattern: &str) -> Option<Branches> {
+        let hir = Parser::new().parse(pattern).ok()?;
+        let branches = match hir.into_kind() {
+            HirKind::Alternation(hirs) => hirs,
+            _ => return None,
+        };
+
+        // Prefer the branches as the user wrote them
+        let mut texts: Vec<String> = branches.iter().map(|h| h.to_string()).collect();
+        if let Ok(Ast::Alternation(ref alt)) = ast::parse::Parser::new().parse(pattern) {
+            if alt.asts.len() == texts.len() {
+                texts = alt.asts
+                    .iter()
+                    .map(|a| {
+                        let span = a.span();
+                        pattern[span.start.offset..span.end.offset].to_owned()
+                    })
+                    .collect();
+            }
+        }
+
+        let wrapped = branches.into_iter()
+            .enumerate()
+            .map(|(i, h)| {
+                Hir::group(Group {
+                    kind: GroupKind::CaptureName {
+                        name: format!("{}{}", GROUP_PREFIX, i),
+                        index: 0,
+                    },
+                    hir: Box::new(h),
+                })
+            })
+            .collect();
+        let regex = Regex::new(&Hir::alternation(wrapped).to_string()).ok()?;
+        Some(Branches { regex, texts })
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// The index and text of the branch taken by the first match
+    /// in `input`.
+    pub fn which(&self, input: &str) -> Option<(usize, &str)> {
+        let caps = self.regex.captures(input)?;
+        (0..self.texts.len())
+            .find(|i| caps.name(&format!("{}{}", GROUP_PREFIX, i)).is_some())
+            .map(|i| (i, &self.texts[i][..]))
+    }
+}
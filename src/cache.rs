@@ -0,0 +1,170 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Regexes compiled during a session, so going back to a pattern
+//! doesn't compile it again.
+
+use std::collections::{HashMap, VecDeque};
+
+use regex::{self, Regex};
+use time::{self, Duration};
+
+use flags::Flags;
+use logging;
+use settings;
+
+/// The most regexes kept. The oldest is dropped to make room.
+const CACHE_LIMIT: usize = 64;
+
+/// What a regex is cached under.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Key {
+    pattern: String,
+    flags: Flags,
+    engine: &'static str,
+}
+
+/// A compiled regex and how long compiling it took.
+struct Entry {
+    regex: Regex,
+    took: Duration,
+}
+
+pub struct Cache {
+    entries: HashMap<Key, Entry>,
+    /// Keys in the order they were added, oldest first.
+    order: VecDeque<Key>,
+    pub hits: usize,
+    pub misses: usize,
+    /// Compile time spent on the first compile of every hit.
+    pub saved: Duration,
+}
+
+impl Cache {
+    pub fn new() -> Cache {
+        Cache {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+            saved: Duration::zero(),
+        }
+    }
+
+    /// The regex for `pattern` with `flags`, compiling it if it
+    /// isn't cached. The duration is how long compiling took, or
+    /// `None` if the regex came from the cache. Errors aren't cached.
+    pub fn compile(&mut self,
+                   pattern: &str,
+                   flags: Flags)
+                   -> (Result<Regex, regex::Error>, Option<Duration>) {
+        let key = Key {
+            pattern: pattern.to_owned(),
+            flags,
+            engine: settings::ENGINE,
+        };
+        if let Some(e) = self.entries.get(&key) {
+            debug!("{:?} with flags {} from the cache", pattern, flags.name());
+            self.hits += 1;
+            self.saved = self.saved + e.took;
+            return (Ok(e.regex.clone()), None);
+        }
+
+        self.misses += 1;
+        let t1 = time::now();
+        let reg = flags.build(pattern);
+        let took = time::now() - t1;
+        debug!("compiled {:?} with flags {} on the {} engine in {:.3}ms{}",
+               pattern,
+               flags.name(),
+               settings::ENGINE,
+               logging::ms(took),
+               if reg.is_ok() { "" } else { ", failed" });
+        if let Ok(ref r) = reg {
+            if self.order.len() == CACHE_LIMIT {
+                let oldest = self.order.pop_front().unwrap();
+                self.entries.remove(&oldest);
+            }
+            self.order.push_back(key.clone());
+            self.entries.insert(key,
+                                Entry {
+                                    regex: r.clone(),
+                                    took,
+                                });
+        }
+        (reg, Some(took))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every regex and reset the counts.
+    pub fn clear(&mut self) {
+        *self = Cache::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_each_pattern_and_flags_once() {
+        let mut cache = Cache::new();
+        let (reg, took) = cache.compile("a+", Flags::default());
+        assert_eq!(reg.unwrap().as_str(), "a+");
+        assert!(took.is_some());
+        let (reg, took) = cache.compile("a+", Flags::default());
+        assert!(reg.unwrap().is_match("aa"));
+        assert!(took.is_none());
+
+        // Other flags are compiled on their own
+        let (reg, took) = cache.compile("a+", Flags::default() | Flags::CASE_INSENSITIVE);
+        assert!(reg.unwrap().is_match("A"));
+        assert!(took.is_some());
+        assert_eq!((cache.len(), cache.hits, cache.misses), (2, 1, 2));
+    }
+
+    #[test]
+    fn errors_are_not_cached() {
+        let mut cache = Cache::new();
+        assert!(cache.compile("(", Flags::default()).0.is_err());
+        assert!(cache.compile("(", Flags::default()).0.is_err());
+        assert!(cache.is_empty());
+        assert_eq!((cache.hits, cache.misses), (0, 2));
+    }
+
+    #[test]
+    fn drops_the_oldest_when_full() {
+        let mut cache = Cache::new();
+        for i in 0..CACHE_LIMIT + 1 {
+            cache.compile(&format!("a{{{}}}", i), Flags::default()).0.unwrap();
+        }
+        assert_eq!(cache.len(), CACHE_LIMIT);
+        assert!(cache.compile("a{1}", Flags::default()).1.is_none());
+        assert!(cache.compile("a{0}", Flags::default()).1.is_some());
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!((cache.hits, cache.misses), (0, 0));
+    }
+}
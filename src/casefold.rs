@@ -0,0 +1,175 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Helpers for showing what case-insensitive matching does to
+//! a pattern.
+
+use regex_syntax::ParserBuilder;
+use regex_syntax::hir::{Class, ClassUnicode, ClassUnicodeRange, Hir, HirKind, Literal};
+
+/// Classes larger than this are not inspected character by
+/// character for surprising folds.
+const MAX_CLASS_CHARS: u32 = 512;
+
+/// All characters `c` is equal to under simple case folding,
+/// including `c` itself.
+pub fn simple_fold(c: char) -> Vec<char> {
+    let mut class = ClassUnicode::new(vec![ClassUnicodeRange::new(c, c)]);
+    class.case_fold_simple();
+    class.iter()
+        .flat_map(|r| r.start() as u32..r.end() as u32 + 1)
+        .filter_map(::std::char::from_u32)
+        .collect()
+}
+
+/// `c` under simple case folding, which maps one character to one
+/// and is all `(?i)` does: its lowercase form, if that's a single
+/// character `c` folds with. `İ` stays as it is.
+pub fn simple_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) if simple_fold(c).contains(&l) => l,
+        _ => c,
+    }
+}
+
+/// `text` under simple case folding.
+pub fn simple(text: &str) -> String {
+    text.chars().map(simple_char).collect()
+}
+
+/// `text` under full case folding, which can turn one character into
+/// several, like `ß` into `ss`. This is the lowercase of the
+/// uppercase, which agrees with Unicode's full folding except for a
+/// few scripts like Cherokee that fold to uppercase.
+pub fn full(text: &str) -> String {
+    text.chars().flat_map(char::to_uppercase).flat_map(char::to_lowercase).collect()
+}
+
+/// Characters that `c` folds to which aren't just its ordinary
+/// upper or lower case form, e.g. `ſ` for `s`.
+pub fn surprising_folds(c: char) -> Vec<char> {
+    simple_fold(c)
+        .into_iter()
+        .filter(|&f| {
+            f != c && !c.to_uppercase().eq(Some(f)) && !c.to_lowercase().eq(Some(f))
+        })
+        .collect()
+}
+
+/// Parse `pattern` as if `(?i)` was set and return the resulting
+/// expression.
+pub fn fold_pattern(pattern: &str) -> Result<Hir, String> {
+    ParserBuilder::new()
+        .case_insensitive(true)
+        .build()
+        .parse(pattern)
+        .map_err(|e| e.to_string())
+}
+
+/// Every character appearing literally (or in a small class) in
+/// `pattern`, in order of first appearance.
+pub fn pattern_chars(pattern: &str) -> Result<Vec<char>, String> {
+    let hir = ParserBuilder::new().build().parse(pattern).map_err(|e| e.to_string())?;
+    let mut chars = vec![];
+    collect_chars(&hir, &mut chars);
+    Ok(chars)
+}
+
+fn collect_chars(hir: &Hir, out: &mut Vec<char>) {
+    fn push(c: char, out: &mut Vec<char>) {
+        if !out.contains(&c) {
+            out.push(c);
+        }
+    }
+
+    match *hir.kind() {
+        HirKind::Literal(Literal::Unicode(c)) => push(c, out),
+        HirKind::Class(Class::Unicode(ref cls)) => {
+            let size: u32 = cls.iter().map(|r| r.end() as u32 - r.start() as u32 + 1).sum();
+            if size <= MAX_CLASS_CHARS {
+                for r in cls.iter() {
+                    for c in (r.start() as u32..r.end() as u32 + 1)
+                        .filter_map(::std::char::from_u32) {
+                        push(c, out);
+                    }
+                }
+            }
+        }
+        HirKind::Repetition(ref rep) => collect_chars(&rep.hir, out),
+        HirKind::Group(ref group) => collect_chars(&group.hir, out),
+        HirKind::Concat(ref hirs) |
+        HirKind::Alternation(ref hirs) => {
+            for h in hirs {
+                collect_chars(h, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Format a character along with its code point, e.g. `'K' (U+212A)`.
+pub fn describe(c: char) -> String {
+    format!("{:?} (U+{:04X})", c, c as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_simply() {
+        assert_eq!(simple_fold('k'), vec!['K', 'k', '\u{212A}']);
+        assert_eq!(simple_fold('1'), vec!['1']);
+        assert_eq!(simple("ÉCOLE Straße"), "école straße");
+        // The dotted capital I has no single character lowercase
+        // it folds with
+        assert_eq!(simple_char('\u{130}'), '\u{130}');
+    }
+
+    #[test]
+    fn folds_fully() {
+        assert_eq!(full("Straße"), "strasse");
+        assert_eq!(full("ΣΊΣΥΦΟΣ"), "σίσυφοσ");
+    }
+
+    #[test]
+    fn finds_surprising_folds() {
+        assert_eq!(surprising_folds('s'), vec!['\u{17F}']);
+        assert_eq!(surprising_folds('k'), vec!['\u{212A}']);
+        assert!(surprising_folds('a').is_empty());
+    }
+
+    #[test]
+    fn collects_pattern_chars() {
+        assert_eq!(pattern_chars("ab|[bc]+").unwrap(), vec!['a', 'b', 'c']);
+        // Large classes are skipped
+        assert_eq!(pattern_chars(r"x\w").unwrap(), vec!['x']);
+        assert!(pattern_chars("(").is_err());
+    }
+
+    #[test]
+    fn folds_patterns() {
+        assert!(fold_pattern("k").unwrap().to_string().contains("K"));
+        assert!(fold_pattern("[").is_err());
+    }
+
+    #[test]
+    fn describes_chars() {
+        assert_eq!(describe('\u{212A}'), "'\u{212A}' (U+212A)");
+    }
+}
@@ -0,0 +1,128 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A short reference for the syntax supported by the regex crate.
+
+const CLASSES: &str = "\
+.             any character except new line (includes new line with s flag)
+[xyz]         a character class matching x, y or z
+[^xyz]        any character except x, y or z
+[a-z]         a character in the range a to z
+[[:alpha:]]   ASCII class ([A-Za-z])
+[x[^xyz]]     nested/grouping class (matches any character except y and z)
+[a-y&&xyz]    intersection (matches x or y)
+[0-9&&[^4]]   subtraction using intersection and negation
+[0-9--4]      direct subtraction (matches 0-9 except 4)
+[a-g~~b-h]    symmetric difference (matches a and h only)
+\\d            digit (\\p{Nd})
+\\D            not digit
+\\s            whitespace (\\p{White_Space})
+\\S            not whitespace
+\\w            word character (\\p{Alphabetic} + \\p{M} + \\d + \\p{Pc} + \\p{Join_Control})
+\\W            not word character";
+
+const ANCHORS: &str = "\
+^             beginning of text (or start of line with m flag)
+$             end of text (or end of line with m flag)
+\\A            only the beginning of text (even with m flag)
+\\z            only the end of text (even with m flag)
+\\b            Unicode word boundary
+\\B            not a Unicode word boundary";
+
+const GROUPS: &str = "\
+(exp)          numbered capture group (indexed by opening parenthesis)
+(?P<name>exp)  named (also numbered) capture group
+(?:exp)        non-capturing group
+(?flags)       set flags within current group
+(?flags:exp)   set flags for exp (non-capturing)
+x|y            alternation, prefers x
+               look-around and backreferences are not supported";
+
+const QUANTIFIERS: &str = "\
+x*        zero or more of x (greedy)
+x+        one or more of x (greedy)
+x?        zero or one of x (greedy)
+x*?       zero or more of x (ungreedy/lazy)
+x+?       one or more of x (ungreedy/lazy)
+x??       zero or one of x (ungreedy/lazy)
+x{n,m}    at least n x and at most m x (greedy)
+x{n,}     at least n x (greedy)
+x{n}      exactly n x
+x{n,m}?   at least n x and at most m x (ungreedy/lazy)
+x{n,}?    at least n x (ungreedy/lazy)
+x{n}?     exactly n x
+          x{,m} is not supported, write x{0,m}";
+
+const FLAGS: &str = "\
+i     case-insensitive: letters match both upper and lower case
+m     multi-line mode: ^ and $ match begin/end of line
+s     allow . to match \\n
+U     swap the meaning of x* and x*?
+u     Unicode support (enabled by default)
+x     ignore whitespace and allow line comments (starting with `#`)
+      e.g. (?i)a+(?-i)b+ or (?x: a + b )";
+
+const ESCAPES: &str = "\
+\\*          literal *, works for any punctuation character: \\.+*?()|[]{}^$
+\\a          bell (\\x07)
+\\f          form feed (\\x0C)
+\\t          horizontal tab
+\\n          new line
+\\r          carriage return
+\\v          vertical tab (\\x0B)
+\\123        octal character code (up to three digits, when enabled)
+\\x7F        hex character code (exactly two digits)
+\\x{10FFFF}  any hex character code corresponding to a Unicode code point
+\\u007F      hex character code (exactly four digits)
+\\U0010FFFF  hex character code (exactly eight digits)";
+
+const UNICODE: &str = "\
+\\pN              one-letter name Unicode character class
+\\p{Greek}        Unicode character class (general category or script)
+\\PN              negated one-letter name Unicode character class
+\\P{Greek}        negated Unicode character class
+\\p{Lu}           uppercase letter (general category)
+\\p{Script=Latin} explicit property name
+\\p{sc!=Greek}    negated property value
+(?-u:\\w)         ASCII only word character; disable Unicode with the u flag";
+
+/// Topic names and their reference text.
+pub const SECTIONS: &[(&str, &str)] = &[
+    ("classes", CLASSES),
+    ("anchors", ANCHORS),
+    ("groups", GROUPS),
+    ("quantifiers", QUANTIFIERS),
+    ("flags", FLAGS),
+    ("escapes", ESCAPES),
+    ("unicode", UNICODE),
+];
+
+/// Find the section whose name starts with `topic`.
+pub fn section(topic: &str) -> Option<(&'static str, &'static str)> {
+    let topic = topic.to_lowercase();
+    SECTIONS.iter().cloned().find(|&(name, _)| name.starts_with(&topic[..]))
+}
+
+/// Every reference line mentioning `keyword`, along with the
+/// section it's in.
+pub fn search(keyword: &str) -> Vec<(&'static str, &'static str)> {
+    let keyword = keyword.to_lowercase();
+    SECTIONS.iter()
+        .flat_map(|&(name, text)| text.lines().map(move |l| (name, l)))
+        .filter(|&(_, line)| line.to_lowercase().contains(&keyword[..]))
+        .collect()
+}
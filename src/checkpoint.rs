@@ -0,0 +1,232 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Checkpoints of long scans, so an interrupted one can be resumed
+//! where it stopped. The state is a JSON file listing the files
+//! done, how far into the current one the scan got and how long the
+//! `--output-file` was then:
+//!
+//! ```text
+//! {"pattern": "ERROR", "done": ["a.log"], "file": "b.log", "offset": 5120, "line": 87,
+//!  "lines": 12, "files": 1, "output": 1480}
+//! ```
+//!
+//! It's saved after each file, every few seconds in between and when
+//! the scan stops at `--max-time`, and removed once the scan
+//! completes. A resumed scan cuts the output file back to the length
+//! saved, so results written after the last save aren't repeated.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+
+use json;
+use output;
+use time;
+
+/// Nanoseconds between saves within a file.
+const INTERVAL_NS: u64 = 5_000_000_000;
+
+pub struct Checkpoint {
+    path: String,
+    pattern: String,
+    done: Vec<String>,
+    /// The file being scanned, with the byte offset and number of
+    /// records read up to the last save.
+    file: Option<String>,
+    offset: u64,
+    line: usize,
+    /// Matching lines and files read before the last save.
+    pub lines: usize,
+    pub files: usize,
+    /// The length of the output file at the last save, if there is
+    /// one.
+    pub output: Option<u64>,
+    saved_at: u64,
+}
+
+impl Checkpoint {
+    /// A scan with `pattern` from the start, saved to `path`.
+    pub fn new(path: &str, pattern: &str) -> Checkpoint {
+        Checkpoint {
+            path: path.to_owned(),
+            pattern: pattern.to_owned(),
+            done: vec![],
+            file: None,
+            offset: 0,
+            line: 0,
+            lines: 0,
+            files: 0,
+            output: None,
+            saved_at: time::precise_time_ns(),
+        }
+    }
+
+    /// The scan saved to `path`, or a new one if there's no such
+    /// file. It must be for the same `pattern`.
+    pub fn resume(path: &str, pattern: &str) -> Result<Checkpoint, String> {
+        let mut text = String::new();
+        match File::open(path).and_then(|mut f| f.read_to_string(&mut text)) {
+            Ok(_) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Checkpoint::new(path, pattern))
+            }
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        }
+        let doc = json::parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+        let number = |key: &str| doc.get(key).and_then(json::Value::as_number).unwrap_or(0.0);
+        let saved = doc.get("pattern").and_then(json::Value::as_str).unwrap_or("");
+        if saved != pattern {
+            return Err(msg!(CheckpointOtherPattern, path, format!("{:?}", saved)));
+        }
+        let done = doc.get("done")
+            .and_then(json::Value::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|d| d.as_str().map(str::to_owned))
+            .collect();
+        Ok(Checkpoint {
+            done,
+            file: doc.get("file").and_then(json::Value::as_str).map(str::to_owned),
+            offset: number("offset") as u64,
+            line: number("line") as usize,
+            lines: number("lines") as usize,
+            files: number("files") as usize,
+            output: doc.get("output").and_then(json::Value::as_number).map(|n| n as u64),
+            ..Checkpoint::new(path, pattern)
+        })
+    }
+
+    /// Was `file` scanned completely before?
+    pub fn is_done(&self, file: &str) -> bool {
+        self.done.iter().any(|d| d == file)
+    }
+
+    /// The byte offset and number of records to start `file` at.
+    pub fn start(&self, file: &str) -> (u64, usize) {
+        match self.file {
+            Some(ref f) if f == file => (self.offset, self.line),
+            _ => (0, 0),
+        }
+    }
+
+    /// Has it been a while since the last save?
+    pub fn due(&self) -> bool {
+        time::precise_time_ns() - self.saved_at >= INTERVAL_NS
+    }
+
+    /// Note that the scan got to `offset` in `file`, `line` records
+    /// in, and save it.
+    pub fn progress(&mut self, file: &str, offset: u64, line: usize) -> io::Result<()> {
+        self.file = Some(file.to_owned());
+        self.offset = offset;
+        self.line = line;
+        self.save()
+    }
+
+    /// Note that `file` has been scanned completely, and save it.
+    pub fn finish_file(&mut self, file: &str) -> io::Result<()> {
+        self.done.push(file.to_owned());
+        self.file = None;
+        self.offset = 0;
+        self.line = 0;
+        self.save()
+    }
+
+    /// Write the state to a temporary file and then move it over the
+    /// last one, so an interruption can't leave half of it.
+    fn save(&mut self) -> io::Result<()> {
+        let done: Vec<String> = self.done.iter().map(|d| output::quote(d)).collect();
+        let file = self.file.as_ref().map_or("null".to_owned(), |f| output::quote(f));
+        let length = self.output.map_or("null".to_owned(), |l| l.to_string());
+        let temp = format!("{}.tmp", self.path);
+        {
+            let mut f = File::create(&temp)?;
+            writeln!(f,
+                     "{{\"pattern\": {}, \"done\": [{}], \"file\": {}, \"offset\": {}, \
+                      \"line\": {}, \"lines\": {}, \"files\": {}, \"output\": {}}}",
+                     output::quote(&self.pattern),
+                     done.join(", "),
+                     file,
+                     self.offset,
+                     self.line,
+                     self.lines,
+                     self.files,
+                     length)?;
+            f.sync_all()?;
+        }
+        fs::rename(&temp, &self.path)?;
+        self.saved_at = time::precise_time_ns();
+        Ok(())
+    }
+
+    /// Remove the state once the scan is complete.
+    pub fn remove(self) -> io::Result<()> {
+        fs::remove_file(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::Path;
+    use std::process;
+
+    fn temp(name: &str) -> String {
+        let path = env::temp_dir().join(format!("regtest-checkpoint-{}-{}", process::id(), name));
+        fs::remove_file(&path);
+        path.display().to_string()
+    }
+
+    #[test]
+    fn resumes_where_it_stopped() {
+        let path = temp("resume");
+        let mut checkpoint = Checkpoint::resume(&path, "ERR\"OR").unwrap();
+        assert_eq!(checkpoint.start("a.log"), (0, 0));
+        checkpoint.lines = 3;
+        checkpoint.files = 1;
+        checkpoint.finish_file("a \"quoted\" \\ name.log").unwrap();
+        checkpoint.output = Some(1480);
+        checkpoint.progress("b.log", 5120, 87).unwrap();
+
+        let checkpoint = Checkpoint::resume(&path, "ERR\"OR").unwrap();
+        assert!(checkpoint.is_done("a \"quoted\" \\ name.log"));
+        assert!(!checkpoint.is_done("b.log"));
+        assert_eq!(checkpoint.start("b.log"), (5120, 87));
+        assert_eq!(checkpoint.start("c.log"), (0, 0));
+        assert_eq!((checkpoint.lines, checkpoint.files), (3, 1));
+        assert_eq!(checkpoint.output, Some(1480));
+        checkpoint.remove().unwrap();
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn other_patterns_can_not_resume() {
+        let path = temp("other");
+        Checkpoint::new(&path, "a").progress("f", 1, 1).unwrap();
+        assert!(Checkpoint::resume(&path, "b").err().unwrap().contains("\"a\""));
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn corrupt_checkpoints_are_errors() {
+        let path = temp("corrupt");
+        fs::write(&path, "{\"pattern\": \"a\", \"done\": [").unwrap();
+        assert!(Checkpoint::resume(&path, "a").err().unwrap().starts_with(&path));
+        fs::remove_file(&path);
+    }
+}
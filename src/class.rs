@@ -0,0 +1,202 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A character class put together one item at a time: characters,
+//! ranges, Perl classes and Unicode properties, maybe negated.
+
+use regex::Regex;
+use regex_syntax::ParserBuilder;
+use regex_syntax::hir::{Class, HirKind, Literal};
+
+use flags::Flags;
+
+/// The last code point looked at for sample members.
+const SAMPLE_LIMIT: u32 = 0x2FFFF;
+
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    /// Items as written inside the brackets, like `a-z` or `\p{Greek}`.
+    pub items: Vec<String>,
+    pub negated: bool,
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// The class as written in a pattern.
+    pub fn syntax(&self) -> String {
+        format!("[{}{}]", if self.negated { "^" } else { "" }, self.items.concat())
+    }
+
+    /// Add `item`, or return why it can't be in a class.
+    pub fn add(&mut self, item: &str) -> Result<(), String> {
+        let item = item.trim();
+        // Validate it on its own, so errors point at this item
+        Regex::new(&format!("[{}]", item)).map_err(|e| e.to_string())?;
+        if !self.items.iter().any(|i| i == item) {
+            self.items.push(item.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Take out `item`, returning whether it was there.
+    pub fn remove(&mut self, item: &str) -> bool {
+        let before = self.items.len();
+        self.items.retain(|i| i != item.trim());
+        self.items.len() != before
+    }
+
+    /// Up to `n` printable characters the class matches, lowest
+    /// first, and whether there are more.
+    pub fn samples(&self, n: usize) -> (Vec<char>, bool) {
+        let reg = match Regex::new(&format!("^{}$", self.syntax())) {
+            Ok(r) => r,
+            Err(_) => return (vec![], false),
+        };
+        let mut out = vec![];
+        let mut buf = [0; 4];
+        for c in (0x20..SAMPLE_LIMIT).filter_map(::std::char::from_u32) {
+            if c.is_control() || !reg.is_match(c.encode_utf8(&mut buf)) {
+                continue;
+            }
+            if out.len() == n {
+                return (out, true);
+            }
+            out.push(c);
+        }
+        (out, false)
+    }
+}
+
+/// The characters of a class as written in a pattern, like
+/// `[[:punct:]]`, `\p{Greek}` or `\d`, parsed but not compiled.
+pub enum Members {
+    Unicode(Vec<(char, char)>),
+    Bytes(Vec<(u8, u8)>),
+}
+
+impl Members {
+    /// Parse `class` with `flags`, or say why it isn't one. A bare `[:punct:]` is read as
+    /// `[[:punct:]]`, and a single character as a class of one.
+    pub fn parse(class: &str, flags: Flags) -> Result<Members, String> {
+        let class = if class.starts_with("[:") && class.ends_with(":]") {
+            format!("[{}]", class)
+        } else {
+            class.to_owned()
+        };
+        let hir = ParserBuilder::new()
+            .case_insensitive(flags.contains(Flags::CASE_INSENSITIVE))
+            .unicode(flags.contains(Flags::UNICODE))
+            .allow_invalid_utf8(!flags.contains(Flags::UNICODE))
+            .build()
+            .parse(&class)
+            .map_err(|e| msg!(ErrorCompiling, e))?;
+        match *hir.kind() {
+            HirKind::Class(Class::Unicode(ref cls)) => {
+                Ok(Members::Unicode(cls.iter().map(|r| (r.start(), r.end())).collect()))
+            }
+            HirKind::Class(Class::Bytes(ref cls)) => {
+                Ok(Members::Bytes(cls.iter().map(|r| (r.start(), r.end())).collect()))
+            }
+            HirKind::Literal(Literal::Unicode(c)) => Ok(Members::Unicode(vec![(c, c)])),
+            HirKind::Literal(Literal::Byte(b)) => Ok(Members::Bytes(vec![(b, b)])),
+            _ => Err(msg!(NotAClass, class)),
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        match *self {
+            Members::Unicode(ref ranges) => ranges.iter().any(|&(s, e)| s <= c && c <= e),
+            Members::Bytes(ref ranges) => {
+                (c as u32) < 0x100 && ranges.iter().any(|&(s, e)| s <= c as u8 && c as u8 <= e)
+            }
+        }
+    }
+}
+
+/// Split `arg` after the class it starts with, which may hold
+/// spaces inside its brackets, like `[ a-z]`.
+pub fn split_class(arg: &str) -> (&str, &str) {
+    let arg = arg.trim_start();
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in arg.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => return (&arg[..i], arg[i..].trim_start()),
+            _ => {}
+        }
+    }
+    (arg, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_classes() {
+        let mut class = Builder::new();
+        class.add("a-c").unwrap();
+        class.add(" \\d ").unwrap();
+        class.add("a-c").unwrap();
+        assert!(class.add("z-a").is_err());
+        assert_eq!(class.syntax(), r"[a-c\d]");
+        class.negated = true;
+        assert_eq!(class.syntax(), r"[^a-c\d]");
+        assert!(class.remove("a-c"));
+        assert!(!class.remove("a-c"));
+        assert_eq!(class.items, vec![r"\d".to_owned()]);
+    }
+
+    #[test]
+    fn samples_members() {
+        let mut class = Builder::new();
+        class.add("a-e").unwrap();
+        assert_eq!(class.samples(3), (vec!['a', 'b', 'c'], true));
+        assert_eq!(class.samples(5), (vec!['a', 'b', 'c', 'd', 'e'], false));
+    }
+
+    #[test]
+    fn parses_members() {
+        let digits = Members::parse(r"\d", Flags::empty()).unwrap();
+        assert!(digits.contains('7') && !digits.contains('x'));
+        let punct = Members::parse("[:punct:]", Flags::default()).unwrap();
+        assert!(punct.contains('!') && !punct.contains('a'));
+        let k = Members::parse("k", Flags::CASE_INSENSITIVE | Flags::UNICODE).unwrap();
+        assert!(k.contains('K') && k.contains('\u{212A}'));
+        let greek = Members::parse(r"\p{Greek}", Flags::default()).unwrap();
+        assert!(greek.contains('\u{3bb}'));
+        let bytes = Members::parse(r"[\x80-\xff]", Flags::empty()).unwrap();
+        assert!(bytes.contains('\u{e9}') && !bytes.contains('\u{100}'));
+        assert!(Members::parse("ab", Flags::default()).is_err());
+        assert!(Members::parse("[a", Flags::default()).is_err());
+    }
+
+    #[test]
+    fn splits_off_the_class() {
+        assert_eq!(split_class("  [ a-z] x y"), ("[ a-z]", "x y"));
+        assert_eq!(split_class(r"[\]] x"), (r"[\]]", "x"));
+        assert_eq!(split_class(r"[[:alpha:][0-9]] 1"), (r"[[:alpha:][0-9]]", "1"));
+        assert_eq!(split_class(r"\w"), (r"\w", ""));
+    }
+}
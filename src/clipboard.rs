@@ -0,0 +1,51 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Reading the system clipboard through whichever of the usual
+//! command line tools is installed.
+
+use std::io;
+use std::process::Command;
+
+/// Commands that print the clipboard, tried in order.
+const COMMANDS: &[&[&str]] = &[&["pbpaste"],
+                               &["wl-paste", "--no-newline"],
+                               &["xclip", "-selection", "clipboard", "-o"],
+                               &["xsel", "--clipboard", "--output"],
+                               &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"]];
+
+/// The text on the clipboard, with Windows line endings turned
+/// into `\n`.
+pub fn paste() -> io::Result<String> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound,
+                                  "no pbpaste, wl-paste, xclip, xsel or powershell.exe");
+    for command in COMMANDS {
+        match Command::new(command[0]).args(&command[1..]).output() {
+            Ok(ref out) if out.status.success() => {
+                debug!("read {} bytes from the clipboard with {}", out.stdout.len(), command[0]);
+                return Ok(String::from_utf8_lossy(&out.stdout).replace("\r\n", "\n"));
+            }
+            Ok(out) => {
+                let err = String::from_utf8_lossy(&out.stderr);
+                last = io::Error::other(format!("{}: {}", command[0], err.trim()));
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
@@ -0,0 +1,201 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Compare the languages of two patterns by sampling inputs
+//! from both and checking them against full-match versions of
+//! each regex.
+
+use regex::Regex;
+use regex_syntax::hir::{Anchor, Group, GroupKind, Hir};
+
+use flags::Flags;
+use sample::{self, Rng};
+
+/// Number of inputs generated from each pattern.
+const SAMPLES: usize = 2000;
+
+/// A pattern prepared for comparison.
+pub struct Language {
+    hir: Hir,
+    full: Regex,
+}
+
+impl Language {
+    /// Parse `pattern` with `flags` and compile a copy of it anchored
+    /// at both ends, so `is_match` answers "is this string in the
+    /// language".
+    pub fn new(pattern: &str, flags: Flags) -> Result<Language, String> {
+        let hir = flags.parser().parse(pattern).map_err(|e| e.to_string())?;
+        let full = full_match(&hir);
+        let full = Regex::new(&full.to_string()).map_err(|e| e.to_string())?;
+        Ok(Language { hir, full })
+    }
+
+    pub fn contains(&self, s: &str) -> bool {
+        self.full.is_match(s)
+    }
+}
+
+/// Wrap `hir` in `\A(?:...)\z`.
+pub fn full_match(hir: &Hir) -> Hir {
+    Hir::concat(vec![Hir::anchor(Anchor::StartText),
+                     Hir::group(Group {
+                         kind: GroupKind::NonCapturing,
+                         hir: Box::new(hir.clone()),
+                     }),
+                     Hir::anchor(Anchor::EndText)])
+}
+
+/// Generate candidate inputs from both languages, plus small
+/// mutations of them. The result is sorted shortest first so the
+/// first interesting input found is also the easiest to read.
+fn candidates(a: &Language, b: &Language) -> Vec<String> {
+    let mut rng = Rng::from_time();
+    let mut out = vec![String::new()];
+    for lang in &[a, b] {
+        for _ in 0..SAMPLES {
+            let s = sample::generate(&lang.hir, &mut rng);
+            if rng.one_in(4) {
+                out.push(sample::mutate(&s, &mut rng));
+            }
+            out.push(s);
+        }
+    }
+    out.sort_by(|x, y| x.len().cmp(&y.len()).then_with(|| x.cmp(y)));
+    out.dedup();
+    out
+}
+
+/// The result of an equivalence check.
+pub enum Equivalence {
+    /// The patterns parse to the same HIR.
+    Identical,
+    /// No generated input told them apart.
+    NoDifferenceFound(usize),
+    /// An input matched by the first but not the second pattern,
+    /// or the other way around (`first_matches` says which).
+    Differs { input: String, first_matches: bool },
+}
+
+/// Compare two patterns by full-match language.
+pub fn equivalent(a: &Language, b: &Language) -> Equivalence {
+    if a.hir == b.hir {
+        return Equivalence::Identical;
+    }
+    let inputs = candidates(a, b);
+    for input in &inputs {
+        let in_a = a.contains(input);
+        if in_a != b.contains(input) {
+            return Equivalence::Differs {
+                input: input.clone(),
+                first_matches: in_a,
+            };
+        }
+    }
+    Equivalence::NoDifferenceFound(inputs.len())
+}
+
+/// What sampling found out about two languages `a` and `b`.
+pub struct Overlap {
+    /// An input fully matched by both patterns.
+    pub common: Option<String>,
+    /// An input matched by `a` but not `b`, proving `a` is not a
+    /// subset of `b`.
+    pub only_a: Option<String>,
+    /// An input matched by `b` but not `a`.
+    pub only_b: Option<String>,
+    /// How many inputs were tried.
+    pub tried: usize,
+}
+
+/// Look for common inputs and for witnesses that neither
+/// language contains the other.
+pub fn overlap(a: &Language, b: &Language) -> Overlap {
+    let inputs = candidates(a, b);
+    let mut result = Overlap {
+        common: None,
+        only_a: None,
+        only_b: None,
+        tried: inputs.len(),
+    };
+    for input in inputs {
+        match (a.contains(&input), b.contains(&input)) {
+            (true, true) if result.common.is_none() => result.common = Some(input),
+            (true, false) if result.only_a.is_none() => result.only_a = Some(input),
+            (false, true) if result.only_b.is_none() => result.only_b = Some(input),
+            _ => {}
+        }
+        if result.common.is_some() && result.only_a.is_some() && result.only_b.is_some() {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(pattern: &str) -> Language {
+        Language::new(pattern, Flags::default()).unwrap()
+    }
+
+    #[test]
+    fn languages_match_whole_strings() {
+        let l = language("a|bc");
+        assert!(l.contains("a") && l.contains("bc"));
+        assert!(!l.contains("abc") && !l.contains(""));
+        assert!(Language::new("(", Flags::default()).is_err());
+    }
+
+    #[test]
+    fn the_same_expression_is_identical() {
+        let insensitive = Language::new("abc", Flags::default() | Flags::CASE_INSENSITIVE).unwrap();
+        match equivalent(&insensitive, &language("(?i)abc")) {
+            Equivalence::Identical => {}
+            _ => panic!("flags should apply to the whole pattern"),
+        }
+        match equivalent(&language("a|b"), &language("[ab]")) {
+            Equivalence::NoDifferenceFound(n) => assert!(n > 0),
+            _ => panic!("a|b and [ab] match the same strings"),
+        }
+    }
+
+    #[test]
+    fn shows_the_shortest_difference() {
+        match equivalent(&language("abc"), &language("ab")) {
+            Equivalence::Differs { input, first_matches } => {
+                assert_eq!((&input[..], first_matches), ("ab", false));
+            }
+            _ => panic!("abc and ab differ"),
+        }
+    }
+
+    #[test]
+    fn finds_common_inputs_and_witnesses() {
+        let result = overlap(&language("a+"), &language("aa?"));
+        assert_eq!(result.common.as_ref().map(|s| &s[..]), Some("a"));
+        assert_eq!(result.only_a.as_ref().map(|s| &s[..]), Some("aaa"));
+        assert_eq!(result.only_b, None);
+        assert!(result.tried > 0);
+
+        let result = overlap(&language("x"), &language("y"));
+        assert_eq!(result.common, None);
+        assert_eq!(result.only_a.as_ref().map(|s| &s[..]), Some("x"));
+        assert_eq!(result.only_b.as_ref().map(|s| &s[..]), Some("y"));
+    }
+}
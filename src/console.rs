@@ -0,0 +1,86 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Making the Windows console behave like a Unix terminal: escape
+//! sequences for colors, and lines read without the `\r` of CRLF.
+//! Elsewhere there's nothing to do.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rustyline;
+
+/// Whether the console understands ANSI escape sequences.
+static ANSI: AtomicBool = AtomicBool::new(true);
+
+#[cfg(windows)]
+mod windows {
+    use std::os::raw::c_void;
+
+    pub const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    pub const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+    pub const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    extern "system" {
+        pub fn GetStdHandle(handle: u32) -> *mut c_void;
+        pub fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        pub fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+    }
+
+    /// Turn on escape sequences for one handle. Handles that aren't
+    /// a console, like pipes, are left alone and don't count as
+    /// failing.
+    pub fn enable(handle: u32) -> bool {
+        unsafe {
+            let console = GetStdHandle(handle);
+            let mut mode = 0;
+            if console.is_null() || GetConsoleMode(console, &mut mode) == 0 {
+                return true;
+            }
+            mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 ||
+            SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    }
+}
+
+/// Set the console up for escape sequences. They need Windows 10 or
+/// later: on older consoles output is left uncolored.
+#[cfg(windows)]
+pub fn init() {
+    let ok = windows::enable(windows::STD_OUTPUT_HANDLE) &
+             windows::enable(windows::STD_ERROR_HANDLE);
+    debug!("virtual terminal processing {}", if ok { "enabled" } else { "unavailable" });
+    ANSI.store(ok, Ordering::Relaxed);
+}
+
+#[cfg(not(windows))]
+pub fn init() {}
+
+/// Whether colors can be written as escape sequences.
+pub fn ansi() -> bool {
+    ANSI.load(Ordering::Relaxed)
+}
+
+/// A line read from the console, without the `\r` Windows leaves
+/// at its end.
+pub fn line(read: rustyline::Result<String>) -> rustyline::Result<String> {
+    read.map(|mut l| {
+        if l.ends_with('\r') {
+            l.pop();
+        }
+        l
+    })
+}
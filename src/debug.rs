@@ -0,0 +1,386 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A tiny backtracking matcher used only to show, step by step,
+//! how a match attempt proceeds. It follows the same leftmost-first
+//! semantics as the regex crate but makes no attempt to be fast.
+
+use std::collections::HashSet;
+
+use regex_syntax;
+use regex_syntax::hir::{Anchor, Class, Hir, HirKind, Literal, RepetitionKind,
+                        RepetitionRange, WordBoundary};
+
+use flags::Flags;
+
+/// Programs larger than this are refused; counted repetitions
+/// are expanded so they can get big quickly.
+const MAX_INSTS: usize = 10_000;
+
+enum Inst {
+    Char(char),
+    Class(Class),
+    Anchor(Anchor),
+    WordBoundary(WordBoundary),
+    /// Try the first target, backtrack to the second.
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+/// An instruction along with the sub-expression it came from.
+struct Prog {
+    insts: Vec<(Inst, String)>,
+}
+
+/// What happened at a single step of the simulation.
+pub enum Event {
+    /// Starting a new attempt at this input offset.
+    Start,
+    /// The current expression matched and consumed input.
+    Advance,
+    /// An assertion held.
+    Assert,
+    /// Choosing between alternatives; the other one is saved.
+    Choice,
+    /// The current expression failed to match here.
+    Fail,
+    /// Resuming from a saved alternative.
+    Backtrack,
+    /// The whole pattern matched.
+    Match,
+}
+
+pub struct Step {
+    pub event: Event,
+    /// Offset into the input.
+    pub pos: usize,
+    /// The sub-expression being tried.
+    pub expr: String,
+}
+
+/// The recorded steps of a match attempt.
+pub struct Trace {
+    pub steps: Vec<Step>,
+    /// The match found, if any.
+    pub matched: Option<(usize, usize)>,
+    /// Whether recording stopped before the search finished.
+    pub truncated: bool,
+}
+
+/// Simulate matching `pattern`, compiled with `flags`, against
+/// `input`, recording at most `limit` steps.
+pub fn trace(pattern: &str, flags: Flags, input: &str, limit: usize) -> Result<Trace, String> {
+    let hir = flags.parser().parse(pattern).map_err(|e| e.to_string())?;
+    let mut prog = Prog { insts: vec![] };
+    prog.compile(&hir)?;
+    prog.push(Inst::Match, String::new());
+    Ok(prog.run(input, limit))
+}
+
+impl Prog {
+    fn push(&mut self, inst: Inst, expr: String) -> usize {
+        self.insts.push((inst, expr));
+        self.insts.len() - 1
+    }
+
+    /// Patch the targets of a `Split` or `Jmp` emitted earlier.
+    fn patch(&mut self, at: usize, inst: Inst) {
+        self.insts[at].0 = inst;
+    }
+
+    fn compile(&mut self, hir: &Hir) -> Result<(), String> {
+        if self.insts.len() > MAX_INSTS {
+            return Err("pattern is too large to step through".to_owned());
+        }
+        let expr = hir.to_string();
+        match *hir.kind() {
+            HirKind::Empty => {}
+            HirKind::Literal(Literal::Unicode(c)) => {
+                self.push(Inst::Char(c), expr);
+            }
+            HirKind::Literal(Literal::Byte(b)) => {
+                self.push(Inst::Char(b as char), expr);
+            }
+            HirKind::Class(ref cls) => {
+                self.push(Inst::Class(cls.clone()), expr);
+            }
+            HirKind::Anchor(ref a) => {
+                self.push(Inst::Anchor(a.clone()), expr);
+            }
+            HirKind::WordBoundary(ref wb) => {
+                self.push(Inst::WordBoundary(wb.clone()), expr);
+            }
+            HirKind::Group(ref g) => self.compile(&g.hir)?,
+            HirKind::Concat(ref hirs) => {
+                for h in hirs {
+                    self.compile(h)?;
+                }
+            }
+            HirKind::Alternation(ref hirs) => {
+                let mut jumps = vec![];
+                for (i, h) in hirs.iter().enumerate() {
+                    if i + 1 == hirs.len() {
+                        self.compile(h)?;
+                        break;
+                    }
+                    let split = self.push(Inst::Jmp(0), expr.clone());
+                    self.compile(h)?;
+                    jumps.push(self.push(Inst::Jmp(0), expr.clone()));
+                    let next = self.insts.len();
+                    self.patch(split, Inst::Split(split + 1, next));
+                }
+                let end = self.insts.len();
+                for j in jumps {
+                    self.patch(j, Inst::Jmp(end));
+                }
+            }
+            HirKind::Repetition(ref rep) => {
+                let (min, max) = match rep.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => (n, Some(n)),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (n, None),
+                    RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => (m, Some(n)),
+                };
+                for _ in 0..min {
+                    self.compile(&rep.hir)?;
+                }
+                match max {
+                    None => {
+                        let split = self.push(Inst::Jmp(0), expr.clone());
+                        self.compile(&rep.hir)?;
+                        self.push(Inst::Jmp(split), expr);
+                        let exit = self.insts.len();
+                        self.patch(split, self.split(rep.greedy, split + 1, exit));
+                    }
+                    Some(max) => {
+                        let mut splits = vec![];
+                        for _ in min..max {
+                            splits.push(self.push(Inst::Jmp(0), expr.clone()));
+                            self.compile(&rep.hir)?;
+                        }
+                        let exit = self.insts.len();
+                        for s in splits {
+                            self.patch(s, self.split(rep.greedy, s + 1, exit));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A split preferring `body` when greedy and `exit` otherwise.
+    fn split(&self, greedy: bool, body: usize, exit: usize) -> Inst {
+        if greedy {
+            Inst::Split(body, exit)
+        } else {
+            Inst::Split(exit, body)
+        }
+    }
+
+    fn run(&self, input: &str, limit: usize) -> Trace {
+        let mut trace = Trace {
+            steps: vec![],
+            matched: None,
+            truncated: false,
+        };
+        let record = |trace: &mut Trace, event: Event, pos: usize, pc: usize| {
+            if trace.steps.len() >= limit {
+                trace.truncated = true;
+            } else {
+                trace.steps.push(Step {
+                    event,
+                    pos,
+                    expr: self.insts[pc].1.clone(),
+                });
+            }
+        };
+
+        let starts = input.char_indices().map(|(i, _)| i).chain(Some(input.len()));
+        for start in starts {
+            // A (pc, pos) pair that failed once will always fail
+            let mut visited = HashSet::new();
+            let mut stack = vec![(0, start, false)];
+            record(&mut trace, Event::Start, start, 0);
+            while let Some((mut pc, mut pos, resumed)) = stack.pop() {
+                if resumed {
+                    record(&mut trace, Event::Backtrack, pos, pc);
+                }
+                loop {
+                    if !visited.insert((pc, pos)) {
+                        break;
+                    }
+                    let next = input[pos..].chars().next();
+                    match self.insts[pc].0 {
+                        Inst::Char(c) => {
+                            if next != Some(c) {
+                                record(&mut trace, Event::Fail, pos, pc);
+                                break;
+                            }
+                            record(&mut trace, Event::Advance, pos, pc);
+                            pos += c.len_utf8();
+                            pc += 1;
+                        }
+                        Inst::Class(ref cls) => {
+                            match next {
+                                Some(c) if class_contains(cls, c) => {
+                                    record(&mut trace, Event::Advance, pos, pc);
+                                    pos += c.len_utf8();
+                                    pc += 1;
+                                }
+                                _ => {
+                                    record(&mut trace, Event::Fail, pos, pc);
+                                    break;
+                                }
+                            }
+                        }
+                        Inst::Anchor(ref a) => {
+                            if !anchor_holds(a, input, pos) {
+                                record(&mut trace, Event::Fail, pos, pc);
+                                break;
+                            }
+                            record(&mut trace, Event::Assert, pos, pc);
+                            pc += 1;
+                        }
+                        Inst::WordBoundary(ref wb) => {
+                            if !word_boundary_holds(wb, input, pos) {
+                                record(&mut trace, Event::Fail, pos, pc);
+                                break;
+                            }
+                            record(&mut trace, Event::Assert, pos, pc);
+                            pc += 1;
+                        }
+                        Inst::Split(first, second) => {
+                            record(&mut trace, Event::Choice, pos, pc);
+                            stack.push((second, pos, true));
+                            pc = first;
+                        }
+                        Inst::Jmp(to) => pc = to,
+                        Inst::Match => {
+                            record(&mut trace, Event::Match, pos, pc);
+                            trace.matched = Some((start, pos));
+                            return trace;
+                        }
+                    }
+                }
+            }
+        }
+        trace
+    }
+}
+
+fn class_contains(cls: &Class, c: char) -> bool {
+    match *cls {
+        Class::Unicode(ref cls) => cls.iter().any(|r| r.start() <= c && c <= r.end()),
+        Class::Bytes(ref cls) => {
+            (c as u32) < 0x80 && cls.iter().any(|r| r.start() <= c as u8 && c as u8 <= r.end())
+        }
+    }
+}
+
+fn anchor_holds(anchor: &Anchor, input: &str, pos: usize) -> bool {
+    match *anchor {
+        Anchor::StartText => pos == 0,
+        Anchor::EndText => pos == input.len(),
+        Anchor::StartLine => pos == 0 || input[..pos].ends_with('\n'),
+        Anchor::EndLine => pos == input.len() || input[pos..].starts_with('\n'),
+    }
+}
+
+fn word_boundary_holds(wb: &WordBoundary, input: &str, pos: usize) -> bool {
+    let is_word = |c: Option<char>| match (c, wb) {
+        (Some(c), &WordBoundary::Ascii) |
+        (Some(c), &WordBoundary::AsciiNegate) => c.is_ascii_alphanumeric() || c == '_',
+        (Some(c), _) => regex_syntax::is_word_character(c),
+        (None, _) => false,
+    };
+    let boundary = is_word(input[..pos].chars().next_back()) != is_word(input[pos..].chars().next());
+    boundary != wb.is_negated()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn matched(pattern: &str, flags: Flags, input: &str) -> Option<(usize, usize)> {
+        trace(pattern, flags, input, usize::MAX).unwrap().matched
+    }
+
+    #[test]
+    fn finds_the_same_match_as_the_regex_crate() {
+        for &(pattern, input) in &[(r"a+b", "xaab"),
+                                   ("cat|dog", "hotdog"),
+                                   (r"\d{2,3}", "a12345"),
+                                   ("a*?b", "aab"),
+                                   (r"\bis\b", "this is"),
+                                   ("^b|c$", "abc"),
+                                   ("é.", "café!"),
+                                   ("x", "abc")] {
+            let expected = Regex::new(pattern).unwrap().find(input).map(|m| (m.start(), m.end()));
+            assert_eq!(matched(pattern, Flags::default(), input), expected, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn uses_the_flags() {
+        let insensitive = Flags::default() | Flags::CASE_INSENSITIVE;
+        assert_eq!(matched("abc", insensitive, "xABC"), Some((1, 4)));
+        assert_eq!(matched("abc", Flags::default(), "xABC"), None);
+        let multi_line = Flags::default() | Flags::MULTI_LINE;
+        assert_eq!(matched("^b", multi_line, "a\nb"), Some((2, 3)));
+        let lazy = Flags::default() | Flags::SWAP_GREED;
+        assert_eq!(matched("a+", lazy, "aaa"), Some((0, 1)));
+    }
+
+    #[test]
+    fn records_each_step() {
+        let t = trace("a|b", Flags::default(), "b", 100).unwrap();
+        let events: Vec<&str> = t.steps
+            .iter()
+            .map(|s| match s.event {
+                Event::Start => "start",
+                Event::Advance => "advance",
+                Event::Assert => "assert",
+                Event::Choice => "choice",
+                Event::Fail => "fail",
+                Event::Backtrack => "backtrack",
+                Event::Match => "match",
+            })
+            .collect();
+        assert_eq!(events, ["start", "choice", "fail", "backtrack", "advance", "match"]);
+        assert_eq!(t.steps[2].expr, "a");
+        assert!(!t.truncated);
+    }
+
+    #[test]
+    fn stops_recording_at_the_limit() {
+        let t = trace("a*b", Flags::default(), "aaaaaaaa", 5).unwrap();
+        assert_eq!(t.steps.len(), 5);
+        assert!(t.truncated);
+        assert_eq!(t.matched, None);
+    }
+
+    #[test]
+    fn refuses_bad_and_huge_patterns() {
+        assert!(trace("(", Flags::default(), "", 10).is_err());
+        assert!(trace("(?:a{100}){200}", Flags::default(), "", 10).is_err());
+    }
+}
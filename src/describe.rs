@@ -0,0 +1,98 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Plain sentences describing matches and positions, used by
+//! `--accessible` in place of visual markers.
+
+use regex::{Captures, Regex};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The 1-based column of byte offset `pos` in `input`, counting
+/// grapheme clusters so a letter and its combining marks are one
+/// column. An offset inside a cluster gets that cluster's column.
+pub fn column(input: &str, pos: usize) -> usize {
+    input.grapheme_indices(true).take_while(|&(i, g)| i + g.len() <= pos).count() + 1
+}
+
+/// Describe the span `start..end` of `input` in columns.
+pub fn span(input: &str, start: usize, end: usize) -> String {
+    if start == end {
+        return msg!(AccEmptySpan, column(input, start));
+    }
+    msg!(AccSpan,
+         column(input, start),
+         column(input, end) - 1,
+         format!("{:?}", &input[start..end]))
+}
+
+/// Describe a match and each of its groups.
+pub fn captures(reg: &Regex, input: &str, caps: &Captures) -> String {
+    let mut parts = vec![];
+    for (i, name) in reg.capture_names().enumerate() {
+        let group = match name {
+            Some(n) => format!("{} ({})", i, n),
+            None => i.to_string(),
+        };
+        let part = match caps.get(i) {
+            Some(m) if i == 0 => msg!(AccMatch, span(input, m.start(), m.end())),
+            Some(m) => msg!(AccGroup, group, span(input, m.start(), m.end())),
+            None => msg!(AccGroupUnset, group),
+        };
+        parts.push(part);
+    }
+    parts.join("; ")
+}
+
+/// Describe a cursor at byte offset `pos` in `input`.
+pub fn cursor(input: &str, pos: usize) -> String {
+    if pos == input.len() {
+        return msg!(AccCursorEnd, column(input, pos));
+    }
+    let next = input[pos..].chars().next().unwrap();
+    msg!(AccCursor, column(input, pos), format!("{:?}", next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_columns_in_graphemes() {
+        let input = "e\u{301}x";
+        assert_eq!(column(input, 0), 1);
+        assert_eq!(column(input, 1), 1);
+        assert_eq!(column(input, 3), 2);
+        assert_eq!(column(input, 4), 3);
+    }
+
+    #[test]
+    fn describes_spans_and_cursors() {
+        assert_eq!(span("abcd", 1, 3), "from column 2 to 3: \"bc\"");
+        assert_eq!(span("abcd", 2, 2), "empty at column 3");
+        assert_eq!(cursor("abc", 1), "at column 2, before 'b'");
+        assert_eq!(cursor("abc", 3), "at column 4, the end of the input");
+    }
+
+    #[test]
+    fn describes_every_group() {
+        let reg = Regex::new(r"(?P<word>\w+)(!)?").unwrap();
+        let caps = reg.captures(" hi").unwrap();
+        assert_eq!(captures(&reg, " hi", &caps),
+                   "match from column 2 to 3: \"hi\"; group 1 (word) from column 2 to 3: \"hi\"; \
+                    group 2 did not match");
+    }
+}
@@ -0,0 +1,188 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Word-level diffs between an input and what a replacement turned
+//! it into.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use console;
+use settings;
+
+/// A run of text and whether it was kept, removed or added.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Split `text` into the byte ranges of its words, runs of
+/// whitespace and single other characters.
+fn tokens(text: &str) -> Vec<(usize, usize)> {
+    let kind = |c: char| if c.is_alphanumeric() || c == '_' {
+        0
+    } else if c.is_whitespace() {
+        1
+    } else {
+        2
+    };
+    let mut out = vec![];
+    let mut start = 0;
+    let mut last = None;
+    for (i, c) in text.char_indices() {
+        let k = kind(c);
+        if i > start && (Some(k) != last || k == 2) {
+            out.push((start, i));
+            start = i;
+        }
+        last = Some(k);
+    }
+    if start < text.len() {
+        out.push((start, text.len()));
+    }
+    out
+}
+
+/// The most words either side of a diff can have. Past this the
+/// table comparing them would take too long to fill in.
+pub const MAX_TOKENS: usize = 4000;
+
+/// The changes turning `old` into `new`, a word at a time, or `None`
+/// if either has more than `MAX_TOKENS` words.
+pub fn words<'a>(old: &'a str, new: &'a str) -> Option<Vec<Change<'a>>> {
+    let (a, b) = (tokens(old), tokens(new));
+    if a.len() > MAX_TOKENS || b.len() > MAX_TOKENS {
+        return None;
+    }
+    let same = |i: usize, j: usize| old[a[i].0..a[i].1] == new[b[j].0..b[j].1];
+    // lcs[i][j] is the longest common run of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if same(i, j) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Walk the table, widening the last range while the kind of
+    // change stays the same. Kinds are 0 same, 1 delete, 2 insert.
+    let mut runs: Vec<(u8, usize, usize)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let (kind, (start, end)) = if i < a.len() && j < b.len() && same(i, j) {
+            i += 1;
+            j += 1;
+            (0, a[i - 1])
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            i += 1;
+            (1, a[i - 1])
+        } else {
+            j += 1;
+            (2, b[j - 1])
+        };
+        match runs.last_mut() {
+            Some(last) if last.0 == kind => last.2 = end,
+            _ => runs.push((kind, start, end)),
+        }
+    }
+    Some(runs.into_iter()
+        .map(|(kind, start, end)| match kind {
+            0 => Change::Same(&old[start..end]),
+            1 => Change::Delete(&old[start..end]),
+            _ => Change::Insert(&new[start..end]),
+        })
+        .collect())
+}
+
+/// Show `changes` as one line, with deletions in red and insertions
+/// in green, or marked `[-...-]` and `{+...+}` without `color`.
+pub fn render(changes: &[Change], color: bool) -> String {
+    let mut out = String::new();
+    for change in changes {
+        match *change {
+            Change::Same(t) => out.push_str(t),
+            Change::Delete(t) if color => out.push_str(&format!("\x1b[31m{}\x1b[0m", t)),
+            Change::Insert(t) if color => out.push_str(&format!("\x1b[32m{}\x1b[0m", t)),
+            Change::Delete(t) => out.push_str(&format!("[-{}-]", t)),
+            Change::Insert(t) => out.push_str(&format!("{{+{}+}}", t)),
+        }
+    }
+    out
+}
+
+/// Whether output on stderr should be colored: `REGTEST_COLOR` is
+/// `always`, or it isn't `never`, it's a terminal and `NO_COLOR`
+/// isn't set.
+pub fn color_stderr() -> bool {
+    color(io::stderr().is_terminal())
+}
+
+/// Whether output on stdout should be colored.
+pub fn color_stdout() -> bool {
+    color(io::stdout().is_terminal())
+}
+
+fn color(terminal: bool) -> bool {
+    match env::var(settings::ENV_COLOR).as_ref().map(|c| &c[..]) {
+        Ok("always") => true,
+        Ok("never") => false,
+        _ => env::var_os("NO_COLOR").is_none() && terminal && console::ansi(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_words_spaces_and_punctuation() {
+        let text = "foo  bar,.";
+        let parts: Vec<&str> = tokens(text).into_iter().map(|(s, e)| &text[s..e]).collect();
+        assert_eq!(parts, ["foo", "  ", "bar", ",", "."]);
+    }
+
+    #[test]
+    fn diffs_a_word_at_a_time() {
+        assert_eq!(words("the cat sat", "the dog sat").unwrap(),
+                   [Change::Same("the "),
+                    Change::Delete("cat"),
+                    Change::Insert("dog"),
+                    Change::Same(" sat")]);
+        assert_eq!(words("same", "same").unwrap(), [Change::Same("same")]);
+        assert_eq!(words("", "new").unwrap(), [Change::Insert("new")]);
+    }
+
+    #[test]
+    fn gives_up_on_long_inputs() {
+        let long = "a ".repeat(MAX_TOKENS);
+        assert!(words(&long, "a").is_none());
+        assert!(words("a", &long).is_none());
+        assert!(words("a b", "a c").is_some());
+    }
+
+    #[test]
+    fn renders_with_and_without_color() {
+        let changes = words("a b", "a c").unwrap();
+        assert_eq!(render(&changes, false), "a [-b-]{+c+}");
+        assert_eq!(render(&changes, true), "a \x1b[31mb\x1b[0m\x1b[32mc\x1b[0m");
+    }
+}
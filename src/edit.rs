@@ -0,0 +1,86 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Editing a pattern in the user's text editor, in verbose
+//! (`(?x)`) form so it can be spread over lines and commented.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::process::{self, Command};
+
+/// Rewrite `pattern` so it means the same with the `x` flag set,
+/// escaping whitespace and `#`. Patterns already starting with
+/// `(?x)` are returned as they are.
+pub fn to_verbose(pattern: &str) -> String {
+    if pattern.starts_with("(?x)") {
+        return pattern.to_owned();
+    }
+    let mut out = String::from("(?x)\n");
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                if let Some(c) = chars.next() {
+                    out.push(c);
+                }
+            }
+            ' ' => out.push_str("\\ "),
+            '#' => out.push_str("\\#"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// The editor to run: `$VISUAL`, then `$EDITOR`, then `vi`.
+fn editor() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_owned())
+}
+
+/// Open `text` in the user's editor and return what was saved,
+/// without the final newline editors add.
+pub fn edit(text: &str) -> io::Result<String> {
+    let path = env::temp_dir().join(format!("regtest-{}.regex", process::id()));
+    File::create(&path)?.write_all(text.as_bytes())?;
+
+    let editor = editor();
+    let mut words = editor.split_whitespace();
+    let status = Command::new(words.next().unwrap()).args(words).arg(&path).status();
+    let result = match status {
+        Ok(ref s) if s.success() => {
+            let mut saved = String::new();
+            File::open(&path).and_then(|mut f| f.read_to_string(&mut saved)).map(|_| saved)
+        }
+        Ok(s) => Err(io::Error::other(format!("{} exited with {}", editor, s))),
+        Err(e) => Err(io::Error::new(e.kind(), format!("{}: {}", editor, e))),
+    };
+    fs::remove_file(&path);
+    let mut saved = result?;
+    if saved.ends_with('\n') {
+        saved.pop();
+    }
+    Ok(saved)
+}
@@ -0,0 +1,319 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Differential fuzzing: random patterns and inputs run through
+//! every matcher this build has, looking for cases where they
+//! disagree. The backtracking engines regtest knows of aren't
+//! built in, so the matchers compared are the regex crate's own
+//! entry points, which run on different internal engines.
+
+use regex::{self, RegexSet};
+use regex::bytes;
+use regex_syntax::Parser;
+
+use sample::{self, Rng};
+
+/// A way of running a pattern.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Engine {
+    /// `Regex::captures_iter` on `&str`.
+    Regex,
+    /// `bytes::Regex::captures_iter` on the UTF-8 bytes.
+    Bytes,
+    /// `RegexSet::is_match`, which only says whether it matched.
+    Set,
+    /// `Regex::shortest_match`, which only says whether it matched.
+    Shortest,
+}
+
+/// Every engine by name, and whether this build has it.
+pub const ENGINES: &[(&str, Option<Engine>)] = &[("regex", Some(Engine::Regex)),
+                                                  ("regex-bytes", Some(Engine::Bytes)),
+                                                  ("regex-set", Some(Engine::Set)),
+                                                  ("regex-shortest", Some(Engine::Shortest)),
+                                                  ("regex-lite", None),
+                                                  ("fancy", None),
+                                                  ("pcre2", None)];
+
+impl Engine {
+    /// Look `name` up: `Err(true)` if regtest knows it but wasn't
+    /// built with it, `Err(false)` if it's unknown.
+    pub fn from_name(name: &str) -> Result<Engine, bool> {
+        match ENGINES.iter().find(|&&(n, _)| n == name) {
+            Some(&(_, Some(e))) => Ok(e),
+            Some(&(_, None)) => Err(true),
+            None => Err(false),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        ENGINES.iter().find(|&&(_, e)| e == Some(self)).unwrap().0
+    }
+
+    /// What `pattern` does on `input`, or `None` if this engine
+    /// rejects the pattern.
+    pub fn run(self, pattern: &str, input: &str) -> Option<Outcome> {
+        let spans = |caps: Vec<Groups>| Outcome {
+            matched: !caps.is_empty(),
+            spans: Some(caps),
+        };
+        let matched = |m: bool| Outcome {
+            matched: m,
+            spans: None,
+        };
+        match self {
+            Engine::Regex => {
+                let reg = regex::Regex::new(pattern).ok()?;
+                Some(spans(reg.captures_iter(input)
+                    .map(|c| c.iter().map(|g| g.map(|g| (g.start(), g.end()))).collect())
+                    .collect()))
+            }
+            Engine::Bytes => {
+                // Empty matches inside a character are how bytes::Regex
+                // is documented to work, not a disagreement
+                let reg = bytes::Regex::new(pattern).ok()?;
+                Some(spans(reg.captures_iter(input.as_bytes())
+                    .filter(|c| input.is_char_boundary(c.get(0).unwrap().start()))
+                    .map(|c| c.iter().map(|g| g.map(|g| (g.start(), g.end()))).collect())
+                    .collect()))
+            }
+            Engine::Set => Some(matched(RegexSet::new([pattern]).ok()?.is_match(input))),
+            Engine::Shortest => {
+                Some(matched(regex::Regex::new(pattern).ok()?.shortest_match(input).is_some()))
+            }
+        }
+    }
+}
+
+/// The spans of the groups of one match, `None` for those that took
+/// no part.
+pub type Groups = Vec<Option<(usize, usize)>>;
+
+/// What an engine found: whether there was a match, and the spans of
+/// every group of every match if it reports them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub matched: bool,
+    pub spans: Option<Vec<Groups>>,
+}
+
+impl Outcome {
+    /// Do the two say different things? Spans are only compared
+    /// when both have them.
+    fn differs(&self, other: &Outcome) -> bool {
+        self.matched != other.matched ||
+        match (&self.spans, &other.spans) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        }
+    }
+}
+
+/// A pattern and input the engines disagree on, with what each one
+/// did.
+#[derive(Clone, Debug)]
+pub struct Disagreement {
+    pub pattern: String,
+    pub input: String,
+    pub outcomes: Vec<(Engine, Option<Outcome>)>,
+}
+
+/// The result of every engine in `engines` on `pattern` and `input`,
+/// if they don't all agree. An engine rejecting a pattern the others
+/// accept counts as disagreeing.
+pub fn compare(engines: &[Engine], pattern: &str, input: &str) -> Option<Disagreement> {
+    let outcomes: Vec<(Engine, Option<Outcome>)> =
+        engines.iter().map(|&e| (e, e.run(pattern, input))).collect();
+    let first = &outcomes[0].1;
+    let differs = outcomes[1..].iter().any(|(_, o)| match (first, o) {
+        (Some(a), Some(b)) => a.differs(b),
+        (None, None) => false,
+        _ => true,
+    });
+    if !differs {
+        return None;
+    }
+    Some(Disagreement {
+        pattern: pattern.to_owned(),
+        input: input.to_owned(),
+        outcomes,
+    })
+}
+
+/// Make `d` smaller while the engines keep disagreeing: drop
+/// characters of the input, then of the pattern, one at a time.
+pub fn minimize(engines: &[Engine], mut d: Disagreement) -> Disagreement {
+    loop {
+        let shorter = without_each(&d.input)
+            .filter_map(|input| compare(engines, &d.pattern, &input))
+            .chain(without_each(&d.pattern).filter_map(|p| compare(engines, &p, &d.input)))
+            .next();
+        match shorter {
+            Some(s) => d = s,
+            None => return d,
+        }
+    }
+}
+
+/// `s` with each of its characters left out in turn.
+fn without_each(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.char_indices().map(move |(i, c)| format!("{}{}", &s[..i], &s[i + c.len_utf8()..]))
+}
+
+/// Atoms random patterns are built from.
+const ATOMS: &[&str] = &["a", "b", "A", " ", "é", ".", "\\w", "\\d", "\\s", "\\b", "\\B", "^", "$",
+                         "[ab]", "[^a]", "[a-z]", "\\p{L}", "(?i:a)", "\\A", "\\z"];
+
+/// Characters random inputs are built from.
+const INPUT_CHARS: &[char] = &['a', 'b', 'A', 'B', ' ', '\n', '1', '_', 'é', 'É', 'ß', '-'];
+
+/// A random pattern, `depth` levels of nesting at most.
+pub fn random_pattern(rng: &mut Rng, depth: u32) -> String {
+    let mut out = String::new();
+    if depth == 3 && rng.one_in(4) {
+        out.push_str(["(?m)", "(?s)", "(?i)", "(?U)"][rng.below(4) as usize]);
+    }
+    for _ in 0..1 + rng.below(3) {
+        let atom = if depth > 0 && rng.one_in(3) {
+            let inner = random_pattern(rng, depth - 1);
+            match rng.below(3) {
+                0 => format!("({})", inner),
+                1 => format!("(?:{}|{})", inner, random_pattern(rng, depth - 1)),
+                _ => format!("(?P<g{}>{})", rng.below(10), inner),
+            }
+        } else {
+            ATOMS[rng.below(ATOMS.len() as u64) as usize].to_owned()
+        };
+        out.push_str(&atom);
+        if rng.one_in(3) {
+            out.push_str(["*", "+", "?", "{1,2}", "{0,3}", "*?", "+?", "??"][rng.below(8) as usize]);
+        }
+    }
+    out
+}
+
+/// A random input, sometimes one the pattern is likely to match.
+fn random_input(pattern: &str, rng: &mut Rng) -> String {
+    if rng.one_in(2) {
+        if let Ok(hir) = Parser::new().parse(pattern) {
+            let s = sample::generate(&hir, rng);
+            return if rng.one_in(2) { sample::mutate(&s, rng) } else { s };
+        }
+    }
+    (0..rng.below(9)).map(|_| INPUT_CHARS[rng.below(INPUT_CHARS.len() as u64) as usize]).collect()
+}
+
+/// What a fuzzing run found.
+pub struct Report {
+    /// Patterns and inputs tried.
+    pub cases: usize,
+    /// Minimized disagreements, each pattern at most once.
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Try `count` random patterns, each on a few inputs, on every one
+/// of `engines`.
+pub fn fuzz(engines: &[Engine], count: usize, rng: &mut Rng) -> Report {
+    let mut report = Report {
+        cases: 0,
+        disagreements: vec![],
+    };
+    for _ in 0..count {
+        let pattern = random_pattern(rng, 3);
+        for _ in 0..4 {
+            let input = random_input(&pattern, rng);
+            report.cases += 1;
+            if let Some(d) = compare(engines, &pattern, &input) {
+                let d = minimize(engines, d);
+                if !report.disagreements.iter().any(|o| o.pattern == d.pattern) {
+                    debug!("engines disagree on {:?} with {:?}", d.pattern, d.input);
+                    report.disagreements.push(d);
+                }
+                break;
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_engines_up_by_name() {
+        assert_eq!(Engine::from_name("regex-bytes"), Ok(Engine::Bytes));
+        assert_eq!(Engine::from_name("pcre2"), Err(true));
+        assert_eq!(Engine::from_name("nope"), Err(false));
+        for &(name, engine) in ENGINES {
+            if let Some(e) = engine {
+                assert_eq!(e.name(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn runs_each_engine() {
+        let spans = Engine::Regex.run(r"(a)(b)?", "a ab").unwrap();
+        assert!(spans.matched);
+        assert_eq!(spans.spans,
+                   Some(vec![vec![Some((0, 1)), Some((0, 1)), None],
+                             vec![Some((2, 4)), Some((2, 3)), Some((3, 4))]]));
+        assert_eq!(Engine::Bytes.run(r"(a)(b)?", "a ab"), Some(spans));
+        assert_eq!(Engine::Set.run("b", "abc"),
+                   Some(Outcome {
+                       matched: true,
+                       spans: None,
+                   }));
+        assert_eq!(Engine::Shortest.run("x", "abc").map(|o| o.matched), Some(false));
+        assert_eq!(Engine::Regex.run("(", "abc"), None);
+    }
+
+    #[test]
+    fn engines_on_the_same_crate_agree() {
+        let all: Vec<Engine> = ENGINES.iter().filter_map(|e| e.1).collect();
+        assert!(compare(&all, r"\w+", "héllo wörld").is_none());
+        assert!(compare(&all, "", "é").is_none());
+        assert!(compare(&all, "(", "").is_none());
+    }
+
+    #[test]
+    fn a_rejected_pattern_is_a_disagreement() {
+        // Only bytes::Regex takes patterns that can match invalid UTF-8
+        let engines = [Engine::Regex, Engine::Bytes];
+        let d = compare(&engines, r"a(?-u:\xFF)", "a").unwrap();
+        assert_eq!(d.outcomes[0], (Engine::Regex, None));
+        assert!(d.outcomes[1].1.is_some());
+
+        let d = minimize(&engines, d);
+        assert_eq!((&d.pattern[..], &d.input[..]), (r"(?-u:\xFF)", ""));
+    }
+
+    #[test]
+    fn leaves_out_each_character() {
+        let shorter: Vec<String> = without_each("aé!").collect();
+        assert_eq!(shorter, ["é!", "a!", "aé"]);
+    }
+
+    #[test]
+    fn fuzzing_tries_a_few_inputs_per_pattern() {
+        let all: Vec<Engine> = ENGINES.iter().filter_map(|e| e.1).collect();
+        let report = fuzz(&all, 20, &mut Rng::new(7));
+        assert!(report.cases >= 20 && report.cases <= 80);
+        assert!(report.disagreements.is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Flags set on the `RegexBuilder` rather than inline in the
+//! pattern, written with the same letters as inline flags.
+
+use regex::{self, Regex, RegexBuilder};
+use regex_syntax::{self, ParserBuilder};
+use regex_syntax::ast::{Ast, ClassSet, ClassSetItem};
+use regex_syntax::ast::parse::{self, Parser};
+
+bitflags! {
+    pub struct Flags: u8 {
+        const CASE_INSENSITIVE     = 0b00000001;
+        const MULTI_LINE           = 0b00000010;
+        const DOT_MATCHES_NEW_LINE = 0b00000100;
+        const SWAP_GREED           = 0b00001000;
+        const IGNORE_WHITESPACE    = 0b00010000;
+        const UNICODE              = 0b00100000;
+    }
+}
+
+/// Does `pattern` match an uppercase letter literally? Escapes like
+/// `\S` or `\P{L}` don't count. Patterns that don't parse are taken
+/// to have none.
+pub fn has_uppercase(pattern: &str) -> bool {
+    fn ast(a: &Ast) -> bool {
+        match *a {
+            Ast::Literal(ref l) => l.c.is_uppercase(),
+            Ast::Class(::regex_syntax::ast::Class::Bracketed(ref c)) => set(&c.kind),
+            Ast::Repetition(ref r) => ast(&r.ast),
+            Ast::Group(ref g) => ast(&g.ast),
+            Ast::Alternation(ref alt) => alt.asts.iter().any(ast),
+            Ast::Concat(ref c) => c.asts.iter().any(ast),
+            _ => false,
+        }
+    }
+    fn set(s: &ClassSet) -> bool {
+        match *s {
+            ClassSet::Item(ref i) => item(i),
+            ClassSet::BinaryOp(ref op) => set(&op.lhs) || set(&op.rhs),
+        }
+    }
+    fn item(i: &ClassSetItem) -> bool {
+        match *i {
+            ClassSetItem::Literal(ref l) => l.c.is_uppercase(),
+            ClassSetItem::Range(ref r) => r.start.c.is_uppercase() || r.end.c.is_uppercase(),
+            ClassSetItem::Bracketed(ref c) => set(&c.kind),
+            ClassSetItem::Union(ref u) => u.items.iter().any(item),
+            _ => false,
+        }
+    }
+    Parser::new().parse(pattern).map(|a| ast(&a)).unwrap_or(false)
+}
+
+/// Most bytes a compiled program may take, the builder's default.
+pub const SIZE_LIMIT: usize = 10 * (1 << 20);
+/// Most bytes the lazy DFA's cache may take, the builder's default.
+pub const DFA_SIZE_LIMIT: usize = 2 * (1 << 20);
+/// Deepest nesting of groups and repetitions, the parser's default.
+pub const NEST_LIMIT: u32 = 250;
+
+impl Default for Flags {
+    fn default() -> Flags {
+        Flags::UNICODE
+    }
+}
+
+/// Every flag and its letter.
+pub const LETTERS: &[(char, Flags)] = &[('i', Flags::CASE_INSENSITIVE),
+                                        ('m', Flags::MULTI_LINE),
+                                        ('s', Flags::DOT_MATCHES_NEW_LINE),
+                                        ('U', Flags::SWAP_GREED),
+                                        ('x', Flags::IGNORE_WHITESPACE),
+                                        ('u', Flags::UNICODE)];
+
+fn flag(letter: char) -> Result<Flags, char> {
+    LETTERS.iter().find(|&&(c, _)| c == letter).map(|&(_, f)| f).ok_or(letter)
+}
+
+impl Flags {
+    /// The flags that differ from the defaults, as in an inline
+    /// flag group: `im-u`, or `none`.
+    pub fn name(self) -> String {
+        let changed = self ^ Flags::default();
+        let letters = |on: bool| -> String {
+            LETTERS.iter()
+                .filter(|&&(_, f)| changed.contains(f) && self.contains(f) == on)
+                .map(|&(c, _)| c)
+                .collect()
+        };
+        let (on, off) = (letters(true), letters(false));
+        match (on.is_empty(), off.is_empty()) {
+            (true, true) => "none".to_owned(),
+            (_, true) => on,
+            _ => format!("{}-{}", on, off),
+        }
+    }
+
+    /// Parse what `name` returns.
+    pub fn from_name(name: &str) -> Result<Flags, char> {
+        if name == "none" {
+            return Ok(Flags::default());
+        }
+        let (on, off) = match name.find('-') {
+            Some(i) => (&name[..i], &name[i + 1..]),
+            None => (name, ""),
+        };
+        let mut flags = Flags::default();
+        for c in on.chars() {
+            flags.insert(flag(c)?);
+        }
+        for c in off.chars() {
+            flags.remove(flag(c)?);
+        }
+        Ok(flags)
+    }
+
+    /// Apply changes like `+i -m` or `+is`. Letters without a sign
+    /// are turned on.
+    pub fn change(self, changes: &str) -> Result<Flags, char> {
+        let mut flags = self;
+        for word in changes.split_whitespace() {
+            let (on, letters) = if let Some(rest) = word.strip_prefix('-') {
+                (false, rest)
+            } else {
+                (true, word.trim_start_matches('+'))
+            };
+            for c in letters.chars() {
+                flags.set(flag(c)?, on);
+            }
+        }
+        Ok(flags)
+    }
+
+    /// Compile `pattern` with these flags.
+    pub fn build(self, pattern: &str) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(self.contains(Flags::CASE_INSENSITIVE))
+            .multi_line(self.contains(Flags::MULTI_LINE))
+            .dot_matches_new_line(self.contains(Flags::DOT_MATCHES_NEW_LINE))
+            .swap_greed(self.contains(Flags::SWAP_GREED))
+            .ignore_whitespace(self.contains(Flags::IGNORE_WHITESPACE))
+            .unicode(self.contains(Flags::UNICODE))
+            .size_limit(SIZE_LIMIT)
+            .dfa_size_limit(DFA_SIZE_LIMIT)
+            .nest_limit(NEST_LIMIT)
+            .build()
+    }
+
+    /// A parser that reads patterns into a syntax tree the way
+    /// `build` would.
+    pub fn ast_parser(self) -> Parser {
+        parse::ParserBuilder::new()
+            .ignore_whitespace(self.contains(Flags::IGNORE_WHITESPACE))
+            .nest_limit(NEST_LIMIT)
+            .build()
+    }
+
+    /// A parser that translates patterns with these flags applied.
+    pub fn parser(self) -> regex_syntax::Parser {
+        ParserBuilder::new()
+            .case_insensitive(self.contains(Flags::CASE_INSENSITIVE))
+            .multi_line(self.contains(Flags::MULTI_LINE))
+            .dot_matches_new_line(self.contains(Flags::DOT_MATCHES_NEW_LINE))
+            .swap_greed(self.contains(Flags::SWAP_GREED))
+            .ignore_whitespace(self.contains(Flags::IGNORE_WHITESPACE))
+            .unicode(self.contains(Flags::UNICODE))
+            .nest_limit(NEST_LIMIT)
+            .build()
+    }
+}
@@ -0,0 +1,159 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Output templates given with `--format`, written once per match:
+//!
+//! ```text
+//! {file}:{line}: {match} [{group.name}]
+//! ```
+//!
+//! `{file}` and `{line}` say where the match is, `{match}`,
+//! `{start}` and `{end}` give its text and byte span, and
+//! `{group.N}` or `{group.name}` the text of a group, empty if it
+//! didn't match. `{{` and `}}` stand for literal braces.
+
+use regex::Captures;
+
+/// Part of an output template.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Piece {
+    Literal(String),
+    File,
+    Line,
+    Match,
+    Start,
+    End,
+    /// A group by number or name.
+    Group(String),
+}
+
+/// Where a match was found.
+pub struct Place<'a> {
+    pub file: &'a str,
+    /// Line number, starting at 1.
+    pub line: usize,
+}
+
+/// Parse `template`, failing on unknown or unclosed placeholders.
+pub fn parse(template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = vec![];
+    let mut literal = String::new();
+    let mut chars = template.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|p| p.1) == Some('{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek().map(|p| p.1) == Some('}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let end = template[i..].find('}').ok_or_else(|| msg!(FormatUnclosed, i))?;
+                let name = &template[i + 1..i + end];
+                let piece = match name {
+                    "file" => Piece::File,
+                    "line" => Piece::Line,
+                    "match" => Piece::Match,
+                    "start" => Piece::Start,
+                    "end" => Piece::End,
+                    n if n.starts_with("group.") && n.len() > "group.".len() => {
+                        Piece::Group(n["group.".len()..].to_owned())
+                    }
+                    n => return Err(msg!(FormatUnknown, n)),
+                };
+                if !literal.is_empty() {
+                    pieces.push(Piece::Literal(literal.clone()));
+                    literal.clear();
+                }
+                pieces.push(piece);
+                while chars.peek().is_some_and(|p| p.0 <= i + end) {
+                    chars.next();
+                }
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push(Piece::Literal(literal));
+    }
+    Ok(pieces)
+}
+
+/// Fill in `pieces` for the match `caps` found at `place`.
+pub fn render(pieces: &[Piece], place: &Place, caps: &Captures) -> String {
+    let m = caps.get(0).unwrap();
+    let mut out = String::new();
+    for piece in pieces {
+        match *piece {
+            Piece::Literal(ref s) => out.push_str(s),
+            Piece::File => out.push_str(place.file),
+            Piece::Line => out.push_str(&place.line.to_string()),
+            Piece::Match => out.push_str(m.as_str()),
+            Piece::Start => out.push_str(&m.start().to_string()),
+            Piece::End => out.push_str(&m.end().to_string()),
+            Piece::Group(ref g) => {
+                let group = match g.parse::<usize>() {
+                    Ok(i) => caps.get(i),
+                    Err(_) => caps.name(g),
+                };
+                out.push_str(group.map_or("", |g| g.as_str()));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn parses_placeholders() {
+        assert_eq!(parse("{file}:{line}: {{{match}}} {group.1}").unwrap(),
+                   vec![Piece::File,
+                        Piece::Literal(":".to_owned()),
+                        Piece::Line,
+                        Piece::Literal(": {".to_owned()),
+                        Piece::Match,
+                        Piece::Literal("} ".to_owned()),
+                        Piece::Group("1".to_owned())]);
+        assert_eq!(parse("a}b").unwrap(), vec![Piece::Literal("a}b".to_owned())]);
+    }
+
+    #[test]
+    fn bad_placeholders_are_errors() {
+        assert!(parse("{match").is_err());
+        assert!(parse("{lines}").unwrap_err().contains("lines"));
+        assert!(parse("{group.}").is_err());
+        assert!(parse("{}").is_err());
+    }
+
+    #[test]
+    fn renders_matches() {
+        let reg = Regex::new(r"(?P<k>\w+)=(\d+)?").unwrap();
+        let caps = reg.captures("  size= ").unwrap();
+        let place = Place {
+            file: "f.txt",
+            line: 7,
+        };
+        let pieces = parse("{file}:{line}:{start}-{end} {group.k}[{group.2}] {match}").unwrap();
+        assert_eq!(render(&pieces, &place, &caps), "f.txt:7:2-7 size[] size=");
+    }
+}
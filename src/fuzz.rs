@@ -0,0 +1,113 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Throwing random text at a regex to find what it matches that it
+//! probably shouldn't: junk that has nothing to do with the inputs
+//! tried, and edits of those inputs the match takes in.
+
+use regex::Regex;
+
+use sample::{self, Rng};
+
+/// Characters random strings are made of: some of every kind a
+/// pattern might be too lenient with.
+const ALPHABET: &[char] = &['a', 'z', 'A', 'Q', '0', '7', '9', ' ', '\t', '\n', '.', ',', '-', '_',
+                            '@', '/', ':', '"', '\'', '(', ')', '[', '\\', '+', '*', '#', 'é', 'ß',
+                            'İ', '٣', '漢', '\u{301}', '\u{a0}', '😀'];
+
+/// The longest random string.
+const MAX_LEN: u64 = 16;
+
+/// The most findings of each kind kept.
+const MAX_FINDINGS: usize = 5;
+
+/// An input the regex matched and the input it was made from, if it
+/// was an edit of one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub input: String,
+    pub from: Option<String>,
+}
+
+/// What a run of `:fuzz` found.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub tried: usize,
+    /// Random strings that matched, shortest first.
+    pub random: Vec<Finding>,
+    /// Edits of matching inputs whose match took in a character the
+    /// original match didn't have, so the regex accepted the edit.
+    pub edits: Vec<Finding>,
+}
+
+/// A random string of up to `MAX_LEN` characters.
+pub fn random_string(rng: &mut Rng) -> String {
+    let len = rng.below(MAX_LEN + 1);
+    (0..len).map(|_| ALPHABET[rng.below(ALPHABET.len() as u64) as usize]).collect()
+}
+
+/// Try `n` strings on `reg`, half random and half edits of the
+/// `inputs` it matches. Without such inputs, all are random.
+pub fn fuzz(reg: &Regex, inputs: &[String], n: usize, rng: &mut Rng) -> Report {
+    let matching: Vec<&String> = inputs.iter().filter(|i| reg.is_match(i)).collect();
+    let mut report = Report::default();
+    let mut random = vec![];
+    let mut edits = vec![];
+    for i in 0..n {
+        report.tried += 1;
+        if matching.is_empty() || i % 2 == 0 {
+            let s = random_string(rng);
+            if reg.is_match(&s) {
+                random.push(Finding {
+                    input: s,
+                    from: None,
+                });
+            }
+            continue;
+        }
+
+        let from = matching[rng.below(matching.len() as u64) as usize];
+        // A few edits at once reach further from the input
+        let mut s = from.clone();
+        for _ in 0..1 + rng.below(3) {
+            s = sample::mutate(&s, rng);
+        }
+        // Only matches taking in a character the original match
+        // didn't have show the regex accepting something new
+        let before = reg.find(from).map_or("", |m| m.as_str());
+        match reg.find(&s) {
+            Some(m) if m.as_str().chars().any(|c| !before.contains(c)) && !inputs.contains(&s) => {
+                edits.push(Finding {
+                    input: s.clone(),
+                    from: Some(from.clone()),
+                })
+            }
+            _ => {}
+        }
+    }
+    report.random = shortest(random);
+    report.edits = shortest(edits);
+    report
+}
+
+/// The `MAX_FINDINGS` shortest distinct findings.
+fn shortest(mut findings: Vec<Finding>) -> Vec<Finding> {
+    findings.sort_by(|a, b| a.input.len().cmp(&b.input.len()).then_with(|| a.input.cmp(&b.input)));
+    findings.dedup_by(|a, b| a.input == b.input);
+    findings.truncate(MAX_FINDINGS);
+    findings
+}
@@ -0,0 +1,213 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Grading students' patterns against a spec of inputs they must
+//! and must not match. A spec is a TOML file:
+//!
+//! ```text
+//! title = "ISO dates"
+//! flags = "i"
+//! match = ["2024-01-15", "1999-12-31"]
+//! nomatch = ["2024-1-15", "15/01/2024"]
+//! ```
+//!
+//! Each submission is a file holding a student's pattern on its
+//! first non-blank line, named after the student.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use flags::Flags;
+use json::Value;
+use suite::Expectation;
+use toml;
+
+pub struct Spec {
+    pub title: String,
+    /// Flags every submission is compiled with.
+    pub flags: Flags,
+    pub cases: Vec<Expectation>,
+}
+
+impl Spec {
+    /// Parse the text of a spec file.
+    pub fn parse(text: &str) -> Result<Spec, String> {
+        let doc = toml::parse(text)?;
+        let title = doc.get("title").and_then(Value::as_str).unwrap_or("").to_owned();
+        let flags = match doc.get("flags") {
+            None => Flags::default(),
+            Some(f) => {
+                let name = f.as_str().ok_or("flags should be a string")?;
+                Flags::default()
+                    .change(name)
+                    .map_err(|c| format!("unknown flag {:?}", c))?
+            }
+        };
+        let mut cases = vec![];
+        for &(key, should_match) in &[("match", true), ("nomatch", false)] {
+            let inputs = match doc.get(key) {
+                None => continue,
+                Some(v) => v.as_array().ok_or_else(|| format!("{} should be an array", key))?,
+            };
+            for input in inputs {
+                let input = input.as_str()
+                    .ok_or_else(|| format!("{} should only hold strings", key))?;
+                cases.push(Expectation {
+                    input: input.to_owned(),
+                    should_match,
+                });
+            }
+        }
+        if cases.is_empty() {
+            return Err("no match or nomatch inputs".to_owned());
+        }
+        Ok(Spec { title, flags, cases })
+    }
+
+    /// Read and parse the spec file at `path`.
+    pub fn load(path: &str) -> Result<Spec, String> {
+        let mut text = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut text))
+            .map_err(|e| format!("{}: {}", path, e))?;
+        Spec::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+/// How one student did.
+pub struct Scorecard {
+    pub student: String,
+    /// The cases of the spec the pattern got wrong.
+    pub failed: Vec<usize>,
+    /// Why there's no score: the pattern is missing or doesn't
+    /// compile.
+    pub error: Option<String>,
+}
+
+impl Scorecard {
+    /// Cases passed out of `total`.
+    pub fn score(&self, total: usize) -> usize {
+        match self.error {
+            Some(_) => 0,
+            None => total - self.failed.len(),
+        }
+    }
+}
+
+/// The submission files in `dir`, sorted by name. Hidden files are
+/// left out.
+pub fn submissions(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if path.is_file() && !hidden {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Grade the submission at `path`.
+pub fn grade(spec: &Spec, path: &Path) -> Scorecard {
+    let student = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+    let mut text = String::new();
+    let pattern = File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut text))
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            text.lines()
+                .map(|l| l.trim_end_matches('\r'))
+                .find(|l| !l.trim().is_empty())
+                .ok_or_else(|| msg!(GradeNoPattern).to_owned())
+        });
+    let reg = pattern.and_then(|p| spec.flags.build(p).map_err(|e| msg!(ErrorCompiling, e)));
+    match reg {
+        Ok(reg) => {
+            Scorecard {
+                student,
+                failed: (0..spec.cases.len())
+                    .filter(|&i| reg.is_match(&spec.cases[i].input) != spec.cases[i].should_match)
+                    .collect(),
+                error: None,
+            }
+        }
+        Err(e) => {
+            Scorecard {
+                student,
+                failed: vec![],
+                error: Some(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    const SPEC: &str = "title = \"Dates\"\nflags = \"i\"\nmatch = [\"2024-01-15\", \"x\"]\n\
+                        nomatch = [\"15/01/2024\"]\n";
+
+    #[test]
+    fn parses_specs() {
+        let spec = Spec::parse(SPEC).unwrap();
+        assert_eq!(spec.title, "Dates");
+        assert!(spec.flags.contains(Flags::CASE_INSENSITIVE));
+        assert_eq!(spec.cases.len(), 3);
+        assert!(!spec.cases[2].should_match);
+    }
+
+    #[test]
+    fn malformed_specs_are_errors() {
+        assert!(Spec::parse("title = \"x\"\n").is_err());
+        assert!(Spec::parse("match = \"a\"\n").is_err());
+        assert!(Spec::parse("match = [1]\n").is_err());
+        assert!(Spec::parse("flags = \"q\"\nmatch = [\"a\"]\n").is_err());
+        assert!(Spec::parse("match = [\"a\"").is_err());
+    }
+
+    #[test]
+    fn grades_submissions() {
+        let dir = env::temp_dir().join(format!("regtest-grade-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ana.txt"), "\n  \n^\\d{4}-\\d\\d-\\d\\d$\n").unwrap();
+        fs::write(dir.join("bo.txt"), "X|/").unwrap();
+        fs::write(dir.join("cy.txt"), "(").unwrap();
+        fs::write(dir.join("di.txt"), "\n").unwrap();
+        fs::write(dir.join(".hidden"), "a").unwrap();
+
+        let spec = Spec::parse(SPEC).unwrap();
+        let cards: Vec<Scorecard> = submissions(&dir)
+            .unwrap()
+            .iter()
+            .map(|p| grade(&spec, p))
+            .collect();
+        let students: Vec<&str> = cards.iter().map(|c| &c.student[..]).collect();
+        assert_eq!(students, ["ana", "bo", "cy", "di"]);
+        assert_eq!(cards[0].failed, [1]);
+        assert_eq!(cards[1].failed, [0, 2]);
+        assert_eq!(cards[1].score(3), 1);
+        assert!(cards[2].error.is_some() && cards[3].error.is_some());
+        assert_eq!(cards[2].score(3), 0);
+        fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,328 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Markers drawn under an input to show where matches are. Inputs
+//! are measured in grapheme clusters and terminal columns rather
+//! than bytes, so the markers stay lined up under wide characters
+//! (CJK, emoji) and combining marks. Several patterns can also be
+//! shown at once, each in its own color.
+
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How an input is echoed above its markers. Tabs become a space
+/// so they take up a known width.
+pub fn line(input: &str) -> String {
+    input.replace('\t', " ")
+}
+
+/// The number of terminal columns `text` takes up.
+pub fn width(text: &str) -> usize {
+    text.graphemes(true).map(cluster_width).sum()
+}
+
+/// A single grapheme cluster is at least one column wide, so even
+/// a lone combining mark gets a marker.
+fn cluster_width(g: &str) -> usize {
+    if g == "\t" { 1 } else { g.width().max(1) }
+}
+
+/// A line of `^` under every grapheme cluster touched by one of the
+/// byte ranges in `spans`. An empty span gets a `|` before the
+/// cluster it's at.
+pub fn underline(input: &str, spans: &[(usize, usize)]) -> String {
+    let cells = cells(input, spans);
+    let out: String = cells.iter().map(|c| c.marks()).collect();
+    out.trim_end().to_owned()
+}
+
+/// A grapheme cluster as echoed, and the mark under it.
+struct Cell<'a> {
+    text: &'a str,
+    width: usize,
+    mark: char,
+}
+
+impl<'a> Cell<'a> {
+    /// The marks under the cell, one per column.
+    fn marks(&self) -> String {
+        let rest = if self.mark == '^' { '^' } else { ' ' };
+        let mut out = self.mark.to_string();
+        for _ in 1..self.width {
+            out.push(rest);
+        }
+        out
+    }
+}
+
+/// The cells of `input` marked for `spans`, with an empty cell at
+/// the end for an empty match there.
+fn cells<'a>(input: &'a str, spans: &[(usize, usize)]) -> Vec<Cell<'a>> {
+    let mut out = vec![];
+    for (pos, g) in input.grapheme_indices(true) {
+        let end = pos + g.len();
+        let marked = spans.iter().any(|&(s, e)| s < end && e > pos);
+        let mark = if marked {
+            '^'
+        } else if spans.iter().any(|&(s, e)| s == e && s >= pos && s < end) {
+            '|'
+        } else {
+            ' '
+        };
+        let text = if g == "\t" { " " } else { g };
+        out.push(Cell { text, width: cluster_width(g), mark });
+    }
+    if spans.iter().any(|&(s, e)| s == e && s == input.len()) {
+        out.push(Cell { text: "", width: 1, mark: '|' });
+    }
+    out
+}
+
+/// What's done with inputs wider than the terminal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LongLines {
+    /// Print them whole and let the terminal wrap them.
+    Full,
+    /// Cut them down to the part around the first match.
+    Truncate,
+    /// Break them into rows, each with its own markers.
+    Wrap,
+}
+
+impl LongLines {
+    pub fn from_name(name: &str) -> Option<LongLines> {
+        match name {
+            "full" => Some(LongLines::Full),
+            "truncate" => Some(LongLines::Truncate),
+            "wrap" => Some(LongLines::Wrap),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LongLines::Full => "full",
+            LongLines::Truncate => "truncate",
+            LongLines::Wrap => "wrap",
+        }
+    }
+}
+
+/// Cut or wrapped inputs are marked with this where text is left
+/// out or continues.
+const ELLIPSIS: &str = "…";
+
+/// `input` and the markers under `spans`, as pairs of rows fitting
+/// in `columns` the way `mode` says. Truncating keeps the first
+/// match in view, with a little of what comes before it.
+pub fn fit(input: &str, spans: &[(usize, usize)], columns: usize, mode: LongLines) -> Vec<(String, String)> {
+    let cells = cells(input, spans);
+    let total: usize = cells.iter().map(|c| c.width).sum();
+    let row = |cells: &[Cell], before: bool, after: bool| {
+        let mut text = String::new();
+        let mut marks = String::new();
+        if before {
+            text.push_str(ELLIPSIS);
+            marks.push(' ');
+        }
+        for c in cells {
+            text.push_str(c.text);
+            marks.push_str(&c.marks());
+        }
+        if after {
+            text.push_str(ELLIPSIS);
+        }
+        (text, marks.trim_end().to_owned())
+    };
+    if mode == LongLines::Full || total <= columns || columns < 8 {
+        return vec![row(&cells, false, false)];
+    }
+
+    // The most cells from `start` that fit in `room` columns
+    let span_from = |start: usize, room: usize| -> usize {
+        let mut used = 0;
+        let mut end = start;
+        while end < cells.len() && used + cells[end].width <= room {
+            used += cells[end].width;
+            end += 1;
+        }
+        end.max(start + 1).min(cells.len())
+    };
+    match mode {
+        LongLines::Wrap => {
+            let mut rows = vec![];
+            let mut start = 0;
+            while start < cells.len() {
+                let end = span_from(start, columns - 1);
+                rows.push(row(&cells[start..end], false, end < cells.len()));
+                start = end;
+            }
+            rows
+        }
+        _ => {
+            let room = columns - 2;
+            let first = cells.iter().position(|c| c.mark != ' ').unwrap_or(0);
+            // Back up a quarter of the room from the first match
+            let mut start = first;
+            let mut lead = 0;
+            while start > 0 && lead + cells[start - 1].width <= room / 4 {
+                start -= 1;
+                lead += cells[start].width;
+            }
+            let end = span_from(start, room);
+            vec![row(&cells[start..end], start > 0, end < cells.len())]
+        }
+    }
+}
+
+/// ANSI colors given to each of several patterns in turn.
+const COLORS: &[&str] = &["31", "32", "34", "35", "36", "33"];
+
+/// Text matched by more than one pattern is shown reversed.
+const OVERLAP: &str = "7";
+
+/// How pattern `i` of several is marked: in its color, or as `[i]`
+/// without `color`. Numbers start at 1.
+pub fn swatch(i: usize, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m[{}]\x1b[0m", COLORS[i % COLORS.len()], i + 1)
+    } else {
+        format!("[{}]", i + 1)
+    }
+}
+
+/// `input` with the matches of each of `regexes` in their own
+/// color, or bracketed and tagged with the pattern numbers like
+/// `[1:abc]` without `color`. Text matched by more than one pattern
+/// is reversed, or tagged with all of them.
+pub fn several(input: &str, regexes: &[&Regex], color: bool) -> String {
+    // The patterns matching each byte, as bits
+    let mut owners = vec![0u64; input.len()];
+    for (i, reg) in regexes.iter().enumerate().take(64) {
+        for m in reg.find_iter(input) {
+            for o in &mut owners[m.start()..m.end()] {
+                *o |= 1 << i;
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let mut start = 0;
+    while start < input.len() {
+        let mask = owners[start];
+        let end = (start..input.len())
+            .find(|&i| owners[i] != mask && input.is_char_boundary(i))
+            .unwrap_or(input.len());
+        let text = &input[start..end];
+        if mask == 0 {
+            out.push_str(text);
+        } else if color {
+            let style = if mask.count_ones() > 1 {
+                OVERLAP
+            } else {
+                COLORS[mask.trailing_zeros() as usize % COLORS.len()]
+            };
+            out.push_str(&format!("\x1b[{}m{}\x1b[0m", style, text));
+        } else {
+            let tags: Vec<String> = (0..64)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| (i + 1).to_string())
+                .collect();
+            out.push_str(&format!("[{}:{}]", tags.join(","), text));
+        }
+        start = end;
+    }
+    out
+}
+
+/// A line with a `^` under the grapheme cluster at byte offset
+/// `pos`, or just past the end of `input`.
+pub fn caret(input: &str, pos: usize) -> String {
+    let before = input.grapheme_indices(true)
+        .take_while(|&(i, _)| i < pos)
+        .map(|(_, g)| g);
+    let mut out: String = before.map(|g| " ".repeat(cluster_width(g))).collect();
+    out.push('^');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_columns() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本"), 4);
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(width("a\tb"), 3);
+        assert_eq!(line("a\tb"), "a b");
+    }
+
+    #[test]
+    fn underlines_matches_by_cluster() {
+        assert_eq!(underline("abcdef", &[(1, 3)]), " ^^");
+        assert_eq!(underline("日本語", &[(3, 6)]), "  ^^");
+        assert_eq!(underline("e\u{301}x", &[(0, 1)]), "^");
+        // Empty matches
+        assert_eq!(underline("ab", &[(1, 1)]), " |");
+        assert_eq!(underline("ab", &[(2, 2)]), "  |");
+    }
+
+    #[test]
+    fn fits_long_lines() {
+        let input = "0123456789abcdefghij";
+        let full = fit(input, &[(12, 13)], 10, LongLines::Full);
+        assert_eq!(full, [(input.to_owned(), "            ^".to_owned())]);
+
+        let wrapped = fit(input, &[(12, 13)], 10, LongLines::Wrap);
+        let texts: Vec<&str> = wrapped.iter().map(|r| &r.0[..]).collect();
+        assert_eq!(texts, ["012345678…", "9abcdefgh…", "ij"]);
+        assert_eq!(wrapped[1].1, "   ^");
+
+        let cut = fit(input, &[(12, 13)], 10, LongLines::Truncate);
+        assert_eq!(cut, [("…abcdefgh…".to_owned(), "   ^".to_owned())]);
+
+        // Short lines are left alone
+        assert_eq!(fit("abc", &[], 10, LongLines::Wrap), [("abc".to_owned(), String::new())]);
+    }
+
+    #[test]
+    fn long_lines_by_name() {
+        for &mode in &[LongLines::Full, LongLines::Truncate, LongLines::Wrap] {
+            assert_eq!(LongLines::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(LongLines::from_name("nope"), None);
+    }
+
+    #[test]
+    fn marks_several_patterns() {
+        let (a, b) = (Regex::new("ab").unwrap(), Regex::new("bc").unwrap());
+        assert_eq!(several("xabcx", &[&a, &b], false), "x[1:a][1,2:b][2:c]x");
+        assert_eq!(several("ab", &[&a, &b], true), "\x1b[31mab\x1b[0m");
+        assert_eq!(swatch(1, false), "[2]");
+        assert_eq!(swatch(1, true), "\x1b[32m[2]\x1b[0m");
+    }
+
+    #[test]
+    fn points_at_a_position() {
+        assert_eq!(caret("abc", 2), "  ^");
+        assert_eq!(caret("日本", 3), "  ^");
+        assert_eq!(caret("ab", 2), "  ^");
+    }
+}
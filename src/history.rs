@@ -0,0 +1,262 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! The history file, written to as lines are entered rather than
+//! only on the way out, so a crash or kill keeps what was typed.
+//! Lines are appended as they're added and synced to disk every few
+//! seconds. A clean exit rewrites the file from the editor's
+//! history, which also trims it to the editor's limit.
+//!
+//! Lines matching the `history-exclude` regex, and inputs tested with
+//! a `:private` prefix, are kept out of both the editor's history and
+//! the file.
+
+use std::fs::{File, OpenOptions};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use rustyline::Editor;
+
+/// Lines starting with this are tested without being kept.
+pub const PRIVATE_PREFIX: &str = ":private ";
+
+/// How long appended lines may wait before being synced to disk.
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct History {
+    path: Option<PathBuf>,
+    /// The file open for appending.
+    file: Option<File>,
+    /// Whether lines were appended since the last sync.
+    dirty: bool,
+    synced_at: Instant,
+    /// Lines never kept.
+    exclude: Option<Regex>,
+    /// Whether lines already in the editor's history need checking
+    /// against `exclude`.
+    purge: bool,
+}
+
+/// Open `path` for appending, readable only by the user.
+fn append(path: &Path) -> Option<File> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    match options.open(path) {
+        Ok(f) => Some(f),
+        Err(e) => {
+            debug!("can't append to history file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+impl History {
+    /// History kept only in the editor.
+    pub fn none() -> History {
+        History {
+            path: None,
+            file: None,
+            dirty: false,
+            synced_at: Instant::now(),
+            exclude: None,
+            purge: false,
+        }
+    }
+
+    /// Load the history at `path` into `editor` without the lines
+    /// `exclude` matches, and keep appending to it.
+    pub fn open(path: &Path, editor: &mut Editor<()>, exclude: Option<Regex>) -> History {
+        editor.load_history(path);
+        let mut history = History::none();
+        history.exclude = exclude;
+        history.path = Some(path.to_owned());
+        if history.purge(editor) {
+            history.rewrite(editor);
+        } else {
+            history.file = append(path);
+        }
+        history
+    }
+
+    /// Write the editor's history over the file and append to it
+    /// from there.
+    fn rewrite(&mut self, editor: &Editor<()>) {
+        if let Some(ref path) = self.path {
+            // The editor leaves the file alone if it has no history
+            if editor.history().is_empty() {
+                File::create(path);
+            }
+            editor.save_history(path);
+            self.file = append(path);
+        }
+    }
+
+    pub fn exclude(&self) -> Option<&Regex> {
+        self.exclude.as_ref()
+    }
+
+    /// Keep lines `exclude` matches out from now on, and drop those
+    /// already in history when the next line is added.
+    pub fn set_exclude(&mut self, exclude: Option<Regex>) {
+        self.exclude = exclude;
+        self.purge = true;
+    }
+
+    /// Can `line` be kept?
+    fn keeps(&self, line: &str) -> bool {
+        !line.starts_with(PRIVATE_PREFIX.trim_end()) &&
+        !self.exclude.as_ref().is_some_and(|r| r.is_match(line))
+    }
+
+    /// Take the lines that can't be kept out of the editor's
+    /// history. Returns true if there were any.
+    fn purge(&mut self, editor: &mut Editor<()>) -> bool {
+        self.purge = false;
+        let kept: Vec<String> = editor.history()
+            .iter()
+            .filter(|l| self.keeps(l))
+            .cloned()
+            .collect();
+        if kept.len() == editor.history().len() {
+            return false;
+        }
+        let history = editor.history_mut();
+        history.clear();
+        for line in kept {
+            history.add(line);
+        }
+        true
+    }
+
+    /// Add `line` to the editor's history and, if it took it, to the
+    /// file.
+    pub fn add(&mut self, editor: &mut Editor<()>, line: &str) {
+        if self.purge && self.purge(editor) {
+            self.rewrite(editor);
+        }
+        if !self.keeps(line) || !editor.add_history_entry(line) {
+            return;
+        }
+        if let Some(ref mut f) = self.file {
+            if writeln!(f, "{}", line).is_err() {
+                self.file = None;
+                return;
+            }
+            self.dirty = true;
+            if self.synced_at.elapsed() >= SYNC_INTERVAL {
+                f.sync_data();
+                self.dirty = false;
+                self.synced_at = Instant::now();
+            }
+        }
+    }
+
+    /// Write out the editor's history in place of what was appended.
+    pub fn save(&mut self, editor: &mut Editor<()>) {
+        self.purge(editor);
+        if let Some(ref path) = self.path {
+            self.file = None;
+            if let Err(e) = editor.save_history(path) {
+                debug!("can't save history file {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+impl Drop for History {
+    fn drop(&mut self) {
+        if let (Some(ref f), true) = (&self.file, self.dirty) {
+            f.sync_data();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    fn lines(editor: &Editor<()>) -> Vec<String> {
+        editor.history().iter().cloned().collect()
+    }
+
+    fn temp(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("regtest-history-{}-{}", process::id(), name));
+        fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn keeps_ordinary_lines_only() {
+        let mut history = History::none();
+        assert!(history.keeps("abc"));
+        assert!(!history.keeps(":private hunter2"));
+        assert!(!history.keeps(":private"));
+        history.set_exclude(Some(Regex::new("pass").unwrap()));
+        assert!(!history.keeps("my password"));
+        assert!(history.keeps("abc"));
+    }
+
+    #[test]
+    fn new_exclusions_purge_earlier_lines() {
+        let mut editor = Editor::<()>::new();
+        let mut history = History::none();
+        history.add(&mut editor, "one");
+        history.add(&mut editor, "secret two");
+        history.add(&mut editor, ":private three");
+        assert_eq!(lines(&editor), ["one", "secret two"]);
+
+        history.set_exclude(Some(Regex::new("secret").unwrap()));
+        history.add(&mut editor, "four");
+        assert_eq!(lines(&editor), ["one", "four"]);
+    }
+
+    #[test]
+    fn appends_to_the_file_as_lines_are_added() {
+        let path = temp("append");
+        let mut editor = Editor::<()>::new();
+        let mut history = History::open(&path, &mut editor, None);
+        history.add(&mut editor, "first");
+        history.add(&mut editor, ":private second");
+        history.add(&mut editor, "third");
+        // Read back before `save` rewrites it
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nthird\n");
+        drop(history);
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn excluded_lines_are_dropped_from_the_file_on_open() {
+        let path = temp("open");
+        fs::write(&path, "keep\nsecret\nalso keep\n").unwrap();
+        let mut editor = Editor::<()>::new();
+        let exclude = Some(Regex::new("secret").unwrap());
+        let history = History::open(&path, &mut editor, exclude);
+        assert_eq!(lines(&editor), ["keep", "also keep"]);
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret"));
+        drop(history);
+        fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,247 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Commands run for each match, like the `on-match` option:
+//!
+//! ```text
+//! on-match = notify-send "regtest: {file}" "{group.level} at line {line}"
+//! ```
+//!
+//! The command is split into words the way a shell would, with
+//! quotes keeping spaces in, and each word takes the placeholders of
+//! `--format`. It's run directly rather than through a shell, so
+//! whatever a match holds stays in the word it was put in.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::process::{Child, Command};
+
+use regex::Captures;
+
+use format::{self, Piece, Place};
+
+/// A command line with placeholders.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hook {
+    words: Vec<Vec<Piece>>,
+}
+
+/// Split `line` into words at unquoted whitespace. Single and double
+/// quotes group words and are removed, and a backslash keeps the
+/// character after it.
+fn split(line: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            (q, Some(open)) if q == open => quote = None,
+            ('\\', None) | ('\\', Some('"')) => {
+                let next = chars.next().ok_or_else(|| msg!(HookTrailingBackslash).to_owned())?;
+                word.get_or_insert_with(String::new).push(next);
+            }
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('\'', None) | ('"', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(msg!(HookUnclosedQuote).to_owned());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+impl Hook {
+    /// Parse `command`, failing on bad placeholders or quotes. A
+    /// command quoted as a whole, as in `"notify-send {match}"`, is
+    /// taken apart.
+    pub fn parse(command: &str) -> Result<Hook, String> {
+        let mut words = split(command)?;
+        if words.len() == 1 && words[0].contains(char::is_whitespace) {
+            words = split(&words[0])?;
+        }
+        if words.is_empty() {
+            return Err(msg!(HookEmpty).to_owned());
+        }
+        let words = words.iter().map(|w| format::parse(w)).collect::<Result<_, _>>()?;
+        Ok(Hook { words })
+    }
+
+    /// The command for the match `caps` at `place`, filled in.
+    pub fn command(&self, place: &Place, caps: &Captures) -> Command {
+        let args: Vec<String> = self.words.iter().map(|w| format::render(w, place, caps)).collect();
+        let mut command = Command::new(&args[0]);
+        command.args(&args[1..]);
+        command
+    }
+
+    /// Run the command for `caps` and wait for it, failing if it
+    /// can't be started or exits unsuccessfully.
+    pub fn run(&self, place: &Place, caps: &Captures) -> Result<(), String> {
+        let mut command = self.command(place, caps);
+        let name = command.get_program().to_string_lossy().into_owned();
+        debug!("running {:?}", command);
+        match command.status() {
+            Ok(ref s) if s.success() => Ok(()),
+            Ok(s) => Err(msg!(HookStatus, name, s)),
+            Err(e) => Err(msg!(HookFailed, name, e)),
+        }
+    }
+}
+
+/// Runs a hook for many matches, with at most `jobs` commands
+/// running at once, counting those that fail.
+pub struct Runner {
+    hook: Hook,
+    jobs: usize,
+    running: VecDeque<(String, Child)>,
+    /// Commands started.
+    pub started: usize,
+    /// Commands that couldn't be started or exited unsuccessfully.
+    pub failed: usize,
+}
+
+impl Runner {
+    pub fn new(hook: Hook, jobs: usize) -> Runner {
+        Runner {
+            hook,
+            jobs: jobs.max(1),
+            running: VecDeque::new(),
+            started: 0,
+            failed: 0,
+        }
+    }
+
+    /// Start the command for `caps` at `place`, first waiting for
+    /// the oldest one running if there's no room. Commands that fail
+    /// are reported to `errors`.
+    pub fn spawn(&mut self, place: &Place, caps: &Captures, errors: &mut dyn Write) {
+        if self.running.len() == self.jobs {
+            self.wait_oldest(errors);
+        }
+        let mut command = self.hook.command(place, caps);
+        let name = command.get_program().to_string_lossy().into_owned();
+        self.started += 1;
+        debug!("starting {:?}, {} running", command, self.running.len());
+        match command.spawn() {
+            Ok(child) => self.running.push_back((name, child)),
+            Err(e) => self.fail(errors, msg!(HookFailed, name, e)),
+        }
+    }
+
+    /// Wait for every command still running, reporting those that
+    /// fail to `errors`.
+    pub fn finish(&mut self, errors: &mut dyn Write) {
+        while !self.running.is_empty() {
+            self.wait_oldest(errors);
+        }
+    }
+
+    fn wait_oldest(&mut self, errors: &mut dyn Write) {
+        let (name, mut child) = match self.running.pop_front() {
+            Some(c) => c,
+            None => return,
+        };
+        match child.wait() {
+            Ok(ref s) if s.success() => {}
+            Ok(s) => self.fail(errors, msg!(HookStatus, name, s)),
+            Err(e) => self.fail(errors, msg!(HookFailed, name, e)),
+        }
+    }
+
+    fn fail(&mut self, errors: &mut dyn Write, message: String) {
+        self.failed += 1;
+        writeln!(errors, "{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn splits_like_a_shell() {
+        assert_eq!(split(r#"a  "b c" 'd "e"' f\ g "h\"i" '' "#).unwrap(),
+                   vec!["a", "b c", r#"d "e""#, "f g", "h\"i", ""]);
+        assert_eq!(split(r"'a\b'").unwrap(), vec![r"a\b"]);
+        assert!(split("'open").is_err());
+        assert!(split(r"end\").is_err());
+    }
+
+    #[test]
+    fn fills_in_each_word() {
+        let hook = Hook::parse("echo 'at {line}:' {match}").unwrap();
+        let reg = Regex::new(r"\w+").unwrap();
+        let caps = reg.captures("two words").unwrap();
+        let place = Place {
+            file: "f",
+            line: 3,
+        };
+        let command = hook.command(&place, &caps);
+        assert_eq!(command.get_program(), "echo");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["at 3:", "two"]);
+        // Quoted as a whole
+        assert_eq!(Hook::parse("\"echo {match}\"").unwrap().words.len(), 2);
+    }
+
+    #[test]
+    fn bad_commands_are_errors() {
+        assert!(Hook::parse("").is_err());
+        assert!(Hook::parse("  ").is_err());
+        assert!(Hook::parse("echo {nope}").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn counts_failed_commands() {
+        let reg = Regex::new("x").unwrap();
+        let caps = reg.captures("x").unwrap();
+        let place = Place {
+            file: "f",
+            line: 1,
+        };
+        assert!(Hook::parse("true").unwrap().run(&place, &caps).is_ok());
+        assert!(Hook::parse("false").unwrap().run(&place, &caps).is_err());
+
+        let mut errors = vec![];
+        let mut runner = Runner::new(Hook::parse("false").unwrap(), 2);
+        for _ in 0..3 {
+            runner.spawn(&place, &caps, &mut errors);
+        }
+        runner.finish(&mut errors);
+        assert_eq!((runner.started, runner.failed), (3, 3));
+        let errors = String::from_utf8(errors).unwrap();
+        assert_eq!(errors.lines().count(), 3);
+        assert!(errors.lines().all(|l| l.starts_with("false exited with")), "{}", errors);
+
+        let mut errors = vec![];
+        let mut runner = Runner::new(Hook::parse("/nonexistent/regtest-hook").unwrap(), 1);
+        runner.spawn(&place, &caps, &mut errors);
+        runner.finish(&mut errors);
+        assert_eq!(runner.failed, 1);
+        let errors = String::from_utf8(errors).unwrap();
+        assert!(errors.starts_with("Can't run /nonexistent/regtest-hook"), "{}", errors);
+    }
+}
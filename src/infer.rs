@@ -0,0 +1,256 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Finding a small pattern that matches every string of one set and
+//! none of another, as a starting point to refine by hand.
+//!
+//! Candidates are built a few simple ways (shared prefixes and
+//! suffixes, the shape of the strings in character classes, a
+//! substring only the wanted strings have, and alternations of
+//! literals) and kept if they separate the two sets.
+
+use std::collections::BTreeSet;
+use std::slice;
+
+use regex::{self, Regex};
+
+/// Longest substrings considered when looking for ones only the
+/// wanted strings have.
+const MAX_SUBSTRING: usize = 12;
+
+/// Classes tried on their own, as `C` and `^C+$`.
+const CLASSES: &[&str] = &[r"\d", "[a-z]", "[A-Z]", "[a-zA-Z]", r"\w", r"\s", r"\W"];
+
+/// Patterns that match all of `good` and none of `bad`, shortest
+/// first. Empty if no candidate separates them, as when a string is
+/// in both.
+pub fn infer(good: &[String], bad: &[String]) -> Vec<String> {
+    if good.is_empty() {
+        return vec![];
+    }
+    let mut candidates = BTreeSet::new();
+    for &c in CLASSES {
+        candidates.insert(c.to_owned());
+        candidates.insert(format!("^{}+$", c));
+    }
+    affixes(good, &mut candidates);
+    if let Some(s) = shape(good) {
+        candidates.insert(s);
+    }
+    if let Some(s) = substring(good, bad) {
+        candidates.insert(regex::escape(&s));
+    }
+    candidates.insert(alternation(good, bad));
+    candidates.insert(whole_alternation(good));
+
+    let mut out: Vec<String> = candidates.into_iter()
+        .filter(|c| separates(c, good, bad))
+        .collect();
+    out.sort_by_key(|c| c.chars().count());
+    out
+}
+
+/// Does `pattern` match each of `good` and none of `bad`?
+pub fn separates(pattern: &str, good: &[String], bad: &[String]) -> bool {
+    match Regex::new(pattern) {
+        Ok(r) => good.iter().all(|g| r.is_match(g)) && !bad.iter().any(|b| r.is_match(b)),
+        Err(_) => false,
+    }
+}
+
+/// `^prefix`, `suffix$` and both with `.*` between, for the
+/// prefix and suffix every string in `good` shares.
+fn affixes(good: &[String], candidates: &mut BTreeSet<String>) {
+    let prefix = good.iter().skip(1).fold(good[0].clone(), |p, g| common_prefix(&p, g));
+    let reversed: Vec<String> = good.iter().map(|g| g.chars().rev().collect()).collect();
+    let suffix: String = reversed.iter()
+        .skip(1)
+        .fold(reversed[0].clone(), |p, g| common_prefix(&p, g))
+        .chars()
+        .rev()
+        .collect();
+    // Every shorter prefix too, since a short one may be enough
+    for (i, _) in prefix.char_indices().skip(1) {
+        candidates.insert(format!("^{}", regex::escape(&prefix[..i])));
+    }
+    for (i, _) in suffix.char_indices().skip(1) {
+        candidates.insert(format!("{}$", regex::escape(&suffix[i..])));
+    }
+    if !prefix.is_empty() {
+        candidates.insert(format!("^{}", regex::escape(&prefix)));
+    }
+    if !suffix.is_empty() {
+        candidates.insert(format!("{}$", regex::escape(&suffix)));
+    }
+    if !prefix.is_empty() && !suffix.is_empty() {
+        candidates.insert(format!("^{}.*{}$", regex::escape(&prefix), regex::escape(&suffix)));
+    }
+}
+
+fn common_prefix(a: &str, b: &str) -> String {
+    a.chars().zip(b.chars()).take_while(|&(x, y)| x == y).map(|(x, _)| x).collect()
+}
+
+/// The class `c` is written with in a shape, or `None` if it's
+/// kept as it is.
+fn class(c: char) -> Option<&'static str> {
+    if c.is_ascii_digit() {
+        Some(r"\d")
+    } else if c.is_ascii_lowercase() {
+        Some("[a-z]")
+    } else if c.is_ascii_uppercase() {
+        Some("[A-Z]")
+    } else if c.is_alphabetic() {
+        Some(r"\pL")
+    } else if c.is_whitespace() {
+        Some(r"\s")
+    } else {
+        None
+    }
+}
+
+/// `s` as runs of classes and single characters kept as they are,
+/// each with whether it repeats.
+fn tokens(s: &str) -> Vec<(String, bool)> {
+    let mut out = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match class(c) {
+            Some(k) => {
+                let mut run = false;
+                while chars.peek().and_then(|&n| class(n)) == Some(k) {
+                    chars.next();
+                    run = true;
+                }
+                out.push((k.to_owned(), run));
+            }
+            None => out.push((regex::escape(&c.to_string()), false)),
+        }
+    }
+    out
+}
+
+/// The shape every string in `good` has, if they share one, like
+/// `^[A-Z][a-z]+\d+$`. A class seen once in some strings and
+/// repeated in others is taken as repeated.
+fn shape(good: &[String]) -> Option<String> {
+    let mut shape = tokens(&good[0]);
+    for g in &good[1..] {
+        let other = tokens(g);
+        if other.len() != shape.len() || other.iter().zip(&shape).any(|(a, b)| a.0 != b.0) {
+            return None;
+        }
+        for (s, o) in shape.iter_mut().zip(other) {
+            s.1 |= o.1;
+        }
+    }
+    let body: String = shape.into_iter()
+        .map(|(t, run)| if run { t + "+" } else { t })
+        .collect();
+    Some(format!("^{}$", body))
+}
+
+/// The shortest substring found in every string of `good` and in
+/// none of `bad`.
+fn substring(good: &[String], bad: &[String]) -> Option<String> {
+    let first = &good[0];
+    let starts: Vec<usize> = first.char_indices().map(|(i, _)| i).collect();
+    for len in 1..MAX_SUBSTRING + 1 {
+        for (n, &start) in starts.iter().enumerate() {
+            let end = match starts.get(n + len) {
+                Some(&e) => e,
+                None if n + len == starts.len() => first.len(),
+                None => break,
+            };
+            let sub = &first[start..end];
+            if good.iter().all(|g| g.contains(sub)) && !bad.iter().any(|b| b.contains(sub)) {
+                return Some(sub.to_owned());
+            }
+        }
+    }
+    None
+}
+
+/// An alternation of short substrings that between them cover
+/// `good` without occurring in `bad`, picking for each string not
+/// yet covered its shortest such substring. Strings with none are
+/// matched whole.
+fn alternation(good: &[String], bad: &[String]) -> String {
+    let mut parts: Vec<String> = vec![];
+    for g in good {
+        if parts.iter().any(|p| g.contains(&p[..])) {
+            continue;
+        }
+        let part = substring(slice::from_ref(g), bad).unwrap_or_else(|| g.clone());
+        parts.push(part);
+    }
+    let alts: Vec<String> = parts.iter().map(|p| regex::escape(p)).collect();
+    if alts.len() == 1 {
+        alts[0].clone()
+    } else {
+        format!("(?:{})", alts.join("|"))
+    }
+}
+
+/// Every string of `good`, matched whole.
+fn whole_alternation(good: &[String]) -> String {
+    let set: BTreeSet<&String> = good.iter().collect();
+    let alts: Vec<String> = set.into_iter().map(|g| regex::escape(g)).collect();
+    format!("^(?:{})$", alts.join("|"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_the_shape_of_the_strings() {
+        assert_eq!(shape(&strings(&["Ab12", "Cde3"])).unwrap(), r"^[A-Z][a-z]+\d+$");
+        assert_eq!(shape(&strings(&["a-1", "b-22"])).unwrap(), r"^[a-z]\-\d+$");
+        assert_eq!(shape(&strings(&["a1", "1a"])), None);
+    }
+
+    #[test]
+    fn finds_a_substring_only_the_good_have() {
+        let good = strings(&["foo.log", "bar.log"]);
+        assert_eq!(substring(&good, &strings(&["foo.txt"])).unwrap(), "l");
+        assert_eq!(substring(&good, &strings(&["x.log"])), None);
+        assert_eq!(alternation(&strings(&["cat", "dog"]), &strings(&["cow"])), "(?:a|d)");
+        assert_eq!(whole_alternation(&strings(&["b", "a.", "b"])), r"^(?:a\.|b)$");
+    }
+
+    #[test]
+    fn every_pattern_separates_the_sets() {
+        let good = strings(&["2024-01-02", "1999-12-31"]);
+        let bad = strings(&["2024/01/02", "hello"]);
+        let found = infer(&good, &bad);
+        assert_eq!(found[0], r"\-");
+        assert!(found.contains(&r"^\d+\-\d+\-\d+$".to_owned()));
+        assert!(found.iter().all(|p| separates(p, &good, &bad)));
+        assert!(found.windows(2).all(|w| w[0].chars().count() <= w[1].chars().count()));
+    }
+
+    #[test]
+    fn nothing_separates_a_string_from_itself() {
+        assert!(infer(&strings(&["a"]), &strings(&["a"])).is_empty());
+        assert!(infer(&[], &strings(&["a"])).is_empty());
+    }
+}
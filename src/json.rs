@@ -0,0 +1,329 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Reading JSON documents, enough for the files regtest writes.
+//! Writing them is done with `output::quote`.
+
+use std::char;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// How deep arrays and objects may be nested, so a hostile document
+/// can't run the parser out of stack.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in the order they were written.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match *self {
+            Value::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match *self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match *self {
+            Value::Object(ref members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the JSON document `text`.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut p = Parser { chars: text.chars().peekable(), pos: 0, depth: 0 };
+    let value = p.value()?;
+    p.space();
+    match p.chars.peek() {
+        None => Ok(value),
+        Some(_) => Err(p.error("end of document")),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// Characters read so far, for errors.
+    pos: usize,
+    /// Arrays and objects open around the value being read.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<char> {
+        self.pos += 1;
+        self.chars.next()
+    }
+
+    fn error(&self, expected: &str) -> String {
+        format!("expected {} at character {}", expected, self.pos + 1)
+    }
+
+    fn space(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.space();
+        if self.next() == Some(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", c)))
+        }
+    }
+
+    fn word(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for c in word.chars() {
+            if self.next() != Some(c) {
+                return Err(self.error(word));
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.space();
+        match self.chars.peek().cloned() {
+            Some(c) if c == '{' || c == '[' => {
+                if self.depth == MAX_DEPTH {
+                    return Err(format!("more than {} nested arrays and objects at character {}",
+                                       MAX_DEPTH,
+                                       self.pos + 1));
+                }
+                self.depth += 1;
+                let value = if c == '{' { self.object() } else { self.array() };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(Value::String),
+            Some('t') => self.word("true", Value::Bool(true)),
+            Some('f') => self.word("false", Value::Bool(false)),
+            Some('n') => self.word("null", Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut members = vec![];
+        self.space();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.space();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.space();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(members)),
+                _ => return Err(self.error("`,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.space();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.space();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error("`,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.next() != Some('"') {
+            return Err(self.error("a string"));
+        }
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => self.unicode()?,
+                        Some(c @ '"') | Some(c @ '\\') | Some(c @ '/') => c,
+                        _ => return Err(self.error("an escape")),
+                    };
+                    out.push(c);
+                }
+                Some(c) => out.push(c),
+                None => return Err(self.error("`\"`")),
+            }
+        }
+    }
+
+    /// The character of a `\u` escape, which may be followed by the
+    /// second half of a surrogate pair.
+    fn unicode(&mut self) -> Result<char, String> {
+        let first = self.hex()?;
+        if (0xD800..0xDC00).contains(&first) {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err(self.error("a low surrogate"));
+            }
+            let second = self.hex()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(self.error("a low surrogate"));
+            }
+            let c = 0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00);
+            return char::from_u32(c).ok_or_else(|| self.error("a character"));
+        }
+        char::from_u32(first).ok_or_else(|| self.error("a character"))
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let d = self.next().and_then(|c| c.to_digit(16)).ok_or_else(|| self.error("a hex digit"))?;
+            n = n * 16 + d;
+        }
+        Ok(n)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || "+-.eE".contains(c) {
+                text.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        text.parse().map(Value::Number).map_err(|_| self.error("a number"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_documents() {
+        let doc = parse(r#" {"a": [1, -2.5e3, true, null], "b": {"c": "d"}, "e": []} "#).unwrap();
+        let a = doc.get("a").unwrap().as_array().unwrap();
+        assert_eq!(a[1].as_number(), Some(-2500.0));
+        assert_eq!(a[2].as_bool(), Some(true));
+        assert_eq!(a[3], Value::Null);
+        assert_eq!(doc.get("b").unwrap().get("c").unwrap().as_str(), Some("d"));
+    }
+
+    #[test]
+    fn reads_escapes() {
+        let doc = parse(r#""tab\t \"quoted\" \/ é 😀""#).unwrap();
+        assert_eq!(doc.as_str(), Some("tab\t \"quoted\" / \u{e9} \u{1f600}"));
+    }
+
+    #[test]
+    fn unterminated_strings_are_errors() {
+        assert!(parse(r#""open"#).unwrap_err().starts_with("expected `\"`"));
+        assert!(parse(r#"{"key: 1}"#).is_err());
+        assert!(parse(r#"["a", "b]"#).is_err());
+    }
+
+    #[test]
+    fn bad_escapes_are_errors() {
+        assert!(parse(r#""\q""#).is_err());
+        assert!(parse(r#""\u12""#).is_err());
+        assert!(parse(r#""\u12G4""#).is_err());
+        // Surrogates must come in a high and low pair
+        assert!(parse(r#""\ud83d""#).is_err());
+        assert!(parse(r#""\ud83dA""#).is_err());
+        assert!(parse(r#""\ude00""#).is_err());
+    }
+
+    #[test]
+    fn malformed_documents_are_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("[1 2]").is_err());
+        assert!(parse(r#"{"a" 1}"#).is_err());
+        assert!(parse("{1: 2}").is_err());
+        assert!(parse("tru").is_err());
+        assert!(parse("1.2.3").is_err());
+        assert!(parse("[] []").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(parse(&deep).unwrap_err().contains("nested"));
+        let deep = "{\"a\":".repeat(100_000);
+        assert!(parse(&deep).unwrap_err().contains("nested"));
+        let fine = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&fine).is_ok());
+    }
+}
@@ -0,0 +1,385 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A small rule engine run over the `regex-syntax` AST of a
+//! pattern. Each rule looks at a single node and may produce
+//! a `Lint` describing what could be improved.
+
+use regex_syntax::ast::{self, Ast, ClassSet, ClassSetItem, LiteralKind, RepetitionKind,
+                        RepetitionRange};
+
+use flags::Flags;
+
+/// A single suggestion produced by the linter.
+#[derive(Clone, Debug)]
+pub struct Lint {
+    /// Byte offsets into the pattern the lint refers to.
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// Everything a rule may need while looking at a node.
+struct Context<'a> {
+    pattern: &'a str,
+    ignore_whitespace: bool,
+    lints: Vec<Lint>,
+}
+
+impl<'a> Context<'a> {
+    fn push(&mut self, span: &ast::Span, message: String) {
+        self.lints.push(Lint {
+            start: span.start.offset,
+            end: span.end.offset,
+            message,
+        });
+    }
+
+    fn text(&self, span: &ast::Span) -> &'a str {
+        &self.pattern[span.start.offset..span.end.offset]
+    }
+}
+
+/// Run every rule over `pattern`, read with `flags`. Patterns that
+/// fail to parse produce no lints; the compile error is more useful.
+pub fn lint(pattern: &str, flags: Flags) -> Vec<Lint> {
+    let ast = match flags.ast_parser().parse(pattern) {
+        Ok(a) => a,
+        Err(_) => return vec![],
+    };
+
+    let mut ctx = Context {
+        pattern,
+        ignore_whitespace: flags.contains(Flags::IGNORE_WHITESPACE),
+        lints: vec![],
+    };
+    check_anchors(&mut ctx, &ast);
+    walk(&mut ctx, &ast);
+    ctx.lints.sort_by_key(|l| l.start);
+    ctx.lints
+}
+
+fn walk(ctx: &mut Context, ast: &Ast) {
+    match *ast {
+        Ast::Flags(ref f) => {
+            if let Some(on) = f.flags.flag_state(ast::Flag::IgnoreWhitespace) {
+                ctx.ignore_whitespace = on;
+            }
+        }
+        Ast::Literal(ref lit) => check_escape(ctx, lit, false),
+        Ast::Class(ast::Class::Bracketed(ref class)) => {
+            check_shorthand(ctx, class);
+            walk_class_set(ctx, &class.kind);
+        }
+        Ast::Repetition(ref rep) => {
+            check_nested_quantifier(ctx, rep);
+            walk(ctx, &rep.ast);
+        }
+        Ast::Group(ref group) => {
+            let saved = ctx.ignore_whitespace;
+            if let ast::GroupKind::NonCapturing(ref flags) = group.kind {
+                if let Some(on) = flags.flag_state(ast::Flag::IgnoreWhitespace) {
+                    ctx.ignore_whitespace = on;
+                }
+            }
+            walk(ctx, &group.ast);
+            ctx.ignore_whitespace = saved;
+        }
+        Ast::Alternation(ref alt) => {
+            check_duplicate_branches(ctx, alt);
+            for a in &alt.asts {
+                walk(ctx, a);
+            }
+        }
+        Ast::Concat(ref concat) => {
+            for a in &concat.asts {
+                walk(ctx, a);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn walk_class_set(ctx: &mut Context, set: &ClassSet) {
+    match *set {
+        ClassSet::Item(ref item) => walk_class_item(ctx, item),
+        ClassSet::BinaryOp(ref op) => {
+            walk_class_set(ctx, &op.lhs);
+            walk_class_set(ctx, &op.rhs);
+        }
+    }
+}
+
+fn walk_class_item(ctx: &mut Context, item: &ClassSetItem) {
+    match *item {
+        ClassSetItem::Literal(ref lit) => check_escape(ctx, lit, true),
+        ClassSetItem::Range(ref range) => {
+            check_escape(ctx, &range.start, true);
+            check_escape(ctx, &range.end, true);
+        }
+        ClassSetItem::Bracketed(ref class) => {
+            check_shorthand(ctx, class);
+            walk_class_set(ctx, &class.kind);
+        }
+        ClassSetItem::Union(ref union) => {
+            for i in &union.items {
+                walk_class_item(ctx, i);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escaped punctuation that would have meant the same thing
+/// without the backslash.
+fn check_escape(ctx: &mut Context, lit: &ast::Literal, in_class: bool) {
+    if lit.kind != LiteralKind::Punctuation {
+        return;
+    }
+    let redundant = if in_class {
+        match lit.c {
+            '.' | '+' | '*' | '?' | '(' | ')' | '|' | '{' | '}' | '$' => true,
+            '#' => !ctx.ignore_whitespace,
+            _ => false,
+        }
+    } else {
+        match lit.c {
+            '-' | '&' | '~' | ']' | '}' => true,
+            '#' => !ctx.ignore_whitespace,
+            _ => false,
+        }
+    };
+    if redundant {
+        let msg = format!("redundant escape `{}`, `{}` is not special here",
+                          ctx.text(&lit.span),
+                          lit.c);
+        ctx.push(&lit.span, msg);
+    }
+}
+
+/// Bracketed classes that spell out a Perl shorthand class.
+fn check_shorthand(ctx: &mut Context, class: &ast::ClassBracketed) {
+    let mut ranges = match simple_ranges(&class.kind) {
+        Some(r) => r,
+        None => return,
+    };
+    ranges.sort();
+    let mut merged: Vec<(char, char)> = vec![];
+    for (s, e) in ranges {
+        if let Some(last) = merged.last_mut() {
+            if s as u32 <= last.1 as u32 + 1 {
+                if e > last.1 {
+                    last.1 = e;
+                }
+                continue;
+            }
+        }
+        merged.push((s, e));
+    }
+
+    let digit = [('0', '9')];
+    let word = [('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+    let space = [('\t', '\n'), ('\x0B', '\r'), (' ', ' ')];
+    let shorthand = if merged[..] == digit[..] {
+        "d"
+    } else if merged[..] == word[..] {
+        "w"
+    } else if merged[..] == space[..] {
+        "s"
+    } else {
+        return;
+    };
+    let shorthand = if class.negated {
+        shorthand.to_uppercase()
+    } else {
+        shorthand.to_owned()
+    };
+    let msg = format!("`{}` can be written as `\\{}` (note that `\\{}` is Unicode-aware unless \
+                       `(?-u)` is set)",
+                      ctx.text(&class.span),
+                      shorthand,
+                      shorthand);
+    ctx.push(&class.span, msg);
+}
+
+/// Flatten a class consisting only of literals and ranges.
+fn simple_ranges(set: &ClassSet) -> Option<Vec<(char, char)>> {
+    fn item(i: &ClassSetItem, out: &mut Vec<(char, char)>) -> bool {
+        match *i {
+            ClassSetItem::Literal(ref l) => {
+                out.push((l.c, l.c));
+                true
+            }
+            ClassSetItem::Range(ref r) => {
+                out.push((r.start.c, r.end.c));
+                true
+            }
+            ClassSetItem::Union(ref u) => u.items.iter().all(|i| item(i, out)),
+            _ => false,
+        }
+    }
+
+    let mut out = vec![];
+    match *set {
+        ClassSet::Item(ref i) if item(i, &mut out) && !out.is_empty() => Some(out),
+        _ => None,
+    }
+}
+
+/// Branches of an alternation that are written identically.
+fn check_duplicate_branches(ctx: &mut Context, alt: &ast::Alternation) {
+    let mut seen: Vec<&str> = vec![];
+    for branch in &alt.asts {
+        let text = ctx.text(branch.span());
+        if seen.contains(&text) {
+            let msg = format!("duplicate alternation branch `{}`", text);
+            ctx.push(branch.span(), msg);
+        } else {
+            seen.push(text);
+        }
+    }
+}
+
+/// Can this repetition match its operand more than once?
+fn repeats(kind: &RepetitionKind) -> bool {
+    match *kind {
+        RepetitionKind::ZeroOrOne => false,
+        RepetitionKind::ZeroOrMore | RepetitionKind::OneOrMore => true,
+        RepetitionKind::Range(RepetitionRange::Exactly(n)) => n > 1,
+        RepetitionKind::Range(RepetitionRange::AtLeast(_)) => true,
+        RepetitionKind::Range(RepetitionRange::Bounded(_, n)) => n > 1,
+    }
+}
+
+/// Quantifiers directly applied to an already quantified
+/// sub-expression, e.g. `(a+)*`.
+fn check_nested_quantifier(ctx: &mut Context, rep: &ast::Repetition) {
+    if !repeats(&rep.op.kind) {
+        return;
+    }
+    let mut inner: &Ast = &rep.ast;
+    while let Ast::Group(ref g) = *inner {
+        inner = &g.ast;
+    }
+    if let Ast::Repetition(ref r) = *inner {
+        if repeats(&r.op.kind) {
+            let msg = format!("nested quantifier in `{}`; this is slow on backtracking \
+                               engines and can usually be flattened",
+                              ctx.text(&rep.span));
+            ctx.push(&rep.span, msg);
+        }
+    }
+}
+
+/// A pattern anchored at only one end often means a full
+/// match (`^...$`) was intended.
+fn check_anchors(ctx: &mut Context, ast: &Ast) {
+    let asts = match *ast {
+        Ast::Concat(ref c) => &c.asts[..],
+        _ => return,
+    };
+    let is_anchor = |a: &Ast, kinds: &[ast::AssertionKind]| match *a {
+        Ast::Assertion(ref x) => kinds.contains(&x.kind),
+        _ => false,
+    };
+    let start = [ast::AssertionKind::StartLine, ast::AssertionKind::StartText];
+    let end = [ast::AssertionKind::EndLine, ast::AssertionKind::EndText];
+    let anchored_start = asts.first().is_some_and(|a| is_anchor(a, &start));
+    let anchored_end = asts.last().is_some_and(|a| is_anchor(a, &end));
+
+    if anchored_start && !anchored_end {
+        let msg = "pattern is anchored at the start but not the end; add `$` if the whole \
+                   input should match"
+            .to_owned();
+        ctx.push(ast.span(), msg);
+    } else if anchored_end && !anchored_start {
+        let msg = "pattern is anchored at the end but not the start; add `^` if the whole \
+                   input should match"
+            .to_owned();
+        ctx.push(ast.span(), msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(pattern: &str) -> Vec<String> {
+        lint(pattern, Flags::default()).into_iter().map(|l| l.message).collect()
+    }
+
+    #[test]
+    fn clean_patterns_have_no_lints() {
+        assert!(lint(r"^\d+-[a-z]+$", Flags::default()).is_empty());
+        assert!(lint(r"foo|bar", Flags::default()).is_empty());
+        assert!(lint(r"a\.b[\]x]", Flags::default()).is_empty());
+    }
+
+    #[test]
+    fn unparseable_patterns_have_no_lints() {
+        assert!(lint(r"(a", Flags::default()).is_empty());
+        assert!(lint(r"[z-a]", Flags::default()).is_empty());
+    }
+
+    #[test]
+    fn finds_redundant_escapes() {
+        let lints = lint(r"a\-b[\.]", Flags::default());
+        assert_eq!(lints.len(), 2);
+        assert_eq!((lints[0].start, lints[0].end), (1, 3));
+        assert!(lints[0].message.contains(r"`\-`"));
+        assert_eq!((lints[1].start, lints[1].end), (5, 7));
+        // `#` only needs escaping in verbose mode
+        assert_eq!(lint(r"\#", Flags::default()).len(), 1);
+        assert!(lint(r"(?x)\#", Flags::default()).is_empty());
+        assert!(lint(r"(?x:\#)", Flags::default()).is_empty());
+        assert_eq!(lint(r"(?x:a)\#", Flags::default()).len(), 1);
+        assert!(lint(r"\#", Flags::default() | Flags::IGNORE_WHITESPACE).is_empty());
+    }
+
+    #[test]
+    fn finds_spelled_out_shorthands() {
+        assert!(messages("[0-9]")[0].contains(r"`\d`"));
+        assert!(messages("[^0-9]")[0].contains(r"`\D`"));
+        assert!(messages("[_a-zA-Z0-9]")[0].contains(r"`\w`"));
+        assert!(messages("[a-z0-9]").is_empty());
+        assert!(messages("[0-4][5-9]").is_empty());
+    }
+
+    #[test]
+    fn finds_duplicate_branches() {
+        let lints = lint("cat|dog|cat", Flags::default());
+        assert_eq!(lints.len(), 1);
+        assert_eq!((lints[0].start, lints[0].end), (8, 11));
+        assert!(lint("cat|cats", Flags::default()).is_empty());
+    }
+
+    #[test]
+    fn finds_nested_quantifiers() {
+        assert_eq!(lint("(a+)*", Flags::default()).len(), 1);
+        assert_eq!(lint("(?:a*){2,}", Flags::default()).len(), 1);
+        assert!(lint("(a+)?", Flags::default()).is_empty());
+        assert!(lint("(ab)+", Flags::default()).is_empty());
+        assert!(lint("(a{1})+", Flags::default()).is_empty());
+    }
+
+    #[test]
+    fn finds_half_anchored_patterns() {
+        assert!(messages("^abc")[0].contains("add `$`"));
+        assert!(messages(r"abc\z")[0].contains("add `^`"));
+        assert!(lint(r"\Aabc$", Flags::default()).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! The `--debug` log: which engine and flags patterns are compiled
+//! with, where input comes from and output goes, and how long
+//! things take, one line per event:
+//!
+//! ```text
+//! [   12.345ms DEBUG regtest::batch] compiled "\d+" with flags none in 0.402ms
+//! ```
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use log::{self, Level, LevelFilter, Log, Metadata, Record};
+
+struct Logger {
+    out: Mutex<Box<dyn Write + Send>>,
+    start: Instant,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug && metadata.target().starts_with("regtest")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let elapsed = self.start.elapsed();
+        let ms = elapsed.as_secs() as f64 * 1000.0 + f64::from(elapsed.subsec_nanos()) / 1e6;
+        if let Ok(mut out) = self.out.lock() {
+            writeln!(out,
+                     "[{:>10.3}ms {:5} {}] {}",
+                     ms,
+                     record.level(),
+                     record.target(),
+                     record.args());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut out) = self.out.lock() {
+            out.flush();
+        }
+    }
+}
+
+/// Start logging to the file at `path`, or stderr if `None`.
+pub fn init(path: Option<&str>) -> io::Result<()> {
+    let out: Box<dyn Write + Send> = match path {
+        Some(p) => Box::new(File::create(p)?),
+        None => Box::new(io::stderr()),
+    };
+    let logger = Logger {
+        out: Mutex::new(out),
+        start: Instant::now(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+    Ok(())
+}
+
+/// Milliseconds in `d`, for log lines.
+pub fn ms(d: ::time::Duration) -> f64 {
+    d.num_microseconds().map_or(f64::INFINITY, |us| us as f64 / 1000.0)
+}
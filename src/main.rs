@@ -20,25 +20,125 @@
 #![allow(unused_must_use)]
 
 extern crate regex;
+extern crate regex_syntax;
 extern crate time;
 #[macro_use]
 extern crate bitflags;
 extern crate rustyline;
 extern crate clap;
 extern crate app_dirs;
+extern crate unicode_normalization;
+extern crate unicode_segmentation;
+extern crate unicode_width;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[cfg(feature = "archives")]
+extern crate flate2;
+#[cfg(feature = "archives")]
+extern crate tar;
+#[cfg(feature = "archives")]
+extern crate zip;
+#[macro_use]
+extern crate log;
+
+#[macro_use]
+mod messages;
+
+mod batch;
+mod bench;
+mod cache;
+#[cfg(feature = "archives")]
+mod archive;
+mod astdiff;
+mod branch;
+mod casefold;
+mod cheat;
+mod checkpoint;
+mod clipboard;
+mod class;
+mod compare;
+mod console;
+mod debug;
+mod describe;
+mod format;
+mod grade;
+mod fuzz;
+mod diff;
+mod edit;
+mod engines;
+mod flags;
+mod highlight;
+mod history;
+mod hook;
+mod json;
+mod logging;
+mod infer;
+mod lint;
+mod mode;
+mod mutate;
+mod offsets;
+mod output;
+mod pager;
+mod pipe;
+mod props;
+mod records;
+mod redos;
+mod relax;
+mod rename;
+mod sample;
+mod script;
+mod serve;
+mod settings;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod suggest;
+mod split;
+mod suite;
+mod template;
+mod term;
+mod toml;
+mod timestamp;
+mod transform;
+mod tutorial;
+mod watch;
+mod why;
+mod window;
+mod workspace;
+mod words;
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
 use std::io;
-use std::io::Write;
+use std::process;
+use std::io::{Read, Write};
 use std::default::Default;
-use std::path::PathBuf;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use cache::Cache;
+use flags::Flags;
+use highlight::LongLines;
+use history::History;
+use hook::Hook;
+use mode::Mode;
+use offsets::Offsets;
+use pager::Paged;
+use records::Delimiter;
+use settings::Source;
+use suite::Expectation;
+use transform::{Normalization, Transform};
 
 use regex::Regex;
 
-use clap::{Arg, App};
+use clap::{Arg, App, ArgMatches, Shell, SubCommand};
 
 use rustyline::Editor;
+use rustyline::error::ReadlineError;
 
-use app_dirs::{AppInfo, AppDataType, app_root};
+use app_dirs::{AppInfo, AppDataType, AppDirsError, app_root};
 
 const APP_INFO: AppInfo = AppInfo {
     name: "regtest",
@@ -46,27 +146,384 @@ const APP_INFO: AppInfo = AppInfo {
 };
 
 bitflags! {
-    flags Config: u32 {
-        const VERBOSE_ERRORS = 0b00000001,
-        const CAPTURE_GROUPS = 0b00000010,
-        const COMPILE_TIME   = 0b00000100,
+    struct Config: u32 {
+        const VERBOSE_ERRORS = 0b00000001;
+        const CAPTURE_GROUPS = 0b00000010;
+        const COMPILE_TIME   = 0b00000100;
+        const ACCESSIBLE     = 0b00001000;
+        const REPLACE_PREVIEW = 0b00010000;
+        const SPLIT_DELIMITERS = 0b00100000;
+        const LITERAL        = 0b01000000;
+        const SMART_CASE     = 0b10000000;
+        const PAGER          = 0b1_00000000;
     }
 }
 
 impl Default for Config {
     fn default() -> Config {
-        VERBOSE_ERRORS | COMPILE_TIME
+        Config::VERBOSE_ERRORS | Config::COMPILE_TIME | Config::PAGER
     }
 }
 
-const HELP: &'static str = "\
-:t - Toggle compile time display
-:g - Toggle capture groups display
-:v - Toggle verbose errors
-:h - Print this menu
-:q - Quit";
+/// The longest line `grep` reads, by default.
+const DEFAULT_MAX_LINE: u64 = 16 << 20;
+
+/// The biggest file read into memory without asking, by default.
+const DEFAULT_MAX_FILE: u64 = 256 << 20;
+
+/// The most steps `:debug` will record for one match attempt.
+const DEBUG_STEP_LIMIT: usize = 5000;
+
+/// The most changes `:undo` can revert.
+const UNDO_LIMIT: usize = 100;
+
+const MENU_PRMT: &str = ":b - Go back to the regex prompt";
+
+/// State carried through an interactive session.
+struct Session {
+    config: Config,
+    /// The regex currently being tested, if any.
+    regex: Option<Regex>,
+    /// The last line tested against `regex`.
+    last_input: Option<String>,
+    /// `last_input` as it was typed, before normalization and
+    /// transforms, to test again when options change.
+    last_typed: Option<String>,
+    /// Unicode normalization applied to each input before it's
+    /// matched.
+    normalization: Normalization,
+    /// Applied to each input before it's matched, after
+    /// normalization.
+    transforms: Vec<Transform>,
+    /// The unit spans of inputs are reported in.
+    offsets: Offsets,
+    /// How inputs wider than the terminal are shown.
+    long_lines: LongLines,
+    /// Show only the matches, each with this many characters of
+    /// the input either side, instead of the whole input.
+    segments: Option<usize>,
+    /// Run for each new match of `:watch` and `grep --follow`.
+    on_match: Option<Hook>,
+    /// The `on-match` command as it was given.
+    on_match_text: String,
+    /// The history file, appended to as lines are entered.
+    history: History,
+    /// Keep nothing on disk: no history, workspace saves or log file.
+    private: bool,
+    /// Lines longer than this many bytes are skipped by `grep`.
+    max_line: Option<u64>,
+    /// Files bigger than this many bytes aren't read into memory
+    /// without asking, or at all in batch modes.
+    max_file: Option<u64>,
+    /// Flags regexes are compiled with.
+    flags: Flags,
+    /// The flags `regex` was compiled with, which lag behind
+    /// `flags` until it's compiled again.
+    regex_flags: Flags,
+    /// Replacement template applied to each input, if any.
+    replace: Option<String>,
+    /// Matches replaced per input, 0 for all of them.
+    replace_limit: usize,
+    /// When splitting inputs on the regex, the most fields to split
+    /// into, 0 for no limit.
+    split: Option<usize>,
+    /// Regexes run after `regex`, each on what the one before it
+    /// extracted.
+    pipe: Vec<Regex>,
+    /// Regexes whose matches are shown alongside those of `regex`,
+    /// each in its own color.
+    highlights: Vec<Regex>,
+    /// Inputs every pattern tried must or must not match.
+    expectations: Vec<Expectation>,
+    /// Patterns given with `-e`, used before prompting for one.
+    preloaded: VecDeque<String>,
+    /// Lines left of the scripts being run, read before prompting.
+    script: VecDeque<script::Line>,
+    /// Whether to show the script lines run along with their prompt.
+    echo: bool,
+    /// Whether the last line read came from a script.
+    scripted: bool,
+    /// Whether to quit once the script runs out, with `--batch`.
+    batch: bool,
+    /// Earlier states for `:undo`, the most recent last.
+    undo: Vec<Snapshot>,
+    /// Where options not at their default got their value.
+    origins: HashMap<&'static str, Source>,
+    /// Settings of the profiles in the config file.
+    profiles: Vec<settings::Entry>,
+    /// The last profile applied.
+    profile: Option<String>,
+    /// The options as they were when the session started, for
+    /// `:reset`.
+    startup: Startup,
+    /// Regexes compiled so far.
+    cache: Cache,
+    /// Every pattern compiled this session and its flags, in the
+    /// order they were first used, for `:patterns` and `:use`.
+    patterns: Vec<(String, Flags)>,
+    /// The pattern as it was typed, while `regex` is it wrapped by
+    /// `:wrap`.
+    unwrapped: Option<String>,
+    /// Text to start the next regex prompt with, for `:edit`.
+    prefill: Option<String>,
+    /// Every input tested this session, as it was matched.
+    inputs: Vec<String>,
+    /// The workspace the session is saved to, if any.
+    workspace: Option<String>,
+}
+
+/// Option values at the start of a session.
+struct Startup {
+    options: Vec<(&'static str, String)>,
+    origins: HashMap<&'static str, Source>,
+}
+
+/// The state of a session before a change.
+struct Snapshot {
+    options: Vec<(&'static str, String)>,
+    origins: HashMap<&'static str, Source>,
+    regex: Option<Regex>,
+    /// The pattern before `:wrap`, if `regex` was wrapped.
+    unwrapped: Option<String>,
+    /// Whether the change replaced the pattern.
+    pattern_change: bool,
+}
+
+impl Session {
+    fn new(config: Config) -> Session {
+        Session {
+            config,
+            regex: None,
+            last_input: None,
+            last_typed: None,
+            normalization: Normalization::None,
+            transforms: vec![],
+            offsets: Offsets::Bytes,
+            long_lines: LongLines::Truncate,
+            segments: None,
+            on_match: None,
+            on_match_text: "off".to_owned(),
+            history: History::none(),
+            private: false,
+            max_line: Some(DEFAULT_MAX_LINE),
+            max_file: Some(DEFAULT_MAX_FILE),
+            flags: Flags::default(),
+            regex_flags: Flags::default(),
+            replace: None,
+            replace_limit: 0,
+            split: None,
+            pipe: vec![],
+            highlights: vec![],
+            expectations: vec![],
+            preloaded: VecDeque::new(),
+            script: VecDeque::new(),
+            echo: true,
+            scripted: false,
+            batch: false,
+            undo: vec![],
+            origins: HashMap::new(),
+            profiles: vec![],
+            profile: None,
+            startup: Startup {
+                options: vec![],
+                origins: HashMap::new(),
+            },
+            cache: Cache::new(),
+            patterns: vec![],
+            unwrapped: None,
+            prefill: None,
+            inputs: vec![],
+            workspace: None,
+        }
+    }
+
+    /// Treat the current options as the ones `:reset` returns to.
+    fn mark_startup(&mut self) {
+        self.startup = Startup {
+            options: self.options(),
+            origins: self.origins.clone(),
+        };
+    }
+
+    /// The value of every option, as text.
+    fn options(&self) -> Vec<(&'static str, String)> {
+        settings::names().into_iter().map(|n| (n, self.value(n).unwrap())).collect()
+    }
+
+    /// The value of option `name` as text.
+    fn value(&self, name: &str) -> Option<String> {
+        if let Some(flag) = settings::flag(name) {
+            let on = self.config.contains(flag);
+            return Some(if on { "on" } else { "off" }.to_owned());
+        }
+        match name {
+            "lang" => Some(messages::lang().code().to_owned()),
+            "transforms" => Some(transform::names(&self.transforms)),
+            "normalize" => Some(self.normalization.name().to_owned()),
+            "offsets" => Some(self.offsets.name().to_owned()),
+            "long-lines" => Some(self.long_lines.name().to_owned()),
+            "segments" => Some(self.segments.map_or("off".to_owned(), |n| n.to_string())),
+            "on-match" => Some(self.on_match_text.clone()),
+            "history-exclude" => Some(self.history.exclude().map_or("off", |r| r.as_str()).to_owned()),
+            "max-line" => Some(self.max_line.map_or("off".to_owned(), settings::size_name)),
+            "max-file" => Some(self.max_file.map_or("off".to_owned(), settings::size_name)),
+            "flags" => Some(self.flags.name()),
+            _ => None,
+        }
+    }
+
+    /// Set option `name` from its text form, recording where the
+    /// value came from.
+    fn set_value(&mut self, name: &'static str, value: &str, source: Source) -> Result<(), SetError> {
+        if let Some(flag) = settings::flag(name) {
+            let on = settings::parse_bool(value).ok_or(SetError::BadValue)?;
+            self.config.set(flag, on);
+        } else {
+            match name {
+                "lang" => {
+                    messages::set_lang(messages::Lang::from_code(value).ok_or(SetError::BadValue)?)
+                }
+                "transforms" => {
+                    self.transforms = transform::parse_list(value).map_err(|_| SetError::BadValue)?
+                }
+                "normalize" => {
+                    self.normalization = Normalization::from_name(value).ok_or(SetError::BadValue)?
+                }
+                "offsets" => self.offsets = Offsets::from_name(value).ok_or(SetError::BadValue)?,
+                "long-lines" => {
+                    self.long_lines = LongLines::from_name(value).ok_or(SetError::BadValue)?
+                }
+                "segments" => {
+                    self.segments = match value {
+                        "off" => None,
+                        _ => Some(value.parse().map_err(|_| SetError::BadValue)?),
+                    }
+                }
+                "on-match" => {
+                    self.on_match = match value {
+                        "off" | "" => None,
+                        _ => Some(Hook::parse(value).map_err(|_| SetError::BadValue)?),
+                    };
+                    self.on_match_text = if self.on_match.is_some() { value } else { "off" }.to_owned();
+                }
+                "history-exclude" => {
+                    let exclude = match value {
+                        "off" | "" => None,
+                        _ => Some(Regex::new(value).map_err(|_| SetError::BadValue)?),
+                    };
+                    self.history.set_exclude(exclude);
+                }
+                "max-line" | "max-file" => {
+                    let size = match value {
+                        "off" => None,
+                        _ => Some(settings::parse_size(value).ok_or(SetError::BadValue)?),
+                    };
+                    if name == "max-line" {
+                        self.max_line = size;
+                    } else {
+                        self.max_file = size;
+                    }
+                }
+                "flags" => self.flags = Flags::from_name(value).map_err(|_| SetError::BadValue)?,
+                _ => return Err(SetError::Unknown),
+            }
+        }
+        self.origins.insert(name, source);
+        Ok(())
+    }
+
+    /// Put back option values saved by `options`.
+    fn restore(&mut self, options: &[(&'static str, String)], origins: &HashMap<&'static str, Source>) {
+        for &(name, ref value) in options {
+            self.set_value(name, value, Source::Default).unwrap();
+        }
+        self.origins = origins.clone();
+    }
+
+    /// Where the value of option `name` came from.
+    fn origin(&self, name: &str) -> Source {
+        self.origins.get(name).cloned().unwrap_or(Source::Default)
+    }
+
+    /// Turn `flag` on or off, recording where that came from.
+    fn set_flag(&mut self, flag: Config, on: bool, source: Source) {
+        self.config.set(flag, on);
+        if let Some(&(name, _)) = settings::FLAGS.iter().find(|&&(_, f)| f == flag) {
+            self.origins.insert(name, source);
+        }
+    }
+
+    /// Flip `flag` during the session, returning whether it's now
+    /// on.
+    fn toggle(&mut self, flag: Config) -> bool {
+        self.remember(false);
+        let on = !self.config.contains(flag);
+        self.set_flag(flag, on, Source::Runtime);
+        on
+    }
+
+    /// Compile `pattern` with the session's flags.
+    fn compile(&mut self, pattern: &str) -> (Result<Regex, regex::Error>, Option<time::Duration>) {
+        let flags = self.flags_for(pattern, self.flags);
+        self.cache.compile(pattern, flags)
+    }
+
+    /// The flags `pattern` is really compiled with when `flags` are
+    /// set: with smart case on, all-lowercase patterns ignore case.
+    fn flags_for(&self, pattern: &str, flags: Flags) -> Flags {
+        if self.config.contains(Config::SMART_CASE) && !flags::has_uppercase(pattern) {
+            flags | Flags::CASE_INSENSITIVE
+        } else {
+            flags
+        }
+    }
+
+    /// Compile the current pattern again if the flags changed.
+    fn recompile(&mut self) -> Result<(), regex::Error> {
+        let pattern = match self.regex {
+            Some(ref r) if self.regex_flags != self.flags_for(r.as_str(), self.flags) => {
+                r.as_str().to_owned()
+            }
+            _ => return Ok(()),
+        };
+        let reg = self.compile(&pattern).0?;
+        self.use_regex(reg);
+        Ok(())
+    }
+
+    /// Make `reg`, compiled with the current flags, the regex
+    /// being tested, and record it for `:patterns`.
+    fn use_regex(&mut self, reg: Regex) {
+        self.unwrapped = None;
+        let entry = (reg.as_str().to_owned(), self.flags);
+        if !self.patterns.contains(&entry) {
+            self.patterns.push(entry);
+        }
+        self.regex_flags = self.flags_for(reg.as_str(), self.flags);
+        self.regex = Some(reg);
+    }
+
+    /// Save the current state so the next change can be undone.
+    fn remember(&mut self, pattern_change: bool) {
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(Snapshot {
+            options: self.options(),
+            origins: self.origins.clone(),
+            regex: self.regex.clone(),
+            unwrapped: self.unwrapped.clone(),
+            pattern_change,
+        });
+    }
+}
 
-const MENU_PRMT: &'static str = ":b - Go back to the regex prompt";
+/// Why an option couldn't be set.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum SetError {
+    Unknown,
+    BadValue,
+}
 
 /// Define the possible things that may happen after a menu
 /// ineration within any of the sub menus (regex input or
@@ -76,24 +533,39 @@ enum Action {
     Continue,
     Loop,
     ToRegexPrompt,
+    /// The current regex or options changed, so prompt with the
+    /// new ones and test the last input again.
+    Reload,
     Exit,
 }
 
 /// Check if a given `line` corresponds to a menu command.
-fn options_menu(line: &str, config: &mut Config) -> Action {
+fn options_menu(line: &str, session: &mut Session) -> Action {
     let mut stderr = io::stderr();
+    let reg = session.regex.as_ref();
+    // Commands may take an argument after the first space
+    let (cmd, arg) = match line.find(' ') {
+        Some(i) => (&line[..i], &line[i + 1..]),
+        None => (line, ""),
+    };
+    // Commands working on an input default to the last one tested
+    let input = if arg.is_empty() {
+        session.last_input.as_ref().map(|s| &s[..])
+    } else {
+        Some(arg)
+    };
+
     // What can you do from here?
-    match &line as &str {
+    match cmd {
         // Quit on :q
         ":q" => Action::Exit,
 
         // Toggle verbose errors
         ":v" => {
-            config.toggle(VERBOSE_ERRORS);
-            if config.contains(VERBOSE_ERRORS) {
-                write!(stderr, "Verbose errors: on\n");
+            if session.toggle(Config::VERBOSE_ERRORS) {
+                writeln!(stderr, "{}", msg!(VerboseErrorsOn));
             } else {
-                write!(stderr, "Verbose errors: off\n");
+                writeln!(stderr, "{}", msg!(VerboseErrorsOff));
             }
             Action::Loop
         }
@@ -101,11 +573,10 @@ fn options_menu(line: &str, config: &mut Config) -> Action {
         // Toggle message reporting time to
         // compile regex
         ":t" => {
-            config.toggle(COMPILE_TIME);
-            if config.contains(COMPILE_TIME) {
-                write!(stderr, "Show compile time: on\n");
+            if session.toggle(Config::COMPILE_TIME) {
+                writeln!(stderr, "{}", msg!(CompileTimeOn));
             } else {
-                write!(stderr, "Show compile time: off\n");
+                writeln!(stderr, "{}", msg!(CompileTimeOff));
             }
             Action::Loop
         }
@@ -116,176 +587,3572 @@ fn options_menu(line: &str, config: &mut Config) -> Action {
 
         // Toggle displaying capture groups
         ":g" => {
-            config.toggle(CAPTURE_GROUPS);
-            if config.contains(CAPTURE_GROUPS) {
-                write!(stderr, "Show capture groups: on\n");
+            if session.toggle(Config::CAPTURE_GROUPS) {
+                writeln!(stderr, "{}", msg!(CaptureGroupsOn));
             } else {
-                write!(stderr, "Show capture groups: off\n");
+                writeln!(stderr, "{}", msg!(CaptureGroupsOff));
             }
-            Action::Loop
+            Action::Reload
         }
 
-        // Display help
-        ":h" | ":?" => {
-            write!(stderr, "{}\n", HELP);
-            Action::Loop
+        // Show each input with its matches replaced, all of them
+        // or the first N with :r/N
+        c if c == ":r" || c.starts_with(":r/") => {
+            let limit = match c[2..].trim_start_matches('/') {
+                "" => 0,
+                n => {
+                    match n.parse::<usize>() {
+                        Ok(n) => n,
+                        Err(_) => {
+                            writeln!(stderr, "{}", msg!(BadReplaceLimit, n));
+                            return Action::Loop;
+                        }
+                    }
+                }
+            };
+            if arg.is_empty() {
+                session.replace = None;
+                writeln!(stderr, "{}", msg!(ReplaceOff));
+            } else {
+                session.replace = Some(arg.to_owned());
+                session.replace_limit = limit;
+            }
+            Action::Reload
         }
 
-        // Continue
-        _ => Action::Continue,
-    }
-}
+        // Split each input on the regex, into at most N fields
+        // with :split/N. Splitting again turns it off.
+        c if c == ":split" || c.starts_with(":split/") => {
+            let limit = match c[6..].trim_start_matches('/') {
+                "" => 0,
+                n => {
+                    match n.parse::<usize>() {
+                        Ok(n) => n,
+                        Err(_) => {
+                            writeln!(stderr, "{}", msg!(BadSplitLimit, n));
+                            return Action::Loop;
+                        }
+                    }
+                }
+            };
+            if session.split == Some(limit) {
+                session.split = None;
+                writeln!(stderr, "{}", msg!(SplitOff));
+            } else {
+                session.split = Some(limit);
+            }
+            Action::Reload
+        }
 
-/// Show a prompt saying "n>" requesting that a regex be input.
-/// If this function returns true, the user will be prompted
-/// to input a regex and if false the program will exit.
-fn regex_prompt(editor: &mut Editor<()>, config: &mut Config) -> bool {
-    // Get stderr up here just for convienience
-    let mut stderr = io::stderr();
+        // Toggle listing the delimiters between split fields
+        ":delims" => {
+            if session.toggle(Config::SPLIT_DELIMITERS) {
+                writeln!(stderr, "{}", msg!(DelimitersOn));
+            } else {
+                writeln!(stderr, "{}", msg!(DelimitersOff));
+            }
+            Action::Reload
+        }
 
-    // Read the line and add it to history
-    let line = editor.readline("Input> ").expect("Failed to read line!");
-    editor.add_history_entry(&line);
+        // Set the transforms applied to inputs before matching
+        ":transform" => {
+            match transform::parse_list(arg) {
+                Ok(list) => {
+                    session.remember(false);
+                    session.set_value("transforms", &transform::names(&list), Source::Runtime);
+                    if list.is_empty() {
+                        writeln!(stderr, "{}", msg!(TransformsOff));
+                    }
+                    Action::Reload
+                }
+                Err(name) => {
+                    let names: Vec<&str> = transform::TRANSFORMS.iter().map(|t| t.0).collect();
+                    writeln!(stderr, "{}", msg!(UnknownTransform, name, names.join(", ")));
+                    Action::Loop
+                }
+            }
+        }
 
-    // Process the line against the options menu
-    match options_menu(&line, config) {
-        Action::Continue => {}
-        Action::ToRegexPrompt | Action::Loop => return true,
-        Action::Exit => return false,
-    }
+        // Set any option by name
+        ":set" => set_command(session, arg.trim()),
 
-    // Get the time for compiling regex
-    let t1 = time::now();
-    let reg = match Regex::new(&line) {
-        Ok(r) => r,
-        Err(e) => {
-            if config.contains(VERBOSE_ERRORS) {
-                write!(stderr, "Error compiling regex: {:?}\n", e);
+        // Toggle explaining how each replacement was built
+        ":preview" => {
+            if session.toggle(Config::REPLACE_PREVIEW) {
+                writeln!(stderr, "{}", msg!(PreviewOn));
             } else {
-                stderr.write(b"Failed to compile regex\n");
-                stderr.write(b"Turn on verbose errors with :v\n");
+                writeln!(stderr, "{}", msg!(PreviewOff));
             }
-            return true;
+            Action::Reload
         }
-    };
 
-    let t2 = time::now();
-    // Display the time if the appropriate flag is set
-    if config.contains(COMPILE_TIME) {
-        let dur = t2 - t1;
-        write!(stderr,
-               "Regex compiled in {}ns\n",
-               match dur.num_nanoseconds() {
-                   Some(x) => x,
-                   None => dur.num_milliseconds(),
-               });
-    }
+        // Suggest improvements to the current regex
+        ":lint" => {
+            match reg {
+                Some(r) => print_lints(r.as_str(), session.flags_for(r.as_str(), session.flags)),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToLint));
+                }
+            }
+            Action::Loop
+        }
 
-    // Display a prompt using the compiled regex
-    prompt(editor, &reg, config)
-}
+        // Compare the current regex against another one
+        ":equiv" => {
+            match reg {
+                Some(r) => print_equivalence(session, r.as_str(), arg),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToCompare));
+                }
+            }
+            Action::Loop
+        }
 
-// If this returns false, the program with exit.
-// If it returns true, the prompt for a new regex
-// will be shown.
-fn prompt(editor: &mut Editor<()>, reg: &Regex, config: &mut Config) -> bool {
-    let mut stderr = io::stderr();
-    let prompt = &format!("Regex({})> ", reg.as_str());
+        // Look for strings both regexes match
+        ":overlap-with" => {
+            match reg {
+                Some(r) => print_overlap(session, r.as_str(), arg),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToCompare));
+                }
+            }
+            Action::Loop
+        }
 
-    loop {
-        let line = editor.readline(prompt).expect("Failed to read line");
-        editor.add_history_entry(&line);
+        // Show what (?i) turns the current regex into
+        ":fold" => {
+            match reg {
+                Some(r) => print_fold(r.as_str()),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToFold));
+                }
+            }
+            Action::Loop
+        }
 
-        // Enable menu
-        match options_menu(&line, config) {
-            Action::Exit => return false,
-            Action::Loop => continue,
-            Action::ToRegexPrompt => return true,
-            // Not a command so test it against the regex
-            Action::Continue => {
-                // Are we dealing with capture groups?
-                if config.contains(CAPTURE_GROUPS) {
-                    let caps = reg.captures_iter(&line).enumerate();
-                    write!(stderr, "Captures:\n");
-                    for (i, outer_cap) in caps {
-                        for (j, cap) in outer_cap.iter().enumerate() {
-                            write!(stderr,
-                                   "{}:{}: {}\n",
-                                   i,
-                                   j,
-                                   if let Some(c) = cap { c.as_str() } else { "None" });
+        // Show how case folding treats some text
+        ":casefold" => {
+            match input {
+                Some(text) => print_casefold(session, text),
+                None => {
+                    writeln!(stderr, "{}", msg!(CasefoldUsage));
+                }
+            }
+            Action::Loop
+        }
+
+        // Look for random text the regex matches
+        ":fuzz" => {
+            match (reg, arg.trim()) {
+                (None, _) => {
+                    writeln!(stderr, "{}", msg!(NoRegexToFuzz));
+                }
+                (Some(r), "") => print_fuzz(r, &session.inputs, DEFAULT_FUZZ),
+                (Some(r), n) => {
+                    match n.parse() {
+                        Ok(n) => print_fuzz(r, &session.inputs, n),
+                        Err(_) => {
+                            writeln!(stderr, "{}", msg!(FuzzUsage));
                         }
                     }
-                } else {
-                    if reg.is_match(&line) {
-                        write!(stderr, "Matched\n");
-                    } else {
-                        write!(stderr, "Failed to match\n");
+                }
+            }
+            Action::Loop
+        }
+
+        // Show which edits of the regex change its results
+        ":mutate" => {
+            match reg {
+                Some(r) => print_mutations(r, session),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToMutate));
+                }
+            }
+            Action::Loop
+        }
+
+        // Time the ways of matching against each other
+        ":benchapis" => {
+            match (reg, input) {
+                (Some(r), Some(input)) => print_benchapis(r, input),
+                (None, _) => {
+                    writeln!(stderr, "{}", msg!(NoRegexToBench));
+                }
+                (_, None) => {
+                    writeln!(stderr, "{}", msg!(NoInputToBench));
+                }
+            }
+            Action::Loop
+        }
+
+        // Show what's needed to report a bug in matching
+        ":version" | ":engineinfo" => {
+            print_version(session.flags);
+            Action::Loop
+        }
+
+        // Compare the regex with another one part by part
+        ":diff" => {
+            match (reg, arg.trim()) {
+                (None, _) => {
+                    writeln!(stderr, "{}", msg!(NoRegexToDiff));
+                }
+                (Some(_), "") => {
+                    writeln!(stderr, "{}", msg!(DiffUsage));
+                }
+                (Some(r), other) => print_astdiff(r.as_str(), other),
+            }
+            Action::Loop
+        }
+
+        // Run the lines of a script ahead of anything typed
+        ":source" => {
+            match arg.trim() {
+                "" => {
+                    writeln!(stderr, "{}", msg!(SourceUsage));
+                }
+                path => match script::load(path) {
+                    Ok(lines) => {
+                        for line in lines.into_iter().rev() {
+                            session.script.push_front(line);
+                        }
+                    }
+                    Err(e) => {
+                        writeln!(stderr, "{}", e);
                     }
+                },
+            }
+            Action::Loop
+        }
+
+        // Look characters up in a class
+        ":member" => {
+            member_command(session, arg);
+            Action::Loop
+        }
+
+        // Show the \p{...} classes a character is in
+        ":props" => {
+            if arg.trim().is_empty() {
+                writeln!(stderr, "{}", msg!(PropsUsage));
+            }
+            for c in props::chars(arg.trim()) {
+                print_props(c);
+            }
+            Action::Loop
+        }
+
+        // Look for catastrophic backtracking
+        ":redos" => {
+            match reg {
+                Some(r) => print_redos(r.as_str(), session.flags_for(r.as_str(), session.flags)),
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToAnalyze));
                 }
             }
+            Action::Loop
         }
-    }
-}
 
-/// Determine and load the history file erroring out
-/// upon failure.
-///
-/// # Notes
-/// Failure within this function is non-fatal. It will
-/// not panic and only show a warning to the user.
-fn with_history_file<F>(mut f: F)
-    where F: FnMut(&PathBuf)
-{
-    let mut path = match app_root(AppDataType::UserData, &APP_INFO) {
-        Ok(p) => p,
-        Err(e) => {
-            println!("Failed to write history file: {:?}", e);
-            return;
+        // Explain why the last input (or the argument) failed
+        ":why" => {
+            match (reg, input) {
+                (Some(r), Some(input)) => {
+                    let flags = session.flags_for(r.as_str(), session.flags);
+                    print_why(r.as_str(), flags, input, session.offsets)
+                }
+                (None, _) => {
+                    writeln!(stderr, "{}", msg!(NoRegexToExplain));
+                }
+                (_, None) => {
+                    writeln!(stderr, "{}", msg!(NoInputToExplain));
+                }
+            }
+            Action::Loop
         }
-    };
-    path.push("history");
-    f(&path);
-}
 
-fn main() {
-    let mut config = Config::default();
-    // Configure command line flags
-    let matches = App::new("regtest")
-        .version(env!("CARGO_PKG_VERSION"))
-        .author("Lucas Salibian <lucas.salibian@gmail.com>")
-        .about("Test regexes from the command line")
-        .arg(Arg::with_name("no-verbose-errors")
-            .long("no-verbose-errors")
-            .help("Disable verbose errors when the regex fails to compile"))
-        .arg(Arg::with_name("capture")
-            .short("c")
-            .long("capture")
-            .help("Enable capture group display after matching test"))
-        .arg(Arg::with_name("no-compile-time")
-            .long("no-compile-time")
-            .help("Disable showing the amount of time it took to compile the regular expression."))
-        .get_matches();
+        // Replay a match attempt step by step
+        ":debug" => {
+            match (reg, input) {
+                (Some(r), Some(input)) => {
+                    let accessible = session.config.contains(Config::ACCESSIBLE);
+                    let flags = session.flags_for(r.as_str(), session.flags);
+                    debug_match(r.as_str(), flags, input, accessible, session.offsets)
+                }
+                (None, _) => {
+                    writeln!(stderr, "{}", msg!(NoRegexToDebug));
+                }
+                (_, None) => {
+                    writeln!(stderr, "{}", msg!(NoInputToDebug));
+                }
+            }
+            Action::Loop
+        }
 
-    if matches.is_present("no-verbose-errors") {
-        config.remove(VERBOSE_ERRORS);
-    }
+        // Collect inputs every pattern must or must not match
+        ":expect" => expect_command(session, arg),
 
-    if matches.is_present("capture") {
-        config.insert(CAPTURE_GROUPS);
-    }
+        // Show how much the regex cache saved, or empty it
+        ":cache" => {
+            match arg.trim() {
+                "" => {
+                    let c = &session.cache;
+                    writeln!(stderr,
+                             "{}",
+                             msg!(CacheStats, c.len(), c.hits, c.misses, nanoseconds(c.saved)));
+                }
+                "clear" => {
+                    session.cache.clear();
+                    writeln!(stderr, "{}", msg!(CacheCleared));
+                }
+                _ => {
+                    writeln!(stderr, "{}", msg!(CacheUsage));
+                }
+            }
+            Action::Loop
+        }
 
-    // Initialize the rustline (readline) editor
-    let mut editor = Editor::<()>::new();
+        // Syntax reference
+        ":cheat" => {
+            print_cheat(arg.trim(), session.config.contains(Config::PAGER));
+            Action::Loop
+        }
 
-    with_history_file(|path| { editor.load_history(path); });
+        // Revert the last option or pattern change
+        ":undo" => {
+            let snapshot = match session.undo.pop() {
+                Some(s) => s,
+                None => {
+                    writeln!(stderr, "{}", msg!(NothingToUndo));
+                    return Action::Loop;
+                }
+            };
+            session.restore(&snapshot.options, &snapshot.origins);
+            if !snapshot.pattern_change {
+                writeln!(stderr, "{}", msg!(UndidOptions));
+                return Action::Reload;
+            }
+            if let Some(ref r) = snapshot.regex {
+                writeln!(stderr, "{}", msg!(UndidPattern, r.as_str()));
+            }
+            session.regex = snapshot.regex;
+            session.unwrapped = snapshot.unwrapped;
+            session.last_input = None;
+            session.last_typed = None;
+            Action::Reload
+        }
 
-    // Enter the main loop
-    loop {
-        if !regex_prompt(&mut editor, &mut config) {
-            break;
+        // Go back to the regex prompt with the pattern typed in
+        ":edit" => {
+            match reg {
+                Some(r) => {
+                    session.prefill = Some(r.as_str().to_owned());
+                    Action::ToRegexPrompt
+                }
+                None => {
+                    writeln!(stderr, "{}", msg!(NoRegexToEdit));
+                    Action::Loop
+                }
+            }
         }
-    }
 
-    with_history_file(|path| { editor.save_history(path).unwrap(); });
+        // Edit the pattern in $EDITOR, in verbose form
+        ":vi" | ":editor" => editor_command(session),
+
+        // List the patterns compiled this session
+        ":patterns" => {
+            let mut stderr = Paged::new(pager::Target::Stderr, session.config.contains(Config::PAGER));
+            if session.patterns.is_empty() {
+                writeln!(stderr, "{}", msg!(PatternsNone));
+            }
+            for (i, &(ref p, flags)) in session.patterns.iter().enumerate() {
+                if flags == Flags::default() {
+                    writeln!(stderr, "{:>4}  {}", format!("!{}", i + 1), p);
+                } else {
+                    writeln!(stderr, "{:>4}  {}  ({})", format!("!{}", i + 1), p, flags.name());
+                }
+            }
+            Action::Loop
+        }
+
+        // Switch back to one of them
+        ":use" => use_command(session, arg.trim()),
+
+        // Run what the regex extracts through more regexes
+        ":pipe" => pipe_command(session, arg.trim()),
+
+        // Try the regex as a whole word or line, and back
+        ":wrap" => wrap_command(session, arg.trim()),
+        ":unwrap" => {
+            match session.unwrapped.clone() {
+                Some(p) => {
+                    match session.compile(&p).0 {
+                        Ok(reg) => {
+                            session.remember(true);
+                            session.use_regex(reg);
+                            Action::Reload
+                        }
+                        Err(e) => {
+                            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                            Action::Loop
+                        }
+                    }
+                }
+                None => {
+                    writeln!(stderr, "{}", msg!(NotWrapped));
+                    Action::Loop
+                }
+            }
+        }
+
+        // Put a character class together step by step
+        ":class" => class_command(session),
+
+        // Name or rename a capture group
+        ":rename" => rename_command(session, arg.trim()),
+
+        // Match any word of a list
+        ":fromwords" => fromwords_command(session, arg.trim()),
+
+        // Test what's on the clipboard
+        ":paste" => paste_command(session),
+
+        // Run the regex on a file again each time it changes
+        ":watch" => watch_command(session, arg.trim()),
+
+        // Show the matches of more regexes on each input
+        ":hl" => highlight_command(session, arg.trim()),
+
+        // Change the flags the regex is compiled with
+        ":flags" => flags_command(session, arg.trim()),
+
+        // Go back to the options the session started with
+        ":reset" => reset(session, arg.trim()),
+
+        // Switch to another workspace, or say which one is in use
+        ":workspace" => workspace_command(session, arg.trim()),
+
+        // Switch to a profile from the config file
+        ":profile" => profile_command(session, arg.trim()),
+
+        // Show every option and where it came from
+        ":config" => {
+            print_config(session);
+            Action::Loop
+        }
+
+        // Display help
+        ":h" | ":?" => {
+            let paged = session.config.contains(Config::PAGER);
+            writeln!(Paged::new(pager::Target::Stderr, paged), "{}", msg!(Help));
+            Action::Loop
+        }
+
+        // Continue
+        _ => Action::Continue,
+    }
+}
+
+/// Restore option `name`, or every option and the pattern if
+/// empty, to its value at startup, printing what changed.
+fn reset(session: &mut Session, name: &str) -> Action {
+    let mut stderr = io::stderr();
+    let names = settings::names();
+    if !name.is_empty() && !names.contains(&name) {
+        writeln!(stderr, "{}", msg!(ResetUnknown, name, names.join(", ")));
+        return Action::Loop;
+    }
+
+    let clear_pattern = name.is_empty() && session.regex.is_some();
+    session.remember(clear_pattern);
+    let mut changed = false;
+    let startup = session.startup.options.clone();
+    for (option, then) in startup {
+        if !name.is_empty() && name != option {
+            continue;
+        }
+        let now = session.value(option).unwrap();
+        session.set_value(option, &then, Source::Default).unwrap();
+        match session.startup.origins.get(option).cloned() {
+            Some(source) => session.origins.insert(option, source),
+            None => session.origins.remove(option),
+        };
+        if now != then {
+            writeln!(stderr, "{}", msg!(ResetChanged, option, now, then));
+            changed = true;
+        }
+    }
+
+    if clear_pattern {
+        if let Some(r) = session.regex.take() {
+            writeln!(stderr, "{}", msg!(ResetPattern, r.as_str()));
+        }
+        session.last_input = None;
+        session.last_typed = None;
+        return Action::Reload;
+    }
+    if !changed {
+        session.undo.pop();
+        writeln!(stderr, "{}", msg!(ResetNothing));
+        return Action::Loop;
+    }
+    Action::Reload
+}
+
+/// Set an option with `:set <name> <value>`, or show its value if
+/// no value is given.
+fn set_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    let names = settings::names();
+    let (name, value) = match arg.find(' ') {
+        Some(i) => (&arg[..i], arg[i + 1..].trim()),
+        None => (arg, ""),
+    };
+    let name = match names.iter().find(|&&n| n == name) {
+        Some(&n) => n,
+        None => {
+            writeln!(stderr, "{}", msg!(ResetUnknown, name, names.join(", ")));
+            return Action::Loop;
+        }
+    };
+    if value.is_empty() {
+        writeln!(stderr, "{} = {}", name, session.value(name).unwrap());
+        return Action::Loop;
+    }
+
+    session.remember(false);
+    match session.set_value(name, value, Source::Runtime) {
+        Ok(()) => {
+            writeln!(stderr, "{} = {}", name, session.value(name).unwrap());
+            Action::Reload
+        }
+        Err(_) => {
+            session.undo.pop();
+            writeln!(stderr, "{}", msg!(SetBadValue, value, name));
+            Action::Loop
+        }
+    }
+}
+
+/// Edit the current pattern in the user's editor and switch to
+/// what was saved.
+fn editor_command(session: &mut Session) -> Action {
+    let mut stderr = io::stderr();
+    let text = match session.regex {
+        Some(ref r) => edit::to_verbose(r.as_str()),
+        None => {
+            writeln!(stderr, "{}", msg!(NoRegexToEdit));
+            return Action::Loop;
+        }
+    };
+    let pattern = match edit::edit(&text) {
+        Ok(p) => p,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(EditorFailed, e));
+            return Action::Loop;
+        }
+    };
+    if pattern == text.trim_end_matches('\n') {
+        writeln!(stderr, "{}", msg!(EditorUnchanged));
+        return Action::Loop;
+    }
+    match session.compile(&pattern).0 {
+        Ok(reg) => {
+            session.remember(true);
+            session.use_regex(reg);
+            Action::Reload
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            Action::Loop
+        }
+    }
+}
+
+/// List the pipeline, clear it or add a stage to it.
+fn pipe_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    match arg {
+        "" => {
+            if session.pipe.is_empty() {
+                writeln!(stderr, "{}", msg!(PipeNone));
+            }
+            let first = session.regex.iter();
+            for (i, r) in first.chain(session.pipe.iter()).enumerate() {
+                writeln!(stderr, "  {}. {}", i + 1, r.as_str());
+            }
+            Action::Loop
+        }
+        "clear" => {
+            session.pipe.clear();
+            writeln!(stderr, "{}", msg!(PipeCleared));
+            Action::Loop
+        }
+        pattern => {
+            match session.compile(pattern).0 {
+                Ok(reg) => {
+                    session.pipe.push(reg);
+                    Action::Reload
+                }
+                Err(e) => {
+                    writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                    Action::Loop
+                }
+            }
+        }
+    }
+}
+
+/// Wrap the pattern so it must match a whole word or line, keeping
+/// what was typed for `:unwrap`.
+fn wrap_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    let pattern = match (&session.unwrapped, &session.regex) {
+        (Some(p), _) => p.clone(),
+        (None, Some(r)) => r.as_str().to_owned(),
+        (None, None) => {
+            writeln!(stderr, "{}", msg!(NoRegexToWrap));
+            return Action::Loop;
+        }
+    };
+    let wrapped = match arg {
+        "word" => format!("\\b(?:{})\\b", pattern),
+        "line" => format!("^(?:{})$", pattern),
+        _ => {
+            writeln!(stderr, "{}", msg!(WrapUsage));
+            return Action::Loop;
+        }
+    };
+    match session.compile(&wrapped).0 {
+        Ok(reg) => {
+            session.remember(true);
+            // Kept past use_regex, so wrapping again replaces the wrap
+            session.use_regex(reg);
+            session.unwrapped = Some(pattern);
+            Action::Reload
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            Action::Loop
+        }
+    }
+}
+
+/// Rename the group named or numbered by the first word of `arg`
+/// to the second, in the regex and the replacement template.
+fn rename_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let (old, new) = match words[..] {
+        [old, new] => (old, new),
+        _ => {
+            writeln!(stderr, "{}", msg!(RenameUsage));
+            return Action::Loop;
+        }
+    };
+    let pattern = match session.regex {
+        Some(ref r) => r.as_str().to_owned(),
+        None => {
+            writeln!(stderr, "{}", msg!(NoRegexToRename));
+            return Action::Loop;
+        }
+    };
+    let renamed = match rename::rename(&pattern, old, new) {
+        Ok(p) => p,
+        Err(rename::Error::Parse(e)) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return Action::Loop;
+        }
+        Err(rename::Error::NoGroup) => {
+            writeln!(stderr, "{}", msg!(RenameNoGroup, old));
+            return Action::Loop;
+        }
+        Err(rename::Error::Taken) => {
+            writeln!(stderr, "{}", msg!(RenameTaken, new));
+            return Action::Loop;
+        }
+    };
+    match session.compile(&renamed).0 {
+        Ok(reg) => {
+            session.remember(true);
+            if let Some(t) = session.replace.take() {
+                let t = template::rename(&t, old, new);
+                writeln!(stderr, "{}", msg!(RenameTemplate, t));
+                session.replace = Some(t);
+            }
+            session.use_regex(reg);
+            Action::Reload
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            Action::Loop
+        }
+    }
+}
+
+/// Build a character class at its own prompt, then go back to the
+/// regex prompt with it added to the end of the regex.
+fn class_command(session: &mut Session) -> Action {
+    let mut stderr = io::stderr();
+    let mut editor = Editor::<()>::new();
+    let mut builder = class::Builder::new();
+    writeln!(stderr, "{}", msg!(ClassHelp));
+    loop {
+        let line = match editor.readline(&msg!(ClassPrompt, builder.syntax())) {
+            Ok(l) => l,
+            Err(_) => return Action::Loop,
+        };
+        let line = line.trim();
+        let (command, rest) = match line.find(' ') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => (line, ""),
+        };
+        match command {
+            "" | "done" => break,
+            "cancel" => return Action::Loop,
+            "not" => builder.negated = !builder.negated,
+            "del" => {
+                if !builder.remove(rest) {
+                    writeln!(stderr, "{}", msg!(ClassNoItem, rest));
+                    continue;
+                }
+            }
+            _ => {
+                let item = if command == "add" { rest } else { line };
+                if let Err(e) = builder.add(item) {
+                    writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                    continue;
+                }
+            }
+        }
+        if builder.items.is_empty() {
+            continue;
+        }
+        let (samples, more) = builder.samples(24);
+        let samples: Vec<String> = samples.iter()
+            .map(|&c| if c.is_whitespace() { format!("{:?}", c) } else { c.to_string() })
+            .collect();
+        let ellipsis = if more { " ..." } else { "" };
+        writeln!(stderr, "{}", msg!(ClassSamples, samples.join(" "), ellipsis));
+    }
+
+    if builder.items.is_empty() {
+        return Action::Loop;
+    }
+    let pattern = session.regex.as_ref().map(|r| r.as_str()).unwrap_or("");
+    session.prefill = Some(format!("{}{}", pattern, builder.syntax()));
+    Action::ToRegexPrompt
+}
+
+/// Make the regex an alternation of the words in the file at
+/// `path`, one per line.
+fn fromwords_command(session: &mut Session, path: &str) -> Action {
+    let mut stderr = io::stderr();
+    if path.is_empty() {
+        writeln!(stderr, "{}", msg!(FromWordsUsage));
+        return Action::Loop;
+    }
+    if let Some((size, limit)) = over_max_file(session, path) {
+        if !confirm(&msg!(ConfirmBigFile, path, size, limit)) {
+            return Action::Loop;
+        }
+    }
+    let mut text = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut text)) {
+        writeln!(stderr, "{}", msg!(CantOpenFile, path, e));
+        return Action::Loop;
+    }
+    let words: Vec<String> = text.lines()
+        .map(|l| l.trim().to_owned())
+        .filter(|l| !l.is_empty())
+        .collect();
+    if words.is_empty() {
+        writeln!(stderr, "{}", msg!(FromWordsEmpty, path));
+        return Action::Loop;
+    }
+
+    let pattern = words::alternation(&words);
+    match session.compile(&pattern).0 {
+        Ok(reg) => {
+            writeln!(stderr, "{}", msg!(FromWordsBuilt, words.len(), pattern.chars().count()));
+            if session.regex.is_some() {
+                session.remember(true);
+            }
+            session.use_regex(reg);
+            Action::Reload
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            Action::Loop
+        }
+    }
+}
+
+/// Run the regex on the clipboard as one input, so matches can span
+/// lines, and show the lines it matched in.
+fn paste_command(session: &mut Session) -> Action {
+    let mut stderr = io::stderr();
+    let reg = match session.regex {
+        Some(ref r) => r.clone(),
+        None => {
+            writeln!(stderr, "{}", msg!(NoRegexToPaste));
+            return Action::Loop;
+        }
+    };
+    let text = match clipboard::paste() {
+        Ok(t) => t,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(CantPaste, e));
+            return Action::Loop;
+        }
+    };
+    if text.is_empty() {
+        writeln!(stderr, "{}", msg!(PasteEmpty));
+        return Action::Loop;
+    }
+
+    let spans: Vec<_> = reg.find_iter(&text).map(|m| (m.start(), m.end())).collect();
+    if spans.is_empty() {
+        writeln!(stderr, "{}", msg!(FailedToMatch));
+        return Action::Loop;
+    }
+    let mut stderr = Paged::new(pager::Target::Stderr, session.config.contains(Config::PAGER));
+    let mut touched = 0;
+    let mut start = 0;
+    for (n, line) in text.split('\n').enumerate() {
+        let end = start + line.len();
+        // The parts of matches on this line
+        let marks: Vec<_> = spans.iter()
+            .filter(|&&(s, e)| s <= end && (e > start || s == e && s >= start))
+            .map(|&(s, e)| (s.max(start) - start, e.min(end) - start))
+            .collect();
+        if !marks.is_empty() {
+            touched += 1;
+            let number = format!("{:>4}: ", n + 1);
+            writeln!(stderr, "{}{}", number, highlight::line(line));
+            writeln!(stderr, "{:w$}{}", "", highlight::underline(line, &marks), w = number.len());
+        }
+        start = end + 1;
+    }
+    writeln!(stderr, "{}", msg!(PasteMatches, spans.len(), touched, text.lines().count()));
+    Action::Loop
+}
+
+/// Print the lines of the file at `path` the regex matches, then
+/// the new ones each time the file changes, until Enter is pressed.
+fn watch_command(session: &mut Session, path: &str) -> Action {
+    let mut stderr = io::stderr();
+    let reg = match session.regex {
+        Some(ref r) => r.clone(),
+        None => {
+            writeln!(stderr, "{}", msg!(NoRegexToWatch));
+            return Action::Loop;
+        }
+    };
+    if path.is_empty() {
+        writeln!(stderr, "{}", msg!(WatchUsage));
+        return Action::Loop;
+    }
+    // Open the file before anything reads stdin, so a file that can't
+    // be read leaves the prompt as it was
+    let streamed = over_max_file(session, path);
+    let follower = match streamed {
+        Some(_) => watch::Follower::open(Path::new(path), session.max_line).map(Some),
+        None => File::open(path).map(|_| None),
+    };
+    let follower = match follower {
+        Ok(f) => f,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(CantOpenFile, path, e));
+            return Action::Loop;
+        }
+    };
+    writeln!(stderr, "{}", msg!(WatchStarted, path));
+
+    // Stop when a line is read, or stdin is closed. The thread is
+    // waited for before going back to the prompt, so it can't take
+    // the next line typed there.
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopper = stop.clone();
+    let waiter = thread::spawn(move || {
+        io::stdin().read_line(&mut String::new());
+        stopper.store(true, Ordering::SeqCst);
+    });
+
+    let color = diff::color_stderr() && !session.config.contains(Config::ACCESSIBLE);
+    let on_match = session.on_match.clone();
+    let report = |new: &[&(usize, String)]| {
+        let mut stderr = io::stderr();
+        for &&(n, ref line) in new {
+            writeln!(stderr, "  {}: {}", n, highlight::several(line, &[&reg], color));
+        }
+        if let Some(ref hook) = on_match {
+            for &&(n, ref line) in new {
+                let place = format::Place {
+                    file: path,
+                    line: n,
+                };
+                for caps in reg.captures_iter(line) {
+                    if let Err(e) = hook.run(&place, &caps) {
+                        writeln!(stderr, "{}", e);
+                    }
+                }
+            }
+        }
+    };
+
+    // Too big to read whole each time, so only read what's added
+    if let (Some((size, limit)), Some(follower)) = (streamed, follower) {
+        writeln!(stderr, "{}", msg!(WatchStreaming, path, size, limit));
+        let max_line = session.max_line;
+        let followed = watch::follow(follower, &stop, |lines| {
+            let mut kept = vec![];
+            for (n, line) in lines {
+                match line {
+                    Some(l) => kept.push((n, l)),
+                    None => {
+                        let longest = settings::size_name(max_line.unwrap_or(0));
+                        writeln!(io::stderr(), "{}", msg!(LineTooLong, path, n, longest));
+                    }
+                }
+            }
+            let new: Vec<_> = kept.iter().filter(|l| reg.is_match(&l.1)).collect();
+            report(&new);
+        });
+        if let Err(e) = followed {
+            writeln!(stderr, "{}", msg!(CantOpenFile, path, e));
+            writeln!(stderr, "{}", msg!(WatchEnded));
+        }
+        waiter.join();
+        return Action::Loop;
+    }
+
+    let mut seen: HashSet<(usize, String)> = HashSet::new();
+    watch::watch(Path::new(path), &stop, |text| {
+        let text = match text {
+            Ok(t) => t,
+            Err(e) => {
+                writeln!(stderr, "{}", msg!(CantOpenFile, path, e));
+                return;
+            }
+        };
+        let lines = watch::matching(&reg, &text);
+        let new: Vec<_> = lines.iter().filter(|l| !seen.contains(*l)).collect();
+        writeln!(stderr, "{}", msg!(WatchChanged, path, lines.len(), new.len()));
+        report(&new);
+        seen = lines.iter().cloned().collect();
+    });
+    waiter.join();
+    Action::Loop
+}
+
+/// The size of the file at `path` and the `max-file` limit, if it's
+/// over it.
+fn over_max_file(session: &Session, path: &str) -> Option<(String, String)> {
+    let limit = session.max_file?;
+    let size = fs::metadata(path).ok()?.len();
+    if size > limit {
+        Some((settings::size_name(size), settings::size_name(limit)))
+    } else {
+        None
+    }
+}
+
+/// Ask a yes or no question, taking anything but yes as no.
+fn confirm(question: &str) -> bool {
+    match Editor::<()>::new().readline(question) {
+        Ok(answer) => matches!(answer.trim(), "y" | "Y" | "s" | "S"),
+        Err(_) => false,
+    }
+}
+
+/// Show the legend of the highlighted regexes, clear them or add
+/// one.
+fn highlight_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    match arg {
+        "" if session.highlights.is_empty() => {
+            writeln!(stderr, "{}", msg!(HighlightNone));
+            Action::Loop
+        }
+        "" => {
+            print_legend(session);
+            Action::Loop
+        }
+        "clear" => {
+            session.highlights.clear();
+            writeln!(stderr, "{}", msg!(HighlightCleared));
+            Action::Loop
+        }
+        pattern => {
+            match session.compile(pattern).0 {
+                Ok(reg) => {
+                    session.highlights.push(reg);
+                    print_legend(session);
+                    Action::Reload
+                }
+                Err(e) => {
+                    writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                    Action::Loop
+                }
+            }
+        }
+    }
+}
+
+/// Print how each regex is marked when several are highlighted.
+fn print_legend(session: &Session) {
+    let color = diff::color_stderr() && !session.config.contains(Config::ACCESSIBLE);
+    let regexes = session.regex.iter().chain(session.highlights.iter());
+    for (i, r) in regexes.enumerate() {
+        writeln!(io::stderr(), "  {} {}", highlight::swatch(i, color), r.as_str());
+    }
+}
+
+/// Print what each regex of the pipeline did with `input`,
+/// starting with `reg`.
+fn print_pipeline(reg: &Regex, pipe: &[Regex], input: &str) {
+    let mut stderr = io::stderr();
+    let regexes: Vec<&Regex> = Some(reg).into_iter().chain(pipe.iter()).collect();
+    writeln!(stderr, "{}", msg!(PipeHeader));
+    for (i, stage) in pipe::run(&regexes, input).iter().enumerate() {
+        let result = match stage.output {
+            Some(ref o) => format!("-> {:?}", o),
+            None => msg!(PipeNoMatch).to_owned(),
+        };
+        writeln!(stderr, "  {}. {}  {:?}  {}", i + 1, regexes[i].as_str(), stage.input, result);
+    }
+}
+
+/// Show the flags, or change them with `+i -m` and recompile the
+/// current pattern.
+fn flags_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    if arg.is_empty() {
+        print_flags(session.flags);
+        return Action::Loop;
+    }
+    let flags = match session.flags.change(arg) {
+        Ok(f) => f,
+        Err(c) => {
+            let letters: Vec<String> = flags::LETTERS.iter().map(|l| l.0.to_string()).collect();
+            writeln!(stderr, "{}", msg!(UnknownFlag, c, letters.join(", ")));
+            return Action::Loop;
+        }
+    };
+    if let Some(ref r) = session.regex {
+        let checked = session.flags_for(r.as_str(), flags);
+        if let Err(e) = session.cache.compile(r.as_str(), checked).0 {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return Action::Loop;
+        }
+    }
+    session.remember(false);
+    session.set_value("flags", &flags.name(), Source::Runtime).unwrap();
+    print_flags(flags);
+    Action::Reload
+}
+
+/// Print the flags regexes are compiled with.
+fn print_flags(flags: Flags) {
+    if flags == Flags::default() {
+        writeln!(io::stderr(), "{}", msg!(ConfigNoFlags));
+    } else {
+        writeln!(io::stderr(), "{}", msg!(ConfigFlags, flags.name()));
+    }
+}
+
+/// Switch to pattern `!n` of `:patterns`, testing the last input
+/// against it.
+fn use_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    let n = arg.trim_start_matches('!');
+    let (pattern, flags) = match n.parse::<usize>().ok().and_then(|n| n.checked_sub(1)) {
+        Some(i) if i < session.patterns.len() => session.patterns[i].clone(),
+        Some(_) => {
+            writeln!(stderr, "{}", msg!(NoSuchPattern, n));
+            return Action::Loop;
+        }
+        None => {
+            writeln!(stderr, "{}", msg!(UseUsage));
+            return Action::Loop;
+        }
+    };
+    match session.cache.compile(&pattern, session.flags_for(&pattern, flags)).0 {
+        Ok(reg) => {
+            if session.regex.is_some() {
+                session.remember(true);
+            }
+            if flags != session.flags {
+                session.set_value("flags", &flags.name(), Source::Runtime).unwrap();
+            }
+            session.use_regex(reg);
+            Action::Reload
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            Action::Loop
+        }
+    }
+}
+
+/// Add, clear, save or list the expectations with `:expect`.
+fn expect_command(session: &mut Session, arg: &str) -> Action {
+    let mut stderr = io::stderr();
+    if arg.is_empty() {
+        if session.expectations.is_empty() {
+            writeln!(stderr, "{}", msg!(ExpectNone));
+        } else if let Some(ref r) = session.regex {
+            print_expectations(session, r);
+        } else {
+            for e in &session.expectations {
+                writeln!(stderr, "{} {:?}", e.directive(), e.input);
+            }
+        }
+        return Action::Loop;
+    }
+    if arg == "clear" {
+        session.expectations.clear();
+        writeln!(stderr, "{}", msg!(ExpectCleared));
+        return Action::Loop;
+    }
+    if let Some(path) = arg.strip_prefix("save ") {
+        let path = path.trim();
+        let pattern = match session.regex {
+            Some(ref r) => r.as_str(),
+            None => {
+                writeln!(stderr, "{}", msg!(ExpectNoPattern));
+                return Action::Loop;
+            }
+        };
+        // Suites go in the workspace unless given a path of their own
+        let path = match session.workspace.as_ref().and_then(|w| workspace_dir(w)) {
+            Some(dir) if PathBuf::from(path).is_relative() => dir.join(path).display().to_string(),
+            _ => path.to_owned(),
+        };
+        let path = &path[..];
+        let text = suite::to_text(pattern, &session.expectations);
+        match File::create(path).and_then(|mut f| f.write_all(text.as_bytes())) {
+            Ok(()) => writeln!(stderr, "{}", msg!(ExpectSaved, session.expectations.len(), path)),
+            Err(e) => writeln!(stderr, "{}", msg!(CantWriteFile, path, e)),
+        };
+        return Action::Loop;
+    }
+
+    match Expectation::parse(arg) {
+        Some(e) => {
+            writeln!(stderr,
+                     "{}",
+                     msg!(ExpectAdded, format!("{} {:?}", e.directive(), e.input)));
+            session.expectations.push(e);
+            if let Some(ref r) = session.regex {
+                print_expectations(session, r);
+            }
+        }
+        None => {
+            writeln!(stderr, "{}", msg!(ExpectUsage));
+        }
+    }
+    Action::Loop
+}
+
+/// Check each expectation against `reg`, marking it with ✓ or ✗.
+fn print_expectations(session: &Session, reg: &Regex) {
+    let mut stderr = io::stderr();
+    let accessible = session.config.contains(Config::ACCESSIBLE);
+    let mut met = 0;
+    for e in &session.expectations {
+        let ok = reg.is_match(&e.input) == e.should_match;
+        if ok {
+            met += 1;
+        }
+        if accessible {
+            let status = if ok { msg!(AccExpectMet) } else { msg!(AccExpectNotMet) };
+            writeln!(stderr, "{} {:?}: {}", e.directive(), e.input, status);
+        } else {
+            writeln!(stderr, "{} {} {:?}", if ok { '✓' } else { '✗' }, e.directive(), e.input);
+        }
+    }
+    writeln!(stderr, "{}", msg!(ExpectSummary, met, session.expectations.len()));
+}
+
+/// Print every option with its value and source, followed by the
+/// engine and current pattern.
+fn print_config(session: &Session) {
+    let mut stderr = io::stderr();
+    let names = settings::names();
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+    for name in names {
+        writeln!(stderr,
+                 "{:width$} = {:3}  ({})",
+                 name,
+                 session.value(name).unwrap(),
+                 session.origin(name).label(),
+                 width = width);
+    }
+
+    if let Some(ref p) = session.profile {
+        writeln!(stderr, "{}", msg!(ConfigProfile, p));
+    }
+    writeln!(stderr, "{}", msg!(ConfigEngine, settings::ENGINE));
+    match session.regex {
+        Some(ref r) => writeln!(stderr, "{}", msg!(ConfigPattern, r.as_str())),
+        None => writeln!(stderr, "{}", msg!(ConfigNoPattern)),
+    };
+    print_flags(session.flags);
+    if let Some(path) = config_file() {
+        writeln!(stderr, "{}", msg!(ConfigFile, path.display()));
+    }
+    for (var, value) in settings::env_others() {
+        writeln!(stderr, "{}", msg!(ConfigEnvVar, var, value));
+    }
+    writeln!(stderr, "{}", msg!(ConfigPrecedence));
+}
+
+/// Print the linter's suggestions for `pattern`, read with `flags`.
+fn print_lints(pattern: &str, flags: Flags) {
+    let mut stderr = io::stderr();
+    let lints = lint::lint(pattern, flags);
+    if lints.is_empty() {
+        writeln!(stderr, "{}", msg!(NoLintSuggestions));
+        return;
+    }
+    for l in lints {
+        writeln!(stderr, "{}..{}: {}", l.start, l.end, l.message);
+    }
+}
+
+/// Prepare both patterns for comparison with the session's flags,
+/// reporting any error.
+fn languages(session: &Session,
+             pattern: &str,
+             other: &str)
+             -> Option<(compare::Language, compare::Language)> {
+    let language = |p: &str| compare::Language::new(p, session.flags_for(p, session.flags));
+    match (language(pattern), language(other)) {
+        (Ok(a), Ok(b)) => Some((a, b)),
+        (Err(e), _) | (_, Err(e)) => {
+            writeln!(io::stderr(), "{}", msg!(ErrorCompiling, e));
+            None
+        }
+    }
+}
+
+/// Report whether `pattern` and `other` match the same strings.
+fn print_equivalence(session: &Session, pattern: &str, other: &str) {
+    let mut stderr = io::stderr();
+    let (a, b) = match languages(session, pattern, other) {
+        Some(l) => l,
+        None => return,
+    };
+
+    match compare::equivalent(&a, &b) {
+        compare::Equivalence::Identical => {
+            writeln!(stderr, "{}", msg!(EquivIdentical));
+        }
+        compare::Equivalence::NoDifferenceFound(n) => {
+            writeln!(stderr, "{}", msg!(EquivNoDifference, n));
+        }
+        compare::Equivalence::Differs { input, first_matches } => {
+            let (yes, no) = if first_matches {
+                (pattern, other)
+            } else {
+                (other, pattern)
+            };
+            writeln!(stderr, "{}", msg!(NotEquivalent, format!("{:?}", input), yes, no));
+        }
+    }
+}
+
+/// Report whether `pattern` and `other` have any match in
+/// common and whether either contains the other.
+fn print_overlap(session: &Session, pattern: &str, other: &str) {
+    let mut stderr = io::stderr();
+    let (a, b) = match languages(session, pattern, other) {
+        Some(l) => l,
+        None => return,
+    };
+
+    let result = compare::overlap(&a, &b);
+    match result.common {
+        Some(ref s) => writeln!(stderr, "{}", msg!(Overlap, format!("{:?}", s))),
+        None => {
+            writeln!(stderr, "{}", msg!(NoOverlap, result.tried))
+        }
+    };
+    for &(sub, sup, witness) in &[(pattern, other, &result.only_a),
+                                      (other, pattern, &result.only_b)] {
+        match *witness {
+            Some(ref s) => {
+                writeln!(stderr, "{}", msg!(NotSubset, sub, sup, format!("{:?}", s)))
+            }
+            None => writeln!(stderr, "{}", msg!(Subset, sub, sup, result.tried)),
+        };
+    }
+}
+
+/// Print `pattern` as expanded by case-insensitive matching,
+/// calling out folds that go beyond plain upper/lower case.
+fn print_fold(pattern: &str) {
+    let mut stderr = io::stderr();
+    let (folded, chars) = match (casefold::fold_pattern(pattern), casefold::pattern_chars(pattern)) {
+        (Ok(f), Ok(c)) => (f, c),
+        (Err(e), _) | (_, Err(e)) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return;
+        }
+    };
+
+    writeln!(stderr, "{}", msg!(FoldExpandsTo, folded));
+    for c in chars {
+        let extra = casefold::surprising_folds(c);
+        if extra.is_empty() {
+            continue;
+        }
+        let extra: Vec<String> = extra.into_iter().map(casefold::describe).collect();
+        writeln!(stderr, "{}", msg!(FoldAlsoMatches, casefold::describe(c), extra.join(", ")));
+    }
+}
+
+/// Print `text` under simple and full case folding, the characters
+/// each of its own is equal to under `(?i)`, and whether the current
+/// regex with `(?i)` matches it either way.
+fn print_casefold(session: &Session, text: &str) {
+    let mut stderr = io::stderr();
+    let full = casefold::full(text);
+    writeln!(stderr, "{}", msg!(CasefoldSimple, format!("{:?}", casefold::simple(text))));
+    writeln!(stderr,
+             "{}",
+             msg!(CasefoldFull, format!("{:?}", full), full.chars().count(), text.chars().count()));
+    let mut seen = HashSet::new();
+    for c in text.chars().filter(|&c| seen.insert(c)) {
+        let others: Vec<String> = casefold::simple_fold(c)
+            .into_iter()
+            .filter(|&f| f != c)
+            .map(casefold::describe)
+            .collect();
+        // Only the characters with surprises: folds beyond plain
+        // upper and lower case, or a full folding (?i) doesn't do
+        let folded = casefold::full(&c.to_string());
+        if casefold::surprising_folds(c).is_empty() &&
+           folded == casefold::simple_char(c).to_string() {
+            continue;
+        }
+        let folded = format!("{:?}", folded);
+        if others.is_empty() {
+            writeln!(stderr, "{}", msg!(CasefoldCharAlone, casefold::describe(c), folded));
+        } else {
+            writeln!(stderr,
+                     "{}",
+                     msg!(CasefoldChar, casefold::describe(c), others.join(", "), folded));
+        }
+    }
+
+    let reg = match session.regex {
+        Some(ref r) => r,
+        None => return,
+    };
+    let folded = match (session.flags | Flags::CASE_INSENSITIVE).build(reg.as_str()) {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    let yes_no = |m: bool| if m { msg!(CasefoldYes) } else { msg!(CasefoldNo) };
+    writeln!(stderr,
+             "{}",
+             msg!(CasefoldRegex,
+                  reg.as_str(),
+                  yes_no(folded.is_match(text)),
+                  yes_no(folded.is_match(&full))));
+}
+
+/// Try `n` random strings and edits of `inputs` on `reg`, and show
+/// the surprising matches with their groups.
+fn print_fuzz(reg: &Regex, inputs: &[String], n: usize) {
+    let mut stderr = io::stderr();
+    let report = fuzz::fuzz(reg, inputs, n, &mut sample::Rng::from_time());
+    writeln!(stderr, "{}", msg!(FuzzTried, report.tried));
+    if report.random.is_empty() && report.edits.is_empty() {
+        writeln!(stderr, "{}", msg!(FuzzNothing));
+        return;
+    }
+    let kinds = [(&report.random, msg!(FuzzRandom)), (&report.edits, msg!(FuzzEdits))];
+    for &(findings, title) in &kinds {
+        if findings.is_empty() {
+            continue;
+        }
+        writeln!(stderr, "{}", title);
+        for f in findings.iter() {
+            let caps = reg.captures(&f.input).unwrap();
+            let m = format!("{:?}", &caps[0]);
+            let input = format!("{:?}", f.input);
+            match f.from {
+                Some(ref from) => {
+                    writeln!(stderr, "{}", msg!(FuzzEditMatch, input, format!("{:?}", from), m))
+                }
+                None => writeln!(stderr, "{}", msg!(FuzzMatch, input, m)),
+            };
+            for (i, g) in caps.iter().enumerate().skip(1) {
+                let g = g.map_or("None".to_owned(), |g| format!("{:?}", g.as_str()));
+                writeln!(stderr, "    {}: {}", i, g);
+            }
+        }
+    }
+}
+
+/// Show how each single edit of `reg` changes what it matches in
+/// the session's inputs.
+fn print_mutations(reg: &Regex, session: &Session) {
+    let mut stderr = io::stderr();
+    let inputs = &session.inputs;
+    if inputs.is_empty() {
+        writeln!(stderr, "{}", msg!(NoInputsToMutate));
+        return;
+    }
+    let result = |r: &Regex, input: &str| {
+        r.find(input).map_or(msg!(MutateNoMatch).to_owned(), |m| format!("{:?}", m.as_str()))
+    };
+    let mutations = mutate::mutations(reg.as_str());
+    writeln!(stderr, "{}", msg!(MutateHeader, mutations.len(), inputs.len()));
+    let mut unchanged = 0;
+    for m in mutations {
+        let mutant = match session.flags_for(&m.pattern, session.flags).build(&m.pattern) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let changed: Vec<&String> = inputs.iter()
+            .filter(|i| reg.find(i).map(|m| m.range()) != mutant.find(i).map(|m| m.range()))
+            .collect();
+        writeln!(stderr, "  {}  ({})", m.pattern, m.reason);
+        match changed.first() {
+            None => {
+                unchanged += 1;
+                writeln!(stderr, "{}", msg!(MutateSame));
+            }
+            Some(i) => {
+                writeln!(stderr,
+                         "{}",
+                         msg!(MutateChanged,
+                              changed.len(),
+                              inputs.len(),
+                              format!("{:?}", i),
+                              result(reg, i),
+                              result(&mutant, i)));
+            }
+        }
+    }
+    if unchanged > 0 {
+        writeln!(stderr, "{}", msg!(MutateUnchanged, unchanged));
+    }
+}
+
+/// Print how long `is_match`, `find` and `captures` take on `input`,
+/// relative to `is_match`.
+fn print_benchapis(reg: &Regex, input: &str) {
+    let mut stderr = io::stderr();
+    let (timings, runs) = bench::apis(reg, input);
+    writeln!(stderr, "{}", msg!(BenchApisHeader, runs));
+    let base = timings[0].1.max(1) as f64;
+    for &(api, ns) in &timings {
+        writeln!(stderr, "  {:<9} {:>9}ns  {:>6.1}x", api, ns, ns as f64 / base);
+    }
+    let (find, captures) = (timings[1].1.max(1), timings[2].1);
+    if reg.captures_len() > 1 && captures >= 2 * find {
+        writeln!(stderr, "{}", msg!(BenchApisCaptures));
+    }
+}
+
+/// Print the versions, features, limits and platform matching
+/// depends on, to paste into a bug report.
+fn print_version(flags: Flags) {
+    let mut stderr = io::stderr();
+    writeln!(stderr, "regtest {}", env!("CARGO_PKG_VERSION"));
+    writeln!(stderr,
+             "regex {} (regex-syntax {})",
+             settings::REGEX_VERSION,
+             settings::REGEX_SYNTAX_VERSION);
+    writeln!(stderr, "{}", msg!(VersionFeatures, settings::REGEX_FEATURES));
+    writeln!(stderr, "{}", msg!(VersionEngine, settings::ENGINE));
+    writeln!(stderr,
+             "{}",
+             msg!(VersionLimits,
+                  flags::SIZE_LIMIT,
+                  flags::DFA_SIZE_LIMIT,
+                  flags::NEST_LIMIT));
+    writeln!(stderr, "{}", msg!(VersionFlags, flags.name()));
+    writeln!(stderr,
+             "{}",
+             msg!(VersionPlatform,
+                  env::consts::OS,
+                  env::consts::ARCH,
+                  env::consts::FAMILY));
+}
+
+/// Print the parts of `new` added, removed or changed from `old`.
+fn print_astdiff(old: &str, new: &str) {
+    let mut stderr = io::stderr();
+    let changes = match astdiff::diff(old, new) {
+        Ok(c) => c,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return;
+        }
+    };
+    if changes.is_empty() {
+        writeln!(stderr, "{}", msg!(DiffSame));
+        return;
+    }
+    let color = diff::color_stderr();
+    let paint = |code: &str, s: String| {
+        if color { format!("\x1b[{}m{}\x1b[0m", code, s) } else { s }
+    };
+    for change in changes {
+        let line = match change {
+            astdiff::Change::Added(kind, text) => paint("32", format!("+ {} {}", kind, text)),
+            astdiff::Change::Removed(kind, text) => paint("31", format!("- {} {}", kind, text)),
+            astdiff::Change::Changed { kind, old, new, equivalent } => {
+                let same = if equivalent { msg!(DiffEquivalent) } else { "" };
+                paint("33", format!("~ {} {} -> {}{}", kind, old, new, same))
+            }
+        };
+        writeln!(stderr, "{}", line);
+    }
+}
+
+/// Say whether each character after the class in `arg` is in it,
+/// with the flags of the session.
+fn member_command(session: &Session, arg: &str) {
+    let mut stderr = io::stderr();
+    let (class, rest) = class::split_class(arg);
+    if class.is_empty() || rest.is_empty() {
+        writeln!(stderr, "{}", msg!(MemberUsage));
+        return;
+    }
+    let members = match class::Members::parse(class, session.flags) {
+        Ok(m) => m,
+        Err(e) => {
+            writeln!(stderr, "{}", e);
+            return;
+        }
+    };
+    for c in rest.split_whitespace().flat_map(props::chars) {
+        if members.contains(c) {
+            writeln!(stderr, "{}", msg!(MemberIn, casefold::describe(c), class));
+        } else {
+            writeln!(stderr, "{}", msg!(MemberOut, casefold::describe(c), class));
+        }
+    }
+}
+
+/// Print the `\p{...}` classes `c` is in, as they're written in a
+/// pattern.
+fn print_props(c: char) {
+    let mut stderr = io::stderr();
+    let props = props::of(c);
+    let class = |name: &str| format!("\\p{{{}}}", name);
+    writeln!(stderr, "{}", casefold::describe(c));
+    if let Some((short, long)) = props.category {
+        let mut groups = vec![class(&short[..1])];
+        if ["Lu", "Ll", "Lt"].contains(&short) {
+            groups.push(class("LC"));
+        }
+        writeln!(stderr, "{}", msg!(PropsCategory, class(short), long, groups.join(" ")));
+    }
+    if let Some(script) = props.script {
+        writeln!(stderr, "{}", msg!(PropsScript, class(script)));
+    }
+    if props.extensions.len() > 1 || props.extensions.first().cloned() != props.script {
+        let scx: Vec<String> =
+            props.extensions.iter().map(|s| class(&format!("scx={}", s))).collect();
+        writeln!(stderr, "{}", msg!(PropsExtensions, scx.join(" ")));
+    }
+    match props.age {
+        Some(age) => writeln!(stderr, "{}", msg!(PropsAge, age, class(&format!("Age={}", age)))),
+        None => writeln!(stderr, "{}", msg!(PropsUnassigned)),
+    };
+    if !props.binary.is_empty() {
+        let binary: Vec<String> = props.binary.iter().map(|p| class(p)).collect();
+        writeln!(stderr, "{}", msg!(PropsBinary, binary.join(" ")));
+    }
+    writeln!(stderr, "{}", msg!(PropsNoBlock));
+}
+
+/// Print the constructs of `pattern` that backtracking engines
+/// handle badly, with an input that shows it.
+fn print_redos(pattern: &str, flags: Flags) {
+    let mut stderr = io::stderr();
+    let risks = match redos::analyze(pattern, flags) {
+        Ok(r) => r,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return;
+        }
+    };
+    if risks.is_empty() {
+        writeln!(stderr, "{}", msg!(RedosNone));
+    }
+    for risk in risks {
+        let construct = &pattern[risk.start..risk.end];
+        match risk.kind {
+            redos::Kind::NestedQuantifier => {
+                writeln!(stderr, "{}", msg!(RedosNested, construct));
+            }
+            redos::Kind::AmbiguousAlternation => {
+                let pump = format!("{:?}", risk.pump);
+                writeln!(stderr, "{}", msg!(RedosAmbiguous, construct, pump));
+            }
+        }
+        match risk.attack {
+            Some(attack) => writeln!(stderr, "{}", msg!(RedosAttack, format!("{:?}", attack))),
+            None => writeln!(stderr, "{}", msg!(RedosNoAttack)),
+        };
+    }
+}
+
+/// Print how far into `pattern`, compiled with `flags`, matching
+/// `input` gets, with spans in `offsets`.
+fn print_why(pattern: &str, flags: Flags, input: &str, offsets: Offsets) {
+    let mut stderr = io::stderr();
+    let d = match why::explain(pattern, flags, input) {
+        Ok(Some(d)) => d,
+        Ok(None) => {
+            writeln!(stderr, "{}", msg!(WhyMatches, format!("{:?}", input)));
+            return;
+        }
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return;
+        }
+    };
+
+    match d.matched {
+        Some((start, end)) => {
+            writeln!(stderr,
+                     "{}",
+                     msg!(WhyPrefixMatches,
+                          format!("{:?}", d.prefix),
+                          format!("{:?}", &input[start..end]),
+                          offsets.span(input, start, end)));
+        }
+        None => {
+            writeln!(stderr, "{}", msg!(WhyNothingMatches));
+        }
+    }
+    writeln!(stderr,
+             "{}",
+             msg!(WhyDiverges, d.offset, format!("{:?}", d.next)));
+    writeln!(stderr, "  {}", highlight::line(pattern));
+    writeln!(stderr, "  {}", highlight::caret(pattern, d.offset));
+}
+
+/// Step through a simulated match of `pattern`, compiled with
+/// `flags`, against `input`, waiting for Enter between steps.
+/// `accessible` spells out the position instead of drawing a
+/// cursor. The final match is reported in `offsets`.
+fn debug_match(pattern: &str, flags: Flags, input: &str, accessible: bool, offsets: Offsets) {
+    let mut stderr = io::stderr();
+    let trace = match debug::trace(pattern, flags, input, DEBUG_STEP_LIMIT) {
+        Ok(t) => t,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            return;
+        }
+    };
+
+    writeln!(stderr, "{}", msg!(DebugIntro, trace.steps.len()));
+    let mut running = false;
+    let total = trace.steps.len();
+    for (i, step) in trace.steps.iter().enumerate() {
+        let what = match step.event {
+            debug::Event::Start => msg!(DebugStart),
+            debug::Event::Advance => msg!(DebugAdvance),
+            debug::Event::Assert => msg!(DebugAssert),
+            debug::Event::Choice => msg!(DebugChoice),
+            debug::Event::Fail => msg!(DebugFail),
+            debug::Event::Backtrack => msg!(DebugBacktrack),
+            debug::Event::Match => msg!(DebugMatch),
+        };
+        if accessible {
+            write!(stderr, "[{}/{}] {}: ", i + 1, total, describe::cursor(input, step.pos));
+            if !step.expr.is_empty() {
+                write!(stderr, "{}: ", step.expr);
+            }
+            write!(stderr, "{}", what);
+        } else {
+            write!(stderr,
+                   "[{}/{}] {}|{}  {}  {}",
+                   i + 1,
+                   total,
+                   input[..step.pos].escape_debug(),
+                   input[step.pos..].escape_debug(),
+                   step.expr,
+                   what);
+        }
+
+        if running {
+            writeln!(stderr);
+            continue;
+        }
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() {
+            return;
+        }
+        match answer.trim() {
+            "q" => return,
+            "c" => running = true,
+            _ => {}
+        }
+    }
+
+    if trace.truncated {
+        writeln!(stderr, "{}", msg!(DebugTruncated, DEBUG_STEP_LIMIT));
+    }
+    match trace.matched {
+        Some((start, end)) if accessible => {
+            writeln!(stderr, "{}", msg!(AccDebugMatched, describe::span(input, start, end)))
+        }
+        Some((start, end)) => {
+            writeln!(stderr,
+                     "{}",
+                     msg!(DebugMatched,
+                          format!("{:?}", &input[start..end]),
+                          offsets.span(input, start, end)))
+        }
+        None => writeln!(stderr, "{}", msg!(DebugNoMatch)),
+    };
+}
+
+/// List corrected versions of a pattern that failed to compile
+/// and let the user pick one with a single key.
+fn pick_suggestion(editor: &mut Editor<()>, pattern: &str) -> Option<String> {
+    let mut stderr = io::stderr();
+    let mut suggestions = suggest::suggest(pattern);
+    suggestions.truncate(9);
+    if suggestions.is_empty() {
+        return None;
+    }
+
+    writeln!(stderr, "{}", msg!(DidYouMean));
+    for (i, s) in suggestions.iter().enumerate() {
+        writeln!(stderr, "  {}) {}  ({})", i + 1, s.pattern, s.reason);
+    }
+    let question = if suggestions.len() == 1 {
+        msg!(UseSuggestion).to_owned()
+    } else {
+        msg!(UseOneSuggestion, suggestions.len())
+    };
+    let answer = editor.readline(&question).ok()?;
+    let i = answer.trim().parse::<usize>().ok()?;
+    suggestions.into_iter().nth(i.checked_sub(1)?).map(|s| s.pattern)
+}
+
+/// Ask whether to match `pattern` literally if it looks like text
+/// rather than a regex.
+fn offer_escape(editor: &mut Editor<()>, pattern: &str) -> bool {
+    let reason = match suggest::looks_literal(pattern) {
+        Some(r) => r,
+        None => return false,
+    };
+    writeln!(io::stderr(), "{}", msg!(LooksLiteral, reason));
+    match editor.readline(msg!(EscapeLiteral)) {
+        Ok(answer) => matches!(answer.trim(), "y" | "Y" | "s" | "S"),
+        Err(_) => false,
+    }
+}
+
+/// Print a section of the syntax reference, or every line
+/// mentioning `topic` if it isn't a section name.
+fn print_cheat(topic: &str, paged: bool) {
+    let mut stderr = Paged::new(pager::Target::Stderr, paged);
+    if topic.is_empty() {
+        let names: Vec<&str> = cheat::SECTIONS.iter().map(|s| s.0).collect();
+        writeln!(stderr, "{}", msg!(CheatTopics, names.join(", ")));
+        writeln!(stderr, "{}", msg!(CheatUsage));
+        return;
+    }
+
+    if let Some((name, text)) = cheat::section(topic) {
+        writeln!(stderr, "{}:\n{}", name, text);
+        return;
+    }
+    let found = cheat::search(topic);
+    if found.is_empty() {
+        writeln!(stderr, "{}", msg!(CheatNothingFound, format!("{:?}", topic)));
+    }
+    for (name, line) in found {
+        writeln!(stderr, "[{}] {}", name, line);
+    }
+}
+
+/// Show a prompt saying "n>" requesting that a regex be input.
+/// If this function returns true, the user will be prompted
+/// to input a regex and if false the program will exit.
+fn regex_prompt(editor: &mut Editor<()>, session: &mut Session) -> bool {
+    // Get stderr up here just for convienience
+    let mut stderr = io::stderr();
+
+    // The pattern being replaced, kept for :undo
+    let previous = session.regex.take();
+    let mut typed = session.preloaded.is_empty();
+    let line = match session.preloaded.pop_front() {
+        Some(pattern) => {
+            session.history.add(editor, &pattern);
+            pattern
+        }
+        None => {
+            // Read the line and add it to history
+            let prefill = session.prefill.take();
+            let line = match read_line(editor, session, msg!(InputPrompt), prefill) {
+                Ok(l) => l,
+                Err(e) => return read_failed(e),
+            };
+            typed = !session.scripted;
+            let line = read_continued(editor, session, line);
+
+            // Process the line against the options menu
+            match options_menu(&line, session) {
+                Action::Continue => {}
+                Action::Reload if session.regex.is_some() => return prompt(editor, session),
+                Action::Reload => {
+                    session.regex = previous;
+                    return true;
+                }
+                Action::ToRegexPrompt | Action::Loop => {
+                    session.regex = previous;
+                    return true;
+                }
+                Action::Exit => return false,
+            }
+            line
+        }
+    };
+
+    // Take the pattern as a fixed string if asked to, or if it
+    // looks like text pasted as it is and the user agrees
+    let literal = session.config.contains(Config::LITERAL);
+    let line = if literal || typed && offer_escape(editor, &line) {
+        regex::escape(&line)
+    } else {
+        line
+    };
+
+    // Get the time for compiling regex
+    let (reg, dur) = match session.compile(&line) {
+        (Ok(r), dur) => (r, dur),
+        (Err(e), _) => {
+            if session.config.contains(Config::VERBOSE_ERRORS) {
+                writeln!(stderr, "{}", msg!(ErrorCompiling, format!("{:?}", e)));
+            } else {
+                writeln!(stderr, "{}", msg!(FailedToCompile));
+                writeln!(stderr, "{}", msg!(TurnOnVerboseErrors));
+            }
+            // Offer a corrected pattern if we can guess one
+            let fixed = if typed { pick_suggestion(editor, &line) } else { None };
+            match fixed.as_ref().map(|f| (f, session.compile(f))) {
+                Some((f, (Ok(r), dur))) => {
+                    session.history.add(editor, f);
+                    (r, dur)
+                }
+                _ => {
+                    session.regex = previous;
+                    return true;
+                }
+            }
+        }
+    };
+
+    // Display the time if the appropriate flag is set
+    if session.config.contains(Config::COMPILE_TIME) {
+        match dur {
+            Some(dur) => writeln!(stderr, "{}", msg!(CompiledIn, nanoseconds(dur))),
+            None => writeln!(stderr, "{}", msg!(CompiledCached)),
+        };
+    }
+
+    // Display a prompt using the compiled regex
+    if previous.is_some() {
+        session.regex = previous;
+        session.remember(true);
+    }
+    session.use_regex(reg);
+    session.last_input = None;
+    session.last_typed = None;
+    prompt(editor, session)
+}
+
+/// Does `line` end in a backslash that isn't escaped?
+fn continues(line: &str) -> bool {
+    line.bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1
+}
+
+/// `first`, joined to the lines after it while they end in `\`. In
+/// ignore-whitespace mode the lines are kept apart, so a `#` comment
+/// ends with its line. Each line goes into the history as typed.
+fn read_continued(editor: &mut Editor<()>, session: &mut Session, first: String) -> String {
+    if first.starts_with(':') {
+        return first;
+    }
+    let verbose = session.flags.contains(Flags::IGNORE_WHITESPACE) || first.starts_with("(?x");
+    let mut pattern = first;
+    while continues(&pattern) {
+        pattern.pop();
+        let line = match read_line(editor, session, msg!(ContinuePrompt), None) {
+            Ok(l) => l,
+            Err(_) => break,
+        };
+        if verbose {
+            pattern.push('\n');
+        }
+        pattern.push_str(&line);
+    }
+    pattern
+}
+
+/// Read a line at `prompt`: the next one of the scripts being run,
+/// or else one typed, which goes into the history. Once a `--batch`
+/// script runs out this is an end of file.
+fn read_line(editor: &mut Editor<()>,
+             session: &mut Session,
+             prompt: &str,
+             prefill: Option<String>)
+             -> rustyline::Result<String> {
+    let mut stderr = io::stderr();
+    session.scripted = false;
+    while let Some(line) = session.script.pop_front() {
+        match line.step {
+            script::Step::Echo(on) => session.echo = on,
+            script::Step::Say(text) => {
+                writeln!(stderr, "{}", text);
+            }
+            script::Step::Line(text) => {
+                if session.echo {
+                    writeln!(stderr, "{}{}", prompt, text);
+                }
+                session.scripted = true;
+                return Ok(text);
+            }
+        }
+    }
+    if session.batch {
+        return Err(ReadlineError::Eof);
+    }
+    let line = match prefill {
+        Some(text) => editor.readline_with_initial(prompt, (&text, "")),
+        None => editor.readline(prompt),
+    };
+    let line = console::line(line)?;
+    session.history.add(editor, &line);
+    Ok(line)
+}
+
+/// What the prompts do when they can't read a line, returning false
+/// to quit. Input ending or Ctrl-C quits as `:q` does, any other
+/// error is reported and exits.
+fn read_failed(e: ReadlineError) -> bool {
+    match e {
+        ReadlineError::Eof | ReadlineError::Interrupted => false,
+        e => {
+            writeln!(io::stderr(), "{}", msg!(CantReadInput, e));
+            process::exit(batch::EXIT_ERROR);
+        }
+    }
+}
+
+/// `dur` in nanoseconds, or milliseconds if that overflows.
+fn nanoseconds(dur: time::Duration) -> i64 {
+    match dur.num_nanoseconds() {
+        Some(x) => x,
+        None => dur.num_milliseconds(),
+    }
+}
+
+/// Print the fields of `input` split on `reg`, along with the
+/// delimiters between them if `delimiters` is set.
+fn print_fields(reg: &Regex, input: &str, limit: usize, delimiters: bool) {
+    let mut stderr = io::stderr();
+    let fields = split::fields(reg, input, limit);
+    writeln!(stderr, "{}", msg!(SplitFields, fields.len()));
+    for (i, f) in fields.iter().enumerate() {
+        writeln!(stderr, "  {}: {:?}", i + 1, f.text);
+        if let (true, Some(d)) = (delimiters, f.delimiter) {
+            writeln!(stderr, "     {}", msg!(SplitDelimiter, format!("{:?}", d)));
+        }
+    }
+}
+
+/// The prompt shown while testing inputs against `reg`.
+fn regex_prompt_text(session: &Session, reg: &Regex) -> String {
+    let mut modes = String::new();
+    let mut steps: Vec<&str> = session.transforms.iter().map(|t| t.name()).collect();
+    if session.normalization != Normalization::None {
+        steps.insert(0, session.normalization.name());
+    }
+    if !steps.is_empty() {
+        modes.push_str(&format!(" [{}]", steps.join(",")));
+    }
+    match session.replace {
+        Some(ref t) if session.replace_limit > 0 => {
+            modes.push_str(&msg!(PromptReplacen, session.replace_limit, t));
+        }
+        Some(ref t) => modes.push_str(&msg!(PromptReplace, t)),
+        None => {}
+    }
+    if session.regex_flags.contains(Flags::CASE_INSENSITIVE) &&
+       !session.flags.contains(Flags::CASE_INSENSITIVE) {
+        modes.push_str(msg!(PromptSmartCase));
+    }
+    match session.split {
+        Some(0) => modes.push_str(msg!(PromptSplit)),
+        Some(n) => modes.push_str(&msg!(PromptSplitn, n)),
+        None => {}
+    }
+    // A pattern typed over several lines shows on one
+    msg!(RegexPrompt, reg.as_str().replace('\n', "⏎"), modes)
+}
+
+// If this returns false, the program with exit.
+// If it returns true, the prompt for a new regex
+// will be shown.
+fn prompt(editor: &mut Editor<()>, session: &mut Session) -> bool {
+    let mut stderr = io::stderr();
+    let mut reg = match session.regex {
+        Some(ref r) => r.clone(),
+        None => return true,
+    };
+    let mut prompt = regex_prompt_text(session, &reg);
+    let mut branches = branch::Branches::new(reg.as_str());
+    if !session.expectations.is_empty() {
+        print_expectations(session, &reg);
+    }
+
+    loop {
+        let line = match read_line(editor, session, &prompt, None) {
+            Ok(l) => l,
+            Err(e) => return read_failed(e),
+        };
+
+        // Test the rest of the line without keeping it anywhere
+        if let Some(rest) = line.strip_prefix(history::PRIVATE_PREFIX) {
+            let remembered = session.inputs.len();
+            test_input(session, &reg, &branches, rest.to_owned());
+            session.inputs.truncate(remembered);
+            session.last_typed = None;
+            session.last_input = None;
+            continue;
+        }
+
+        // Enable menu
+        match options_menu(&line, session) {
+            Action::Exit => return false,
+            Action::Loop => continue,
+            Action::Reload => {
+                if let Err(e) = session.recompile() {
+                    writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                }
+                reg = match session.regex {
+                    Some(ref r) => r.clone(),
+                    None => return true,
+                };
+                prompt = regex_prompt_text(session, &reg);
+                branches = branch::Branches::new(reg.as_str());
+                if let Some(typed) = session.last_typed.clone() {
+                    writeln!(stderr, "{}", msg!(Retesting, format!("{:?}", typed)));
+                    test_input(session, &reg, &branches, typed);
+                }
+            }
+            Action::ToRegexPrompt => return true,
+            // Not a command so test it against the regex
+            Action::Continue => test_input(session, &reg, &branches, line),
+        }
+    }
+}
+
+/// Test `typed` against `reg` and show the results as the session's
+/// options say.
+fn test_input(session: &mut Session,
+              reg: &Regex,
+              branches: &Option<branch::Branches>,
+              typed: String) {
+    let mut stderr = io::stderr();
+    let line = if session.transforms.is_empty() &&
+                  session.normalization == Normalization::None {
+        typed.clone()
+    } else {
+        let normalized = session.normalization.apply(&typed);
+        let transformed = transform::apply_all(&session.transforms, &normalized);
+        if transformed != typed {
+            writeln!(stderr, "{}", msg!(Transformed, format!("{:?}", transformed)));
+        }
+        transformed
+    };
+    let accessible = session.config.contains(Config::ACCESSIBLE);
+    // Are we dealing with capture groups?
+    if session.config.contains(Config::CAPTURE_GROUPS) && !accessible {
+        let caps = reg.captures_iter(&line).enumerate();
+        writeln!(stderr, "{}", msg!(Captures));
+        for (i, outer_cap) in caps {
+            for (j, cap) in outer_cap.iter().enumerate() {
+                match cap {
+                    Some(c) => {
+                        writeln!(stderr,
+                                 "{}:{}: {} ({})",
+                                 i,
+                                 j,
+                                 c.as_str(),
+                                 session.offsets.span(&line, c.start(), c.end()))
+                    }
+                    None => writeln!(stderr, "{}:{}: None", i, j),
+                };
+            }
+        }
+    } else if reg.is_match(&line) {
+        writeln!(stderr, "{}", msg!(Matched));
+        // Spell out every match and group, or mark the
+        // matches under the input
+        if accessible {
+            for (i, caps) in reg.captures_iter(&line).enumerate() {
+                let text = describe::captures(reg, &line, &caps);
+                writeln!(stderr, "{}", msg!(AccNthMatch, i + 1, text));
+            }
+        } else if let Some(context) = session.segments {
+            let spans: Vec<_> = reg.find_iter(&line).map(|m| (m.start(), m.end())).collect();
+            for w in window::windows(&line, &spans, context, false) {
+                writeln!(stderr, "  {}: {}", w.column, w.text);
+            }
+        } else {
+            let spans: Vec<_> = reg.find_iter(&line).map(|m| (m.start(), m.end())).collect();
+            // Fit the input to the terminal, past the indent
+            match term::size() {
+                Some((columns, _)) => {
+                    let columns = columns.saturating_sub(2);
+                    for (text, marks) in highlight::fit(&line, &spans, columns, session.long_lines) {
+                        writeln!(stderr, "  {}", text);
+                        if !marks.is_empty() {
+                            writeln!(stderr, "  {}", marks);
+                        }
+                    }
+                }
+                None => {
+                    writeln!(stderr, "  {}", highlight::line(&line));
+                    writeln!(stderr, "  {}", highlight::underline(&line, &spans));
+                }
+            }
+        }
+        // Say which arm of a top-level alternation was taken
+        if let Some(ref b) = branches {
+            if let Some((i, text)) = b.which(&line) {
+                writeln!(stderr, "{}", msg!(ViaBranch, i + 1, b.len(), text));
+            }
+        }
+    } else {
+        writeln!(stderr, "{}", msg!(FailedToMatch));
+        writeln!(stderr, "{}", msg!(UseWhy));
+        if !session.inputs.iter().any(|i| reg.is_match(i)) {
+            let mut inputs = session.inputs.clone();
+            inputs.push(line.clone());
+            print_relaxations(reg, session.regex_flags, &inputs);
+        }
+    }
+    if let Some(limit) = session.split {
+        let delimiters = session.config.contains(Config::SPLIT_DELIMITERS);
+        print_fields(reg, &line, limit, delimiters);
+    }
+    if let Some(ref t) = session.replace {
+        let opts = batch::ReplaceOptions {
+            limit: session.replace_limit,
+            preview: session.config.contains(Config::REPLACE_PREVIEW),
+            offsets: session.offsets,
+            color: !accessible && diff::color_stderr(),
+            stats: false,
+        };
+        batch::write_replacement(&mut stderr, reg, &line, t, &opts);
+    }
+    if !session.pipe.is_empty() {
+        print_pipeline(reg, &session.pipe, &line);
+    }
+    if !session.highlights.is_empty() {
+        let regexes: Vec<&Regex> = Some(reg).into_iter().chain(session.highlights.iter()).collect();
+        let color = !accessible && diff::color_stderr();
+        writeln!(stderr, "  {}", highlight::several(&line, &regexes, color));
+    }
+    if !session.inputs.contains(&line) {
+        session.inputs.push(line.clone());
+    }
+    session.last_input = Some(line);
+    session.last_typed = Some(typed);
+}
+
+/// Print the looser versions of `reg` that match some of `inputs`,
+/// when `reg` matches none of them.
+fn print_relaxations(reg: &Regex, flags: Flags, inputs: &[String]) {
+    let mut stderr = io::stderr();
+    let mut shown = false;
+    for r in relax::relax(reg.as_str()) {
+        let loose = match flags.build(&r.pattern) {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+        let matched = inputs.iter().filter(|i| loose.is_match(i)).count();
+        if matched == 0 {
+            continue;
+        }
+        if !shown {
+            writeln!(stderr, "{}", msg!(Relaxations));
+            shown = true;
+        }
+        writeln!(stderr,
+                 "  {}  ({}; {})",
+                 r.pattern,
+                 r.reason,
+                 msg!(RelaxationMatches, matched, inputs.len()));
+    }
+}
+
+/// Determine and load the history file erroring out
+/// upon failure.
+///
+/// # Notes
+/// Failure within this function is non-fatal. It will
+/// not panic and only show a warning to the user.
+fn with_history_file<F>(mut f: F)
+    where F: FnMut(&PathBuf)
+{
+    if let Some(path) = env::var_os(settings::ENV_HISTORY) {
+        debug!("history file {:?} from {}", path, settings::ENV_HISTORY);
+        f(&PathBuf::from(path));
+        return;
+    }
+    let mut path = match app_dir(AppDataType::UserData) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("{}", msg!(HistoryFileError, format!("{:?}", e)));
+            return;
+        }
+    };
+    path.push("history");
+    f(&path);
+}
+
+/// The directory regtest keeps `kind` of data in. On Windows,
+/// `%APPDATA%` is used directly when the shell can't say where the
+/// roaming profile is, as happens for services and some remote
+/// sessions.
+fn app_dir(kind: AppDataType) -> Result<PathBuf, AppDirsError> {
+    let err = match app_root(kind, &APP_INFO) {
+        Ok(p) => return Ok(p),
+        Err(e) => e,
+    };
+    if !cfg!(windows) {
+        return Err(err);
+    }
+    let appdata = match env::var_os("APPDATA").filter(|a| !a.is_empty()) {
+        Some(a) => PathBuf::from(a),
+        None => return Err(err),
+    };
+    let path = appdata.join(APP_INFO.author).join(APP_INFO.name);
+    debug!("app_dirs failed with {:?}, using {:?}", err, path);
+    fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// Where the config file lives.
+fn config_file() -> Option<PathBuf> {
+    let mut path = app_dir(AppDataType::UserConfig).ok()?;
+    path.push("config");
+    Some(path)
+}
+
+/// Where workspace `name` is kept.
+fn workspace_dir(name: &str) -> Option<PathBuf> {
+    let mut path = app_dir(AppDataType::UserData).ok()?;
+    path.push("workspaces");
+    path.push(name);
+    Some(path)
+}
+
+/// What of the session is kept in a workspace.
+fn workspace_contents(session: &Session) -> workspace::Contents {
+    // Only options changed in the session or loaded from the
+    // workspace belong to it
+    let options = session.options()
+        .into_iter()
+        .filter(|&(n, _)| {
+            matches!(session.origins.get(n), Some(&Source::Runtime) | Some(&Source::Workspace))
+        })
+        .map(|(n, v)| (n.to_owned(), v))
+        .collect();
+    workspace::Contents {
+        options,
+        patterns: session.patterns.clone(),
+        current: session.regex.as_ref().map(|r| r.as_str().to_owned()),
+        expectations: session.expectations.clone(),
+    }
+}
+
+/// Write the session to its workspace, if it has one.
+fn save_workspace(session: &Session) {
+    let name = match session.workspace {
+        Some(ref n) if !session.private => n,
+        _ => return,
+    };
+    let dir = match workspace_dir(name) {
+        Some(d) => d,
+        None => return,
+    };
+    if let Err(e) = workspace::save(&dir, &workspace_contents(session)) {
+        writeln!(io::stderr(), "{}", msg!(CantWriteFile, dir.display(), e));
+    }
+}
+
+/// Switch the session to workspace `name`, loading it if it exists
+/// and creating it from the session if it doesn't. Returns false if
+/// it can't be used.
+fn open_workspace(session: &mut Session, name: &str) -> bool {
+    let mut stderr = io::stderr();
+    let dir = match workspace_dir(name) {
+        Some(d) if workspace::valid_name(name) => d,
+        _ => {
+            writeln!(stderr, "{}", msg!(WorkspaceBadName, name));
+            return false;
+        }
+    };
+    if !dir.exists() {
+        session.workspace = Some(name.to_owned());
+        save_workspace(session);
+        writeln!(stderr, "{}", msg!(WorkspaceCreated, name, dir.display()));
+        return true;
+    }
+
+    let contents = match workspace::load(&dir) {
+        Ok(c) => c,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(WorkspaceError, e));
+            return false;
+        }
+    };
+    let names = settings::names();
+    for (option, value) in &contents.options {
+        let result = match names.iter().find(|&&n| n == option) {
+            Some(&n) => session.set_value(n, value, Source::Workspace),
+            None => Err(SetError::Unknown),
+        };
+        if result.is_err() {
+            writeln!(stderr, "{}", msg!(WorkspaceBadOption, option, value));
+        }
+    }
+    session.patterns = contents.patterns;
+    session.expectations = contents.expectations;
+    if let Some(ref p) = contents.current {
+        match session.compile(p).0 {
+            Ok(reg) => session.use_regex(reg),
+            Err(e) => {
+                writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+            }
+        }
+    }
+    session.workspace = Some(name.to_owned());
+    writeln!(stderr,
+             "{}",
+             msg!(WorkspaceOpened, name, session.patterns.len(), session.expectations.len()));
+    true
+}
+
+/// Show the workspace in use, or save it and switch to `name`.
+fn workspace_command(session: &mut Session, name: &str) -> Action {
+    let mut stderr = io::stderr();
+    if name.is_empty() {
+        let dir = session.workspace.as_ref().and_then(|w| workspace_dir(w).map(|d| (w, d)));
+        match dir {
+            Some((w, d)) => {
+                writeln!(stderr, "{}", msg!(WorkspaceInfo, w, d.display()));
+                let suites: Vec<String> = workspace::suites(&d)
+                    .iter()
+                    .filter_map(|p| p.file_name().map(|f| f.to_string_lossy().into_owned()))
+                    .collect();
+                if !suites.is_empty() {
+                    writeln!(stderr, "{}", msg!(WorkspaceSuites, suites.join(", ")));
+                }
+            }
+            None => {
+                writeln!(stderr, "{}", msg!(WorkspaceNone));
+            }
+        }
+        return Action::Loop;
+    }
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let name = match words[..] {
+        ["export", path] => return export_workspace(session, path),
+        ["import", path] => return import_workspace(session, path, None),
+        ["import", path, name] => return import_workspace(session, path, Some(name)),
+        [name] if name != "export" && name != "import" => name,
+        _ => {
+            writeln!(stderr, "{}", msg!(WorkspaceUsage));
+            return Action::Loop;
+        }
+    };
+
+    save_workspace(session);
+    if session.regex.is_some() {
+        session.remember(true);
+    }
+    if open_workspace(session, name) {
+        Action::Reload
+    } else {
+        Action::Loop
+    }
+}
+
+/// Write the session, and the suites of its workspace, to `path` as
+/// a JSON bundle.
+fn export_workspace(session: &Session, path: &str) -> Action {
+    let mut stderr = io::stderr();
+    save_workspace(session);
+    let name = session.workspace.as_ref().map_or("default", |w| &w[..]);
+    let suites = match session.workspace.as_ref().and_then(|w| workspace_dir(w)) {
+        Some(dir) => {
+            match workspace::read_suites(&dir) {
+                Ok(s) => s,
+                Err(e) => {
+                    writeln!(stderr, "{}", msg!(WorkspaceError, e));
+                    return Action::Loop;
+                }
+            }
+        }
+        None => vec![],
+    };
+    let bundle = workspace::Bundle {
+        name: name.to_owned(),
+        contents: workspace_contents(session),
+        suites,
+    };
+    let text = workspace::to_json(&bundle);
+    match File::create(path).and_then(|mut f| f.write_all(text.as_bytes())) {
+        Ok(()) => writeln!(stderr, "{}", msg!(WorkspaceExported, name, path)),
+        Err(e) => writeln!(stderr, "{}", msg!(CantWriteFile, path, e)),
+    };
+    Action::Loop
+}
+
+/// Make a new workspace from the bundle at `path`, named `name` or
+/// as in the bundle, and switch to it.
+fn import_workspace(session: &mut Session, path: &str, name: Option<&str>) -> Action {
+    let mut stderr = io::stderr();
+    let mut text = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut text)) {
+        writeln!(stderr, "{}", msg!(CantOpenFile, path, e));
+        return Action::Loop;
+    }
+    let bundle = match workspace::from_json(&text) {
+        Ok(b) => b,
+        Err(e) => {
+            writeln!(stderr, "{}", msg!(WorkspaceError, format!("{}: {}", path, e)));
+            return Action::Loop;
+        }
+    };
+    let name = name.unwrap_or(&bundle.name);
+    let dir = match workspace_dir(name) {
+        Some(d) if workspace::valid_name(name) => d,
+        _ => {
+            writeln!(stderr, "{}", msg!(WorkspaceBadName, name));
+            return Action::Loop;
+        }
+    };
+    if dir.exists() {
+        writeln!(stderr, "{}", msg!(WorkspaceExists, name));
+        return Action::Loop;
+    }
+    let written = workspace::save(&dir, &bundle.contents)
+        .and_then(|_| workspace::write_suites(&dir, &bundle.suites));
+    if let Err(e) = written {
+        writeln!(stderr, "{}", msg!(CantWriteFile, dir.display(), e));
+        return Action::Loop;
+    }
+
+    save_workspace(session);
+    if session.regex.is_some() {
+        session.remember(true);
+    }
+    if open_workspace(session, name) {
+        Action::Reload
+    } else {
+        Action::Loop
+    }
+}
+
+/// Apply the top-level settings from the config file, warning
+/// about any that can't be used, and keep its profiles.
+fn apply_config_file(session: &mut Session) {
+    let path = match config_file() {
+        Some(p) => p,
+        None => return,
+    };
+    debug!("reading config file {}", path.display());
+    let entries = match settings::load(&path) {
+        Ok(e) => e,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(ConfigFileError, e));
+            return;
+        }
+    };
+
+    let (top, profiles): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| e.profile.is_none());
+    apply_entries(session, &top, Source::ConfigFile);
+    session.profiles = profiles;
+}
+
+/// Apply the options set with `REGTEST_` variables, and check the
+/// other variables hold values that can be used.
+fn apply_environment(session: &mut Session) {
+    let mut stderr = io::stderr();
+    for (var, name, value) in settings::from_env() {
+        debug!("{}={} sets {}", var, value, name);
+        if session.set_value(name, &value, Source::Environment).is_err() {
+            writeln!(stderr, "{}", msg!(EnvBadValue, var, value));
+        }
+    }
+    for (var, value) in settings::env_others() {
+        let ok = match var {
+            settings::ENV_ENGINE => value == settings::ENGINE,
+            settings::ENV_COLOR => ["always", "never", "auto"].contains(&&value[..]),
+            settings::ENV_OUTPUT => output::Output::from_name(&value).is_some(),
+            _ => true,
+        };
+        if !ok {
+            writeln!(stderr, "{}", msg!(EnvBadValue, var, value));
+        }
+    }
+}
+
+/// Apply config file `entries`, recording `source` as their origin.
+fn apply_entries(session: &mut Session, entries: &[settings::Entry], source: Source) {
+    let mut stderr = io::stderr();
+    let path = config_file().map(|p| p.display().to_string()).unwrap_or_default();
+    let names = settings::names();
+    for e in entries {
+        let result = match names.iter().find(|&&n| n == e.name) {
+            Some(&name) => session.set_value(name, &e.value, source),
+            None => Err(SetError::Unknown),
+        };
+        match result {
+            Ok(()) => {}
+            Err(SetError::BadValue) => {
+                writeln!(stderr, "{}", msg!(ConfigBadValue, path, e.line, e.value, e.name));
+            }
+            Err(SetError::Unknown) => {
+                writeln!(stderr, "{}", msg!(ConfigUnknownOption, path, e.line, e.name));
+            }
+        }
+    }
+}
+
+/// Apply the settings of profile `name`. Returns false if there's
+/// no such profile.
+fn use_profile(session: &mut Session, name: &str) -> bool {
+    let entries: Vec<settings::Entry> = session.profiles
+        .iter()
+        .filter(|e| e.profile.as_ref().is_some_and(|p| p == name))
+        .cloned()
+        .collect();
+    if entries.is_empty() {
+        return false;
+    }
+    apply_entries(session, &entries, Source::Profile);
+    session.profile = Some(name.to_owned());
+    true
+}
+
+/// Apply profile `name` during the session, or list the profiles
+/// if it's empty.
+fn profile_command(session: &mut Session, name: &str) -> Action {
+    let mut stderr = io::stderr();
+    if name.is_empty() {
+        let mut names: Vec<&str> = vec![];
+        for e in &session.profiles {
+            let p = e.profile.as_ref().map(|p| &p[..]).unwrap_or("");
+            if !names.contains(&p) {
+                names.push(p);
+            }
+        }
+        if names.is_empty() {
+            let path = config_file().map(|p| p.display().to_string()).unwrap_or_default();
+            writeln!(stderr, "{}", msg!(NoProfiles, path));
+        } else {
+            writeln!(stderr, "{}", msg!(Profiles, names.join(", ")));
+        }
+        return Action::Loop;
+    }
+
+    session.remember(false);
+    if use_profile(session, name) {
+        writeln!(stderr, "{}", msg!(UsingProfile, name));
+        Action::Reload
+    } else {
+        session.undo.pop();
+        writeln!(stderr, "{}", msg!(UnknownProfile, name));
+        Action::Loop
+    }
+}
+
+/// The `--format` option of batch commands.
+fn format_arg() -> Arg<'static, 'static> {
+    Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .help("Write this template for each match, e.g. '{file}:{line}: {match} [{group.name}]'")
+}
+
+/// Parse the `--format` template of `m`, exiting if it's invalid.
+fn parse_format(m: &ArgMatches) -> Option<Vec<format::Piece>> {
+    let template = m.value_of("format")?;
+    match format::parse(template) {
+        Ok(pieces) => Some(pieces),
+        Err(e) => {
+            writeln!(io::stderr(), "{}", e);
+            process::exit(batch::EXIT_ERROR);
+        }
+    }
+}
+
+/// The `--output` option of batch commands.
+fn output_arg() -> Arg<'static, 'static> {
+    Arg::with_name("output")
+        .long("output")
+        .takes_value(true)
+        .possible_values(&["text", "json", "yaml", "jsonl", "csv", "tsv", "psv", "json-compact"])
+        .help("Write each input with its matches and groups as a document")
+}
+
+/// The `--output` picked in `m`, or in `REGTEST_OUTPUT` if nothing
+/// else was asked for, text by default.
+fn output_of(m: &ArgMatches) -> output::Output {
+    let other = ["format", "only-matching", "top", "window", "bucket", "follow"]
+        .iter()
+        .any(|a| m.is_present(a));
+    let env = if other { None } else { env::var(settings::ENV_OUTPUT).ok() };
+    m.value_of("output")
+        .or(env.as_ref().map(|o| &o[..]))
+        .and_then(output::Output::from_name)
+        .unwrap_or(output::Output::Text)
+}
+
+/// The time range given to `grep`, exiting if a time can't be read.
+fn time_filter(m: &ArgMatches) -> Option<timestamp::TimeFilter> {
+    let group = m.value_of("time-group")?;
+    let format = m.value_of("time-format").unwrap_or(timestamp::DEFAULT_FORMAT);
+    let time = |name| {
+        m.value_of(name).map(|text| match timestamp::parse(text, format) {
+            Some(s) => s,
+            None => {
+                writeln!(io::stderr(), "{}", msg!(BadTime, text, format));
+                process::exit(batch::EXIT_ERROR);
+            }
+        })
+    };
+    Some(timestamp::TimeFilter {
+        group: group.to_owned(),
+        format: format.to_owned(),
+        since: time("since"),
+        until: time("until"),
+    })
+}
+
+fn null_data_arg() -> Arg<'static, 'static> {
+    Arg::with_name("null-data")
+        .short("z")
+        .long("null-data")
+        .help("Input lines end in NUL instead of a newline, as find -print0 writes them")
+}
+
+fn stats_arg() -> Arg<'static, 'static> {
+    Arg::with_name("stats")
+        .long("stats")
+        .help("Print the matches, lines and files found, bytes searched, binary files skipped \
+               and time taken on stderr at the end")
+}
+
+fn delimiter_arg() -> Arg<'static, 'static> {
+    Arg::with_name("delimiter")
+        .long("delimiter")
+        .takes_value(true)
+        .value_name("TEXT")
+        .conflicts_with("null-data")
+        .help("Input lines end in TEXT, which can use \\n, \\t and \\0. An empty TEXT splits \
+               the input at blank lines")
+}
+
+/// What ends a line of input, from `-z` or `--delimiter`.
+fn delimiter_of(m: &ArgMatches) -> Delimiter {
+    if m.is_present("null-data") {
+        Delimiter::Nul
+    } else {
+        m.value_of("delimiter").map_or(Delimiter::Line, Delimiter::from_arg)
+    }
+}
+
+/// The `--columns` option of batch commands.
+fn columns_arg() -> Arg<'static, 'static> {
+    Arg::with_name("columns")
+        .long("columns")
+        .takes_value(true)
+        .help("Columns for --output csv, tsv and psv, from file, line, col, start, end, match, group.N and \
+               group.name")
+}
+
+/// Parse the `--columns` list of `m`, exiting if it's invalid.
+fn parse_columns(m: &ArgMatches) -> Option<Vec<output::Column>> {
+    let list = m.value_of("columns")?;
+    match output::parse_columns(list) {
+        Ok(columns) => Some(columns),
+        Err(e) => {
+            writeln!(io::stderr(), "{}", e);
+            process::exit(batch::EXIT_ERROR);
+        }
+    }
+}
+
+/// Flags for the interactive session, accepted both by `repl` and
+/// at the top level since `repl` is the default.
+fn repl_args() -> Vec<Arg<'static, 'static>> {
+    vec![Arg::with_name("no-verbose-errors")
+             .long("no-verbose-errors")
+             .help("Disable verbose errors when the regex fails to compile"),
+         Arg::with_name("capture")
+             .short("c")
+             .long("capture")
+             .help("Enable capture group display after matching test"),
+         Arg::with_name("no-compile-time")
+             .long("no-compile-time")
+             .help("Disable showing the amount of time it took to compile the regular expression."),
+         Arg::with_name("workspace")
+             .long("workspace")
+             .takes_value(true)
+             .help("Work in this named workspace, creating it if it doesn't exist"),
+         Arg::with_name("regexp")
+             .short("e")
+             .long("regexp")
+             .takes_value(true)
+             .multiple(true)
+             .number_of_values(1)
+             .help("Start testing this pattern right away. May be given several times, \
+                    :b moves on to the next one"),
+         Arg::with_name("repl")
+             .long("repl")
+             .help("Test regexes interactively even if stdin or stdout isn't a terminal"),
+         Arg::with_name("no-repl")
+             .long("no-repl")
+             .conflicts_with("repl")
+             .help("Print the lines of stdin matching the -e patterns, even at a terminal"),
+         Arg::with_name("batch")
+             .long("batch")
+             .takes_value(true)
+             .value_name("FILE")
+             .conflicts_with("no-repl")
+             .help("Run the lines of FILE as if typed at the prompts, then quit. Lines \
+                    starting with # are comments, @echo off stops showing the lines run")]
+}
+
+/// The engines asked for with `--engines`, or every one built in.
+/// Those known but not built in are left out with a warning.
+fn engines_of(m: &ArgMatches) -> Option<Vec<engines::Engine>> {
+    let list = match m.value_of("engines") {
+        Some(l) => l,
+        None => return Some(engines::ENGINES.iter().filter_map(|&(_, e)| e).collect()),
+    };
+    let mut out = vec![];
+    for name in list.split(',').map(str::trim) {
+        match engines::Engine::from_name(name) {
+            Ok(e) => out.push(e),
+            Err(true) => {
+                writeln!(io::stderr(), "{}", msg!(EngineNotBuilt, name));
+            }
+            Err(false) => {
+                writeln!(io::stderr(), "{}", msg!(UnknownEngine, name));
+                return None;
+            }
+        }
+    }
+    Some(out)
+}
+
+/// The pattern argument shared by the batch subcommands.
+fn pattern_arg() -> Arg<'static, 'static> {
+    Arg::with_name("pattern").required(true)
+}
+
+/// How many strings `:fuzz` tries when not told.
+const DEFAULT_FUZZ: usize = 1000;
+
+/// The command line interface.
+fn cli() -> App<'static, 'static> {
+    App::new("regtest")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author("Lucas Salibian <lucas.salibian@gmail.com>")
+        .about("Test regexes from the command line")
+        .args(&repl_args())
+        .arg(Arg::with_name("accessible")
+            .long("accessible")
+            .global(true)
+            .help("Describe matches and positions in words instead of drawing them"))
+        .arg(Arg::with_name("profile")
+            .long("profile")
+            .takes_value(true)
+            .global(true)
+            .help("Apply a [profile.<name>] section of the config file"))
+        .arg(Arg::with_name("lang")
+            .long("lang")
+            .takes_value(true)
+            .global(true)
+            .possible_values(messages::LANG_CODES)
+            .help("Language of interface messages, defaults to the locale from LANG"))
+        .arg(Arg::with_name("fixed-strings")
+            .short("F")
+            .long("fixed-strings")
+            .global(true)
+            .help("Take patterns as literal text rather than regexes"))
+        .arg(Arg::with_name("smart-case")
+            .short("S")
+            .long("smart-case")
+            .global(true)
+            .help("Ignore case unless the pattern has an uppercase letter"))
+        .arg(Arg::with_name("private")
+            .long("private")
+            .global(true)
+            .help("Don't read or write history, save workspaces or write a --debug file"))
+        .arg(Arg::with_name("debug")
+            .long("debug")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .value_name("FILE")
+            .global(true)
+            .help("Log what regtest decides and how long it takes, to stderr or --debug=FILE"))
+        .arg(Arg::with_name("normalize")
+            .long("normalize")
+            .takes_value(true)
+            .global(true)
+            .possible_values(&["none", "nfc", "nfd"])
+            .help("Unicode normalization applied to inputs before matching"))
+        .subcommand(SubCommand::with_name("repl")
+            .about("Test regexes interactively (the default)")
+            .args(&repl_args()))
+        .subcommand(SubCommand::with_name("tutorial")
+            .about("Learn regular expressions through a series of short lessons")
+            .arg(Arg::with_name("pack")
+                .long("pack")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("FILE")
+                .help("Take the lessons from a TOML or JSON exercise pack instead of the \
+                       built-in ones")))
+        .subcommand(SubCommand::with_name("match")
+            .about("Test one input against a pattern, print the captures and exit")
+            .arg(pattern_arg())
+            .arg(Arg::with_name("input").required(true))
+            .arg(format_arg())
+            .arg(output_arg().conflicts_with("format"))
+            .arg(columns_arg()))
+        .subcommand(SubCommand::with_name("check")
+            .about("Test every line of a file against a pattern")
+            .arg(pattern_arg())
+            .arg(Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .takes_value(true)
+                .help("File to check, defaults to stdin"))
+            .arg(Arg::with_name("failures")
+                .long("failures")
+                .help("Only list the lines that failed to match"))
+            .arg(null_data_arg())
+            .arg(delimiter_arg()))
+        .subcommand(SubCommand::with_name("grep")
+            .about("Print the lines of files matching a pattern")
+            .arg(pattern_arg())
+            .arg(Arg::with_name("files")
+                .multiple(true)
+                .help("Files to search, defaults to stdin. The files in .zip, .tar and .tar.gz \
+                       archives are searched, named like archive.zip!inner/path"))
+            .arg(Arg::with_name("invert")
+                .short("v")
+                .long("invert-match")
+                .help("Print lines that don't match"))
+            .arg(Arg::with_name("line-number")
+                .short("n")
+                .long("line-number")
+                .help("Prefix lines with their line number"))
+            .arg(Arg::with_name("only-matching")
+                .short("o")
+                .long("only-matching")
+                .help("Print only the matched parts of each line"))
+            .arg(Arg::with_name("unique")
+                .long("unique")
+                .requires("only-matching")
+                .help("With -o, print each distinct match once"))
+            .arg(Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(&["position", "length", "text"])
+                .requires("only-matching")
+                .help("With -o, print the matches in this order"))
+            .arg(Arg::with_name("group")
+                .long("group")
+                .takes_value(true)
+                .help("With -o or --top, extract this group, by number or name, instead of the \
+                       whole match"))
+            .arg(Arg::with_name("top")
+                .long("top")
+                .takes_value(true)
+                .value_name("N")
+                .conflicts_with_all(&["invert", "only-matching", "format", "output"])
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Print the N most common matches and how often each was seen"))
+            .arg(Arg::with_name("window")
+                .long("window")
+                .takes_value(true)
+                .value_name("N")
+                .conflicts_with_all(&["invert", "only-matching", "format", "output"])
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Print only N characters either side of each match, with its column"))
+            .arg(Arg::with_name("exec")
+                .long("exec")
+                .takes_value(true)
+                .value_name("COMMAND")
+                .conflicts_with_all(&["invert", "only-matching", "top", "window", "format", "output",
+                                      "bucket"])
+                .validator(|v| hook::Hook::parse(&v).map(|_| ()))
+                .help("Run a command for each match instead of printing it, with placeholders \
+                       like --format's: --exec 'touch {group.name}'"))
+            .arg(Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .takes_value(true)
+                .value_name("N")
+                .requires("exec")
+                .validator(|v| match v.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err(msg!(BadJobs).to_owned()),
+                })
+                .help("Run at most N --exec commands at once [default: 1]"))
+            .arg(Arg::with_name("follow")
+                .long("follow")
+                .requires("files")
+                .conflicts_with_all(&["unique", "sort", "top", "format", "output", "sqlite",
+                                      "time-group", "null-data", "delimiter", "output-file"])
+                .help("Keep reading the file as lines are added to it, like tail -f, and print the \
+                       new ones that match"))
+            .arg(null_data_arg())
+            .arg(delimiter_arg())
+            .arg(format_arg().conflicts_with_all(&["invert", "only-matching"]))
+            .arg(output_arg().conflicts_with_all(&["format", "only-matching"]))
+            .arg(columns_arg())
+            .arg(Arg::with_name("sqlite")
+                .long("sqlite")
+                .takes_value(true)
+                .value_name("DB")
+                .help("Also save the matches and groups to an SQLite database, in builds with \
+                       the sqlite feature"))
+            .arg(Arg::with_name("output-file")
+                .long("output-file")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Write the results to a file as they're found instead of stdout, ending it \
+                       with a footer once the run is complete"))
+            .arg(Arg::with_name("recursive")
+                .short("r")
+                .long("recursive")
+                .help("Search every file under the directories given"))
+            .arg(stats_arg())
+            .arg(Arg::with_name("summary")
+                .long("summary")
+                .conflicts_with_all(&["only-matching", "top", "format", "output", "bucket", "exec",
+                                      "window"])
+                .help("Instead of lines, print how many lines and matches each file had, most \
+                       first, and how many distinct matches there were across files"))
+            .arg(Arg::with_name("max-time")
+                .long("max-time")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .conflicts_with("follow")
+                .validator(|v| match v.parse::<f64>() {
+                    Ok(t) if t > 0.0 => Ok(()),
+                    _ => Err(format!("{} isn't a positive number of seconds", v)),
+                })
+                .help("Stop reading after this many seconds, saying how far the scan got. With \
+                       --checkpoint it can be carried on with --resume"))
+            .arg(Arg::with_name("checkpoint")
+                .long("checkpoint")
+                .takes_value(true)
+                .value_name("FILE")
+                .requires("files")
+                .conflicts_with_all(&["unique", "sort", "top", "bucket", "follow"])
+                .help("Save how far the scan got to a JSON file every few seconds, to pick it up \
+                       with --resume if it's interrupted"))
+            .arg(Arg::with_name("resume")
+                .long("resume")
+                .requires("checkpoint")
+                .help("Carry on from where the --checkpoint file says the scan stopped, adding to \
+                       --output-file"))
+            .arg(Arg::with_name("time-group")
+                .long("time-group")
+                .takes_value(true)
+                .conflicts_with("invert")
+                .help("Read the time of each line from this group, by number or name"))
+            .arg(Arg::with_name("time-format")
+                .long("time-format")
+                .takes_value(true)
+                .requires("time-group")
+                .help("How --time-group, --since and --until are written, in strftime syntax \
+                       [default: %Y-%m-%dT%H:%M:%S]"))
+            .arg(Arg::with_name("since")
+                .long("since")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("time-group")
+                .help("Only keep lines from this time on"))
+            .arg(Arg::with_name("until")
+                .long("until")
+                .takes_value(true)
+                .value_name("TIME")
+                .requires("time-group")
+                .help("Only keep lines from before this time"))
+            .arg(Arg::with_name("bucket")
+                .long("bucket")
+                .takes_value(true)
+                .possible_values(&["minute", "hour"])
+                .requires("time-group")
+                .conflicts_with_all(&["only-matching", "top", "format", "output"])
+                .help("Print how many lines were kept per minute or hour")))
+        .subcommand(SubCommand::with_name("highlight")
+            .about("Show the matches of several patterns in a file, each in its own color")
+            .arg(Arg::with_name("regexp")
+                .short("e")
+                .long("regexp")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(true)
+                .help("A pattern to highlight, may be given more than once"))
+            .arg(Arg::with_name("file").help("File to read, defaults to stdin")))
+        .subcommand(SubCommand::with_name("replace")
+            .about("Replace every match in each line of a file")
+            .arg(pattern_arg())
+            .arg(Arg::with_name("template")
+                .required(true)
+                .help("Replacement, may refer to groups with $1 or ${name}"))
+            .arg(Arg::with_name("file").help("File to read, defaults to stdin"))
+            .arg(Arg::with_name("max-replacements")
+                .short("m")
+                .long("max-replacements")
+                .takes_value(true)
+                .default_value("0")
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Replace at most this many matches per line, 0 for all"))
+            .arg(stats_arg())
+            .arg(Arg::with_name("preview")
+                .long("preview")
+                .help("Explain on stderr how each group reference was filled in")))
+        .subcommand(SubCommand::with_name("test")
+            .about("Run suite files of patterns and the inputs they must (not) match")
+            .arg(Arg::with_name("suites")
+                .required(true)
+                .multiple(true)))
+        .subcommand(SubCommand::with_name("verify")
+            .about("Run every .suite file under a directory, for use in CI")
+            .arg(Arg::with_name("dir").required(true))
+            .arg(Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .takes_value(true)
+                .default_value("1")
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Run suites on this many threads")))
+        .subcommand(SubCommand::with_name("grade")
+            .about("Score each student's pattern in a directory against a spec of inputs")
+            .arg(Arg::with_name("spec")
+                .long("spec")
+                .takes_value(true)
+                .required(true)
+                .help("TOML file with the title, flags, and match and nomatch arrays of inputs"))
+            .arg(Arg::with_name("submissions")
+                .required(true)
+                .help("Directory with one file per student holding their pattern")))
+        .subcommand(SubCommand::with_name("bench")
+            .about("Time compiling a pattern and searching a file with it")
+            .arg(pattern_arg())
+            .arg(Arg::with_name("file").help("File to search, defaults to stdin"))
+            .arg(Arg::with_name("iterations")
+                .short("i")
+                .long("iterations")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|v| v.parse::<u32>().map(|_| ()).map_err(|e| e.to_string())))
+            .arg(Arg::with_name("api")
+                .long("api")
+                .takes_value(true)
+                .default_value("find")
+                .possible_values(&["find", "captures", "locations"])
+                .help("Search with find_iter, captures_iter, or captures_read_at into reused \
+                       CaptureLocations")))
+        .subcommand(SubCommand::with_name("infer")
+            .about("Find small patterns matching every line of one file and none of another")
+            .arg(Arg::with_name("match")
+                .long("match")
+                .takes_value(true)
+                .required(true)
+                .help("Lines the pattern must match"))
+            .arg(Arg::with_name("no-match")
+                .long("no-match")
+                .takes_value(true)
+                .help("Lines the pattern must not match"))
+            .arg(Arg::with_name("count")
+                .short("n")
+                .long("count")
+                .takes_value(true)
+                .default_value("5")
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Print at most this many patterns")))
+        .subcommand(SubCommand::with_name("fuzz-engines")
+            .about("Look for random patterns and inputs the matchers built in disagree on")
+            .arg(Arg::with_name("count")
+                .short("n")
+                .long("count")
+                .takes_value(true)
+                .default_value("500")
+                .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("How many patterns to try, each on a few inputs"))
+            .arg(Arg::with_name("engines")
+                .long("engines")
+                .takes_value(true)
+                .value_name("LIST")
+                .help("Comma-separated engines to compare, defaults to every one built in: regex, \
+                       regex-bytes, regex-set and regex-shortest"))
+            .arg(Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .help("Seed the random choices, to repeat a run")))
+        .subcommand(SubCommand::with_name("serve")
+            .about("Answer match requests over HTTP")
+            .arg(Arg::with_name("addr")
+                .long("addr")
+                .takes_value(true)
+                .default_value(serve::DEFAULT_ADDR)))
+        .subcommand(SubCommand::with_name("completions")
+            .about("Print a shell completion script")
+            .arg(Arg::with_name("shell")
+                .required(true)
+                .possible_values(&Shell::variants())))
+}
+
+/// Apply the interactive session flags in `matches`.
+fn apply_repl_args(matches: &ArgMatches, session: &mut Session) {
+    // Options given along with the workspace override its own
+    if let Some(name) = matches.value_of("workspace") {
+        if !open_workspace(session, name) {
+            process::exit(batch::EXIT_ERROR);
+        }
+    }
+
+    if matches.is_present("no-verbose-errors") {
+        session.set_flag(Config::VERBOSE_ERRORS, false, Source::CommandLine);
+    }
+
+    if matches.is_present("capture") {
+        session.set_flag(Config::CAPTURE_GROUPS, true, Source::CommandLine);
+    }
+
+    if matches.is_present("no-compile-time") {
+        session.set_flag(Config::COMPILE_TIME, false, Source::CommandLine);
+    }
+
+    if let Some(patterns) = matches.values_of("regexp") {
+        session.preloaded.extend(patterns.map(|p| p.to_owned()));
+    }
+
+    if let Some(path) = matches.value_of("batch") {
+        match script::load(path) {
+            Ok(lines) => session.script.extend(lines),
+            Err(e) => {
+                writeln!(io::stderr(), "{}", e);
+                process::exit(batch::EXIT_ERROR);
+            }
+        }
+        session.batch = true;
+    }
+}
+
+/// Print the lines of stdin matching any of `patterns`, as `grep`
+/// does. Returns the exit status.
+fn run_filter(session: &Session, patterns: &[String], normalization: Normalization) -> i32 {
+    if patterns.is_empty() {
+        writeln!(io::stderr(), "{}", msg!(FilterNoPattern));
+        return batch::EXIT_ERROR;
+    }
+    let pattern = if patterns.len() == 1 {
+        patterns[0].clone()
+    } else {
+        patterns.iter().map(|p| format!("(?:{})", p)).collect::<Vec<_>>().join("|")
+    };
+    let opts = batch::GrepOptions {
+        invert: false,
+        line_numbers: false,
+        only_matching: false,
+        unique: false,
+        sort: None,
+        group: None,
+        top: None,
+        window: None,
+        on_match: None,
+        exec: None,
+        jobs: 1,
+        max_line: session.max_line,
+        max_file: session.max_file,
+        delimiter: Delimiter::Line,
+        normalization,
+        format: None,
+        output: output::Output::Text,
+        columns: None,
+        sqlite: None,
+        time: None,
+        bucket: None,
+        pager: false,
+        output_file: None,
+        recursive: false,
+        checkpoint: None,
+        resume: false,
+        summary: false,
+        stats: false,
+        max_time: None,
+    };
+    batch::run_grep(&pattern, &[], &opts)
+}
+
+/// Run an interactive session, returning when the user quits.
+fn repl(session: &mut Session) {
+    // Initialize the rustline (readline) editor
+    let mut editor = Editor::<()>::new();
+
+    if session.private {
+        writeln!(io::stderr(), "{}", msg!(PrivateMode));
+    } else {
+        let exclude = session.history.exclude().cloned();
+        with_history_file(|path| session.history = History::open(path, &mut editor, exclude.clone()));
+    }
+
+    // Pick up where an opened workspace left off, then enter the
+    // main loop
+    let mut running = session.regex.is_none() || prompt(&mut editor, session);
+    while running {
+        running = regex_prompt(&mut editor, session);
+    }
+
+    save_workspace(session);
+    session.history.save(&mut editor);
+}
+
+fn main() {
+    let mut session = Session::new(Config::default());
+    let matches = cli().get_matches();
+    console::init();
+    session.private = matches.is_present("private");
+    if matches.is_present("debug") {
+        // A private session only logs to stderr
+        let file = matches.value_of("debug").filter(|_| !session.private);
+        if session.private && matches.value_of("debug").is_some() {
+            writeln!(io::stderr(), "{}", msg!(PrivateNoLogFile));
+        }
+        if let Err(e) = logging::init(file) {
+            writeln!(io::stderr(), "{}", msg!(CantSave, e));
+        }
+        debug!("regtest {}, engine {}", env!("CARGO_PKG_VERSION"), settings::ENGINE);
+    }
+
+    // The locale only picks the language when nothing else does
+    if let Some(lang) = messages::Lang::from_env() {
+        session.set_value("lang", lang.code(), Source::Environment);
+    }
+    apply_config_file(&mut session);
+    if let Some(name) = matches.value_of("profile") {
+        if !use_profile(&mut session, name) {
+            writeln!(io::stderr(), "{}", msg!(UnknownProfile, name));
+            process::exit(batch::EXIT_ERROR);
+        }
+    }
+    apply_environment(&mut session);
+
+    if let Some(lang) = matches.value_of("lang") {
+        session.set_value("lang", lang, Source::CommandLine);
+    }
+    if let Some(form) = matches.value_of("normalize") {
+        session.set_value("normalize", form, Source::CommandLine);
+    }
+    let normalization = session.normalization;
+
+    if matches.is_present("fixed-strings") {
+        session.set_flag(Config::LITERAL, true, Source::CommandLine);
+    }
+    if matches.is_present("smart-case") {
+        session.set_flag(Config::SMART_CASE, true, Source::CommandLine);
+    }
+    let literal = session.config.contains(Config::LITERAL);
+    let smart_case = session.config.contains(Config::SMART_CASE);
+    let adjust = |pattern: &str| -> String {
+        let pattern = if literal { regex::escape(pattern) } else { pattern.to_owned() };
+        if smart_case && !flags::has_uppercase(&pattern) {
+            format!("(?i){}", pattern)
+        } else {
+            pattern
+        }
+    };
+    let pattern_of = |m: &ArgMatches| adjust(m.value_of("pattern").unwrap());
+
+    if matches.is_present("accessible") {
+        session.set_flag(Config::ACCESSIBLE, true, Source::CommandLine);
+    }
+    let accessible = session.config.contains(Config::ACCESSIBLE);
+
+    let status = match matches.subcommand() {
+        ("match", Some(m)) => {
+            let format = parse_format(m);
+            batch::run_match(&pattern_of(m),
+                             m.value_of("input").unwrap(),
+                             normalization,
+                             format.as_ref().map(|f| &f[..]),
+                             output_of(m),
+                             parse_columns(m),
+                             accessible)
+        }
+        ("check", Some(m)) => {
+            batch::run_check(&pattern_of(m),
+                             m.value_of("file"),
+                             delimiter_of(m),
+                             normalization,
+                             m.is_present("failures"))
+        }
+        ("grep", Some(m)) => {
+            let files: Vec<&str> = m.values_of("files").map(|v| v.collect()).unwrap_or_default();
+            let opts = batch::GrepOptions {
+                invert: m.is_present("invert"),
+                line_numbers: m.is_present("line-number"),
+                only_matching: m.is_present("only-matching"),
+                unique: m.is_present("unique"),
+                sort: m.value_of("sort").and_then(batch::Sort::from_name),
+                group: m.value_of("group").map(|g| g.to_owned()),
+                top: m.value_of("top").map(|n| n.parse().unwrap()),
+                window: m.value_of("window").map(|n| n.parse().unwrap()),
+                on_match: session.on_match.clone(),
+                exec: m.value_of("exec").map(|c| Hook::parse(c).unwrap()),
+                jobs: m.value_of("jobs").map_or(1, |n| n.parse().unwrap()),
+                max_line: session.max_line,
+                max_file: session.max_file,
+                delimiter: delimiter_of(m),
+                normalization,
+                format: parse_format(m),
+                output: output_of(m),
+                columns: parse_columns(m),
+                sqlite: m.value_of("sqlite").map(|s| s.to_owned()),
+                time: time_filter(m),
+                bucket: m.value_of("bucket").and_then(timestamp::Bucket::from_name),
+                pager: session.config.contains(Config::PAGER),
+                output_file: m.value_of("output-file").map(|f| f.to_owned()),
+                recursive: m.is_present("recursive"),
+                checkpoint: m.value_of("checkpoint").map(|f| f.to_owned()),
+                resume: m.is_present("resume"),
+                summary: m.is_present("summary"),
+                stats: m.is_present("stats"),
+                max_time: m.value_of("max-time").map(|t| t.parse().unwrap()),
+            };
+            if !m.is_present("follow") {
+                batch::run_grep(&pattern_of(m), &files, &opts)
+            } else if files.len() == 1 {
+                batch::run_follow(&pattern_of(m), files[0], &opts)
+            } else {
+                writeln!(io::stderr(), "{}", msg!(FollowOneFile));
+                batch::EXIT_ERROR
+            }
+        }
+        ("highlight", Some(m)) => {
+            let patterns: Vec<String> = m.values_of("regexp")
+                .unwrap()
+                .map(|p| if literal { regex::escape(p) } else { p.to_owned() })
+                .collect();
+            let patterns: Vec<&str> = patterns.iter().map(|p| &p[..]).collect();
+            batch::run_highlight(&patterns, m.value_of("file"), normalization)
+        }
+        ("replace", Some(m)) => {
+            let opts = batch::ReplaceOptions {
+                limit: m.value_of("max-replacements").unwrap().parse().unwrap(),
+                preview: m.is_present("preview"),
+                offsets: Offsets::Bytes,
+                color: !accessible && diff::color_stderr(),
+                stats: m.is_present("stats"),
+            };
+            batch::run_replace(&pattern_of(m),
+                               m.value_of("template").unwrap(),
+                               m.value_of("file"),
+                               normalization,
+                               &opts)
+        }
+        ("test", Some(m)) => {
+            let suites: Vec<&str> = m.values_of("suites").unwrap().collect();
+            batch::run_test(&suites)
+        }
+        ("verify", Some(m)) => {
+            batch::run_verify(m.value_of("dir").unwrap(),
+                              m.value_of("jobs").unwrap().parse().unwrap())
+        }
+        ("grade", Some(m)) => {
+            batch::run_grade(m.value_of("spec").unwrap(), m.value_of("submissions").unwrap())
+        }
+        ("bench", Some(m)) => {
+            batch::run_bench(&pattern_of(m),
+                             m.value_of("file"),
+                             m.value_of("iterations").unwrap().parse().unwrap(),
+                             bench::Api::from_name(m.value_of("api").unwrap()).unwrap(),
+                             session.max_file)
+        }
+        ("infer", Some(m)) => {
+            batch::run_infer(m.value_of("match").unwrap(),
+                             m.value_of("no-match"),
+                             m.value_of("count").unwrap().parse().unwrap())
+        }
+        ("fuzz-engines", Some(m)) => {
+            match engines_of(m) {
+                Some(list) => {
+                    batch::run_fuzz_engines(&list,
+                                            m.value_of("count").unwrap().parse().unwrap(),
+                                            m.value_of("seed").map(|s| s.parse().unwrap()))
+                }
+                None => batch::EXIT_ERROR,
+            }
+        }
+        ("serve", Some(m)) => serve::run(m.value_of("addr").unwrap(), accessible),
+        ("completions", Some(m)) => {
+            let shell = m.value_of("shell").unwrap().parse::<Shell>().unwrap();
+            cli().gen_completions_to("regtest", shell, &mut io::stdout());
+            batch::EXIT_MATCH
+        }
+        ("tutorial", Some(m)) => {
+            let lessons = match m.values_of("pack") {
+                None => tutorial::builtin_lessons(),
+                Some(paths) => {
+                    let mut lessons = vec![];
+                    for path in paths {
+                        match tutorial::Pack::load(path) {
+                            Ok(pack) => {
+                                writeln!(io::stderr(),
+                                         "{}",
+                                         msg!(TutorialPack, pack.title, pack.lessons.len()));
+                                lessons.extend(pack.lessons);
+                            }
+                            Err(e) => {
+                                writeln!(io::stderr(), "{}", e);
+                                process::exit(batch::EXIT_ERROR);
+                            }
+                        }
+                    }
+                    lessons
+                }
+            };
+            let mut editor = Editor::<()>::new();
+            let exclude = session.history.exclude().cloned();
+            let mut history = History::none();
+            if !session.private {
+                with_history_file(|path| history = History::open(path, &mut editor, exclude.clone()));
+            }
+            tutorial::run(&mut editor, &mut history, &lessons);
+            history.save(&mut editor);
+            batch::EXIT_MATCH
+        }
+        (name, sub) => {
+            apply_repl_args(&matches, &mut session);
+            if let Some(m) = sub {
+                apply_repl_args(m, &mut session);
+            }
+            // Asking for the repl subcommand counts as --repl
+            let args = [Some(&matches), sub];
+            let forced = if name == "repl" || session.batch ||
+                            args.iter().flatten().any(|m| m.is_present("repl")) {
+                Some(true)
+            } else if args.iter().flatten().any(|m| m.is_present("no-repl")) {
+                Some(false)
+            } else {
+                None
+            };
+            match Mode::detect(forced) {
+                Mode::Filter => {
+                    let patterns: Vec<String> =
+                        session.preloaded.iter().map(|p| adjust(p)).collect();
+                    run_filter(&session, &patterns, normalization)
+                }
+                mode => {
+                    if mode == Mode::Machine {
+                        session.config.remove(Config::PAGER | Config::COMPILE_TIME);
+                    }
+                    session.mark_startup();
+                    repl(&mut session);
+                    batch::EXIT_MATCH
+                }
+            }
+        }
+    };
+    process::exit(status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A batch session that will read `script`.
+    fn scripted(script: &str) -> Session {
+        let mut session = Session::new(Config::default());
+        session.script = script::parse("test", script).unwrap().into_iter().collect();
+        session.batch = true;
+        session
+    }
+
+    #[test]
+    fn odd_backslashes_continue_lines() {
+        assert!(continues(r"abc\"));
+        assert!(!continues(r"abc\\"));
+        assert!(continues(r"abc\\\"));
+        assert!(!continues("abc"));
+        assert!(!continues(""));
+    }
+
+    #[test]
+    fn joins_continued_lines() {
+        let mut editor = Editor::<()>::new();
+        let mut session = scripted("@echo off\nb\\\nc\nd\n");
+        let pattern = read_continued(&mut editor, &mut session, r"a\".to_owned());
+        assert_eq!(pattern, "abc");
+        // The next line is left for the prompt
+        assert_eq!(read_line(&mut editor, &mut session, "", None).unwrap(), "d");
+    }
+
+    #[test]
+    fn keeps_lines_apart_in_verbose_mode() {
+        let mut editor = Editor::<()>::new();
+        let mut session = scripted("@echo off\nb # comment\n");
+        let pattern = read_continued(&mut editor, &mut session, r"(?x) a \".to_owned());
+        assert_eq!(pattern, "(?x) a \nb # comment");
+
+        let mut session = scripted("@echo off\nb\n");
+        session.flags.insert(Flags::IGNORE_WHITESPACE);
+        let pattern = read_continued(&mut editor, &mut session, r"a\".to_owned());
+        assert_eq!(pattern, "a\nb");
+    }
+
+    #[test]
+    fn stops_continuing_at_the_end_of_input() {
+        let mut editor = Editor::<()>::new();
+        let mut session = scripted("");
+        assert_eq!(read_continued(&mut editor, &mut session, r"a\".to_owned()), "a");
+    }
+
+    #[test]
+    fn commands_are_never_continued() {
+        let mut editor = Editor::<()>::new();
+        let mut session = scripted("@echo off\nb\n");
+        let command = read_continued(&mut editor, &mut session, r":source x\".to_owned());
+        assert_eq!(command, r":source x\");
+    }
 }
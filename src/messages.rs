@@ -0,0 +1,1265 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! The catalog of user-facing interface messages and their
+//! translations. The language is picked once at startup.
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Look up a message in the current language. Extra arguments
+/// replace the `{}` placeholders in order.
+macro_rules! msg {
+    ($key:ident) => {
+        $crate::messages::text($crate::messages::Msg::$key)
+    };
+    ($key:ident, $($arg:expr),+) => {
+        $crate::messages::fill($crate::messages::Msg::$key,
+                               &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+/// Languages with a translation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Lang {
+    En,
+    Es,
+}
+
+/// Codes accepted by `--lang`.
+pub const LANG_CODES: &[&str] = &["en", "es"];
+
+static LANG: AtomicUsize = AtomicUsize::new(0);
+
+impl Lang {
+    /// Parse a language code or locale name like `es_ES.UTF-8`.
+    pub fn from_code(code: &str) -> Option<Lang> {
+        let code = code.split(['_', '.', '-', '@']).next().unwrap_or("");
+        match &code.to_lowercase()[..] {
+            "en" | "c" | "posix" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            _ => None,
+        }
+    }
+
+    /// The language asked for by the locale environment variables,
+    /// if any.
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|v| env::var(v).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| Lang::from_code(&v))
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+        }
+    }
+}
+
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as usize, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::Es,
+        _ => Lang::En,
+    }
+}
+
+/// Every message shown by the interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Msg {
+    Help,
+    InputPrompt,
+    ContinuePrompt,
+    RegexPrompt,
+    VerboseErrorsOn,
+    VerboseErrorsOff,
+    CompileTimeOn,
+    CompileTimeOff,
+    CaptureGroupsOn,
+    CaptureGroupsOff,
+    ErrorCompiling,
+    FailedToCompile,
+    TurnOnVerboseErrors,
+    CompiledIn,
+    CompiledCached,
+    CacheStats,
+    CacheCleared,
+    CacheUsage,
+    PatternsNone,
+    NoSuchPattern,
+    UseUsage,
+    NoRegexToEdit,
+    UnknownFlag,
+    PipeNone,
+    NoRegexToWrap,
+    WrapUsage,
+    FromWordsUsage,
+    RenameUsage,
+    NoRegexToRename,
+    RenameNoGroup,
+    RenameTaken,
+    RenameTemplate,
+    ClassHelp,
+    ClassPrompt,
+    ClassNoItem,
+    ClassSamples,
+    FromWordsEmpty,
+    FromWordsBuilt,
+    NotWrapped,
+    HighlightNone,
+    HighlightCleared,
+    PipeCleared,
+    PipeHeader,
+    PipeNoMatch,
+    EditorFailed,
+    EditorUnchanged,
+    Captures,
+    Matched,
+    ViaBranch,
+    FailedToMatch,
+    UseWhy,
+    Relaxations,
+    RelaxationMatches,
+    HistoryFileError,
+    NoRegexToLint,
+    NoLintSuggestions,
+    NoRegexToCompare,
+    EquivIdentical,
+    EquivNoDifference,
+    NotEquivalent,
+    Overlap,
+    NoOverlap,
+    NotSubset,
+    Subset,
+    NoRegexToFold,
+    NoRegexToAnalyze,
+    RedosNone,
+    RedosNested,
+    RedosAmbiguous,
+    RedosAttack,
+    RedosNoAttack,
+    FoldExpandsTo,
+    FoldAlsoMatches,
+    NoRegexToExplain,
+    NoInputToExplain,
+    WhyMatches,
+    WhyPrefixMatches,
+    BothOffsets,
+    WhyNothingMatches,
+    WhyDiverges,
+    NoRegexToDebug,
+    NoInputToDebug,
+    DebugIntro,
+    DebugStart,
+    DebugAdvance,
+    DebugAssert,
+    DebugChoice,
+    DebugFail,
+    DebugBacktrack,
+    DebugMatch,
+    DebugTruncated,
+    DebugMatched,
+    DebugNoMatch,
+    AccSpan,
+    AccEmptySpan,
+    AccMatch,
+    AccGroup,
+    AccGroupUnset,
+    AccCursor,
+    AccCursorEnd,
+    AccNthMatch,
+    AccDebugMatched,
+    CantOpenFile,
+    CantReadInput,
+    CheckLineMatched,
+    CheckLineFailed,
+    CheckSummary,
+    SuiteShouldMatch,
+    SuiteShouldNotMatch,
+    SuiteSummary,
+    VerifyNoSuites,
+    InferNothing,
+    FormatUnclosed,
+    FormatUnknown,
+    UnknownColumn,
+    NoSuchGroupColumn,
+    CantSave,
+    NoSqlite,
+    TopHeader,
+    BucketTime,
+    Stats,
+    MaxTimeStopped,
+    SummaryLines,
+    SummaryMatches,
+    SummaryFile,
+    SummaryTotals,
+    BucketCount,
+    NoTimestamp,
+    BadTime,
+    VerifySummary,
+    CheckpointOutput,
+    CheckpointOtherPattern,
+    ScriptUnknownDirective,
+    SourceUsage,
+    GradeNoSubmissions,
+    GradeNoPattern,
+    GradeShouldMatch,
+    GradeShouldNotMatch,
+    GradeSummary,
+    ExpectUsage,
+    ExpectNone,
+    ExpectAdded,
+    ExpectCleared,
+    ExpectSummary,
+    ExpectNoPattern,
+    ExpectSaved,
+    AccExpectMet,
+    AccExpectNotMet,
+    CantWriteFile,
+    WorkspaceBadName,
+    WorkspaceCreated,
+    WorkspaceError,
+    WorkspaceBadOption,
+    WorkspaceOpened,
+    WorkspaceInfo,
+    WorkspaceSuites,
+    WorkspaceNone,
+    WorkspaceUsage,
+    WorkspaceExported,
+    WorkspaceExists,
+    NoRegexToWatch,
+    WatchUsage,
+    WatchEnded,
+    WatchStarted,
+    WatchChanged,
+    FollowOneFile,
+    NoRegexToPaste,
+    CantPaste,
+    PasteEmpty,
+    PasteMatches,
+    BenchCompile,
+    BenchSearch,
+    CantListen,
+    Listening,
+    ServeUsage,
+    NothingToUndo,
+    UndidOptions,
+    UndidPattern,
+    SourceDefault,
+    SourceConfigFile,
+    SourceProfile,
+    SourceWorkspace,
+    SourceEnvironment,
+    SourceCommandLine,
+    SourceRuntime,
+    ConfigBadValue,
+    ConfigUnknownOption,
+    ConfigFileError,
+    ConfigEngine,
+    ConfigEnvVar,
+    ConfigPrecedence,
+    EnvBadValue,
+    HookTrailingBackslash,
+    HookUnclosedQuote,
+    HookEmpty,
+    HookStatus,
+    HookFailed,
+    ExecFailures,
+    PrivateMode,
+    WatchStreaming,
+    ConfirmBigFile,
+    LineTooLong,
+    InputTooBig,
+    PrivateNoLogFile,
+    FilterNoPattern,
+    CasefoldUsage,
+    CasefoldSimple,
+    CasefoldFull,
+    CasefoldChar,
+    CasefoldCharAlone,
+    CasefoldRegex,
+    CasefoldYes,
+    CasefoldNo,
+    PropsUsage,
+    MemberUsage,
+    NoRegexToFuzz,
+    NoRegexToBench,
+    NoInputToBench,
+    BenchApisHeader,
+    BenchApisCaptures,
+    VersionFeatures,
+    VersionEngine,
+    VersionLimits,
+    VersionFlags,
+    VersionPlatform,
+    NoRegexToDiff,
+    DiffUsage,
+    DiffSame,
+    DiffEquivalent,
+    NoRegexToMutate,
+    NoInputsToMutate,
+    MutateHeader,
+    MutateSame,
+    MutateChanged,
+    MutateNoMatch,
+    MutateUnchanged,
+    EnginesTooFew,
+    EnginesFuzzing,
+    EnginesAgree,
+    EnginesCase,
+    EnginesDisagree,
+    EngineRejected,
+    EngineNoMatch,
+    EngineMatched,
+    EngineNotBuilt,
+    UnknownEngine,
+    FuzzUsage,
+    FuzzTried,
+    FuzzNothing,
+    FuzzRandom,
+    FuzzEdits,
+    FuzzMatch,
+    FuzzEditMatch,
+    MemberIn,
+    MemberOut,
+    NotAClass,
+    PropsCategory,
+    PropsScript,
+    PropsExtensions,
+    PropsAge,
+    PropsUnassigned,
+    PropsBinary,
+    PropsNoBlock,
+    BadJobs,
+    ConfigPattern,
+    ConfigNoPattern,
+    ConfigFlags,
+    ConfigNoFlags,
+    ConfigFile,
+    ResetUnknown,
+    SetBadValue,
+    ResetChanged,
+    ResetPattern,
+    ResetNothing,
+    UnknownProfile,
+    NoProfiles,
+    Profiles,
+    UsingProfile,
+    ConfigProfile,
+    PromptReplace,
+    PromptReplacen,
+    PromptSplit,
+    PromptSmartCase,
+    PromptSplitn,
+    BadSplitLimit,
+    SplitOff,
+    SplitFields,
+    SplitDelimiter,
+    DelimitersOn,
+    DelimitersOff,
+    BadReplaceLimit,
+    ReplaceOff,
+    Replaced,
+    ReplacedFrom,
+    PreviewOn,
+    PreviewOff,
+    PreviewMatch,
+    PreviewRef,
+    PreviewUnset,
+    PreviewMissing,
+    TransformsOff,
+    UnknownTransform,
+    Transformed,
+    Retesting,
+    DidYouMean,
+    LooksLiteral,
+    EscapeLiteral,
+    UseSuggestion,
+    UseOneSuggestion,
+    CheatTopics,
+    CheatUsage,
+    CheatNothingFound,
+    TutorialHelp,
+    TutorialWelcome,
+    TutorialLesson,
+    TutorialPack,
+    TutorialSample,
+    TutorialFindList,
+    TutorialFound,
+    TutorialShouldMatchList,
+    TutorialShouldNotMatchList,
+    TutorialPrompt,
+    TutorialHint,
+    TutorialNoMoreHints,
+    TutorialSolution,
+    TutorialCorrect,
+    TutorialShouldMatch,
+    TutorialShouldNotMatch,
+    TutorialNotQuite,
+    TutorialDone,
+}
+
+/// The English and Spanish text of a message.
+fn entry(msg: Msg) -> (&'static str, &'static str) {
+    match msg {
+        Msg::Help => {
+            ("\
+:t - Toggle compile time display
+:g - Toggle capture groups display
+:v - Toggle verbose errors
+:r [template] - Show inputs with matches replaced by template, or stop
+:r/N <template> - Only replace the first N matches
+:preview - Toggle showing how each replacement's groups were filled in
+:split[/N] - Toggle splitting inputs on the regex, into at most N fields
+:delims - Toggle showing the delimiters between split fields
+:transform [names] - Transform inputs before matching (trim, collapse, lower, strip-ansi)
+:lint - Suggest improvements to the current regex
+:equiv <regex> - Check whether <regex> matches the same strings
+:overlap-with <regex> - Look for strings matched by both regexes
+:fold - Show the regex as expanded by case-insensitive matching
+:benchapis [input] - Time is_match, find and captures on the input against each other
+:version - Show versions, features, limits and platform, for bug reports
+:diff <regex> - Show the parts added, removed or changed between the regex and <regex>
+:source <file> - Run the lines of <file> as if typed, skipping # comments
+:mutate - Show how small edits to the regex change its results on the inputs tested
+:fuzz [n] - Try n random strings and edits of your inputs, showing surprising matches
+:member <class> <char...> - Tell whether each character is in a class, like [[:punct:]]
+:props <char> - List the \\p{...} classes a character is in, to pick one for a pattern
+:casefold [text] - Show the text under simple and full case folding, and whether (?i) matches it
+:redos - Look for constructs that are slow on backtracking engines
+:why [input] - Show where the last input stopped matching
+:debug [input] - Step through matching the last input
+:cheat [topic] - Show the syntax reference, or search it
+:cache [clear] - Show how often compiled regexes were reused, or forget them
+:set offsets bytes|chars|both - Report spans in bytes, characters or both
+:set long-lines truncate|wrap|full - Show inputs wider than the terminal around the first match, wrapped, or whole
+:set segments N|off - Show only each match with N characters either side, one per line
+:set on-match <command>|off - Run a command for each new match of :watch and grep --follow, with {match} or {group.name} filled in
+:set history-exclude <regex>|off - Never keep lines matching the regex in history, like tokens or passwords
+:private <input> - Test an input without keeping it in history
+:set max-file <size>|off - Ask before reading bigger files into memory, like 256M
+:set max-line <size>|off - Make grep skip longer lines, like 16M
+:set literal on - Take the next patterns typed as literal text, like grep -F
+:set smart-case on - Ignore case unless the pattern has an uppercase letter
+:set pager off - Don't page help and lists longer than the terminal through $PAGER
+:expect match|nomatch <input> - Require a result from every pattern tried
+:expect clear|save <file> - Forget the expectations, or save them as a suite
+:flags [+i -m ...] - Show or change the flags the regex is compiled with
+:pipe [regex|clear] - Run the first group of each match through another regex
+:hl [regex|clear] - Also show the matches of another regex, in its own color
+:wrap word|line - Make the regex match only whole words or lines, until :unwrap
+:fromwords <file> - Make the regex match any of the words in a file, one per line
+:paste - Test the regex on the clipboard, matching across lines
+:watch <file> - Print the lines of a file the regex matches, and the new ones each time it changes
+:class - Build a character class step by step and add it to the regex
+:rename <group> <name> - Rename a group, or name one given by number, in the regex and replacement
+:edit - Go back to the regex prompt with the current regex typed in
+:vi, :editor - Edit the current regex in $EDITOR, one part per line with (?x)
+:patterns - List the patterns compiled this session
+:use !<n> - Switch back to pattern n of :patterns
+:undo - Revert the last option toggle or pattern change
+:set <option> [value] - Set an option, or show its value (e.g. :set normalize nfc)
+:config - Show every option and where its value came from
+:reset [option] - Restore an option, or all of them and the regex, to startup values
+:profile [name] - Apply a profile from the config file, or list them
+:workspace [name] - Save and switch to a named workspace, or show the current one
+:workspace export|import <file> - Share the workspace as a JSON file, or open a shared one
+:h - Print this menu
+:q - Quit
+
+End a regex line with \\ to carry on typing it on the next one. With the x flag the lines
+are kept apart, so # starts a comment to the end of its line.",
+             "\
+:t - Mostrar u ocultar el tiempo de compilación
+:g - Mostrar u ocultar los grupos de captura
+:v - Activar o desactivar los errores detallados
+:r [plantilla] - Mostrar las entradas con reemplazos, o dejar de hacerlo
+:r/N <plantilla> - Reemplazar solo las N primeras coincidencias
+:preview - Mostrar cómo se rellenan los grupos de cada reemplazo
+:split[/N] - Dividir las entradas por la regex, en N campos como mucho
+:delims - Mostrar los delimitadores entre los campos
+:transform [nombres] - Transformar las entradas antes de probarlas (trim, collapse, lower, strip-ansi)
+:lint - Sugerir mejoras para la regex actual
+:equiv <regex> - Comprobar si <regex> reconoce las mismas cadenas
+:overlap-with <regex> - Buscar cadenas reconocidas por ambas regex
+:fold - Mostrar la regex expandida sin distinguir mayúsculas
+:benchapis [entrada] - Comparar el tiempo de is_match, find y captures con la entrada
+:version - Mostrar versiones, características, límites y plataforma, para informar de errores
+:diff <regex> - Mostrar las partes añadidas, quitadas o cambiadas entre la regex y <regex>
+:source <fichero> - Ejecutar las líneas de <fichero> como si se escribieran, sin los comentarios #
+:mutate - Mostrar cómo pequeños cambios a la regex alteran sus resultados con las entradas probadas
+:fuzz [n] - Probar n cadenas al azar y cambios a tus entradas, mostrando coincidencias sorprendentes
+:member <clase> <carácter...> - Indicar si cada carácter está en una clase, como [[:punct:]]
+:props <carácter> - Listar las clases \\p{...} de un carácter, para elegir una en un patrón
+:casefold [texto] - Mostrar el texto con plegado de mayúsculas simple y completo, y si (?i) lo reconoce
+:redos - Buscar construcciones lentas en motores con retroceso
+:why [entrada] - Mostrar dónde dejó de coincidir la última entrada
+:debug [entrada] - Recorrer paso a paso la última entrada
+:cheat [tema] - Mostrar la referencia de sintaxis o buscar en ella
+:cache [clear] - Mostrar cuántas veces se reutilizaron regex compiladas, u olvidarlas
+:set offsets bytes|chars|both - Mostrar los rangos en bytes, caracteres o ambos
+:set long-lines truncate|wrap|full - Mostrar las entradas más anchas que la terminal en torno a la primera coincidencia, partidas o enteras
+:set segments N|off - Mostrar solo cada coincidencia con N caracteres a cada lado, una por línea
+:set on-match <orden>|off - Ejecutar una orden por cada coincidencia nueva de :watch y grep --follow, con {match} o {group.nombre} sustituidos
+:set history-exclude <regex>|off - No guardar nunca en el historial las líneas que reconoce la regex, como tokens o contraseñas
+:private <entrada> - Probar una entrada sin guardarla en el historial
+:set max-file <tamaño>|off - Preguntar antes de cargar en memoria archivos más grandes, como 256M
+:set max-line <tamaño>|off - Hacer que grep omita las líneas más largas, como 16M
+:set literal on - Tomar los siguientes patrones como texto literal, como grep -F
+:set smart-case on - Ignorar mayúsculas salvo si el patrón tiene alguna
+:set pager off - No paginar con $PAGER la ayuda y listas más largas que la terminal
+:expect match|nomatch <entrada> - Exigir un resultado a cada patrón probado
+:expect clear|save <archivo> - Olvidar las expectativas, o guardarlas como suite
+:flags [+i -m ...] - Mostrar o cambiar los flags con que se compila la regex
+:pipe [regex|clear] - Pasar el primer grupo de cada coincidencia a otra regex
+:hl [regex|clear] - Mostrar también las coincidencias de otra regex, en su color
+:wrap word|line - Hacer que la regex solo reconozca palabras o líneas enteras, hasta :unwrap
+:fromwords <archivo> - Hacer que la regex reconozca las palabras de un archivo, una por línea
+:paste - Probar la regex con el portapapeles, con coincidencias entre líneas
+:watch <archivo> - Mostrar las líneas de un archivo que reconoce la regex, y las nuevas cada vez que cambia
+:class - Construir paso a paso una clase de caracteres y añadirla a la regex
+:rename <grupo> <nombre> - Renombrar un grupo, o nombrar uno por su número, en la regex y el reemplazo
+:edit - Volver a pedir la regex con la actual ya escrita
+:vi, :editor - Editar la regex actual en $EDITOR, por líneas con (?x)
+:patterns - Listar los patrones compilados en esta sesión
+:use !<n> - Volver al patrón n de :patterns
+:undo - Deshacer el último cambio de opción o de patrón
+:set <opción> [valor] - Cambiar una opción, o mostrar su valor (p. ej. :set normalize nfc)
+:config - Mostrar cada opción y de dónde viene su valor
+:reset [opción] - Restaurar una opción, o todas y la regex, a sus valores iniciales
+:profile [nombre] - Aplicar un perfil del archivo de configuración, o listarlos
+:workspace [nombre] - Guardar y cambiar a un espacio de trabajo, o mostrar el actual
+:workspace export|import <archivo> - Compartir el espacio de trabajo como JSON, o abrir uno compartido
+:h - Mostrar este menú
+:q - Salir
+
+Termine una línea de la regex con \\ para seguir escribiéndola en la siguiente. Con el flag x
+las líneas se mantienen separadas, así que # empieza un comentario hasta el final de su línea.")
+        }
+        Msg::InputPrompt => ("Input> ", "Entrada> "),
+        Msg::ContinuePrompt => ("  ...> ", "    ...> "),
+        Msg::RegexPrompt => ("Regex({}){}> ", "Regex({}){}> "),
+        Msg::VerboseErrorsOn => ("Verbose errors: on", "Errores detallados: activados"),
+        Msg::VerboseErrorsOff => ("Verbose errors: off", "Errores detallados: desactivados"),
+        Msg::CompileTimeOn => ("Show compile time: on", "Mostrar tiempo de compilación: sí"),
+        Msg::CompileTimeOff => ("Show compile time: off", "Mostrar tiempo de compilación: no"),
+        Msg::CaptureGroupsOn => ("Show capture groups: on", "Mostrar grupos de captura: sí"),
+        Msg::CaptureGroupsOff => ("Show capture groups: off", "Mostrar grupos de captura: no"),
+        Msg::ErrorCompiling => ("Error compiling regex: {}", "Error al compilar la regex: {}"),
+        Msg::FailedToCompile => ("Failed to compile regex", "No se pudo compilar la regex"),
+        Msg::TurnOnVerboseErrors => {
+            ("Turn on verbose errors with :v", "Active los errores detallados con :v")
+        }
+        Msg::CompiledIn => ("Regex compiled in {}ns", "Regex compilada en {}ns"),
+        Msg::CompiledCached => ("Regex reused from the cache", "Regex reutilizada de la caché"),
+        Msg::CacheStats => {
+            ("Cache: {} regexes, {} hits, {} misses, {}ns of compiling saved",
+             "Caché: {} regex, {} aciertos, {} fallos, {}ns de compilación ahorrados")
+        }
+        Msg::CacheCleared => ("Cache cleared", "Caché vaciada"),
+        Msg::CacheUsage => ("Usage: :cache [clear]", "Uso: :cache [clear]"),
+        Msg::PatternsNone => ("No patterns compiled yet", "Aún no se ha compilado ningún patrón"),
+        Msg::NoSuchPattern => ("No pattern !{}, see :patterns", "No hay patrón !{}, ver :patterns"),
+        Msg::UseUsage => ("Usage: :use !<n>", "Uso: :use !<n>"),
+        Msg::NoRegexToEdit => ("No regex to edit", "No hay regex que editar"),
+        Msg::PipeNone => {
+            ("No pipeline, add a stage with :pipe <regex>",
+             "No hay cadena, añade una etapa con :pipe <regex>")
+        }
+        Msg::HighlightNone => {
+            ("No other regexes highlighted, add one with :hl <regex>",
+             "No se resaltan otras regex, añade una con :hl <regex>")
+        }
+        Msg::HighlightCleared => ("Stopped highlighting other regexes", "Ya no se resaltan otras regex"),
+        Msg::NoRegexToWrap => ("No regex to wrap", "No hay regex que envolver"),
+        Msg::WrapUsage => ("Usage: :wrap word|line", "Uso: :wrap word|line"),
+        Msg::ClassHelp => {
+            ("Type a character, a range like a-z, or a class like \\d or \\p{Greek} to add it. \
+              `del <item>` takes it out, `not` negates the class, `done` or an empty line \
+              adds it to the regex and `cancel` drops it.",
+             "Escriba un carácter, un rango como a-z, o una clase como \\d o \\p{Greek} para \
+              añadirlo. `del <elemento>` lo quita, `not` niega la clase, `done` o una línea \
+              vacía la añade a la regex y `cancel` la descarta.")
+        }
+        Msg::ClassPrompt => ("Class {}> ", "Clase {}> "),
+        Msg::ClassNoItem => ("{} isn't in the class", "{} no está en la clase"),
+        Msg::ClassSamples => ("Matches: {}{}", "Coincide con: {}{}"),
+        Msg::RenameUsage => ("Usage: :rename <group> <name>", "Uso: :rename <grupo> <nombre>"),
+        Msg::NoRegexToRename => ("No regex to rename groups in", "No hay regex cuyos grupos renombrar"),
+        Msg::RenameNoGroup => ("No group {}", "No existe el grupo {}"),
+        Msg::RenameTaken => ("There's already a group named {}", "Ya hay un grupo llamado {}"),
+        Msg::RenameTemplate => ("Replacement is now {}", "El reemplazo ahora es {}"),
+        Msg::FromWordsUsage => ("Usage: :fromwords <file>", "Uso: :fromwords <archivo>"),
+        Msg::FromWordsEmpty => ("No words in {}", "No hay palabras en {}"),
+        Msg::FromWordsBuilt => {
+            ("Built a regex from {} words, {} characters long",
+             "Regex construida a partir de {} palabras, de {} caracteres")
+        }
+        Msg::NotWrapped => ("The regex isn't wrapped", "La regex no está envuelta"),
+        Msg::PipeCleared => ("Pipeline cleared", "Cadena vaciada"),
+        Msg::PipeHeader => ("Pipeline:", "Cadena:"),
+        Msg::PipeNoMatch => ("no match", "sin coincidencia"),
+        Msg::UnknownFlag => {
+            ("Unknown flag {}, expected one of {}", "Flag desconocido {}, se esperaba uno de {}")
+        }
+        Msg::EditorFailed => ("Couldn't edit the regex: {}", "No se pudo editar la regex: {}"),
+        Msg::EditorUnchanged => ("The regex wasn't changed", "La regex no ha cambiado"),
+        Msg::Captures => ("Captures:", "Capturas:"),
+        Msg::Matched => ("Matched", "Coincide"),
+        Msg::ViaBranch => ("Via branch {} of {}: {}", "Por la alternativa {} de {}: {}"),
+        Msg::FailedToMatch => ("Failed to match", "No coincide"),
+        Msg::UseWhy => {
+            ("Use :why to see where matching stopped",
+             "Use :why para ver dónde se detuvo la coincidencia")
+        }
+        Msg::Relaxations => {
+            ("Nothing has matched yet. Looser patterns that would have:",
+             "Nada ha coincidido aún. Patrones más flexibles que sí lo harían:")
+        }
+        Msg::RelaxationMatches => ("matches {} of {} inputs", "coincide con {} de {} entradas"),
+        Msg::HistoryFileError => {
+            ("Failed to write history file: {}", "No se pudo escribir el historial: {}")
+        }
+        Msg::NoRegexToLint => ("No regex to lint", "No hay regex que revisar"),
+        Msg::NoLintSuggestions => ("No suggestions", "Sin sugerencias"),
+        Msg::NoRegexToCompare => ("No regex to compare against", "No hay regex con la que comparar"),
+        Msg::EquivIdentical => {
+            ("Equivalent: both patterns compile to the same expression",
+             "Equivalentes: ambos patrones compilan a la misma expresión")
+        }
+        Msg::EquivNoDifference => {
+            ("No difference found in {} samples (not a proof of equivalence)",
+             "Ninguna diferencia en {} muestras (no prueba que sean equivalentes)")
+        }
+        Msg::NotEquivalent => {
+            ("Not equivalent: {} is fully matched by {} but not {}",
+             "No equivalentes: {} coincide por completo con {} pero no con {}")
+        }
+        Msg::Overlap => {
+            ("Overlap: {} is fully matched by both",
+             "Solapamiento: {} coincide por completo con ambas")
+        }
+        Msg::NoOverlap => {
+            ("No overlap: no common input found in {} generated inputs",
+             "Sin solapamiento: ninguna de las {} entradas generadas es común")
+        }
+        Msg::NotSubset => {
+            ("{} is not a subset of {}: {} is only matched by the first",
+             "{} no es un subconjunto de {}: {} solo coincide con la primera")
+        }
+        Msg::Subset => {
+            ("No input matched by {} but not {} found in {} samples",
+             "Ninguna muestra coincide con {} pero no con {} ({} muestras)")
+        }
+        Msg::NoRegexToFold => ("No regex to fold", "No hay regex que expandir"),
+        Msg::FoldExpandsTo => ("(?i) expands to: {}", "(?i) se expande a: {}"),
+        Msg::FoldAlsoMatches => ("  {} also matches {}", "  {} también coincide con {}"),
+        Msg::CasefoldUsage => ("Usage: :casefold <text>", "Uso: :casefold <texto>"),
+        Msg::CasefoldSimple => {
+            ("Simple folding, used by (?i): {}", "Plegado simple, el que usa (?i): {}")
+        }
+        Msg::CasefoldFull => {
+            ("Full folding: {} ({} characters, from {})",
+             "Plegado completo: {} ({} caracteres, de {})")
+        }
+        Msg::CasefoldChar => {
+            ("  {}: (?i) also matches {}, full folding gives {}",
+             "  {}: (?i) también reconoce {}, el plegado completo da {}")
+        }
+        Msg::CasefoldCharAlone => {
+            ("  {}: (?i) matches only itself, full folding gives {}",
+             "  {}: (?i) solo se reconoce a sí mismo, el plegado completo da {}")
+        }
+        Msg::CasefoldRegex => {
+            ("{} with (?i): text {}, full folding {}",
+             "{} con (?i): texto {}, plegado completo {}")
+        }
+        Msg::CasefoldYes => ("matches", "coincide"),
+        Msg::CasefoldNo => ("doesn't match", "no coincide"),
+        Msg::EnginesTooFew => {
+            ("At least two engines are needed to compare",
+             "Se necesitan al menos dos motores para comparar")
+        }
+        Msg::EnginesFuzzing => ("Comparing {} with seed {}", "Comparando {} con la semilla {}"),
+        Msg::EnginesAgree => {
+            ("No disagreements in {} cases", "Ningún desacuerdo en {} casos")
+        }
+        Msg::EnginesCase => ("Pattern {} on input {}:", "Patrón {} con la entrada {}:"),
+        Msg::EnginesDisagree => {
+            ("{} disagreements in {} cases", "{} desacuerdos en {} casos")
+        }
+        Msg::EngineRejected => ("rejects the pattern", "rechaza el patrón"),
+        Msg::EngineNoMatch => ("no match", "sin coincidencia"),
+        Msg::EngineMatched => ("matches", "coincide"),
+        Msg::EngineNotBuilt => {
+            ("{} isn't built into this regtest, leaving it out",
+             "{} no está incluido en este regtest, se deja fuera")
+        }
+        Msg::UnknownEngine => {
+            ("Unknown engine {}, expected regex, regex-bytes, regex-set, regex-shortest, \
+              regex-lite, fancy or pcre2",
+             "Motor desconocido {}, se esperaba regex, regex-bytes, regex-set, regex-shortest, \
+              regex-lite, fancy o pcre2")
+        }
+        Msg::NoRegexToBench => ("No regex to time", "No hay regex que medir"),
+        Msg::NoInputToBench => {
+            ("No input to time, test one first or use :benchapis <input>",
+             "No hay entrada que medir, pruebe una primero o use :benchapis <entrada>")
+        }
+        Msg::BenchApisHeader => ("Mean of {} runs:", "Media de {} ejecuciones:"),
+        Msg::BenchApisCaptures => {
+            ("Extracting groups is most of the cost: use find, or (?:...) for groups you \
+              don't read",
+             "Extraer los grupos es la mayor parte del coste: use find, o (?:...) para los \
+              grupos que no lea")
+        }
+        Msg::VersionFeatures => ("features: {}", "características: {}"),
+        Msg::VersionEngine => ("engine: {}", "motor: {}"),
+        Msg::VersionLimits => {
+            ("limits: size {}, dfa size {}, nest {}",
+             "límites: tamaño {}, tamaño del dfa {}, anidamiento {}")
+        }
+        Msg::VersionFlags => ("flags: {}", "flags: {}"),
+        Msg::VersionPlatform => ("platform: {} {} ({})", "plataforma: {} {} ({})"),
+        Msg::NoRegexToDiff => ("No regex to compare", "No hay regex que comparar"),
+        Msg::DiffUsage => ("Usage: :diff <regex>", "Uso: :diff <regex>"),
+        Msg::DiffSame => {
+            ("Both regexes have the same structure",
+             "Ambas regex tienen la misma estructura")
+        }
+        Msg::DiffEquivalent => ("  (same meaning)", "  (mismo significado)"),
+        Msg::NoRegexToMutate => ("No regex to mutate", "No hay regex que mutar"),
+        Msg::NoInputsToMutate => {
+            ("Test some inputs first, :mutate compares results on them",
+             "Prueba algunas entradas primero, :mutate compara los resultados con ellas")
+        }
+        Msg::MutateHeader => {
+            ("{} edits of the regex, tried on {} inputs:",
+             "{} cambios a la regex, probados con {} entradas:")
+        }
+        Msg::MutateSame => ("      no change", "      sin cambios"),
+        Msg::MutateChanged => {
+            ("      {} of {} inputs change, like {}: {} becomes {}",
+             "      cambian {} de {} entradas, como {}: {} pasa a {}")
+        }
+        Msg::MutateNoMatch => ("no match", "sin coincidencia"),
+        Msg::MutateUnchanged => {
+            ("{} edits changed nothing: those parts don't matter to these inputs, add inputs \
+              that depend on them",
+             "{} cambios no alteraron nada: esas partes no importan para estas entradas, añade \
+              entradas que dependan de ellas")
+        }
+        Msg::NoRegexToFuzz => ("No regex to fuzz", "No hay regex que probar al azar"),
+        Msg::FuzzUsage => ("Usage: :fuzz [number of strings]", "Uso: :fuzz [número de cadenas]"),
+        Msg::FuzzTried => ("Tried {} strings", "Se probaron {} cadenas"),
+        Msg::FuzzNothing => ("Nothing surprising matched", "No coincidió nada sorprendente"),
+        Msg::FuzzRandom => ("Random strings it matches:", "Cadenas al azar que reconoce:"),
+        Msg::FuzzEdits => {
+            ("Edits of your inputs that change what it matches:",
+             "Cambios a tus entradas que alteran lo que reconoce:")
+        }
+        Msg::FuzzMatch => ("  {} matches {}", "  {} coincide en {}"),
+        Msg::FuzzEditMatch => ("  {}, from {}, matches {}", "  {}, a partir de {}, coincide en {}"),
+        Msg::MemberUsage => {
+            ("Usage: :member <class> <char...>, like :member [[:punct:]] ! a U+2014",
+             "Uso: :member <clase> <carácter...>, como :member [[:punct:]] ! a U+2014")
+        }
+        Msg::MemberIn => ("  {}: in {}", "  {}: en {}"),
+        Msg::MemberOut => ("  {}: not in {}", "  {}: no está en {}"),
+        Msg::NotAClass => ("{} isn't a character class", "{} no es una clase de caracteres"),
+        Msg::PropsUsage => {
+            ("Usage: :props <char>, or a code point like U+00E9",
+             "Uso: :props <carácter>, o un punto de código como U+00E9")
+        }
+        Msg::PropsCategory => {
+            ("  General category: {} ({}), within {}",
+             "  Categoría general: {} ({}), dentro de {}")
+        }
+        Msg::PropsScript => ("  Script: {}", "  Escritura: {}"),
+        Msg::PropsExtensions => ("  Used with the scripts: {}", "  Se usa con las escrituras: {}"),
+        Msg::PropsAge => ("  Added in Unicode {}: {}", "  Añadido en Unicode {}: {}"),
+        Msg::PropsUnassigned => {
+            ("  Not assigned in this version of Unicode",
+             "  Sin asignar en esta versión de Unicode")
+        }
+        Msg::PropsBinary => ("  Properties: {}", "  Propiedades: {}"),
+        Msg::PropsNoBlock => {
+            ("  Blocks: the regex crate has no \\p{Block=...}, use the script or a range instead",
+             "  Bloques: el crate regex no tiene \\p{Block=...}, usa la escritura o un rango")
+        }
+        Msg::NoRegexToAnalyze => ("No regex to analyze", "No hay regex que analizar"),
+        Msg::RedosNone => {
+            ("No constructs found that are slow on backtracking engines",
+             "No se encontraron construcciones lentas en motores con retroceso")
+        }
+        Msg::RedosNested => {
+            ("{}: nested quantifier, exponential on backtracking engines",
+             "{}: cuantificador anidado, exponencial en motores con retroceso")
+        }
+        Msg::RedosAmbiguous => {
+            ("{}: repeated alternation that can match {} in more than one way",
+             "{}: alternancia repetida que puede reconocer {} de varias formas")
+        }
+        Msg::RedosAttack => ("  Slow input: {}", "  Entrada lenta: {}"),
+        Msg::RedosNoAttack => {
+            ("  No input found that fails after the repeated part",
+             "  No se encontró una entrada que falle tras la parte repetida")
+        }
+        Msg::NoRegexToExplain => ("No regex to explain", "No hay regex que explicar"),
+        Msg::NoInputToExplain => {
+            ("No input to explain, test one first or use :why <input>",
+             "No hay entrada que explicar, pruebe una primero o use :why <entrada>")
+        }
+        Msg::WhyMatches => ("The regex matches {}", "La regex coincide con {}"),
+        Msg::BothOffsets => ("bytes {}..{}, chars {}..{}", "bytes {}..{}, caracteres {}..{}"),
+        Msg::WhyPrefixMatches => ("{} matches {} at {}", "{} coincide con {} en {}"),
+        Msg::WhyNothingMatches => {
+            ("Nothing in the pattern matches", "Ninguna parte del patrón coincide")
+        }
+        Msg::WhyDiverges => {
+            ("Matching diverges at pattern offset {}: {}",
+             "La coincidencia se detiene en la posición {} del patrón: {}")
+        }
+        Msg::NoRegexToDebug => ("No regex to debug", "No hay regex que depurar"),
+        Msg::NoInputToDebug => {
+            ("No input to debug, test one first or use :debug <input>",
+             "No hay entrada que depurar, pruebe una primero o use :debug <entrada>")
+        }
+        Msg::DebugIntro => {
+            ("{} steps. Enter: next step, c: run to the end, q: stop",
+             "{} pasos. Intro: siguiente paso, c: hasta el final, q: parar")
+        }
+        Msg::DebugStart => ("new attempt starts here", "nuevo intento desde aquí"),
+        Msg::DebugAdvance => ("matches, advancing", "coincide, avanzando"),
+        Msg::DebugAssert => ("assertion holds", "la aserción se cumple"),
+        Msg::DebugChoice => {
+            ("choice point, trying the preferred branch",
+             "punto de elección, probando la alternativa preferida")
+        }
+        Msg::DebugFail => ("fails", "falla"),
+        Msg::DebugBacktrack => {
+            ("backtracking to a saved choice", "retrocediendo a una elección guardada")
+        }
+        Msg::DebugMatch => ("match found", "coincidencia encontrada"),
+        Msg::DebugTruncated => {
+            ("Stopped recording after {} steps", "Grabación detenida tras {} pasos")
+        }
+        Msg::DebugMatched => ("Matched {} at {}", "Coincide {} en {}"),
+        Msg::DebugNoMatch => ("No match", "Sin coincidencias"),
+        Msg::AccSpan => ("from column {} to {}: {}", "de la columna {} a la {}: {}"),
+        Msg::AccEmptySpan => ("empty at column {}", "vacío en la columna {}"),
+        Msg::AccMatch => ("match {}", "coincidencia {}"),
+        Msg::AccGroup => ("group {} {}", "grupo {} {}"),
+        Msg::AccGroupUnset => ("group {} did not match", "el grupo {} no coincide"),
+        Msg::AccCursor => ("at column {}, before {}", "en la columna {}, antes de {}"),
+        Msg::AccCursorEnd => {
+            ("at column {}, the end of the input", "en la columna {}, el final de la entrada")
+        }
+        Msg::AccDebugMatched => ("Matched {}", "Coincide {}"),
+        Msg::AccNthMatch => ("Match {}: {}", "Coincidencia {}: {}"),
+        Msg::CantOpenFile => ("Can't open {}: {}", "No se puede abrir {}: {}"),
+        Msg::CantReadInput => ("Can't read input: {}", "No se puede leer la entrada: {}"),
+        Msg::CheckLineMatched => ("{}: matched: {}", "{}: coincide: {}"),
+        Msg::CheckLineFailed => ("{}: FAILED: {}", "{}: FALLA: {}"),
+        Msg::CheckSummary => ("{} of {} lines matched", "{} de {} líneas coinciden"),
+        Msg::SuiteShouldMatch => ("{}:{}: {} should match {}", "{}:{}: {} debería coincidir con {}"),
+        Msg::SuiteShouldNotMatch => {
+            ("{}:{}: {} should not match {}", "{}:{}: {} no debería coincidir con {}")
+        }
+        Msg::SuiteSummary => ("{} of {} cases passed", "{} de {} casos correctos"),
+        Msg::FormatUnclosed => {
+            ("Unclosed placeholder at position {} of --format",
+             "Marcador sin cerrar en la posición {} de --format")
+        }
+        Msg::FormatUnknown => {
+            ("Unknown placeholder {} in --format, expected file, line, match, start, end, \
+              group.N or group.name",
+             "Marcador {} desconocido en --format, se esperaba file, line, match, start, end, \
+              group.N o group.name")
+        }
+        Msg::NoSuchGroupColumn => {
+            ("The pattern has no group {} for --columns",
+             "El patrón no tiene grupo {} para --columns")
+        }
+        Msg::UnknownColumn => {
+            ("Unknown column {}, expected file, line, col, start, end, match, group.N or \
+              group.name",
+             "Columna {} desconocida, se esperaba file, line, col, start, end, match, group.N o \
+              group.name")
+        }
+        Msg::CantSave => ("Can't save results: {}", "No se pueden guardar los resultados: {}"),
+        Msg::NoSqlite => {
+            ("this build has no SQLite support, rebuild it with --features sqlite",
+             "esta compilación no incluye SQLite, vuelva a compilar con --features sqlite")
+        }
+        Msg::BucketTime => ("time", "hora"),
+        Msg::BucketCount => ("count", "veces"),
+        Msg::Stats => {
+            ("\n{} matches\n{} matched lines\n{} files contained matches\n{} files searched\n\
+              {} binary files skipped\n{} bytes searched\n{} seconds",
+             "\n{} coincidencias\n{} líneas coincidentes\n{} archivos con coincidencias\n\
+              {} archivos buscados\n{} archivos binarios omitidos\n{} bytes buscados\n\
+              {} segundos")
+        }
+        Msg::MaxTimeStopped => {
+            ("Out of time, stopped at {} line {}: read {} of {} files, {} bytes",
+             "Se acabó el tiempo, parado en {} línea {}: leídos {} de {} archivos, {} bytes")
+        }
+        Msg::SummaryLines => ("lines", "líneas"),
+        Msg::SummaryMatches => ("matches", "coinc."),
+        Msg::SummaryFile => ("file", "archivo"),
+        Msg::SummaryTotals => {
+            ("Searched {} files, {} with matches: {} lines, {} matches, {} distinct",
+             "Se buscó en {} archivos, {} con coincidencias: {} líneas, {} coincidencias, {} \
+              distintas")
+        }
+        Msg::NoTimestamp => {
+            ("{} matching lines were left out for having no time in the format {}",
+             "Se omitieron {} líneas coincidentes sin hora en el formato {}")
+        }
+        Msg::BadTime => {
+            ("Can't read {} as a time in the format {}",
+             "No se puede leer {} como una hora en el formato {}")
+        }
+        Msg::TopHeader => ("  count  value", "  veces  valor"),
+        Msg::VerifyNoSuites => ("No .suite files under {}", "No hay archivos .suite en {}"),
+        Msg::InferNothing => {
+            ("No pattern found that tells the two sets apart",
+             "No se encontró ningún patrón que distinga los dos conjuntos")
+        }
+        Msg::VerifySummary => {
+            ("{} of {} suites passed, {} of {} cases",
+             "{} de {} suites correctas, {} de {} casos")
+        }
+        Msg::CheckpointOutput => {
+            ("--checkpoint can't resume json or json-compact output, pick another --output",
+             "--checkpoint no puede reanudar la salida json o json-compact, elija otro --output")
+        }
+        Msg::CheckpointOtherPattern => {
+            ("{} is a checkpoint of a scan for {}, not this pattern",
+             "{} es un punto de control de una búsqueda de {}, no de este patrón")
+        }
+        Msg::ScriptUnknownDirective => (
+            "{}:{}: unknown directive @{}, write @@ for a line starting with @",
+            "{}:{}: directiva @{} desconocida, escribe @@ para una línea que empiece por @",
+        ),
+        Msg::SourceUsage => ("Usage: :source <file>", "Uso: :source <fichero>"),
+        Msg::GradeNoSubmissions => ("No submissions in {}", "No hay entregas en {}"),
+        Msg::GradeNoPattern => ("No pattern in the file", "No hay patrón en el archivo"),
+        Msg::GradeShouldMatch => ("  should match {}", "  debería coincidir con {}"),
+        Msg::GradeShouldNotMatch => ("  shouldn't match {}", "  no debería coincidir con {}"),
+        Msg::GradeSummary => {
+            ("{} of {} students passed every case, average {}%",
+             "{} de {} estudiantes superaron todos los casos, media {}%")
+        }
+        Msg::ExpectUsage => {
+            ("Usage: :expect match|nomatch <input>, :expect clear or :expect save <file>",
+             "Uso: :expect match|nomatch <entrada>, :expect clear o :expect save <archivo>")
+        }
+        Msg::ExpectNone => ("No expectations yet", "Todavía no hay expectativas"),
+        Msg::ExpectAdded => ("Expectation {} added", "Expectativa {} añadida"),
+        Msg::ExpectCleared => ("Expectations cleared", "Expectativas eliminadas"),
+        Msg::ExpectSummary => ("{} of {} expectations met", "{} de {} expectativas cumplidas"),
+        Msg::ExpectNoPattern => {
+            ("Enter a regex before saving expectations",
+             "Introduzca una regex antes de guardar las expectativas")
+        }
+        Msg::ExpectSaved => ("Wrote {} expectations to {}", "{} expectativas escritas en {}"),
+        Msg::AccExpectMet => ("met", "cumplida"),
+        Msg::AccExpectNotMet => ("not met", "no cumplida"),
+        Msg::CantWriteFile => ("Can't write {}: {}", "No se puede escribir {}: {}"),
+        Msg::WorkspaceBadName => {
+            ("Workspace names are letters, digits, `-`, `_` and `.`, not {}",
+             "Los nombres de espacio de trabajo son letras, dígitos, `-`, `_` y `.`, no {}")
+        }
+        Msg::WorkspaceCreated => {
+            ("Created workspace {} in {}", "Espacio de trabajo {} creado en {}")
+        }
+        Msg::WorkspaceError => ("Can't open workspace: {}", "No se puede abrir el espacio de trabajo: {}"),
+        Msg::WorkspaceBadOption => {
+            ("Ignoring workspace option {} = {}", "Se ignora la opción del espacio de trabajo {} = {}")
+        }
+        Msg::WorkspaceOpened => {
+            ("Opened workspace {}: {} patterns, {} expectations",
+             "Espacio de trabajo {} abierto: {} patrones, {} expectativas")
+        }
+        Msg::WorkspaceInfo => ("Workspace {}, kept in {}", "Espacio de trabajo {}, guardado en {}"),
+        Msg::WorkspaceSuites => ("Suites: {}", "Suites: {}"),
+        Msg::WorkspaceUsage => {
+            ("Usage: :workspace [<name> | export <file> | import <file> [name]]",
+             "Uso: :workspace [<nombre> | export <archivo> | import <archivo> [nombre]]")
+        }
+        Msg::WorkspaceExported => ("Exported workspace {} to {}", "Espacio de trabajo {} exportado a {}"),
+        Msg::WorkspaceExists => {
+            ("There's already a workspace named {}. Give the import another name",
+             "Ya hay un espacio de trabajo llamado {}. Importe con otro nombre")
+        }
+        Msg::WorkspaceNone => {
+            ("Not in a workspace. Use :workspace <name> to start one",
+             "Sin espacio de trabajo. Use :workspace <nombre> para crear uno")
+        }
+        Msg::NoRegexToWatch => ("No regex to watch a file with", "No hay regex con la que vigilar un archivo"),
+        Msg::WatchUsage => ("Usage: :watch <file>", "Uso: :watch <archivo>"),
+        Msg::WatchStarted => {
+            ("Watching {}. Press Enter to stop",
+             "Vigilando {}. Pulse Intro para terminar")
+        }
+        Msg::WatchEnded => ("Press Enter to go back", "Pulse Intro para volver"),
+        Msg::NoRegexToPaste => ("No regex to test the clipboard with", "No hay regex con la que probar el portapapeles"),
+        Msg::CantPaste => ("Can't read the clipboard: {}", "No se puede leer el portapapeles: {}"),
+        Msg::PasteEmpty => ("The clipboard is empty", "El portapapeles está vacío"),
+        Msg::PasteMatches => {
+            ("{} matches on {} of {} lines",
+             "{} coincidencias en {} de {} líneas")
+        }
+        Msg::FollowOneFile => ("--follow takes one file", "--follow admite un solo archivo"),
+        Msg::WatchChanged => {
+            ("{}: {} matching lines, {} new",
+             "{}: {} líneas coinciden, {} nuevas")
+        }
+        Msg::BenchCompile => ("Compile: {}ns (mean of {})", "Compilación: {}ns (media de {})"),
+        Msg::BenchSearch => {
+            ("Search ({}): {}ns per pass over {} bytes, {} matches, {} MB/s",
+             "Búsqueda ({}): {}ns por pasada sobre {} bytes, {} coincidencias, {} MB/s")
+        }
+        Msg::CantListen => ("Can't listen on {}: {}", "No se puede escuchar en {}: {}"),
+        Msg::Listening => {
+            ("Listening on http://{}/match?pattern=..&input=..",
+             "Escuchando en http://{}/match?pattern=..&input=..")
+        }
+        Msg::ServeUsage => {
+            ("Use /match?pattern=<regex>&input=<text>", "Use /match?pattern=<regex>&input=<texto>")
+        }
+        Msg::NothingToUndo => ("Nothing to undo", "Nada que deshacer"),
+        Msg::UndidOptions => ("Restored the previous options", "Opciones anteriores restauradas"),
+        Msg::UndidPattern => ("Back to the previous regex: {}", "De vuelta a la regex anterior: {}"),
+        Msg::SourceDefault => ("default", "predeterminado"),
+        Msg::SourceConfigFile => ("config file", "archivo de configuración"),
+        Msg::SourceProfile => ("profile", "perfil"),
+        Msg::SourceWorkspace => ("workspace", "espacio de trabajo"),
+        Msg::SourceEnvironment => ("environment", "entorno"),
+        Msg::SourceCommandLine => ("command line", "línea de órdenes"),
+        Msg::SourceRuntime => ("changed in session", "cambiado en la sesión"),
+        Msg::ConfigBadValue => ("{}:{}: invalid value {} for {}", "{}:{}: valor {} no válido para {}"),
+        Msg::ConfigUnknownOption => ("{}:{}: unknown option {}", "{}:{}: opción desconocida {}"),
+        Msg::ConfigFileError => {
+            ("Failed to read config file: {}", "No se pudo leer el archivo de configuración: {}")
+        }
+        Msg::ConfigEngine => ("Engine: {}", "Motor: {}"),
+        Msg::ConfigEnvVar => ("{}={} (environment)", "{}={} (entorno)"),
+        Msg::ConfigPrecedence => {
+            ("Later sources win: default, config file, profile, workspace, environment (REGTEST_*), \
+              command line, changed in session",
+             "Las últimas fuentes ganan: predeterminado, archivo de configuración, perfil, espacio \
+              de trabajo, entorno (REGTEST_*), línea de órdenes, cambiado en la sesión")
+        }
+        Msg::HookTrailingBackslash => ("nothing after the last \\", "nada tras la última \\"),
+        Msg::HookUnclosedQuote => ("unclosed quote", "comilla sin cerrar"),
+        Msg::HookEmpty => ("no command", "no hay orden"),
+        Msg::HookStatus => ("{} exited with {}", "{} terminó con {}"),
+        Msg::HookFailed => ("Can't run {}: {}", "No se puede ejecutar {}: {}"),
+        Msg::WatchStreaming => {
+            ("{} is {}, over max-file ({}), so only lines added from now on are checked",
+             "{} ocupa {}, más que max-file ({}), así que solo se revisan las líneas que se añadan")
+        }
+        Msg::ConfirmBigFile => {
+            ("{} is {}, over max-file ({}). Read it anyway? [y/N] ",
+             "{} ocupa {}, más que max-file ({}). ¿Leerlo de todos modos? [s/N] ")
+        }
+        Msg::LineTooLong => {
+            ("{}:{}: skipped a line longer than max-line ({})",
+             "{}:{}: se omitió una línea más larga que max-line ({})")
+        }
+        Msg::InputTooBig => {
+            ("{} is bigger than max-file ({}). Raise it in the config file or with REGTEST_MAX_FILE",
+             "{} es más grande que max-file ({}). Auméntelo en el archivo de configuración o con \
+              REGTEST_MAX_FILE")
+        }
+        Msg::PrivateMode => {
+            ("Private session: history and workspace changes won't be saved",
+             "Sesión privada: no se guardarán el historial ni los cambios del espacio de trabajo")
+        }
+        Msg::PrivateNoLogFile => {
+            ("--private: logging to stderr instead of a file",
+             "--private: registrando en stderr en lugar de en un archivo")
+        }
+        Msg::FilterNoPattern => {
+            ("stdin isn't a terminal, so regtest filters it, but there's no pattern to filter \
+              with: give one with -e, or use --repl to test regexes interactively",
+             "stdin no es una terminal, así que regtest la filtra, pero no hay un patrón con el \
+              que filtrar: indica uno con -e, o usa --repl para probar regexes de forma \
+              interactiva")
+        }
+        Msg::ExecFailures => ("{} of {} commands failed", "Fallaron {} de {} órdenes"),
+        Msg::BadJobs => ("expected a number above 0", "se esperaba un número mayor que 0"),
+        Msg::EnvBadValue => ("{}: invalid value {}", "{}: valor {} no válido"),
+        Msg::ConfigPattern => ("Pattern: {}", "Patrón: {}"),
+        Msg::ConfigNoPattern => ("Pattern: none", "Patrón: ninguno"),
+        Msg::ConfigFlags => ("Flags: {}", "Flags: {}"),
+        Msg::ConfigNoFlags => ("Flags: none", "Flags: ninguno"),
+        Msg::ConfigFile => ("Config file: {}", "Archivo de configuración: {}"),
+        Msg::ResetUnknown => {
+            ("Unknown option {}, expected one of {}", "Opción desconocida {}, se esperaba una de {}")
+        }
+        Msg::SetBadValue => ("Invalid value {} for {}", "Valor {} no válido para {}"),
+        Msg::ResetChanged => ("{}: {} -> {}", "{}: {} -> {}"),
+        Msg::ResetPattern => ("Cleared the regex {}", "Regex {} descartada"),
+        Msg::ResetNothing => {
+            ("Everything is already at its startup value",
+             "Todo tiene ya su valor inicial")
+        }
+        Msg::UnknownProfile => ("No profile named {}", "No hay ningún perfil llamado {}"),
+        Msg::NoProfiles => {
+            ("No profiles, add a [profile.<name>] section to {}",
+             "No hay perfiles, añada una sección [profile.<nombre>] a {}")
+        }
+        Msg::Profiles => ("Profiles: {}", "Perfiles: {}"),
+        Msg::UsingProfile => ("Using profile {}", "Usando el perfil {}"),
+        Msg::ConfigProfile => ("Profile: {}", "Perfil: {}"),
+        Msg::PromptReplace => (" -> {}", " -> {}"),
+        Msg::PromptReplacen => (" -> first {}: {}", " -> primeras {}: {}"),
+        Msg::PromptSplit => (" split", " dividir"),
+        Msg::PromptSmartCase => (" ignoring case", " sin mayúsculas"),
+        Msg::PromptSplitn => (" split/{}", " dividir/{}"),
+        Msg::BadSplitLimit => {
+            ("Expected a number of fields after :split/, got {}",
+             "Se esperaba un número de campos tras :split/, no {}")
+        }
+        Msg::SplitOff => ("Splitting: off", "División: desactivada"),
+        Msg::SplitFields => ("{} fields:", "{} campos:"),
+        Msg::SplitDelimiter => ("delimiter {}", "delimitador {}"),
+        Msg::DelimitersOn => ("Show split delimiters: on", "Mostrar delimitadores: sí"),
+        Msg::DelimitersOff => ("Show split delimiters: off", "Mostrar delimitadores: no"),
+        Msg::BadReplaceLimit => {
+            ("Expected a number of replacements after :r/, got {}",
+             "Se esperaba un número de reemplazos tras :r/, no {}")
+        }
+        Msg::ReplaceOff => ("Replacing: off", "Reemplazo: desactivado"),
+        Msg::Replaced => ("Replaced: {}", "Reemplazado: {}"),
+        Msg::ReplacedFrom => ("Original: {}", "Original: {}"),
+        Msg::PreviewOn => ("Replacement preview: on", "Vista previa del reemplazo: sí"),
+        Msg::PreviewOff => ("Replacement preview: off", "Vista previa del reemplazo: no"),
+        Msg::PreviewMatch => {
+            ("Match {}: {} at {} becomes {}", "Coincidencia {}: {} en {} se convierte en {}")
+        }
+        Msg::PreviewRef => ("{} = {}", "{} = {}"),
+        Msg::PreviewUnset => {
+            ("{} = \"\" (group {} did not match)", "{} = \"\" (el grupo {} no coincide)")
+        }
+        Msg::PreviewMissing => ("{} = \"\" (no group {})", "{} = \"\" (no existe el grupo {})"),
+        Msg::TransformsOff => ("Input transforms: off", "Transformaciones de la entrada: desactivadas"),
+        Msg::UnknownTransform => {
+            ("Unknown transform {}, expected some of {}",
+             "Transformación desconocida {}, se esperaban algunas de {}")
+        }
+        Msg::Retesting => ("Testing {} again", "Probando {} de nuevo"),
+        Msg::Transformed => ("Transformed input: {}", "Entrada transformada: {}"),
+        Msg::DidYouMean => ("Did you mean:", "¿Quiso decir?:"),
+        Msg::LooksLiteral => {
+            ("This looks like literal text: {}", "Esto parece texto literal: {}")
+        }
+        Msg::EscapeLiteral => {
+            ("Escape it and match it as it is? [y/N] ", "¿Escaparlo y buscarlo tal cual? [s/N] ")
+        }
+        Msg::UseSuggestion => ("Use it? [1/N] ", "¿Usarla? [1/N] "),
+        Msg::UseOneSuggestion => ("Use one? [1-{}/N] ", "¿Usar una? [1-{}/N] "),
+        Msg::CheatTopics => ("Topics: {}", "Temas: {}"),
+        Msg::CheatUsage => {
+            ("Use :cheat <topic> or :cheat <keyword> to search",
+             "Use :cheat <tema> o :cheat <palabra> para buscar")
+        }
+        Msg::CheatNothingFound => ("Nothing found for {}", "Nada encontrado para {}"),
+        Msg::TutorialHelp => {
+            ("\
+:hint - Show the next hint
+:solution - Show a solution
+:skip - Go to the next lesson
+:h - Print this menu
+:q - Quit the tutorial",
+             "\
+:hint - Mostrar la siguiente pista
+:solution - Mostrar una solución
+:skip - Pasar a la siguiente lección
+:h - Mostrar este menú
+:q - Salir del tutorial")
+        }
+        Msg::TutorialWelcome => {
+            ("Welcome to the regtest tutorial. Type :h for help.",
+             "Bienvenido al tutorial de regtest. Escriba :h para ver la ayuda.")
+        }
+        Msg::TutorialLesson => ("Lesson {} of {}: {}", "Lección {} de {}: {}"),
+        Msg::TutorialPack => {
+            ("Exercise pack {}: {} lessons", "Paquete de ejercicios {}: {} lecciones")
+        }
+        Msg::TutorialSample => ("Sample text:", "Texto de ejemplo:"),
+        Msg::TutorialFindList => ("Should find:      {}", "Debe encontrar:    {}"),
+        Msg::TutorialFound => ("  ✗ finds {} in the sample", "  ✗ encuentra {} en el ejemplo"),
+        Msg::TutorialShouldMatchList => ("Should match:     {}", "Debe coincidir:    {}"),
+        Msg::TutorialShouldNotMatchList => ("Should not match: {}", "No debe coincidir: {}"),
+        Msg::TutorialPrompt => ("Lesson {}> ", "Lección {}> "),
+        Msg::TutorialHint => ("Hint: {}", "Pista: {}"),
+        Msg::TutorialNoMoreHints => {
+            ("No more hints, try :solution", "No hay más pistas, pruebe :solution")
+        }
+        Msg::TutorialSolution => ("One solution: {}", "Una solución: {}"),
+        Msg::TutorialCorrect => ("Correct!", "¡Correcto!"),
+        Msg::TutorialShouldMatch => ("  ✗ {} should match", "  ✗ {} debería coincidir"),
+        Msg::TutorialShouldNotMatch => {
+            ("  ✗ {} should not match", "  ✗ {} no debería coincidir")
+        }
+        Msg::TutorialNotQuite => {
+            ("Not quite, type :hint for a hint", "Casi, escriba :hint para una pista")
+        }
+        Msg::TutorialDone => {
+            ("That's all the lessons. Well done!", "Esas son todas las lecciones. ¡Bien hecho!")
+        }
+    }
+}
+
+/// The text of `msg` in the current language.
+pub fn text(msg: Msg) -> &'static str {
+    let (en, es) = entry(msg);
+    match lang() {
+        Lang::En => en,
+        Lang::Es => es,
+    }
+}
+
+/// The text of `msg` with each `{}` replaced by the next argument.
+pub fn fill(msg: Msg, args: &[&dyn Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut parts = text(msg).split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
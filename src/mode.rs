@@ -0,0 +1,53 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Choosing how to run from where input comes from and output goes:
+//! the REPL at a terminal, a filter over piped input, or the REPL
+//! with plain output when its output is piped.
+
+use std::io::{self, IsTerminal};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Test regexes interactively.
+    Repl,
+    /// Print the lines of stdin that match the `-e` patterns.
+    Filter,
+    /// The REPL, without a pager or timings in its output.
+    Machine,
+}
+
+impl Mode {
+    /// The mode for stdin and stdout being terminals or not.
+    /// `forced` is `Some(true)` for `--repl` and `Some(false)` for
+    /// `--no-repl`.
+    pub fn choose(stdin_tty: bool, stdout_tty: bool, forced: Option<bool>) -> Mode {
+        match (forced, stdin_tty, stdout_tty) {
+            (Some(true), _, _) => Mode::Repl,
+            (Some(false), _, _) | (None, false, _) => Mode::Filter,
+            (None, true, false) => Mode::Machine,
+            (None, true, true) => Mode::Repl,
+        }
+    }
+
+    /// The mode for this process.
+    pub fn detect(forced: Option<bool>) -> Mode {
+        let mode = Mode::choose(io::stdin().is_terminal(), io::stdout().is_terminal(), forced);
+        debug!("running in {:?} mode", mode);
+        mode
+    }
+}
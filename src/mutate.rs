@@ -0,0 +1,126 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Small edits to a pattern, one place at a time, to see which parts
+//! of it make a difference to what it matches.
+
+use regex_syntax::ast::{Ast, RepetitionKind, Span};
+use regex_syntax::ast::parse::Parser;
+
+use relax::{self, apply};
+use suggest::Suggestion;
+
+/// Every single edit of `pattern`: quantifiers made lazy or greedy,
+/// `+` allowing zero, quantifiers dropped, classes widened to `.`,
+/// assertions and alternatives removed. Patterns that don't parse
+/// have none.
+pub fn mutations(pattern: &str) -> Vec<Suggestion> {
+    let ast = match Parser::new().parse(pattern) {
+        Ok(a) => a,
+        Err(_) => return vec![],
+    };
+    let mut out = vec![];
+    let text = |span: &Span| &pattern[span.start.offset..span.end.offset];
+    relax::walk(&ast, &mut |a| {
+        match *a {
+            Ast::Repetition(ref r) => {
+                let op = text(&r.op.span);
+                let whole = text(&r.span);
+                if r.greedy {
+                    let lazy = format!("{}?", op);
+                    out.push(edit(pattern, &r.op.span, &lazy, format!("make {:?} lazy", whole)));
+                } else {
+                    let greedy = &op[..op.len() - 1];
+                    out.push(edit(pattern, &r.op.span, greedy, format!("make {:?} greedy", whole)));
+                }
+                if r.op.kind == RepetitionKind::OneOrMore {
+                    let star = format!("*{}", &op[1..]);
+                    let reason = format!("let {:?} repeat zero times", whole);
+                    out.push(edit(pattern, &r.op.span, &star, reason));
+                }
+                let reason = format!("drop the quantifier of {:?}", whole);
+                out.push(edit(pattern, &r.op.span, "", reason));
+            }
+            Ast::Class(ref c) => {
+                let span = c.span();
+                let reason = format!("widen {:?} to any character", text(span));
+                out.push(edit(pattern, span, ".", reason));
+            }
+            Ast::Assertion(ref x) => {
+                out.push(edit(pattern, &x.span, "", format!("remove {:?}", text(&x.span))));
+            }
+            Ast::Alternation(ref alt) => {
+                for (i, branch) in alt.asts.iter().enumerate() {
+                    // Take out the branch and the | next to it
+                    let mut span = *branch.span();
+                    if i == 0 {
+                        span.end = alt.asts[1].span().start;
+                    } else {
+                        span.start = alt.asts[i - 1].span().end;
+                    }
+                    let reason = format!("remove the alternative {:?}", text(branch.span()));
+                    out.push(edit(pattern, &span, "", reason));
+                }
+            }
+            _ => {}
+        }
+    });
+    out
+}
+
+fn edit(pattern: &str, span: &Span, with: &str, reason: String) -> Suggestion {
+    Suggestion {
+        pattern: apply(pattern, &[(*span, with)]),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(pattern: &str) -> Vec<String> {
+        mutations(pattern).into_iter().map(|s| s.pattern).collect()
+    }
+
+    #[test]
+    fn edits_quantifiers() {
+        assert_eq!(patterns("a+"), ["a+?", "a*", "a"]);
+        assert_eq!(patterns("a*?"), ["a*", "a"]);
+        assert_eq!(patterns("a{2}"), ["a{2}?", "a"]);
+    }
+
+    #[test]
+    fn widens_classes_and_removes_assertions() {
+        assert_eq!(patterns(r"^\d$"), [r"\d$", "^.$", r"^\d"]);
+        assert_eq!(patterns("[ab]c"), [".c"]);
+    }
+
+    #[test]
+    fn removes_each_alternative() {
+        assert_eq!(patterns("ab|c|d"), ["c|d", "ab|d", "ab|c"]);
+    }
+
+    #[test]
+    fn explains_each_edit() {
+        let reasons: Vec<String> = mutations(r"x+").into_iter().map(|s| s.reason).collect();
+        assert_eq!(reasons,
+                   [r#"make "x+" lazy"#, r#"let "x+" repeat zero times"#,
+                    r#"drop the quantifier of "x+""#]);
+        assert!(mutations("(").is_empty());
+    }
+}
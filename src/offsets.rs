@@ -0,0 +1,74 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! How spans of an input are reported. The regex crate works in
+//! byte offsets, while most other languages index strings by
+//! character.
+
+/// The unit spans are reported in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Offsets {
+    Bytes,
+    /// Unicode scalar values, as `str::chars` counts them.
+    Chars,
+    Both,
+}
+
+impl Offsets {
+    pub fn from_name(name: &str) -> Option<Offsets> {
+        match &name.to_lowercase()[..] {
+            "bytes" => Some(Offsets::Bytes),
+            "chars" => Some(Offsets::Chars),
+            "both" => Some(Offsets::Both),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Offsets::Bytes => "bytes",
+            Offsets::Chars => "chars",
+            Offsets::Both => "both",
+        }
+    }
+
+    /// The byte range `start..end` of `input`, in these units.
+    pub fn span(self, input: &str, start: usize, end: usize) -> String {
+        let chars = |pos: usize| input[..pos].chars().count();
+        match self {
+            Offsets::Bytes => format!("{}..{}", start, end),
+            Offsets::Chars => format!("{}..{}", chars(start), chars(end)),
+            Offsets::Both => msg!(BothOffsets, start, end, chars(start), chars(end)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_chars_and_bytes() {
+        let input = "año €5";
+        let m = input.find('5').unwrap();
+        assert_eq!(Offsets::Bytes.span(input, m, m + 1), "8..9");
+        assert_eq!(Offsets::Chars.span(input, m, m + 1), "5..6");
+        assert!(Offsets::Both.span(input, m, m + 1).contains("5..6"));
+        assert_eq!(Offsets::from_name("Chars"), Some(Offsets::Chars));
+        assert_eq!(Offsets::from_name("utf16"), None);
+    }
+}
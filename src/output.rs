@@ -0,0 +1,506 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Structured output of batch results, picked with `--output`.
+//! Inputs are written as soon as they're tested, so large scans
+//! aren't held in memory.
+
+use std::io::Write;
+
+use regex::{Captures, Regex};
+
+use describe;
+
+/// How results are written.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Output {
+    /// The usual human-readable lines.
+    Text,
+    /// An array with an object per input.
+    Json,
+    /// A sequence with a mapping per input.
+    Yaml,
+    /// A line holding a JSON object per match.
+    Jsonl,
+    /// A header row, then a row per match.
+    Csv,
+    /// Like `Csv`, with tabs between fields.
+    Tsv,
+    /// Like `Csv`, with `|` between fields, for PowerShell's
+    /// `ConvertFrom-Csv -Delimiter '|'`.
+    Psv,
+    /// One line holding an array with an object per match, for
+    /// `ConvertFrom-Json`. Every object has the same properties,
+    /// null when a group took no part.
+    JsonCompact,
+}
+
+/// Every output and its name.
+pub const OUTPUTS: &[(&str, Output)] = &[("text", Output::Text),
+                                         ("json", Output::Json),
+                                         ("yaml", Output::Yaml),
+                                         ("jsonl", Output::Jsonl),
+                                         ("csv", Output::Csv),
+                                         ("tsv", Output::Tsv),
+                                         ("psv", Output::Psv),
+                                         ("json-compact", Output::JsonCompact)];
+
+impl Output {
+    pub fn from_name(name: &str) -> Option<Output> {
+        OUTPUTS.iter().find(|&&(n, _)| n == name).map(|&(_, o)| o)
+    }
+}
+
+/// A column of `csv` and `tsv` output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    File,
+    Line,
+    /// The 1-based column the match starts at.
+    Col,
+    Start,
+    End,
+    Match,
+    /// A group by number or name.
+    Group(String),
+}
+
+impl Column {
+    fn name(&self) -> String {
+        match *self {
+            Column::File => "file".to_owned(),
+            Column::Line => "line".to_owned(),
+            Column::Col => "col".to_owned(),
+            Column::Start => "start".to_owned(),
+            Column::End => "end".to_owned(),
+            Column::Match => "match".to_owned(),
+            Column::Group(ref g) => format!("group.{}", g),
+        }
+    }
+
+    /// The value of this column for `m`, found in `input`.
+    fn value(&self, input: &Input, m: &Match) -> String {
+        match *self {
+            Column::File => input.file.clone(),
+            Column::Line => input.line.to_string(),
+            Column::Col => describe::column(&input.text, m.start).to_string(),
+            Column::Start => m.start.to_string(),
+            Column::End => m.end.to_string(),
+            Column::Match => m.text.clone(),
+            Column::Group(ref g) => {
+                let group = match g.parse::<usize>() {
+                    Ok(0) => return m.text.clone(),
+                    Ok(i) => m.groups.iter().find(|gr| gr.index == i),
+                    Err(_) => m.groups.iter().find(|gr| gr.name.as_ref() == Some(g)),
+                };
+                group.and_then(|gr| gr.span.as_ref()).map_or(String::new(), |s| s.2.clone())
+            }
+        }
+    }
+}
+
+/// Parse a comma-separated list of columns, like
+/// `file,line,match,group.1`.
+pub fn parse_columns(list: &str) -> Result<Vec<Column>, String> {
+    list.split(',')
+        .map(|name| match name.trim() {
+            "file" => Ok(Column::File),
+            "line" => Ok(Column::Line),
+            "col" => Ok(Column::Col),
+            "start" => Ok(Column::Start),
+            "end" => Ok(Column::End),
+            "match" => Ok(Column::Match),
+            n if n.starts_with("group.") && n.len() > "group.".len() => {
+                Ok(Column::Group(n["group.".len()..].to_owned()))
+            }
+            n => Err(msg!(UnknownColumn, n)),
+        })
+        .collect()
+}
+
+/// Check that every group column names a group of `reg`.
+pub fn check_columns(columns: &[Column], reg: &Regex) -> Result<(), String> {
+    for c in columns {
+        if let Column::Group(ref g) = *c {
+            let exists = match g.parse::<usize>() {
+                Ok(i) => i < reg.captures_len(),
+                Err(_) => reg.capture_names().any(|n| n == Some(&g[..])),
+            };
+            if !exists {
+                return Err(msg!(NoSuchGroupColumn, g));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The file, line, column and match, then every group of `reg`.
+pub fn default_columns(reg: &Regex) -> Vec<Column> {
+    let mut columns = vec![Column::File, Column::Line, Column::Col, Column::Match];
+    for (i, name) in reg.capture_names().enumerate().skip(1) {
+        columns.push(Column::Group(name.map_or(i.to_string(), |n| n.to_owned())));
+    }
+    columns
+}
+
+/// A group of one match. `span` is `None` if the group took no part.
+pub struct Group {
+    pub index: usize,
+    pub name: Option<String>,
+    pub span: Option<(usize, usize, String)>,
+}
+
+/// One match and its groups.
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub groups: Vec<Group>,
+}
+
+/// A tested input and everything that matched in it.
+pub struct Input {
+    pub file: String,
+    /// Line number, starting at 1.
+    pub line: usize,
+    pub text: String,
+    pub matches: Vec<Match>,
+}
+
+impl Input {
+    /// Find every match of `reg` in `text`.
+    pub fn new(reg: &Regex, file: &str, line: usize, text: &str) -> Input {
+        Input {
+            file: file.to_owned(),
+            line,
+            text: text.to_owned(),
+            matches: reg.captures_iter(text).map(|caps| match_of(reg, &caps)).collect(),
+        }
+    }
+}
+
+fn match_of(reg: &Regex, caps: &Captures) -> Match {
+    let m = caps.get(0).unwrap();
+    Match {
+        start: m.start(),
+        end: m.end(),
+        text: m.as_str().to_owned(),
+        groups: reg.capture_names()
+            .enumerate()
+            .skip(1)
+            .map(|(i, name)| {
+                Group {
+                    index: i,
+                    name: name.map(|n| n.to_owned()),
+                    span: caps.get(i).map(|g| (g.start(), g.end(), g.as_str().to_owned())),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Writes inputs one at a time in a structured output.
+pub struct Writer<'a> {
+    output: Output,
+    out: &'a mut dyn Write,
+    written: usize,
+    /// Matches written, for `JsonCompact`.
+    matches: usize,
+    columns: Vec<Column>,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(output: Output, out: &'a mut dyn Write) -> Writer<'a> {
+        if output == Output::Json || output == Output::JsonCompact {
+            write!(out, "[");
+        }
+        Writer {
+            output,
+            out,
+            written: 0,
+            matches: 0,
+            columns: vec![],
+        }
+    }
+
+    /// Use `columns` for `csv`, `tsv` and `psv` output.
+    pub fn columns(mut self, columns: Vec<Column>) -> Writer<'a> {
+        self.columns = columns;
+        self
+    }
+
+    /// Carry on a document written before, so without a header.
+    pub fn continued(mut self) -> Writer<'a> {
+        self.written = 1;
+        self
+    }
+
+    fn header(&mut self) {
+        if self.written == 0 {
+            let names: Vec<String> = self.columns.iter().map(|c| c.name()).collect();
+            self.row(&names);
+        }
+    }
+
+    /// Write a `csv`, `tsv` or `psv` row.
+    fn row(&mut self, fields: &[String]) {
+        let (row, sep): (Vec<String>, _) = match self.output {
+            Output::Csv => (fields.iter().map(|f| csv_field(f, ',')).collect(), ","),
+            Output::Tsv => (fields.iter().map(|f| tsv_field(f)).collect(), "\t"),
+            Output::Psv => (fields.iter().map(|f| csv_field(f, '|')).collect(), "|"),
+            _ => return,
+        };
+        writeln!(self.out, "{}", row.join(sep));
+    }
+
+    pub fn input(&mut self, input: &Input) {
+        self.header();
+        match self.output {
+            Output::Text => {}
+            Output::Json => {
+                let sep = if self.written == 0 { "\n  " } else { ",\n  " };
+                write!(self.out, "{}{}", sep, json_input(input));
+            }
+            Output::Yaml => write_yaml(self.out, input),
+            Output::Jsonl => {
+                for m in &input.matches {
+                    writeln!(self.out, "{}", json_line(input, m));
+                }
+            }
+            Output::JsonCompact => {
+                for m in &input.matches {
+                    let sep = if self.matches == 0 { "" } else { "," };
+                    write!(self.out, "{}{}", sep, json_compact(input, m));
+                    self.matches += 1;
+                }
+            }
+            Output::Csv | Output::Tsv | Output::Psv => {
+                for m in &input.matches {
+                    let row: Vec<String> = self.columns.iter().map(|c| c.value(input, m)).collect();
+                    self.row(&row);
+                }
+            }
+        }
+        self.out.flush();
+        self.written += 1;
+    }
+
+    /// Close the document.
+    pub fn finish(mut self) {
+        self.header();
+        match self.output {
+            Output::Json if self.written == 0 => writeln!(self.out, "]"),
+            Output::Json => writeln!(self.out, "\n]"),
+            Output::Yaml if self.written == 0 => writeln!(self.out, "[]"),
+            Output::JsonCompact => writeln!(self.out, "]"),
+            _ => Ok(()),
+        };
+    }
+}
+
+/// The line ending results saved to a file, so a complete file can
+/// be told from one cut short, or `None` where closing the document
+/// already shows it.
+pub fn footer(output: Output, lines: usize, files: usize) -> Option<String> {
+    match output {
+        Output::Json | Output::JsonCompact => None,
+        Output::Jsonl => {
+            Some(format!("{{\"complete\": true, \"lines\": {}, \"files\": {}}}", lines, files))
+        }
+        _ => Some(format!("# regtest: complete, {} matching lines in {} files", lines, files)),
+    }
+}
+
+/// `s` as a double-quoted string. The escapes are valid in both JSON
+/// and YAML.
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `s` as a CSV field separated by `sep`, quoted if it holds the
+/// separator, a quote or a line break.
+fn csv_field(s: &str, sep: char) -> String {
+    if s.contains([sep, '"', '\r', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+/// `s` as a TSV field, with tabs, line breaks and backslashes
+/// escaped.
+fn tsv_field(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn json_group(g: &Group) -> String {
+    let name = g.name.as_ref().map_or("null".to_owned(), |n| quote(n));
+    match g.span {
+        Some((start, end, ref text)) => {
+            format!("{{\"index\": {}, \"name\": {}, \"start\": {}, \"end\": {}, \"text\": {}}}",
+                    g.index,
+                    name,
+                    start,
+                    end,
+                    quote(text))
+        }
+        None => format!("{{\"index\": {}, \"name\": {}, \"text\": null}}", g.index, name),
+    }
+}
+
+fn json_match(m: &Match) -> String {
+    let groups: Vec<String> = m.groups.iter().map(json_group).collect();
+    format!("{{\"start\": {}, \"end\": {}, \"text\": {}, \"groups\": [{}]}}",
+            m.start,
+            m.end,
+            quote(&m.text),
+            groups.join(", "))
+}
+
+fn json_input(input: &Input) -> String {
+    let matches: Vec<String> = input.matches.iter().map(json_match).collect();
+    format!("{{\"file\": {}, \"line\": {}, \"input\": {}, \"matches\": [{}]}}",
+            quote(&input.file),
+            input.line,
+            quote(&input.text),
+            matches.join(", "))
+}
+
+/// A match on its own, with the file and line it's on.
+fn json_line(input: &Input, m: &Match) -> String {
+    let groups: Vec<String> = m.groups.iter().map(json_group).collect();
+    format!("{{\"file\": {}, \"line\": {}, \"start\": {}, \"end\": {}, \"text\": {}, \
+             \"groups\": [{}]}}",
+            quote(&input.file),
+            input.line,
+            m.start,
+            m.end,
+            quote(&m.text),
+            groups.join(", "))
+}
+
+/// A match on its own without spaces, with the file, line and
+/// column it's on. Groups that took no part have null offsets.
+fn json_compact(input: &Input, m: &Match) -> String {
+    let groups: Vec<String> = m.groups
+        .iter()
+        .map(|g| {
+            let name = g.name.as_ref().map_or("null".to_owned(), |n| quote(n));
+            let (start, end, text) = match g.span {
+                Some((start, end, ref text)) => (start.to_string(), end.to_string(), quote(text)),
+                None => ("null".to_owned(), "null".to_owned(), "null".to_owned()),
+            };
+            format!("{{\"index\":{},\"name\":{},\"start\":{},\"end\":{},\"text\":{}}}",
+                    g.index,
+                    name,
+                    start,
+                    end,
+                    text)
+        })
+        .collect();
+    format!("{{\"file\":{},\"line\":{},\"col\":{},\"start\":{},\"end\":{},\"text\":{},\
+             \"groups\":[{}]}}",
+            quote(&input.file),
+            input.line,
+            describe::column(&input.text, m.start),
+            m.start,
+            m.end,
+            quote(&m.text),
+            groups.join(","))
+}
+
+fn write_yaml(out: &mut dyn Write, input: &Input) {
+    writeln!(out, "- file: {}", quote(&input.file));
+    writeln!(out, "  line: {}", input.line);
+    writeln!(out, "  input: {}", quote(&input.text));
+    if input.matches.is_empty() {
+        writeln!(out, "  matches: []");
+        return;
+    }
+    writeln!(out, "  matches:");
+    for m in &input.matches {
+        writeln!(out, "    - start: {}", m.start);
+        writeln!(out, "      end: {}", m.end);
+        writeln!(out, "      text: {}", quote(&m.text));
+        if m.groups.is_empty() {
+            writeln!(out, "      groups: []");
+            continue;
+        }
+        writeln!(out, "      groups:");
+        for g in &m.groups {
+            writeln!(out, "        - index: {}", g.index);
+            writeln!(out,
+                     "          name: {}",
+                     g.name.as_ref().map_or("null".to_owned(), |n| quote(n)));
+            match g.span {
+                Some((start, end, ref text)) => {
+                    writeln!(out, "          start: {}", start);
+                    writeln!(out, "          end: {}", end);
+                    writeln!(out, "          text: {}", quote(text));
+                }
+                None => {
+                    writeln!(out, "          text: null");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(pattern: &str, list: &str) -> Result<(), String> {
+        check_columns(&parse_columns(list).unwrap(), &Regex::new(pattern).unwrap())
+    }
+
+    #[test]
+    fn groups_in_the_pattern_are_columns() {
+        assert_eq!(check("(a)(?P<x>b)", "file,group.0,group.2,group.x"), Ok(()));
+    }
+
+    #[test]
+    fn groups_past_the_pattern_are_rejected() {
+        assert!(check("(a)(b)", "match,group.3").is_err());
+        assert!(check("(a)", "group.y").is_err());
+    }
+
+    #[test]
+    fn unknown_columns_are_rejected() {
+        assert!(parse_columns("file,colour").is_err());
+        assert!(parse_columns("group.").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Showing output longer than the terminal through `$PAGER`.
+//!
+//! Output is held back until it's a screenful. If it ends before
+//! that it's written as usual, otherwise the pager is started and
+//! everything goes to it.
+
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+use std::rc::Rc;
+
+use term;
+
+/// Where output goes when it isn't paged.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Target {
+    Stdout,
+    Stderr,
+}
+
+impl Target {
+    fn write_all(self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Target::Stdout => io::stdout().write_all(buf),
+            Target::Stderr => io::stderr().write_all(buf),
+        }
+    }
+
+    fn is_terminal(self) -> bool {
+        match self {
+            Target::Stdout => io::stdout().is_terminal(),
+            Target::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+struct State {
+    target: Target,
+    /// Lines that fit on the screen, or `None` to never page.
+    rows: Option<usize>,
+    held: Vec<u8>,
+    lines: usize,
+    pager: Option<Child>,
+}
+
+/// A writer paging what's written to it once it's more than a
+/// screenful. Clones write to the same place, so one output can be
+/// shared by several writers.
+#[derive(Clone)]
+pub struct Paged(Rc<RefCell<State>>);
+
+impl Paged {
+    /// Output for `target`, paged if `enabled` and it's a terminal.
+    pub fn new(target: Target, enabled: bool) -> Paged {
+        let rows = if enabled && target.is_terminal() {
+            term::size().map(|(_, rows)| rows.saturating_sub(1).max(1))
+        } else {
+            None
+        };
+        Paged(Rc::new(RefCell::new(State {
+            target,
+            rows,
+            held: vec![],
+            lines: 0,
+            pager: None,
+        })))
+    }
+}
+
+/// The pager to run: `$PAGER`, or `less`. `less` is told to pass
+/// colors through and quit on a short screen unless `$LESS` says
+/// otherwise.
+fn command() -> Command {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less".to_owned());
+    let mut words = pager.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap());
+    cmd.args(words);
+    if env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    cmd
+}
+
+impl State {
+    fn start_pager(&mut self) -> io::Result<()> {
+        let mut command = command();
+        debug!("output passed {} rows, paging with {:?}", self.rows.unwrap_or(0), command.get_program());
+        match command.stdin(Stdio::piped()).spawn() {
+            Ok(mut child) => {
+                let written = child.stdin.as_mut().unwrap().write_all(&self.held);
+                self.pager = Some(child);
+                self.held.clear();
+                written
+            }
+            // Without a pager, write everything as usual
+            Err(e) => {
+                debug!("can't start the pager: {}", e);
+                self.rows = None;
+                self.flush_held()
+            }
+        }
+    }
+
+    fn flush_held(&mut self) -> io::Result<()> {
+        let held = ::std::mem::take(&mut self.held);
+        self.target.write_all(&held)
+    }
+}
+
+impl Write for Paged {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut s = self.0.borrow_mut();
+        if let Some(ref mut child) = s.pager {
+            // The user may quit the pager before reading everything
+            return match child.stdin.as_mut().unwrap().write_all(buf) {
+                Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+                r => r.map(|_| buf.len()),
+            };
+        }
+        let rows = match s.rows {
+            Some(r) => r,
+            None => return s.target.write_all(buf).map(|_| buf.len()),
+        };
+        s.held.extend_from_slice(buf);
+        s.lines += buf.iter().filter(|&&b| b == b'\n').count();
+        if s.lines > rows {
+            s.start_pager()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        match self.pager.take() {
+            Some(mut child) => {
+                drop(child.stdin.take());
+                child.wait();
+            }
+            None => {
+                self.flush_held();
+            }
+        }
+    }
+}
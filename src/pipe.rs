@@ -0,0 +1,56 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Chains of regexes where each one runs on what the one before it
+//! extracted, like code that pulls out a field and then checks it.
+
+use regex::Regex;
+
+/// What one regex of a chain did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stage {
+    /// The text the regex ran on.
+    pub input: String,
+    /// What it passes on, or `None` if it didn't match.
+    pub output: Option<String>,
+}
+
+/// What `reg` passes on from `input`: the first group of its first
+/// match, or the whole match if it has no groups or the group
+/// didn't take part.
+pub fn extract(reg: &Regex, input: &str) -> Option<String> {
+    let caps = reg.captures(input)?;
+    let m = caps.get(1).or_else(|| caps.get(0))?;
+    Some(m.as_str().to_owned())
+}
+
+/// Run `input` through each of `regexes` in turn, stopping at the
+/// first one that doesn't match.
+pub fn run(regexes: &[&Regex], input: &str) -> Vec<Stage> {
+    let mut stages = vec![];
+    let mut input = input.to_owned();
+    for reg in regexes {
+        let output = extract(reg, &input);
+        let next = output.clone();
+        stages.push(Stage { input, output });
+        match next {
+            Some(o) => input = o,
+            None => break,
+        }
+    }
+    stages
+}
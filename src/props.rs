@@ -0,0 +1,168 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Which `\p{...}` classes a character is in, found by asking the
+//! parser for each class the regex crate knows and looking the
+//! character up in it.
+
+use regex_syntax::ParserBuilder;
+use regex_syntax::hir::{Class, HirKind};
+
+/// The general categories with one value per character, by short
+/// and long name.
+pub const CATEGORIES: &[(&str, &str)] = &[("Lu", "Uppercase_Letter"),
+                                          ("Ll", "Lowercase_Letter"),
+                                          ("Lt", "Titlecase_Letter"),
+                                          ("Lm", "Modifier_Letter"),
+                                          ("Lo", "Other_Letter"),
+                                          ("Mn", "Nonspacing_Mark"),
+                                          ("Mc", "Spacing_Mark"),
+                                          ("Me", "Enclosing_Mark"),
+                                          ("Nd", "Decimal_Number"),
+                                          ("Nl", "Letter_Number"),
+                                          ("No", "Other_Number"),
+                                          ("Pc", "Connector_Punctuation"),
+                                          ("Pd", "Dash_Punctuation"),
+                                          ("Ps", "Open_Punctuation"),
+                                          ("Pe", "Close_Punctuation"),
+                                          ("Pi", "Initial_Punctuation"),
+                                          ("Pf", "Final_Punctuation"),
+                                          ("Po", "Other_Punctuation"),
+                                          ("Sm", "Math_Symbol"),
+                                          ("Sc", "Currency_Symbol"),
+                                          ("Sk", "Modifier_Symbol"),
+                                          ("So", "Other_Symbol"),
+                                          ("Zs", "Space_Separator"),
+                                          ("Zl", "Line_Separator"),
+                                          ("Zp", "Paragraph_Separator"),
+                                          ("Cc", "Control"),
+                                          ("Cf", "Format"),
+                                          ("Cs", "Surrogate"),
+                                          ("Co", "Private_Use"),
+                                          ("Cn", "Unassigned")];
+
+pub const SCRIPTS: &[&str] = &["Adlam", "Ahom", "Anatolian_Hieroglyphs", "Arabic", "Armenian",
+                                "Avestan", "Balinese", "Bamum", "Bassa_Vah", "Batak", "Bengali",
+                                "Bhaiksuki", "Bopomofo", "Brahmi", "Braille", "Buginese", "Buhid",
+                                "Canadian_Aboriginal", "Carian", "Caucasian_Albanian", "Chakma",
+                                "Cham", "Cherokee", "Chorasmian", "Common", "Coptic", "Cuneiform",
+                                "Cypriot", "Cypro_Minoan", "Cyrillic", "Deseret", "Devanagari",
+                                "Dives_Akuru", "Dogra", "Duployan", "Egyptian_Hieroglyphs",
+                                "Elbasan", "Elymaic", "Ethiopic", "Georgian", "Glagolitic",
+                                "Gothic", "Grantha", "Greek", "Gujarati", "Gunjala_Gondi",
+                                "Gurmukhi", "Han", "Hangul", "Hanifi_Rohingya", "Hanunoo",
+                                "Hatran", "Hebrew", "Hiragana", "Imperial_Aramaic", "Inherited",
+                                "Inscriptional_Pahlavi", "Inscriptional_Parthian", "Javanese",
+                                "Kaithi", "Kannada", "Katakana", "Kawi", "Kayah_Li", "Kharoshthi",
+                                "Khitan_Small_Script", "Khmer", "Khojki", "Khudawadi", "Lao",
+                                "Latin", "Lepcha", "Limbu", "Linear_A", "Linear_B", "Lisu",
+                                "Lycian", "Lydian", "Mahajani", "Makasar", "Malayalam", "Mandaic",
+                                "Manichaean", "Marchen", "Masaram_Gondi", "Medefaidrin",
+                                "Meetei_Mayek", "Mende_Kikakui", "Meroitic_Cursive",
+                                "Meroitic_Hieroglyphs", "Miao", "Modi", "Mongolian", "Mro",
+                                "Multani", "Myanmar", "Nabataean", "Nag_Mundari", "Nandinagari",
+                                "New_Tai_Lue", "Newa", "Nko", "Nushu", "Nyiakeng_Puachue_Hmong",
+                                "Ogham", "Ol_Chiki", "Old_Hungarian", "Old_Italic",
+                                "Old_North_Arabian", "Old_Permic", "Old_Persian", "Old_Sogdian",
+                                "Old_South_Arabian", "Old_Turkic", "Old_Uyghur", "Oriya", "Osage",
+                                "Osmanya", "Pahawh_Hmong", "Palmyrene", "Pau_Cin_Hau", "Phags_Pa",
+                                "Phoenician", "Psalter_Pahlavi", "Rejang", "Runic", "Samaritan",
+                                "Saurashtra", "Sharada", "Shavian", "Siddham", "SignWriting",
+                                "Sinhala", "Sogdian", "Sora_Sompeng", "Soyombo", "Sundanese",
+                                "Syloti_Nagri", "Syriac", "Tagalog", "Tagbanwa", "Tai_Le",
+                                "Tai_Tham", "Tai_Viet", "Takri", "Tamil", "Tangsa", "Tangut",
+                                "Telugu", "Thaana", "Thai", "Tibetan", "Tifinagh", "Tirhuta",
+                                "Toto", "Ugaritic", "Vai", "Vithkuqi", "Wancho", "Warang_Citi",
+                                "Yezidi", "Yi", "Zanabazar_Square"];
+
+/// Unicode versions, oldest first.
+pub const AGES: &[&str] = &["1.1", "2.0", "2.1", "3.0", "3.1", "3.2", "4.0", "4.1", "5.0", "5.1",
+                            "5.2", "6.0", "6.1", "6.2", "6.3", "7.0", "8.0", "9.0", "10.0", "11.0",
+                            "12.0", "12.1", "13.0", "14.0", "15.0"];
+
+/// Yes or no properties, leaving out the `Other_` ones that only go
+/// into making others.
+pub const BINARY: &[&str] = &["ASCII_Hex_Digit", "Alphabetic", "Bidi_Control", "Bidi_Mirrored",
+                              "Case_Ignorable", "Cased", "Changes_When_Casefolded",
+                              "Changes_When_Casemapped", "Changes_When_Lowercased",
+                              "Changes_When_Titlecased", "Changes_When_Uppercased", "Dash",
+                              "Default_Ignorable_Code_Point", "Deprecated", "Diacritic", "Emoji",
+                              "Emoji_Component", "Emoji_Modifier", "Emoji_Modifier_Base",
+                              "Emoji_Presentation", "Extended_Pictographic", "Extender",
+                              "Grapheme_Base", "Grapheme_Extend", "Grapheme_Link", "Hex_Digit",
+                              "Hyphen", "IDS_Binary_Operator", "IDS_Trinary_Operator",
+                              "ID_Continue", "ID_Start", "Ideographic", "Join_Control",
+                              "Logical_Order_Exception", "Lowercase", "Math",
+                              "Noncharacter_Code_Point", "Pattern_Syntax", "Pattern_White_Space",
+                              "Prepended_Concatenation_Mark", "Quotation_Mark", "Radical",
+                              "Regional_Indicator", "Sentence_Terminal", "Soft_Dotted",
+                              "Terminal_Punctuation", "Unified_Ideograph", "Uppercase",
+                              "Variation_Selector", "White_Space", "XID_Continue", "XID_Start"];
+
+/// The classes of the regex crate `c` is in.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Props {
+    /// Short and long name of the general category.
+    pub category: Option<(&'static str, &'static str)>,
+    pub script: Option<&'static str>,
+    /// Scripts the character is used with, for `\p{scx=...}`.
+    pub extensions: Vec<&'static str>,
+    /// The version it was added in, for `\p{Age=...}`.
+    pub age: Option<&'static str>,
+    pub binary: Vec<&'static str>,
+}
+
+/// Is `c` in `\p{name}`? Names the parser doesn't know are taken to
+/// hold nothing.
+pub fn has(name: &str, c: char) -> bool {
+    let hir = match ParserBuilder::new().build().parse(&format!("\\p{{{}}}", name)) {
+        Ok(h) => h,
+        Err(_) => return false,
+    };
+    match *hir.kind() {
+        HirKind::Class(Class::Unicode(ref cls)) => {
+            cls.iter().any(|r| r.start() <= c && c <= r.end())
+        }
+        _ => false,
+    }
+}
+
+/// Look `c` up in every class.
+pub fn of(c: char) -> Props {
+    Props {
+        category: CATEGORIES.iter().find(|&&(short, _)| has(short, c)).cloned(),
+        script: SCRIPTS.iter().find(|s| has(&format!("Script={}", s), c)).cloned(),
+        extensions: SCRIPTS.iter()
+            .filter(|s| has(&format!("Script_Extensions={}", s), c))
+            .cloned()
+            .collect(),
+        age: AGES.iter().find(|a| has(&format!("Age={}", a), c)).cloned(),
+        binary: BINARY.iter().filter(|p| has(p, c)).cloned().collect(),
+    }
+}
+
+/// The characters `arg` stands for: itself, or a code point written
+/// `U+00E9` or `\u{e9}`.
+pub fn chars(arg: &str) -> Vec<char> {
+    let hex = arg.strip_prefix("U+")
+        .or_else(|| arg.strip_prefix("u+"))
+        .or_else(|| arg.strip_prefix("\\u{").and_then(|h| h.strip_suffix('}')));
+    match hex.and_then(|h| u32::from_str_radix(h, 16).ok()).and_then(::std::char::from_u32) {
+        Some(c) => vec![c],
+        None => arg.chars().collect(),
+    }
+}
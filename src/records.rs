@@ -0,0 +1,305 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Reading input a record at a time: by default lines, but records
+//! can also end in NUL, as `find -print0` writes them, in any other
+//! text, or at blank lines. How long a record may get is capped so a
+//! file without delimiters isn't read into memory whole.
+
+use std::io::{self, BufRead};
+
+/// What ends a record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Delimiter {
+    Line,
+    Nul,
+    Text(Vec<u8>),
+    /// One or more blank lines, so each paragraph is a record.
+    Paragraph,
+}
+
+impl Delimiter {
+    /// Read a `--delimiter`, where `\n`, `\t`, `\0` and `\\` stand
+    /// for those characters. An empty one splits at blank lines.
+    pub fn from_arg(arg: &str) -> Delimiter {
+        let mut text = String::new();
+        let mut chars = arg.chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some(e @ ('n' | 't' | '0' | 'r' | '\\'))) => {
+                    chars.next();
+                    text.push(match e {
+                        'n' => '\n',
+                        't' => '\t',
+                        '0' => '\0',
+                        'r' => '\r',
+                        _ => '\\',
+                    });
+                }
+                _ => text.push(c),
+            }
+        }
+        match &text[..] {
+            "" => Delimiter::Paragraph,
+            "\n" => Delimiter::Line,
+            "\0" => Delimiter::Nul,
+            _ => Delimiter::Text(text.into_bytes()),
+        }
+    }
+
+    /// What's written after each record printed.
+    pub fn terminator(&self) -> String {
+        match *self {
+            Delimiter::Line => "\n".to_owned(),
+            Delimiter::Nul => "\0".to_owned(),
+            Delimiter::Text(ref t) => String::from_utf8_lossy(t).into_owned(),
+            Delimiter::Paragraph => "\n\n".to_owned(),
+        }
+    }
+}
+
+/// The records of a reader. Records longer than the limit come out
+/// as `None`, and are skipped without being kept.
+pub struct Records<R> {
+    reader: R,
+    delimiter: Delimiter,
+    limit: Option<u64>,
+    /// Bytes read so far.
+    offset: u64,
+}
+
+impl<R: BufRead> Records<R> {
+    /// The lines of `reader`.
+    pub fn new(reader: R, limit: Option<u64>) -> Records<R> {
+        Records::with_delimiter(reader, Delimiter::Line, limit)
+    }
+
+    pub fn with_delimiter(reader: R, delimiter: Delimiter, limit: Option<u64>) -> Records<R> {
+        Records {
+            reader,
+            delimiter,
+            limit,
+            offset: 0,
+        }
+    }
+
+    /// Start counting bytes read at `offset`, for a reader that's
+    /// been moved there.
+    pub fn starting_at(mut self, offset: u64) -> Records<R> {
+        self.offset = offset;
+        self
+    }
+
+    /// The offset of the end of the last record read, including its
+    /// delimiter.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Read up to and past the next `delim`, or to the end. Returns
+    /// `None` at the end, and `Some(None)` for a record over the
+    /// limit.
+    fn read_to(&mut self, delim: &[u8]) -> io::Result<Option<Option<Vec<u8>>>> {
+        let last = delim[delim.len() - 1];
+        let mut record = vec![];
+        // The last bytes read, to spot the delimiter in
+        let mut tail = vec![];
+        let mut too_long = false;
+        let mut read_any = false;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(b) => b,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                break;
+            }
+            read_any = true;
+            let (used, maybe_end) = match available.iter().position(|&b| b == last) {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+            let chunk = &available[..used];
+            tail.extend_from_slice(&chunk[chunk.len().saturating_sub(delim.len())..]);
+            let excess = tail.len().saturating_sub(delim.len());
+            tail.drain(..excess);
+            if !too_long {
+                // The delimiter doesn't count towards the limit
+                let room = self.limit.map(|l| l + delim.len() as u64);
+                if room.is_some_and(|r| (record.len() + chunk.len()) as u64 > r) {
+                    too_long = true;
+                    record = vec![];
+                } else {
+                    record.extend_from_slice(chunk);
+                }
+            }
+            self.reader.consume(used);
+            self.offset += used as u64;
+            if maybe_end && tail == delim {
+                if !too_long {
+                    record.truncate(record.len() - delim.len());
+                }
+                break;
+            }
+        }
+
+        if !read_any {
+            return Ok(None);
+        }
+        if self.limit.is_some_and(|l| record.len() as u64 > l) {
+            too_long = true;
+        }
+        Ok(Some(if too_long { None } else { Some(record) }))
+    }
+
+    /// Read lines up to a blank one, skipping blank lines before.
+    fn read_paragraph(&mut self) -> io::Result<Option<Option<Vec<u8>>>> {
+        let mut record: Option<Vec<u8>> = None;
+        let mut too_long = false;
+        while let Some(line) = self.read_to(b"\n")? {
+            let blank = line.as_ref().is_some_and(|l| l.iter().all(|&b| b == b'\r'));
+            match (blank, record.as_mut()) {
+                (true, None) => continue,
+                (true, Some(_)) => break,
+                (false, r) => {
+                    let r = match r {
+                        Some(r) => {
+                            r.push(b'\n');
+                            r
+                        }
+                        None => record.get_or_insert_with(Vec::new),
+                    };
+                    match line {
+                        Some(l) if !too_long => r.extend(l),
+                        _ => too_long = true,
+                    }
+                    if self.limit.is_some_and(|l| r.len() as u64 > l) {
+                        too_long = true;
+                    }
+                    if too_long {
+                        r.clear();
+                    }
+                }
+            }
+        }
+        Ok(record.map(|r| if too_long { None } else { Some(r) }))
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Option<String>>;
+
+    fn next(&mut self) -> Option<io::Result<Option<String>>> {
+        let read = match self.delimiter {
+            Delimiter::Line => self.read_to(b"\n"),
+            Delimiter::Nul => self.read_to(b"\0"),
+            Delimiter::Text(ref t) => {
+                let t = t.clone();
+                self.read_to(&t)
+            }
+            Delimiter::Paragraph => self.read_paragraph(),
+        };
+        match read {
+            Ok(Some(Some(record))) => {
+                Some(String::from_utf8(record).map(Some).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+                }))
+            }
+            Ok(Some(None)) => Some(Ok(None)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    /// The records of `input` read a few bytes at a time, so
+    /// delimiters get split across reads.
+    fn records(input: &str, delimiter: Delimiter, limit: Option<u64>) -> Vec<Option<String>> {
+        let reader = BufReader::with_capacity(3, input.as_bytes());
+        Records::with_delimiter(reader, delimiter, limit).map(|r| r.unwrap()).collect()
+    }
+
+    fn some(records: &[&str]) -> Vec<Option<String>> {
+        records.iter().map(|r| Some(r.to_string())).collect()
+    }
+
+    #[test]
+    fn reads_lines() {
+        assert_eq!(records("ab\n\ncdefg\nh", Delimiter::Line, None),
+                   some(&["ab", "", "cdefg", "h"]));
+        assert_eq!(records("ab\n", Delimiter::Line, None), some(&["ab"]));
+        assert!(records("", Delimiter::Line, None).is_empty());
+    }
+
+    #[test]
+    fn skips_long_lines() {
+        assert_eq!(records("abcd\nabcde\nab", Delimiter::Line, Some(4)),
+                   vec![Some("abcd".to_owned()), None, Some("ab".to_owned())]);
+        assert_eq!(records("abcde", Delimiter::Line, Some(4)), vec![None]);
+    }
+
+    #[test]
+    fn counts_offsets() {
+        let mut lines = Records::new(BufReader::with_capacity(2, &b"abc\nde\n"[..]), None);
+        lines.next();
+        assert_eq!(lines.offset(), 4);
+        lines.next();
+        assert_eq!(lines.offset(), 7);
+        let lines = Records::new(&b""[..], None).starting_at(10);
+        assert_eq!(lines.offset(), 10);
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error() {
+        let mut lines = Records::new(&b"\xff\n"[..], None);
+        assert_eq!(lines.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reads_delimiter_args() {
+        assert_eq!(Delimiter::from_arg(r"\n"), Delimiter::Line);
+        assert_eq!(Delimiter::from_arg(r"\0"), Delimiter::Nul);
+        assert_eq!(Delimiter::from_arg(""), Delimiter::Paragraph);
+        assert_eq!(Delimiter::from_arg(r"\t;\\\x"), Delimiter::Text(b"\t;\\\\x".to_vec()));
+        assert_eq!(Delimiter::from_arg(r"\r\n").terminator(), "\r\n");
+    }
+
+    #[test]
+    fn reads_delimited_records() {
+        assert_eq!(records("a\0b c\0", Delimiter::Nul, None), some(&["a", "b c"]));
+        let text = Delimiter::Text(b"--".to_vec());
+        assert_eq!(records("a-b--c---d", text, None), some(&["a-b", "c", "-d"]));
+        let text = Delimiter::Text(b"--".to_vec());
+        assert_eq!(records("abcdef--ab--", text, Some(2)),
+                   vec![None, Some("ab".to_owned())]);
+    }
+
+    #[test]
+    fn reads_paragraphs() {
+        let input = "\n\nfirst\nstill first\n\n\r\nsecond\r\n";
+        assert_eq!(records(input, Delimiter::Paragraph, None),
+                   some(&["first\nstill first", "second\r"]));
+        assert_eq!(records("ab\ncd\n\nx\n", Delimiter::Paragraph, Some(4)),
+                   vec![None, Some("x".to_owned())]);
+    }
+}
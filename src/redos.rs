@@ -0,0 +1,372 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Find constructs that take exponential time on backtracking
+//! engines like those of JavaScript, Python or PCRE, and build an
+//! input that triggers it. The regex crate itself is immune, so
+//! this is about patterns that will also be used elsewhere.
+
+use regex::Regex;
+use regex_syntax::ast::{self, Ast};
+use regex_syntax::hir::{Class, Hir, HirKind, Literal, RepetitionKind, RepetitionRange};
+
+use compare;
+use flags::Flags;
+use sample::{self, Rng};
+
+/// How many times the ambiguous part is repeated in an attack.
+const PUMPS: usize = 30;
+
+/// Inputs sampled when looking for text a repeated expression
+/// can match in more than one way.
+const SAMPLES: usize = 500;
+
+/// Characters tried after the pumped part to make the match fail.
+const SUFFIXES: &[&str] = &["!", "\u{0}", " ", "\n", "a", "0", "_", "-"];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// A repeated sub-expression that is itself repeated, like
+    /// `(a+)*`.
+    NestedQuantifier,
+    /// A repeated alternation that can match some text in more
+    /// than one way, like `(a|aa)*`.
+    AmbiguousAlternation,
+}
+
+/// A risky construct.
+#[derive(Clone, Debug)]
+pub struct Risk {
+    pub kind: Kind,
+    /// Byte offsets of the construct in the pattern.
+    pub start: usize,
+    pub end: usize,
+    /// Text the construct can match in more than one way.
+    pub pump: String,
+    /// An input a backtracking engine should take exponential time
+    /// to reject, if one was found.
+    pub attack: Option<String>,
+}
+
+/// What the walk needs besides the node it's looking at.
+struct Context<'a> {
+    pattern: &'a str,
+    flags: Flags,
+    /// The whole pattern, anchored at both ends.
+    full: Regex,
+    risks: Vec<Risk>,
+}
+
+impl<'a> Context<'a> {
+    /// The HIR of the part of the pattern `span` covers.
+    fn hir(&self, span: &ast::Span) -> Option<Hir> {
+        self.flags.parser().parse(&self.pattern[span.start.offset..span.end.offset]).ok()
+    }
+}
+
+/// Look for risky constructs in `pattern`, compiled with `flags`.
+pub fn analyze(pattern: &str, flags: Flags) -> Result<Vec<Risk>, String> {
+    let ast = flags.ast_parser().parse(pattern).map_err(|e| e.to_string())?;
+    let hir = flags.parser().parse(pattern).map_err(|e| e.to_string())?;
+    let full = Regex::new(&compare::full_match(&hir).to_string()).map_err(|e| e.to_string())?;
+    let mut ctx = Context {
+        pattern,
+        flags,
+        full,
+        risks: vec![],
+    };
+    walk(&mut ctx, &ast, "");
+    Ok(ctx.risks)
+}
+
+/// Look through `ast`, which is reached after matching `prefix`.
+fn walk(ctx: &mut Context, ast: &Ast, prefix: &str) {
+    match *ast {
+        Ast::Repetition(ref rep) => {
+            if unbounded(&rep.op.kind) {
+                check(ctx, rep, prefix);
+            }
+            walk(ctx, &rep.ast, prefix);
+        }
+        Ast::Group(ref group) => walk(ctx, &group.ast, prefix),
+        Ast::Concat(ref concat) => {
+            let mut prefix = prefix.to_owned();
+            for a in &concat.asts {
+                walk(ctx, a, &prefix);
+                if let Some(h) = ctx.hir(a.span()) {
+                    prefix.push_str(&shortest(&h));
+                }
+            }
+        }
+        Ast::Alternation(ref alt) => {
+            for a in &alt.asts {
+                walk(ctx, a, prefix);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check the operand of an unbounded repetition `rep`.
+fn check(ctx: &mut Context, rep: &ast::Repetition, prefix: &str) {
+    let operand = match ctx.hir(rep.ast.span()) {
+        Some(h) => h,
+        None => return,
+    };
+    let operand = ungroup(&operand);
+    let found = match *operand.kind() {
+        HirKind::Alternation(ref hirs) => {
+            overlap(hirs)
+                .or_else(|| splits(operand))
+                .map(|pump| (Kind::AmbiguousAlternation, pump))
+        }
+        _ if nested(operand) => Some((Kind::NestedQuantifier, nonempty(operand))),
+        _ if repeats(operand) => splits(operand).map(|pump| (Kind::NestedQuantifier, pump)),
+        _ => None,
+    };
+    let (kind, pump) = match found {
+        Some(f) => f,
+        None => return,
+    };
+    let attack = attack(prefix, &pump, &ctx.full);
+    ctx.risks.push(Risk {
+        kind,
+        start: rep.span.start.offset,
+        end: rep.span.end.offset,
+        pump,
+        attack,
+    });
+}
+
+/// Is `hir` a repeated expression, or a sequence of one and parts
+/// that can all match nothing? Either way the outer repetition can
+/// split a run of text between its iterations in many ways.
+fn nested(hir: &Hir) -> bool {
+    let repeated = |h: &Hir| match *ungroup(h).kind() {
+        HirKind::Repetition(ref r) => unbounded_hir(&r.kind),
+        _ => false,
+    };
+    match *hir.kind() {
+        HirKind::Concat(ref hirs) => {
+            hirs.iter().any(&repeated) &&
+            hirs.iter().filter(|h| !h.is_match_empty()).count() <= 1
+        }
+        _ => repeated(hir),
+    }
+}
+
+/// Does `hir` contain an unbounded repetition?
+fn repeats(hir: &Hir) -> bool {
+    match *hir.kind() {
+        HirKind::Repetition(ref r) => unbounded_hir(&r.kind) || repeats(&r.hir),
+        HirKind::Group(ref g) => repeats(&g.hir),
+        HirKind::Concat(ref hirs) | HirKind::Alternation(ref hirs) => hirs.iter().any(repeats),
+        _ => false,
+    }
+}
+
+/// Non-empty samples of `hir`, shortest first.
+fn samples(hir: &Hir, rng: &mut Rng) -> Vec<String> {
+    let mut inputs: Vec<String> = (0..SAMPLES)
+        .map(|_| sample::generate(hir, rng))
+        .filter(|s| !s.is_empty())
+        .collect();
+    inputs.sort_by(|x, y| x.len().cmp(&y.len()).then_with(|| x.cmp(y)));
+    inputs.dedup();
+    inputs
+}
+
+/// Non-empty text that two of `branches` both match.
+fn overlap(branches: &[Hir]) -> Option<String> {
+    let langs: Vec<compare::Language> = branches.iter()
+        .filter_map(|b| compare::Language::new(&b.to_string(), Flags::default()).ok())
+        .collect();
+    if langs.len() != branches.len() {
+        return None;
+    }
+    let mut rng = Rng::new(1);
+    for (i, branch) in branches.iter().enumerate() {
+        for input in samples(branch, &mut rng) {
+            if langs.iter().enumerate().any(|(j, l)| j != i && l.contains(&input)) {
+                return Some(input);
+            }
+        }
+    }
+    None
+}
+
+/// Non-empty text that one iteration of `hir` matches, and that
+/// two or more iterations match as well.
+fn splits(hir: &Hir) -> Option<String> {
+    if hir.is_match_empty() {
+        return None;
+    }
+    let several = Regex::new(&format!("\\A(?:{0})(?:{0})+\\z", hir)).ok()?;
+    samples(hir, &mut Rng::new(1)).into_iter().find(|s| several.is_match(s))
+}
+
+/// `prefix`, `pump` many times and a suffix that makes the whole
+/// pattern fail to match, so a backtracking engine tries every way
+/// of splitting the pumped text before giving up.
+fn attack(prefix: &str, pump: &str, full: &Regex) -> Option<String> {
+    let body = format!("{}{}", prefix, pump.repeat(PUMPS));
+    SUFFIXES.iter()
+        .map(|s| format!("{}{}", body, s))
+        .find(|input| !full.is_match(input))
+}
+
+fn ungroup(mut hir: &Hir) -> &Hir {
+    while let HirKind::Group(ref g) = *hir.kind() {
+        hir = &g.hir;
+    }
+    hir
+}
+
+fn unbounded(kind: &ast::RepetitionKind) -> bool {
+    matches!(*kind,
+             ast::RepetitionKind::ZeroOrMore | ast::RepetitionKind::OneOrMore |
+             ast::RepetitionKind::Range(ast::RepetitionRange::AtLeast(_)))
+}
+
+fn unbounded_hir(kind: &RepetitionKind) -> bool {
+    matches!(*kind,
+             RepetitionKind::ZeroOrMore | RepetitionKind::OneOrMore |
+             RepetitionKind::Range(RepetitionRange::AtLeast(_)))
+}
+
+/// The shortest string `hir` matches, ignoring assertions, taking
+/// the first branch and class member where there's a choice.
+fn shortest(hir: &Hir) -> String {
+    match *hir.kind() {
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => String::new(),
+        HirKind::Literal(Literal::Unicode(c)) => c.to_string(),
+        HirKind::Literal(Literal::Byte(b)) => (b as char).to_string(),
+        HirKind::Class(ref class) => first(class).map(|c| c.to_string()).unwrap_or_default(),
+        HirKind::Repetition(ref rep) => {
+            let min = match rep.kind {
+                RepetitionKind::ZeroOrOne | RepetitionKind::ZeroOrMore => 0,
+                RepetitionKind::OneOrMore => 1,
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) |
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) |
+                RepetitionKind::Range(RepetitionRange::Bounded(n, _)) => n as usize,
+            };
+            shortest(&rep.hir).repeat(min)
+        }
+        HirKind::Group(ref group) => shortest(&group.hir),
+        HirKind::Concat(ref hirs) => hirs.iter().map(shortest).collect(),
+        HirKind::Alternation(ref hirs) => {
+            hirs.iter().map(shortest).min_by_key(|s| s.len()).unwrap_or_default()
+        }
+    }
+}
+
+/// The shortest non-empty string `hir` matches.
+fn nonempty(hir: &Hir) -> String {
+    match *hir.kind() {
+        HirKind::Repetition(ref rep) => {
+            let s = shortest(hir);
+            if s.is_empty() { nonempty(&rep.hir) } else { s }
+        }
+        HirKind::Group(ref group) => nonempty(&group.hir),
+        HirKind::Concat(ref hirs) => {
+            let s: String = hirs.iter().map(shortest).collect();
+            if !s.is_empty() {
+                return s;
+            }
+            hirs.iter().map(nonempty).find(|s| !s.is_empty()).unwrap_or_default()
+        }
+        _ => shortest(hir),
+    }
+}
+
+/// The first printable member of `class`, if any.
+fn first(class: &Class) -> Option<char> {
+    let chars: Vec<char> = match *class {
+        Class::Unicode(ref cls) => cls.iter().flat_map(|r| r.start()..=r.end()).take(128).collect(),
+        Class::Bytes(ref cls) => {
+            cls.iter()
+                .flat_map(|r| r.start()..=r.end())
+                .filter(|&b| b < 0x80)
+                .map(|b| b as char)
+                .collect()
+        }
+    };
+    chars.iter().cloned().find(|c| !c.is_control()).or_else(|| chars.first().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn risks(pattern: &str) -> Vec<(Kind, String, String, Option<String>)> {
+        analyze(pattern, Flags::default())
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.kind, pattern[r.start..r.end].to_owned(), r.pump, r.attack))
+            .collect()
+    }
+
+    #[test]
+    fn safe_patterns_have_no_risks() {
+        assert!(risks(r"^\d+-\w+$").is_empty());
+        assert!(risks("(ab)+").is_empty());
+        assert!(risks("(a|b)*").is_empty());
+        assert!(analyze("(", Flags::default()).is_err());
+    }
+
+    #[test]
+    fn finds_nested_quantifiers() {
+        let found = risks("^(a+)+$");
+        assert_eq!(found.len(), 1);
+        let (kind, construct, pump, attack) = found[0].clone();
+        assert_eq!((kind, &construct[..], &pump[..]), (Kind::NestedQuantifier, "(a+)+", "a"));
+        assert_eq!(attack.unwrap(), format!("{}!", "a".repeat(PUMPS)));
+    }
+
+    #[test]
+    fn finds_ambiguous_alternations() {
+        let found = risks("x(a|aa)*y");
+        assert_eq!(found.len(), 1);
+        let (kind, construct, pump, attack) = found[0].clone();
+        assert_eq!((kind, &construct[..]), (Kind::AmbiguousAlternation, "(a|aa)*"));
+        assert_eq!(pump, "aa");
+        assert_eq!(attack.unwrap(), format!("x{}!", "aa".repeat(PUMPS)));
+    }
+
+    #[test]
+    fn reads_the_pattern_with_the_flags() {
+        // Case insensitive, a and A overlap
+        let insensitive = Flags::default() | Flags::CASE_INSENSITIVE;
+        assert_eq!(analyze("(a|A)*", insensitive).unwrap().len(), 1);
+        let verbose = Flags::default() | Flags::IGNORE_WHITESPACE;
+        let found = analyze("( a + ) +", verbose).unwrap();
+        assert_eq!(found[0].kind, Kind::NestedQuantifier);
+    }
+
+    #[test]
+    fn shortest_strings() {
+        let hir = |p: &str| Flags::default().parser().parse(p).unwrap();
+        assert_eq!(shortest(&hir("ab{2}c*(d|ef)")), "abbd");
+        assert_eq!(nonempty(&hir("a*")), "a");
+        let class = match *hir(r"[\x00-\x1Fb]").kind() {
+            HirKind::Class(ref c) => c.clone(),
+            _ => unreachable!(),
+        };
+        // Control characters are passed over
+        assert_eq!(first(&class), Some('b'));
+    }
+}
@@ -0,0 +1,173 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Looser versions of a pattern that matches nothing, to find out
+//! what about it is too strict.
+
+use regex_syntax::ast::{Ast, AssertionKind, LiteralKind, Span};
+use regex_syntax::ast::parse::Parser;
+
+use suggest::Suggestion;
+
+/// Looser versions of `pattern`, each with one kind of change made
+/// everywhere it applies, then one with every change at once.
+/// Patterns that don't parse have none.
+pub fn relax(pattern: &str) -> Vec<Suggestion> {
+    let ast = match Parser::new().parse(pattern) {
+        Ok(a) => a,
+        Err(_) => return vec![],
+    };
+    let mut spaces = vec![];
+    let mut starts = vec![];
+    let mut ends = vec![];
+    let mut words = vec![];
+    walk(&ast, &mut |a| {
+        match *a {
+            Ast::Literal(ref l) if l.c == ' ' && l.kind == LiteralKind::Verbatim => {
+                spaces.push(l.span)
+            }
+            Ast::Assertion(ref x) => {
+                match x.kind {
+                    AssertionKind::StartLine | AssertionKind::StartText => starts.push(x.span),
+                    AssertionKind::EndLine | AssertionKind::EndText => ends.push(x.span),
+                    AssertionKind::WordBoundary | AssertionKind::NotWordBoundary => {
+                        words.push(x.span)
+                    }
+                }
+            }
+            _ => {}
+        }
+    });
+    let spaces = merge(&spaces);
+
+    let mut out = vec![];
+    let mut every: Vec<(Span, &str)> = vec![];
+    {
+        let mut add = |edits: Vec<(Span, &'static str)>, reason: &str| {
+            if edits.is_empty() {
+                return;
+            }
+            every.extend(edits.iter().cloned());
+            out.push(Suggestion {
+                pattern: apply(pattern, &edits),
+                reason: reason.to_owned(),
+            });
+        };
+        add(spaces.iter().map(|&s| (s, r"\s+")).collect(),
+            "match any run of whitespace for a space");
+        add(starts.iter().map(|&s| (s, "")).collect(), "drop the start anchor");
+        add(ends.iter().map(|&s| (s, "")).collect(), "drop the end anchor");
+        add(words.iter().map(|&s| (s, "")).collect(), "drop the word boundaries");
+    }
+    if pattern.chars().any(|c| c.is_alphabetic()) && !pattern.starts_with("(?i)") {
+        let reasons: Vec<String> = out.iter().map(|s| s.reason.clone()).collect();
+        out.push(Suggestion {
+            pattern: format!("(?i){}", pattern),
+            reason: "ignore case".to_owned(),
+        });
+        if !every.is_empty() {
+            out.push(Suggestion {
+                pattern: format!("(?i){}", apply(pattern, &every)),
+                reason: format!("ignore case, {}", reasons.join(", ")),
+            });
+        }
+    }
+    out
+}
+
+/// Call `f` on `ast` and everything inside it.
+pub fn walk<F: FnMut(&Ast)>(ast: &Ast, f: &mut F) {
+    f(ast);
+    match *ast {
+        Ast::Repetition(ref r) => walk(&r.ast, f),
+        Ast::Group(ref g) => walk(&g.ast, f),
+        Ast::Alternation(ref alt) => alt.asts.iter().for_each(|a| walk(a, f)),
+        Ast::Concat(ref c) => c.asts.iter().for_each(|a| walk(a, f)),
+        _ => {}
+    }
+}
+
+/// Join spans that follow each other, so a run of spaces becomes
+/// one `\s+`.
+fn merge(spans: &[Span]) -> Vec<Span> {
+    let mut out: Vec<Span> = vec![];
+    for &s in spans {
+        match out.last_mut() {
+            Some(last) if last.end.offset == s.start.offset => last.end = s.end,
+            _ => out.push(s),
+        }
+    }
+    out
+}
+
+/// `pattern` with the text of each span replaced.
+pub fn apply(pattern: &str, edits: &[(Span, &str)]) -> String {
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|&(s, _)| s.start.offset);
+    let mut out = String::new();
+    let mut at = 0;
+    for (span, with) in edits {
+        out.push_str(&pattern[at..span.start.offset]);
+        out.push_str(with);
+        at = span.end.offset;
+    }
+    out.push_str(&pattern[at..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relaxed(pattern: &str) -> Vec<(String, String)> {
+        relax(pattern).into_iter().map(|s| (s.pattern, s.reason)).collect()
+    }
+
+    #[test]
+    fn loosens_one_kind_of_thing_at_a_time() {
+        let found = relaxed(r"^error  at\b");
+        let patterns: Vec<&str> = found.iter().map(|s| &s.0[..]).collect();
+        assert_eq!(patterns,
+                   [r"^error\s+at\b",
+                    r"error  at\b",
+                    r"^error  at",
+                    r"(?i)^error  at\b",
+                    r"(?i)error\s+at"]);
+        assert_eq!(found[4].1,
+                   "ignore case, match any run of whitespace for a space, drop the start \
+                    anchor, drop the word boundaries");
+    }
+
+    #[test]
+    fn leaves_alone_what_cant_be_loosened() {
+        assert!(relaxed("[0-9]+").is_empty());
+        assert!(relaxed("(?i)abc").is_empty());
+        assert_eq!(relaxed("abc"), [("(?i)abc".to_owned(), "ignore case".to_owned())]);
+        assert!(relaxed("(").is_empty());
+    }
+
+    #[test]
+    fn replaces_spans() {
+        let ast = Parser::new().parse("a b$").unwrap();
+        let mut spans = vec![];
+        walk(&ast, &mut |a| if let Ast::Literal(ref l) = *a {
+            spans.push(l.span);
+        });
+        assert_eq!(apply("a b$", &[(spans[2], "c"), (spans[0], "")]), " c$");
+        assert_eq!(merge(&spans[..2]).len(), 1);
+    }
+}
@@ -0,0 +1,82 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Renaming a capture group, or naming an unnamed one, found
+//! through the syntax tree so nothing else in the pattern that
+//! looks like a group is touched.
+
+use regex_syntax::ast::{Ast, GroupKind, Span};
+use regex_syntax::ast::parse::Parser;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The pattern doesn't parse.
+    Parse(String),
+    /// No group has that name or number.
+    NoGroup,
+    /// Another group already has the new name.
+    Taken,
+}
+
+/// `pattern` with the group named or numbered `old` named `new`.
+pub fn rename(pattern: &str, old: &str, new: &str) -> Result<String, Error> {
+    let ast = Parser::new().parse(pattern).map_err(|e| Error::Parse(e.to_string()))?;
+    let index = old.parse::<u32>().ok();
+
+    let mut found = None;
+    let mut taken = false;
+    find(&ast, &mut |kind, span| {
+        match *kind {
+            GroupKind::CaptureIndex(i) if Some(i) == index => {
+                // `(` becomes `(?P<new>`
+                let mut at = *span;
+                at.end = at.start;
+                at.end.offset += 1;
+                found = Some((at, format!("(?P<{}>", new)));
+            }
+            GroupKind::CaptureName(ref c) => {
+                if c.name == old || Some(c.index) == index {
+                    found = Some((c.span, new.to_owned()));
+                } else if c.name == new {
+                    taken = true;
+                }
+            }
+            _ => {}
+        }
+    });
+    match found {
+        _ if taken => Err(Error::Taken),
+        Some((span, with)) => {
+            Ok(format!("{}{}{}", &pattern[..span.start.offset], with, &pattern[span.end.offset..]))
+        }
+        None => Err(Error::NoGroup),
+    }
+}
+
+/// Call `f` with the kind and span of every group in `ast`.
+fn find<F: FnMut(&GroupKind, &Span)>(ast: &Ast, f: &mut F) {
+    match *ast {
+        Ast::Group(ref g) => {
+            f(&g.kind, &g.span);
+            find(&g.ast, f);
+        }
+        Ast::Repetition(ref r) => find(&r.ast, f),
+        Ast::Alternation(ref alt) => alt.asts.iter().for_each(|a| find(a, f)),
+        Ast::Concat(ref c) => c.asts.iter().for_each(|a| find(a, f)),
+        _ => {}
+    }
+}
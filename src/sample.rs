@@ -0,0 +1,199 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Generate strings that a pattern is likely to match by
+//! walking its HIR and making random choices.
+
+use time;
+
+use regex_syntax::hir::{Class, Hir, HirKind, Literal, RepetitionKind, RepetitionRange};
+
+/// How many extra iterations an unbounded repetition may take.
+const MAX_EXTRA_REPEATS: u32 = 3;
+
+/// A small xorshift generator. Good enough for picking inputs
+/// and avoids pulling in a dependency just for that.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(if seed == 0 { 0x2545_F491_4F6C_DD1D } else { seed })
+    }
+
+    /// Seed from the current time.
+    pub fn from_time() -> Rng {
+        Rng::new(time::precise_time_ns())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Returns true roughly once every `n` calls.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+}
+
+/// Produce a string built by taking random choices through `hir`.
+/// Assertions are ignored, and bytes above 0x7F, which a `&str`
+/// can't hold, are left out, so the result is not guaranteed to
+/// match; callers are expected to check.
+pub fn generate(hir: &Hir, rng: &mut Rng) -> String {
+    let mut out = String::new();
+    generate_into(hir, rng, &mut out);
+    out
+}
+
+fn generate_into(hir: &Hir, rng: &mut Rng, out: &mut String) {
+    match *hir.kind() {
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => {}
+        HirKind::Literal(Literal::Unicode(c)) => out.push(c),
+        HirKind::Literal(Literal::Byte(b)) if b.is_ascii() => out.push(char::from(b)),
+        HirKind::Literal(Literal::Byte(_)) => {}
+        HirKind::Class(ref class) => {
+            if let Some(c) = pick_from_class(class, rng) {
+                out.push(c);
+            }
+        }
+        HirKind::Repetition(ref rep) => {
+            let (min, max) = repetition_bounds(&rep.kind);
+            let n = min + rng.below(u64::from(max - min) + 1) as u32;
+            for _ in 0..n {
+                generate_into(&rep.hir, rng, out);
+            }
+        }
+        HirKind::Group(ref group) => generate_into(&group.hir, rng, out),
+        HirKind::Concat(ref hirs) => {
+            for h in hirs {
+                generate_into(h, rng, out);
+            }
+        }
+        HirKind::Alternation(ref hirs) => {
+            let i = rng.below(hirs.len() as u64) as usize;
+            generate_into(&hirs[i], rng, out);
+        }
+    }
+}
+
+/// The number of iterations a repetition will be sampled with.
+fn repetition_bounds(kind: &RepetitionKind) -> (u32, u32) {
+    match *kind {
+        RepetitionKind::ZeroOrOne => (0, 1),
+        RepetitionKind::ZeroOrMore => (0, MAX_EXTRA_REPEATS),
+        RepetitionKind::OneOrMore => (1, 1 + MAX_EXTRA_REPEATS),
+        RepetitionKind::Range(RepetitionRange::Exactly(n)) => (n, n),
+        RepetitionKind::Range(RepetitionRange::AtLeast(n)) => (n, n + MAX_EXTRA_REPEATS),
+        RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => {
+            (m, n.min(m + MAX_EXTRA_REPEATS))
+        }
+    }
+}
+
+/// Pick a character from a class, preferring ASCII members since
+/// those are what people usually test with.
+pub fn pick_from_class(class: &Class, rng: &mut Rng) -> Option<char> {
+    let ranges: Vec<(u32, u32)> = match *class {
+        Class::Unicode(ref cls) => {
+            cls.iter().map(|r| (r.start() as u32, r.end() as u32)).collect()
+        }
+        Class::Bytes(ref cls) => {
+            cls.iter()
+                .filter(|r| r.start() < 0x80)
+                .map(|r| (u32::from(r.start()), u32::from(r.end().min(0x7F))))
+                .collect()
+        }
+    };
+    if ranges.is_empty() {
+        return None;
+    }
+
+    let ascii: Vec<(u32, u32)> = ranges.iter()
+        .filter(|r| r.0 < 0x80)
+        .map(|r| (r.0, r.1.min(0x7F)))
+        .collect();
+    let pool = if !ascii.is_empty() && !rng.one_in(4) {
+        &ascii
+    } else {
+        &ranges
+    };
+    let (start, end) = pool[rng.below(pool.len() as u64) as usize];
+    // Ranges may straddle the surrogate block; retry a few times
+    // rather than handle that precisely.
+    for _ in 0..8 {
+        let c = start + rng.below(u64::from(end - start) + 1) as u32;
+        if let Some(c) = ::std::char::from_u32(c) {
+            return Some(c);
+        }
+    }
+    ::std::char::from_u32(start)
+}
+
+/// Make a small random edit to `s`: insert, delete or replace a
+/// character. Useful for finding inputs just outside a language.
+pub fn mutate(s: &str, rng: &mut Rng) -> String {
+    let mut chars: Vec<char> = s.chars().collect();
+    let alphabet = b"aZ09 _-.\n";
+    let c = alphabet[rng.below(alphabet.len() as u64) as usize] as char;
+    let len = chars.len() as u64;
+    match rng.below(3) {
+        0 => {
+            let i = rng.below(len + 1) as usize;
+            chars.insert(i, c);
+        }
+        1 if len > 0 => {
+            chars.remove(rng.below(len) as usize);
+        }
+        _ if len > 0 => {
+            chars[rng.below(len) as usize] = c;
+        }
+        _ => chars.push(c),
+    }
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use regex_syntax::ParserBuilder;
+
+    use super::*;
+
+    fn sample(pattern: &str) -> String {
+        let hir = ParserBuilder::new().allow_invalid_utf8(true).build().parse(pattern).unwrap();
+        generate(&hir, &mut Rng::new(1))
+    }
+
+    #[test]
+    fn ascii_bytes_are_kept() {
+        assert_eq!(sample("(?-u:a\\x41)"), "aA");
+    }
+
+    #[test]
+    fn bytes_past_ascii_are_left_out() {
+        assert_eq!(sample("(?-u:a\\xE9b)"), "ab");
+    }
+}
@@ -0,0 +1,136 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Scripts of lines run as if they were typed at the prompts, with
+//! `--batch <file>` or `:source <file>`:
+//!
+//! ```text
+//! # Dates, year first
+//! @echo off
+//! ^(\d{4})-(\d{2})-(\d{2})$
+//! @echo Should match:
+//! 2024-01-15
+//! :q
+//! ```
+//!
+//! Blank lines and lines starting with `#` are skipped. `@echo off`
+//! and `@echo on` stop and start showing each line with its prompt,
+//! and `@echo <text>` prints the text. Write `\#` or `@@` for a line
+//! that really starts with `#` or `@`.
+
+use std::fs::File;
+use std::io::Read;
+
+/// What a line of a script does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Run the line as if typed.
+    Line(String),
+    /// Turn showing the lines run on or off.
+    Echo(bool),
+    /// Print some text.
+    Say(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    /// Line in the script, starting at 1.
+    pub number: usize,
+    pub step: Step,
+}
+
+/// Parse the text of the script `name`.
+pub fn parse(name: &str, text: &str) -> Result<Vec<Line>, String> {
+    let mut lines = vec![];
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim_start();
+        let step = if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        } else if let Some(rest) = line.strip_prefix("\\#") {
+            Step::Line(format!("#{}", rest))
+        } else if let Some(rest) = line.strip_prefix("@@") {
+            Step::Line(format!("@{}", rest))
+        } else if let Some(directive) = line.strip_prefix('@') {
+            let (word, rest) = match directive.find(' ') {
+                Some(i) => (&directive[..i], &directive[i + 1..]),
+                None => (directive, ""),
+            };
+            match (word, rest.trim()) {
+                ("echo", "on") => Step::Echo(true),
+                ("echo", "off") => Step::Echo(false),
+                ("echo", _) => Step::Say(rest.to_owned()),
+                _ => return Err(msg!(ScriptUnknownDirective, name, n + 1, word)),
+            }
+        } else {
+            Step::Line(line.to_owned())
+        };
+        lines.push(Line {
+            number: n + 1,
+            step,
+        });
+    }
+    Ok(lines)
+}
+
+/// Read and parse the script at `path`.
+pub fn load(path: &str) -> Result<Vec<Line>, String> {
+    let mut text = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut text))
+        .map_err(|e| format!("{}: {}", path, e))?;
+    parse(path, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(number: usize, text: &str) -> Line {
+        Line {
+            number,
+            step: Step::Line(text.to_owned()),
+        }
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let lines = parse("s", "# a comment\n\n  \n  # indented\nabc\r\n").unwrap();
+        assert_eq!(lines, vec![line(5, "abc")]);
+    }
+
+    #[test]
+    fn reads_directives() {
+        let lines = parse("s", "@echo off\n@echo on\n@echo  Some text \n").unwrap();
+        let steps: Vec<Step> = lines.into_iter().map(|l| l.step).collect();
+        assert_eq!(steps,
+                   vec![Step::Echo(false), Step::Echo(true), Step::Say(" Some text ".to_owned())]);
+    }
+
+    #[test]
+    fn escapes_leading_hashes_and_ats() {
+        let lines = parse("s", "\\# not a comment\n@@home\n a@b\n").unwrap();
+        assert_eq!(lines,
+                   vec![line(1, "# not a comment"), line(2, "@home"), line(3, " a@b")]);
+    }
+
+    #[test]
+    fn unknown_directives_are_errors() {
+        let e = parse("s.txt", "abc\n@ech off\n").unwrap_err();
+        assert!(e.contains("s.txt") && e.contains('2') && e.contains("ech"), "{}", e);
+    }
+}
@@ -0,0 +1,162 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A tiny HTTP server answering `GET /match?pattern=..&input=..`
+//! with the same report as `regtest match`. Requests are handled
+//! one at a time, so a client that stays idle is dropped after
+//! `TIMEOUT` rather than holding up the rest.
+
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str;
+use std::time::Duration;
+
+use regex::Regex;
+
+use batch;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+/// How long a client may take to send its request or read the answer.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serve requests on `addr` until the process is killed. Returns
+/// the exit status if the address can't be bound.
+pub fn run(addr: &str, accessible: bool) -> i32 {
+    let listener = match TcpListener::bind(addr) {
+        Ok(l) => l,
+        Err(e) => {
+            writeln!(io::stderr(), "{}", msg!(CantListen, addr, e));
+            return batch::EXIT_ERROR;
+        }
+    };
+    writeln!(io::stderr(), "{}", msg!(Listening, addr));
+    for stream in listener.incoming().flatten() {
+        handle(stream, accessible);
+    }
+    batch::EXIT_MATCH
+}
+
+fn handle(mut stream: TcpStream, accessible: bool) {
+    if stream.set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(TIMEOUT)))
+        .is_err() {
+        return;
+    }
+    let mut request = String::new();
+    if BufReader::new(&stream).read_line(&mut request).is_err() {
+        return;
+    }
+    let target = request.split_whitespace().nth(1).unwrap_or("");
+    let (path, query) = match target.find('?') {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, ""),
+    };
+
+    let (status, body) = if path != "/match" {
+        ("404 Not Found", String::new())
+    } else {
+        respond(query, accessible)
+    };
+    write!(stream,
+           "HTTP/1.0 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: \
+            {}\r\n\r\n{}",
+           status,
+           body.len(),
+           body);
+}
+
+/// The status line and body for a `/match` query.
+fn respond(query: &str, accessible: bool) -> (&'static str, String) {
+    let (mut pattern, mut input) = (None, None);
+    for pair in query.split('&') {
+        let (key, value) = match pair.find('=') {
+            Some(i) => (&pair[..i], decode(&pair[i + 1..])),
+            None => (pair, String::new()),
+        };
+        match key {
+            "pattern" => pattern = Some(value),
+            "input" => input = Some(value),
+            _ => {}
+        }
+    }
+    let (pattern, input) = match (pattern, input) {
+        (Some(p), Some(i)) => (p, i),
+        _ => return ("400 Bad Request", format!("{}\n", msg!(ServeUsage))),
+    };
+
+    let reg = match Regex::new(&pattern) {
+        Ok(r) => r,
+        Err(e) => return ("422 Unprocessable Entity", format!("{}\n", msg!(ErrorCompiling, e))),
+    };
+    let mut out = vec![];
+    batch::write_match(&mut out, &reg, &input, accessible);
+    ("200 OK", String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Undo URL percent-encoding, with `+` for spaces.
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                // `from_str_radix` would also take a sign
+                let hex = Some(&bytes[i + 1..i + 3])
+                    .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|h| str::from_utf8(h).ok());
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_queries() {
+        assert_eq!(decode("a+b%20c%2B%e2%82%ac"), "a b c+\u{20ac}");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%2"), "%2");
+        assert_eq!(decode("%zz%+1"), "%zz% 1");
+        assert_eq!(decode("%ff"), "\u{fffd}");
+    }
+
+    #[test]
+    fn answers_match_queries() {
+        let (status, body) = respond("pattern=%5Cd%2B&input=ab12", false);
+        assert_eq!(status, "200 OK");
+        assert!(body.contains("12"));
+        assert_eq!(respond("pattern=(&input=a", false).0, "422 Unprocessable Entity");
+        assert_eq!(respond("pattern=a", false).0, "400 Bad Request");
+        assert_eq!(respond("", false).0, "400 Bad Request");
+    }
+}
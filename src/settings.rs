@@ -0,0 +1,283 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Named options, the config file setting them and where each
+//! value came from.
+//!
+//! The config file holds one `name = value` per line, with `#`
+//! starting a comment. Settings after a `[profile.<name>]` header
+//! only apply when that profile is picked:
+//!
+//! ```text
+//! capture-groups = on
+//! lang = es
+//!
+//! [profile.logs]
+//! compile-time = off
+//! ```
+//!
+//! Every option can also be set with a `REGTEST_` environment
+//! variable, like `REGTEST_LONG_LINES=wrap`, which wins over the
+//! config file and loses to the command line.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use Config;
+
+/// Options that map onto a `Config` flag.
+pub const FLAGS: &[(&str, Config)] = &[("verbose-errors", Config::VERBOSE_ERRORS),
+                                       ("capture-groups", Config::CAPTURE_GROUPS),
+                                       ("compile-time", Config::COMPILE_TIME),
+                                       ("accessible", Config::ACCESSIBLE),
+                                       ("replace-preview", Config::REPLACE_PREVIEW),
+                                       ("split-delimiters", Config::SPLIT_DELIMITERS),
+                                       ("literal", Config::LITERAL),
+                                       ("smart-case", Config::SMART_CASE),
+                                       ("pager", Config::PAGER)];
+
+/// Options that take a value other than on/off.
+pub const VALUES: &[&str] = &["lang",
+                              "transforms",
+                              "normalize",
+                              "offsets",
+                              "flags",
+                              "long-lines",
+                              "segments",
+                              "on-match",
+                              "history-exclude",
+                              "max-line",
+                              "max-file"];
+
+/// What the environment variables setting options start with.
+pub const ENV_PREFIX: &str = "REGTEST_";
+
+/// Variables that aren't options: the engine, when to color, the
+/// default `--output` and where history is kept.
+pub const ENV_ENGINE: &str = "REGTEST_ENGINE";
+pub const ENV_COLOR: &str = "REGTEST_COLOR";
+pub const ENV_OUTPUT: &str = "REGTEST_OUTPUT";
+pub const ENV_HISTORY: &str = "REGTEST_HISTORY";
+
+/// The variable setting option `name`: `long-lines` is
+/// `REGTEST_LONG_LINES`.
+pub fn env_name(name: &str) -> String {
+    format!("{}{}", ENV_PREFIX, name.to_uppercase().replace('-', "_"))
+}
+
+/// Every option set in the environment, as the variable, the
+/// option and its value.
+pub fn from_env() -> Vec<(String, &'static str, String)> {
+    names()
+        .into_iter()
+        .filter_map(|name| {
+            let var = env_name(name);
+            let value = env::var(&var).ok()?;
+            Some((var, name, value))
+        })
+        .collect()
+}
+
+/// The variables that aren't options and are set, with their values.
+pub fn env_others() -> Vec<(&'static str, String)> {
+    [ENV_ENGINE, ENV_COLOR, ENV_OUTPUT, ENV_HISTORY]
+        .iter()
+        .filter_map(|&var| env::var(var).ok().map(|v| (var, v)))
+        .collect()
+}
+
+/// The engine patterns are compiled with.
+pub const ENGINE: &str = "regex";
+
+/// The versions of the regex crates built in, from `Cargo.lock`.
+pub const REGEX_VERSION: &str = "1.5.6";
+pub const REGEX_SYNTAX_VERSION: &str = "0.6.29";
+
+/// The cargo features of the regex crate built in: its defaults.
+pub const REGEX_FEATURES: &str = "std, perf, unicode";
+
+/// Where the current value of an option came from, lowest
+/// precedence first.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Source {
+    Default,
+    ConfigFile,
+    Profile,
+    Workspace,
+    Environment,
+    CommandLine,
+    /// Changed during the session.
+    Runtime,
+}
+
+impl Source {
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Default => msg!(SourceDefault),
+            Source::ConfigFile => msg!(SourceConfigFile),
+            Source::Profile => msg!(SourceProfile),
+            Source::Workspace => msg!(SourceWorkspace),
+            Source::Environment => msg!(SourceEnvironment),
+            Source::CommandLine => msg!(SourceCommandLine),
+            Source::Runtime => msg!(SourceRuntime),
+        }
+    }
+}
+
+/// The flag for option `name`.
+pub fn flag(name: &str) -> Option<Config> {
+    FLAGS.iter().find(|&&(n, _)| n == name).map(|&(_, f)| f)
+}
+
+/// The name of every option.
+pub fn names() -> Vec<&'static str> {
+    FLAGS.iter().map(|&(n, _)| n).chain(VALUES.iter().cloned()).collect()
+}
+
+/// Units sizes are written in, largest first.
+const UNITS: &[(&str, u64)] = &[("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10)];
+
+/// Read a size in bytes, written like `512K`, `100M` or `2G`.
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    for &(unit, bytes) in UNITS {
+        let lower = unit.to_lowercase();
+        let number = value.strip_suffix(unit).or_else(|| value.strip_suffix(&lower[..]));
+        if let Some(n) = number {
+            return n.trim().parse::<u64>().ok()?.checked_mul(bytes);
+        }
+    }
+    value.parse().ok()
+}
+
+/// Write `bytes` as `parse_size` reads it, in the largest unit that
+/// divides it.
+pub fn size_name(bytes: u64) -> String {
+    match UNITS.iter().find(|&&(_, b)| bytes >= b && bytes.is_multiple_of(b)) {
+        Some(&(unit, b)) => format!("{}{}", bytes / b, unit),
+        None => bytes.to_string(),
+    }
+}
+
+/// Parse an on/off value.
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match &value.to_lowercase()[..] {
+        "on" | "true" | "yes" | "1" => Some(true),
+        "off" | "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// A `name = value` setting and the line it was on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub line: usize,
+    /// The profile the setting belongs to, if any.
+    pub profile: Option<String>,
+    pub name: String,
+    pub value: String,
+}
+
+/// Parse the text of a config file.
+pub fn parse(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    let mut profile = None;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            let section = line[1..line.len() - 1].trim();
+            if !section.starts_with("profile.") || section.len() == "profile.".len() {
+                return Err(format!("line {}: expected [profile.<name>]", n + 1));
+            }
+            profile = Some(section["profile.".len()..].to_owned());
+            continue;
+        }
+        let eq = line.find('=').ok_or_else(|| format!("line {}: expected name = value", n + 1))?;
+        entries.push(Entry {
+            line: n + 1,
+            profile: profile.clone(),
+            name: line[..eq].trim().to_owned(),
+            value: line[eq + 1..].trim().to_owned(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Read and parse the config file at `path`. A missing file has
+/// no entries.
+pub fn load(path: &Path) -> Result<Vec<Entry>, String> {
+    let mut text = String::new();
+    match File::open(path) {
+        Ok(mut f) => {
+            f.read_to_string(&mut text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    }
+    parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config_files() {
+        let text = "# comment\ncapture-groups = on\n\n[profile.logs]\n  compile-time=off # no\n";
+        let entries = parse(text).unwrap();
+        assert_eq!(entries,
+                   vec![Entry {
+                            line: 2,
+                            profile: None,
+                            name: "capture-groups".to_owned(),
+                            value: "on".to_owned(),
+                        },
+                        Entry {
+                            line: 5,
+                            profile: Some("logs".to_owned()),
+                            name: "compile-time".to_owned(),
+                            value: "off # no".to_owned(),
+                        }]);
+    }
+
+    #[test]
+    fn malformed_config_files_are_errors() {
+        assert_eq!(parse("a = 1\nno equals\n").unwrap_err(), "line 2: expected name = value");
+        assert!(parse("[profile.]\n").is_err());
+        assert!(parse("[logs]\n").is_err());
+    }
+
+    #[test]
+    fn a_missing_config_file_is_empty() {
+        assert!(load(Path::new("/nonexistent/regtest.conf")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn names_options() {
+        assert_eq!(flag("pager"), Some(Config::PAGER));
+        assert_eq!(flag("lang"), None);
+        assert!(names().contains(&"lang"));
+        assert_eq!(parse_bool("Yes"), Some(true));
+        assert_eq!(parse_bool("0"), Some(false));
+        assert_eq!(parse_bool("maybe"), None);
+    }
+}
@@ -0,0 +1,95 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Splitting inputs on a pattern, keeping track of the delimiters
+//! `Regex::split` throws away.
+
+use regex::Regex;
+
+/// A field and the delimiter that ended it, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field<'t> {
+    pub text: &'t str,
+    pub delimiter: Option<&'t str>,
+}
+
+/// Split `input` on matches of `reg` into at most `limit` fields,
+/// or as many as there are if `limit` is 0. The fields are the
+/// same as `Regex::split`/`Regex::splitn` give.
+pub fn fields<'t>(reg: &Regex, input: &'t str, limit: usize) -> Vec<Field<'t>> {
+    let mut out = vec![];
+    let mut last = 0;
+    for m in reg.find_iter(input) {
+        if limit > 0 && out.len() + 1 == limit {
+            break;
+        }
+        out.push(Field {
+            text: &input[last..m.start()],
+            delimiter: Some(m.as_str()),
+        });
+        last = m.end();
+    }
+    out.push(Field {
+        text: &input[last..],
+        delimiter: None,
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'t>(fields: &[Field<'t>]) -> Vec<&'t str> {
+        fields.iter().map(|f| f.text).collect()
+    }
+
+    #[test]
+    fn keeps_the_delimiters() {
+        let reg = Regex::new(r"\s*[,;]\s*").unwrap();
+        let fields = fields(&reg, "a, b;c", 0);
+        assert_eq!(fields,
+                   [Field {
+                        text: "a",
+                        delimiter: Some(", "),
+                    },
+                    Field {
+                        text: "b",
+                        delimiter: Some(";"),
+                    },
+                    Field {
+                        text: "c",
+                        delimiter: None,
+                    }]);
+    }
+
+    #[test]
+    fn gives_the_same_fields_as_the_regex_crate() {
+        for &(pattern, input) in &[(",", "a,,b,"), (",", ""), ("x*", "abc"), (r"\d", "1a2")] {
+            let reg = Regex::new(pattern).unwrap();
+            assert_eq!(texts(&fields(&reg, input, 0)), reg.split(input).collect::<Vec<_>>());
+            for limit in 1..4 {
+                assert_eq!(texts(&fields(&reg, input, limit)),
+                           reg.splitn(input, limit).collect::<Vec<_>>(),
+                           "{} on {:?} limit {}",
+                           pattern,
+                           input,
+                           limit);
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Saving scan results to an SQLite database for later querying.
+//! Each run adds to the tables, so one database can hold many:
+//!
+//! ```text
+//! runs(id, pattern, started)
+//! files(id, run_id, path)
+//! matches(id, file_id, line, start, end, text)
+//! groups(match_id, idx, name, start, end, text)
+//! ```
+//!
+//! Offsets are bytes into the line, and a group that took no part
+//! in a match has a null span and text.
+
+use rusqlite::{params, Connection};
+use time;
+
+use output::Input;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        pattern TEXT NOT NULL,
+        started TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS files (
+        id INTEGER PRIMARY KEY,
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        path TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS matches (
+        id INTEGER PRIMARY KEY,
+        file_id INTEGER NOT NULL REFERENCES files(id),
+        line INTEGER NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL,
+        text TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS groups (
+        match_id INTEGER NOT NULL REFERENCES matches(id),
+        idx INTEGER NOT NULL,
+        name TEXT,
+        start INTEGER,
+        end INTEGER,
+        text TEXT
+    );
+";
+
+/// A run being written to a database. Everything is written in one
+/// transaction, committed by `finish`.
+pub struct Store {
+    conn: Connection,
+    run: i64,
+    file: Option<i64>,
+}
+
+impl Store {
+    /// Open or create the database at `path` and start a run of
+    /// `pattern`.
+    pub fn open(path: &str, pattern: &str) -> Result<Store, String> {
+        let err = |e: rusqlite::Error| format!("{}: {}", path, e);
+        let conn = Connection::open(path).map_err(err)?;
+        conn.execute_batch(SCHEMA).map_err(err)?;
+        conn.execute_batch("BEGIN").map_err(err)?;
+        let started = time::now_utc().rfc3339().to_string();
+        conn.execute("INSERT INTO runs (pattern, started) VALUES (?1, ?2)",
+                     params![pattern, started])
+            .map_err(err)?;
+        let run = conn.last_insert_rowid();
+        Ok(Store {
+            conn,
+            run,
+            file: None,
+        })
+    }
+
+    /// Start recording the matches of the file at `path`.
+    pub fn file(&mut self, path: &str) -> rusqlite::Result<()> {
+        self.conn.execute("INSERT INTO files (run_id, path) VALUES (?1, ?2)",
+                          params![self.run, path])?;
+        self.file = Some(self.conn.last_insert_rowid());
+        Ok(())
+    }
+
+    /// Record the matches of `input`, in the current file.
+    pub fn input(&mut self, input: &Input) -> rusqlite::Result<()> {
+        let mut add_match = self.conn
+            .prepare_cached("INSERT INTO matches (file_id, line, start, end, text) \
+                             VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        let mut add_group = self.conn
+            .prepare_cached("INSERT INTO groups (match_id, idx, name, start, end, text) \
+                             VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+        for m in &input.matches {
+            let id = add_match.insert(params![self.file,
+                                              input.line as i64,
+                                              m.start as i64,
+                                              m.end as i64,
+                                              m.text])?;
+            for g in &m.groups {
+                let (start, end, text) = match g.span {
+                    Some((s, e, ref t)) => (Some(s as i64), Some(e as i64), Some(&t[..])),
+                    None => (None, None, None),
+                };
+                add_group.execute(params![id, g.index as i64, g.name, start, end, text])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Commit the run.
+    pub fn finish(self) -> rusqlite::Result<()> {
+        self.conn.execute_batch("COMMIT")
+    }
+}
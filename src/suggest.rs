@@ -0,0 +1,202 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Heuristics turning a pattern that fails to parse into a few
+//! corrected patterns that do.
+
+use regex::{self, Regex};
+use regex_syntax::ast::ErrorKind;
+use regex_syntax::ast::parse::Parser;
+
+/// Errors fixed one after the other before giving up on a
+/// candidate.
+const MAX_FIXES: usize = 5;
+
+/// A corrected pattern and what was changed to get it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub pattern: String,
+    pub reason: String,
+}
+
+/// Suggest corrected versions of `pattern`. Only patterns that
+/// compile are returned.
+pub fn suggest(pattern: &str) -> Vec<Suggestion> {
+    let mut out: Vec<Suggestion> = vec![];
+    for first in fixes(pattern) {
+        // Keep applying the preferred fix until the pattern parses
+        let mut current = first;
+        for _ in 0..MAX_FIXES {
+            if Regex::new(&current.pattern).is_ok() {
+                if !out.iter().any(|s| s.pattern == current.pattern) {
+                    out.push(current);
+                }
+                break;
+            }
+            match fixes(&current.pattern).into_iter().next() {
+                Some(next) => {
+                    let reason = if current.reason.contains(&next.reason) {
+                        current.reason
+                    } else {
+                        format!("{}; {}", current.reason, next.reason)
+                    };
+                    current = Suggestion {
+                        pattern: next.pattern,
+                        reason,
+                    };
+                }
+                None => break,
+            }
+        }
+    }
+    out
+}
+
+/// Why `pattern` looks like text typed as it is rather than a
+/// regex, such as a URL, or `None` if it doesn't. Patterns that
+/// escaping wouldn't change never look literal.
+pub fn looks_literal(pattern: &str) -> Option<&'static str> {
+    if regex::escape(pattern) == pattern {
+        return None;
+    }
+    // The signs below, each only when the character isn't escaped
+    let signs: [(&str, &'static str); 3] =
+        [(r"://", "it has `://`, as in a URL"),
+         (r"\?\w+=", "it has a query string, as in a URL"),
+         (r"\w\.\w+\?", "a `?` follows a dot, as in a URL or file name")];
+    for &(sign, reason) in &signs {
+        let sign = format!(r"(?:^|[^\\])(?:\\\\)*{}", sign);
+        if Regex::new(&sign).unwrap().is_match(pattern) {
+            return Some(reason);
+        }
+    }
+    None
+}
+
+/// Candidate fixes for the first parse error in `pattern`, the
+/// most likely first.
+fn fixes(pattern: &str) -> Vec<Suggestion> {
+    let err = match Parser::new().parse(pattern) {
+        Ok(_) => return vec![],
+        Err(e) => e,
+    };
+    let start = err.span().start.offset;
+    let end = err.span().end.offset;
+    let fix = |pattern: String, reason: &str| {
+        Suggestion {
+            pattern,
+            reason: reason.to_owned(),
+        }
+    };
+    let escape_at = |i: usize| format!("{}\\{}", &pattern[..i], &pattern[i..]);
+
+    match *err.kind() {
+        ErrorKind::GroupUnclosed => {
+            vec![fix(format!("{})", pattern), "close the unbalanced `(`"),
+                 fix(escape_at(start), "match a literal `(`")]
+        }
+        ErrorKind::GroupUnopened => {
+            vec![fix(escape_at(start), "match a literal `)`"),
+                 fix(format!("{}{}", &pattern[..start], &pattern[end..]),
+                     "remove the unbalanced `)`")]
+        }
+        ErrorKind::ClassUnclosed => {
+            vec![fix(format!("{}]", pattern), "close the unterminated class"),
+                 fix(escape_at(start), "match a literal `[`")]
+        }
+        ErrorKind::RepetitionMissing => {
+            vec![fix(escape_at(start), "escape the dangling quantifier")]
+        }
+        // `{,n}` is a common spelling of `{0,n}` elsewhere
+        ErrorKind::DecimalEmpty |
+        ErrorKind::RepetitionCountDecimalEmpty if pattern[..start].ends_with('{') &&
+                                                  pattern[start..].starts_with(',') => {
+            vec![fix(format!("{}0{}", &pattern[..start], &pattern[start..]),
+                     "use `{0,n}` for an upper bound only")]
+        }
+        ErrorKind::RepetitionCountUnclosed |
+        ErrorKind::RepetitionCountInvalid |
+        ErrorKind::RepetitionCountDecimalEmpty |
+        ErrorKind::DecimalEmpty |
+        ErrorKind::DecimalInvalid => {
+            // Find the `{` that started the counted repetition and
+            // escape it along with its `}`, if any.
+            let open = match pattern[..end.max(start + 1).min(pattern.len())].rfind('{') {
+                Some(i) => i,
+                None => return vec![],
+            };
+            let mut fixed = escape_at(open);
+            if let Some(close) = fixed[open + 2..].find('}') {
+                let close = open + 2 + close;
+                fixed = format!("{}\\{}", &fixed[..close], &fixed[close..]);
+            }
+            vec![fix(fixed, "match literal braces with `\\{` and `\\}`")]
+        }
+        ErrorKind::EscapeUnrecognized => {
+            vec![fix(format!("{}{}", &pattern[..start], &pattern[start + 1..]),
+                     "drop the unnecessary backslash")]
+        }
+        ErrorKind::EscapeUnexpectedEof => {
+            vec![fix(format!("{}\\", pattern), "match a literal backslash")]
+        }
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(pattern: &str) -> Vec<String> {
+        suggest(pattern).into_iter().map(|s| s.pattern).collect()
+    }
+
+    #[test]
+    fn fixes_unbalanced_groups_and_classes() {
+        assert_eq!(patterns("(ab"), ["(ab)", r"\(ab"]);
+        assert_eq!(patterns("ab)"), [r"ab\)", "ab"]);
+        assert_eq!(patterns("[ab"), ["[ab]", r"\[ab"]);
+    }
+
+    #[test]
+    fn fixes_quantifiers_and_escapes() {
+        assert_eq!(patterns("*a"), [r"\*a"]);
+        assert_eq!(patterns("a{,3}"), ["a{0,3}"]);
+        assert_eq!(patterns("a{x}"), [r"a\{x\}"]);
+        assert_eq!(patterns(r"a\"), [r"a\\"]);
+        assert_eq!(patterns(r"\y"), ["y"]);
+    }
+
+    #[test]
+    fn keeps_fixing_until_it_compiles() {
+        let found = suggest("((a");
+        assert_eq!(found[0].pattern, "((a))");
+        assert_eq!(found[0].reason, "close the unbalanced `(`");
+        assert!(found.iter().all(|s| Regex::new(&s.pattern).is_ok()));
+        assert!(suggest("fine").is_empty());
+    }
+
+    #[test]
+    fn spots_text_typed_as_it_is() {
+        assert!(looks_literal("http://example.com").is_some());
+        assert!(looks_literal("search?q=1").is_some());
+        assert!(looks_literal("file.txt?").is_some());
+        assert_eq!(looks_literal(r"http:\/\/x"), None);
+        assert_eq!(looks_literal("plain"), None);
+        assert_eq!(looks_literal(r"a\.b+"), None);
+    }
+}
@@ -0,0 +1,233 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Suite files: patterns along with inputs they must and must not
+//! match. One directive per line:
+//!
+//! ```text
+//! # comments start with #
+//! pattern ^\d+$
+//! match 123
+//! nomatch 12a
+//! ```
+//!
+//! Cases apply to the last `pattern` above them. Everything after
+//! the first space is taken literally.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+/// A single expectation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Case {
+    /// Line in the suite file, starting at 1.
+    pub line: usize,
+    pub pattern: String,
+    pub input: String,
+    pub should_match: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suite {
+    pub name: String,
+    pub cases: Vec<Case>,
+}
+
+/// An input that must or must not match whatever pattern is being
+/// tried, as collected with `:expect`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expectation {
+    pub input: String,
+    pub should_match: bool,
+}
+
+impl Expectation {
+    /// Parse `match <input>` or `nomatch <input>`.
+    pub fn parse(text: &str) -> Option<Expectation> {
+        let (directive, input) = match text.find(' ') {
+            Some(i) => (&text[..i], &text[i + 1..]),
+            None => (text, ""),
+        };
+        let should_match = match directive {
+            "match" => true,
+            "nomatch" => false,
+            _ => return None,
+        };
+        Some(Expectation {
+            input: input.to_owned(),
+            should_match,
+        })
+    }
+
+    /// The directive for this expectation in a suite file.
+    pub fn directive(&self) -> &'static str {
+        if self.should_match { "match" } else { "nomatch" }
+    }
+}
+
+/// The text of a suite file holding `expectations` for `pattern`.
+pub fn to_text(pattern: &str, expectations: &[Expectation]) -> String {
+    let mut text = format!("pattern {}\n", pattern);
+    for e in expectations {
+        text.push_str(&format!("{} {}\n", e.directive(), e.input));
+    }
+    text
+}
+
+/// The result of running one case.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// The pattern didn't compile.
+    Error(String),
+}
+
+/// Parse the text of a suite file.
+pub fn parse(name: &str, text: &str) -> Result<Suite, String> {
+    let mut pattern: Option<String> = None;
+    let mut cases = vec![];
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let (directive, rest) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+        let should_match = match directive {
+            "pattern" => {
+                pattern = Some(rest.to_owned());
+                continue;
+            }
+            "match" => true,
+            "nomatch" => false,
+            _ => return Err(format!("line {}: unknown directive {:?}", n + 1, directive)),
+        };
+        let pattern = match pattern {
+            Some(ref p) => p.clone(),
+            None => return Err(format!("line {}: case before any pattern", n + 1)),
+        };
+        cases.push(Case {
+            line: n + 1,
+            pattern,
+            input: rest.to_owned(),
+            should_match,
+        });
+    }
+    Ok(Suite {
+        name: name.to_owned(),
+        cases,
+    })
+}
+
+/// Read and parse the suite file at `path`.
+pub fn load(path: &str) -> Result<Suite, String> {
+    let mut text = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut text))
+        .map_err(|e| format!("{}: {}", path, e))?;
+    parse(path, &text).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Every `.suite` file under `dir`, sorted by path.
+pub fn find(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            found.extend(find(&path)?);
+        } else if path.extension().is_some_and(|e| e == "suite") {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Run every case of `suite`, compiling each pattern once.
+pub fn run(suite: &Suite) -> Vec<Outcome> {
+    let mut compiled: HashMap<&str, Result<Regex, String>> = HashMap::new();
+    suite.cases
+        .iter()
+        .map(|case| {
+            let reg = compiled.entry(&case.pattern)
+                .or_insert_with(|| Regex::new(&case.pattern).map_err(|e| e.to_string()));
+            match *reg {
+                Ok(ref r) if r.is_match(&case.input) == case.should_match => Outcome::Pass,
+                Ok(_) => Outcome::Fail,
+                Err(ref e) => Outcome::Error(e.clone()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cases_take_the_pattern_above_them() {
+        let text = "# digits\npattern ^\\d+$\nmatch 123\n\npattern a b\nnomatch  x\r\n";
+        let suite = parse("s", text).unwrap();
+        assert_eq!(suite.cases,
+                   vec![Case {
+                            line: 3,
+                            pattern: "^\\d+$".to_owned(),
+                            input: "123".to_owned(),
+                            should_match: true,
+                        },
+                        Case {
+                            line: 6,
+                            pattern: "a b".to_owned(),
+                            input: " x".to_owned(),
+                            should_match: false,
+                        }]);
+    }
+
+    #[test]
+    fn malformed_suites_are_errors() {
+        assert_eq!(parse("s", "match 1\n").unwrap_err(), "line 1: case before any pattern");
+        assert_eq!(parse("s", "pattern a\nmatches a\n").unwrap_err(),
+                   "line 2: unknown directive \"matches\"");
+    }
+
+    #[test]
+    fn runs_cases() {
+        let suite = parse("s", "pattern ^a\nmatch abc\nmatch bc\nnomatch bc\npattern (\nmatch (\n")
+            .unwrap();
+        let outcomes = run(&suite);
+        assert_eq!(&outcomes[..3], &[Outcome::Pass, Outcome::Fail, Outcome::Pass]);
+        assert!(matches!(outcomes[3], Outcome::Error(_)));
+    }
+
+    #[test]
+    fn expectations_round_trip() {
+        let expectations = vec![Expectation::parse("match a b").unwrap(),
+                                Expectation::parse("nomatch").unwrap()];
+        assert!(Expectation::parse("maybe x").is_none());
+        let suite = parse("s", &to_text("x", &expectations)).unwrap();
+        assert_eq!(suite.cases.len(), 2);
+        assert_eq!(suite.cases[0].input, "a b");
+        assert!(!suite.cases[1].should_match);
+    }
+}
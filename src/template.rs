@@ -0,0 +1,194 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Replacement templates, parsed the way `Captures::expand` reads
+//! them so each `$n`/`${name}` can be shown with what it became.
+
+use regex::{Captures, Regex};
+
+/// Part of a replacement template.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Piece {
+    Literal(String),
+    /// A group reference, as written and the group it names.
+    Group { written: String, name: String },
+}
+
+/// Split `template` into literals and group references. `$$` is a
+/// literal `$`, `$name` takes the longest run of `[_0-9a-zA-Z]` and
+/// `${name}` takes anything up to the `}`.
+pub fn parse(template: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(i) = rest.find('$') {
+        literal.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let reference = if rest.starts_with("$$") {
+            literal.push('$');
+            rest = &rest[2..];
+            continue;
+        } else if rest.starts_with("${") {
+            rest.find('}').map(|end| (end + 1, &rest[2..end]))
+        } else {
+            let len = rest[1..]
+                .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                .unwrap_or(rest.len() - 1);
+            if len == 0 { None } else { Some((len + 1, &rest[1..len + 1])) }
+        };
+        match reference {
+            Some((len, name)) => {
+                if !literal.is_empty() {
+                    pieces.push(Piece::Literal(literal.clone()));
+                    literal.clear();
+                }
+                pieces.push(Piece::Group {
+                    written: rest[..len].to_owned(),
+                    name: name.to_owned(),
+                });
+                rest = &rest[len..];
+            }
+            // A `$` that doesn't start a reference is kept as is
+            None => {
+                literal.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        pieces.push(Piece::Literal(literal));
+    }
+    pieces
+}
+
+/// `template` with references to the group `old` made to refer to
+/// `new` instead. Any other `$` is written `$$`.
+pub fn rename(template: &str, old: &str, new: &str) -> String {
+    parse(template)
+        .into_iter()
+        .map(|p| match p {
+            Piece::Group { ref name, .. } if name == old => format!("${{{}}}", new),
+            Piece::Group { written, .. } => written,
+            Piece::Literal(text) => text.replace('$', "$$"),
+        })
+        .collect()
+}
+
+/// What a group reference expanded to for one match.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Resolved {
+    Text(String),
+    /// The group exists but took no part in the match.
+    Unset,
+    /// The pattern has no such group.
+    Missing,
+}
+
+/// Resolve each group reference of `pieces` against `caps`.
+pub fn resolve<'p>(reg: &Regex, caps: &Captures, pieces: &'p [Piece]) -> Vec<(&'p str, &'p str, Resolved)> {
+    pieces.iter()
+        .filter_map(|p| match *p {
+            Piece::Group { ref written, ref name } => Some((&written[..], &name[..])),
+            Piece::Literal(_) => None,
+        })
+        .map(|(written, name)| {
+            let exists = match name.parse::<usize>() {
+                Ok(i) => i < reg.captures_len(),
+                Err(_) => reg.capture_names().any(|n| n == Some(name)),
+            };
+            let value = match name.parse::<usize>() {
+                Ok(i) => caps.get(i),
+                Err(_) => caps.name(name),
+            };
+            let resolved = match value {
+                Some(m) => Resolved::Text(m.as_str().to_owned()),
+                None if exists => Resolved::Unset,
+                None => Resolved::Missing,
+            };
+            (written, name, resolved)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(written: &str, name: &str) -> Piece {
+        Piece::Group {
+            written: written.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    fn literal(text: &str) -> Piece {
+        Piece::Literal(text.to_owned())
+    }
+
+    #[test]
+    fn splits_references_from_literals() {
+        assert_eq!(parse("a$1b ${x}-$$-$"),
+                   vec![literal("a"), group("$1b", "1b"), literal(" "), group("${x}", "x"),
+                        literal("-$-$")]);
+        assert_eq!(parse("${open"), vec![literal("${open")]);
+        assert_eq!(parse("$-"), vec![literal("$-")]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn reads_templates_as_expand_does() {
+        let reg = Regex::new(r"(?P<y>\d{4})-(\d{2})").unwrap();
+        let caps = reg.captures("2024-05").unwrap();
+        for template in &["$2/${y}", "$y$2", "${2}x$$", "$3-$z", "$1a"] {
+            let mut expanded = String::new();
+            caps.expand(template, &mut expanded);
+            let pieces = parse(template);
+            let resolved = resolve(&reg, &caps, &pieces);
+            let mut groups = resolved.iter();
+            let ours: String = pieces.iter()
+                .map(|p| match *p {
+                    Piece::Literal(ref text) => text.clone(),
+                    Piece::Group { .. } => match groups.next().unwrap().2 {
+                        Resolved::Text(ref text) => text.clone(),
+                        _ => String::new(),
+                    },
+                })
+                .collect();
+            assert_eq!(ours, expanded, "{}", template);
+        }
+    }
+
+    #[test]
+    fn tells_unset_groups_from_missing_ones() {
+        let reg = Regex::new(r"(a)|(b)").unwrap();
+        let caps = reg.captures("b").unwrap();
+        let pieces = parse("$1$2$3");
+        let resolved: Vec<Resolved> = resolve(&reg, &caps, &pieces)
+            .into_iter()
+            .map(|r| r.2)
+            .collect();
+        assert_eq!(resolved,
+                   vec![Resolved::Unset, Resolved::Text("b".to_owned()), Resolved::Missing]);
+    }
+
+    #[test]
+    fn renames_references() {
+        assert_eq!(rename("$old ${old}x $1 $$", "old", "new"), "${new} ${new}x $1 $$");
+    }
+}
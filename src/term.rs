@@ -0,0 +1,36 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! The size of the terminal output goes to.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use rustyline::Editor;
+
+/// The columns and rows of the terminal, or `None` if stdout isn't
+/// one. `COLUMNS` and `LINES` take precedence when set.
+pub fn size() -> Option<(usize, usize)> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let (cols, rows) = Editor::<()>::new().dimensions()?;
+    let var = |name: &str| env::var(name).ok().and_then(|v| v.parse().ok()).filter(|&n| n > 0);
+    let size = (var("COLUMNS").unwrap_or(cols), var("LINES").unwrap_or(rows));
+    debug!("terminal is {}x{}", size.0, size.1);
+    Some(size)
+}
@@ -0,0 +1,136 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Reading the time of a log line from one of the groups of its
+//! match, to filter lines by time and count them per minute or
+//! hour.
+
+use regex::Regex;
+use time::{self, Tm};
+
+/// The `--time-format` used when none is given.
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// A point in time, as year, month, day, hour, minute, second and
+/// nanosecond, so times compare in order without a time zone.
+pub type Stamp = (i32, i32, i32, i32, i32, i32, i32);
+
+/// Read `text` as a time written in `format`, in `strftime` syntax.
+pub fn parse(text: &str, format: &str) -> Option<Stamp> {
+    let tm = time::strptime(text, format).ok()?;
+    Some(stamp(&tm))
+}
+
+fn stamp(tm: &Tm) -> Stamp {
+    (tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec, tm.tm_nsec)
+}
+
+/// How times are grouped when counting lines.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bucket {
+    Minute,
+    Hour,
+}
+
+impl Bucket {
+    pub fn from_name(name: &str) -> Option<Bucket> {
+        match name {
+            "minute" => Some(Bucket::Minute),
+            "hour" => Some(Bucket::Hour),
+            _ => None,
+        }
+    }
+
+    /// The start of the bucket `s` falls in, which also sorts in
+    /// time order.
+    pub fn label(self, s: Stamp) -> String {
+        let minute = match self {
+            Bucket::Minute => s.4,
+            Bucket::Hour => 0,
+        };
+        format!("{:04}-{:02}-{:02} {:02}:{:02}", s.0, s.1, s.2, s.3, minute)
+    }
+}
+
+/// Which group holds the time of a line, how it's written and the
+/// range of times wanted.
+#[derive(Clone, Debug)]
+pub struct TimeFilter {
+    /// The group, by number or name.
+    pub group: String,
+    pub format: String,
+    /// The earliest time kept.
+    pub since: Option<Stamp>,
+    /// Times from this one on are left out.
+    pub until: Option<Stamp>,
+}
+
+impl TimeFilter {
+    /// The time of the first match of `reg` in `line`, if the group
+    /// took part and reads as a time.
+    pub fn time_of(&self, reg: &Regex, line: &str) -> Option<Stamp> {
+        let caps = reg.captures(line)?;
+        let m = match self.group.parse::<usize>() {
+            Ok(i) => caps.get(i),
+            Err(_) => caps.name(&self.group),
+        }?;
+        parse(m.as_str(), &self.format)
+    }
+
+    /// Is `s` in the range of times wanted?
+    pub fn contains(&self, s: Stamp) -> bool {
+        self.since.is_none_or(|since| s >= since) && self.until.is_none_or(|until| s < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse("2024-03-09T14:05:59", DEFAULT_FORMAT), Some((2024, 3, 9, 14, 5, 59, 0)));
+        assert_eq!(parse("09/Mar/2024:14:05", "%d/%b/%Y:%H:%M"), Some((2024, 3, 9, 14, 5, 0, 0)));
+        assert_eq!(parse("2024-03-09", DEFAULT_FORMAT), None);
+        assert_eq!(parse("yesterday", DEFAULT_FORMAT), None);
+    }
+
+    #[test]
+    fn labels_buckets() {
+        let s = (2024, 3, 9, 14, 5, 59, 0);
+        assert_eq!(Bucket::Minute.label(s), "2024-03-09 14:05");
+        assert_eq!(Bucket::Hour.label(s), "2024-03-09 14:00");
+        assert_eq!(Bucket::from_name("day"), None);
+    }
+
+    #[test]
+    fn filters_lines_by_time() {
+        let filter = TimeFilter {
+            group: "t".to_owned(),
+            format: DEFAULT_FORMAT.to_owned(),
+            since: parse("2024-03-09T00:00:00", DEFAULT_FORMAT),
+            until: parse("2024-03-10T00:00:00", DEFAULT_FORMAT),
+        };
+        let reg = Regex::new(r"^(?P<t>\S+) (\w+)").unwrap();
+        let time = filter.time_of(&reg, "2024-03-09T23:59:59 GET").unwrap();
+        assert!(filter.contains(time));
+        let time = filter.time_of(&reg, "2024-03-10T00:00:00 GET").unwrap();
+        assert!(!filter.contains(time));
+        assert_eq!(filter.time_of(&reg, "garbled GET"), None);
+        assert_eq!(filter.time_of(&reg, "nomatch"), None);
+    }
+}
@@ -0,0 +1,443 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Reading TOML documents, enough for grading specs and exercise
+//! packs: `key = value` pairs, `[table]` and `[[array of tables]]`
+//! headers one name deep, and strings, numbers, booleans and arrays
+//! as values. Documents are read into the same `Value` as JSON.
+
+use std::char;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use json::Value;
+
+/// How deep arrays may be nested, so a hostile document can't run the
+/// parser out of stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parse the TOML document `text` into an object.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut p = Parser { chars: text.chars().peekable(), line: 1, depth: 0 };
+    let mut root = vec![];
+    // The table keys go into: none for the root, or the name of a
+    // table header
+    let mut table: Option<String> = None;
+    loop {
+        p.blank();
+        match p.chars.peek().cloned() {
+            None => break,
+            Some('[') => {
+                p.next();
+                let array = p.chars.peek() == Some(&'[');
+                if array {
+                    p.next();
+                }
+                p.spaces();
+                let name = p.key()?;
+                p.spaces();
+                p.expect(']')?;
+                if array {
+                    p.expect(']')?;
+                }
+                let existing = root.iter().position(|m: &(String, Value)| m.0 == name);
+                match (existing, array) {
+                    (Some(i), true) => {
+                        match root[i].1 {
+                            Value::Array(ref mut items) => items.push(Value::Object(vec![])),
+                            _ => return Err(p.error(&format!("`{}` defined twice", name))),
+                        }
+                    }
+                    (None, true) => {
+                        root.push((name.clone(), Value::Array(vec![Value::Object(vec![])])))
+                    }
+                    (Some(_), false) => return Err(p.error(&format!("`{}` defined twice", name))),
+                    (None, false) => root.push((name.clone(), Value::Object(vec![]))),
+                }
+                table = Some(name);
+            }
+            Some(_) => {
+                let key = p.key()?;
+                p.spaces();
+                p.expect('=')?;
+                p.spaces();
+                let value = p.value()?;
+                let members = match table {
+                    None => &mut root,
+                    Some(ref name) => members_of(&mut root, name),
+                };
+                if members.iter().any(|m| m.0 == key) {
+                    return Err(p.error(&format!("`{}` defined twice", key)));
+                }
+                members.push((key, value));
+            }
+        }
+        p.end_of_line()?;
+    }
+    Ok(Value::Object(root))
+}
+
+/// The members of the table `name`, or of the last table in the
+/// array `name`.
+fn members_of<'a>(root: &'a mut [(String, Value)], name: &str) -> &'a mut Vec<(String, Value)> {
+    let value = &mut root.iter_mut().find(|m| m.0 == name).unwrap().1;
+    let table = match *value {
+        Value::Array(ref mut items) => items.last_mut().unwrap(),
+        ref mut v => v,
+    };
+    match *table {
+        Value::Object(ref mut members) => members,
+        _ => unreachable!(),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    /// Line being read, for errors.
+    line: usize,
+    /// Arrays open around the value being read.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn expected(&self, what: &str) -> String {
+        self.error(&format!("expected {}", what))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.next() == Some(c) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("`{}`", c)))
+        }
+    }
+
+    /// Skip spaces and tabs.
+    fn spaces(&mut self) {
+        while self.chars.peek().is_some_and(|&c| c == ' ' || c == '\t') {
+            self.next();
+        }
+    }
+
+    fn comment(&mut self) {
+        if self.chars.peek() == Some(&'#') {
+            while self.chars.peek().is_some_and(|&c| c != '\n') {
+                self.next();
+            }
+        }
+    }
+
+    /// Skip whitespace, newlines and comments.
+    fn blank(&mut self) {
+        loop {
+            self.spaces();
+            self.comment();
+            match self.chars.peek() {
+                Some(&'\n') | Some(&'\r') => {
+                    self.next();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Nothing but a comment may follow a key or header on its line.
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.spaces();
+        self.comment();
+        if self.chars.peek() == Some(&'\r') {
+            self.next();
+        }
+        match self.next() {
+            None | Some('\n') => Ok(()),
+            Some(_) => Err(self.expected("the end of the line")),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.chars.peek() {
+            Some(&'"') => return self.basic_string(),
+            Some(&'\'') => return self.literal_string(),
+            _ => {}
+        }
+        let mut key = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                key.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        if key.is_empty() {
+            return Err(self.expected("a key"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.chars.peek().cloned() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('t') => self.word("true", Value::Bool(true)),
+            Some('f') => self.word("false", Value::Bool(false)),
+            Some(c) if c == '-' || c == '+' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.expected("a value")),
+        }
+    }
+
+    fn word(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for c in word.chars() {
+            if self.next() != Some(c) {
+                return Err(self.expected(word));
+            }
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || "+-.eE_".contains(c) {
+                if c != '_' {
+                    text.push(c);
+                }
+                self.next();
+            } else {
+                break;
+            }
+        }
+        text.parse().map(Value::Number).map_err(|_| self.expected("a number"))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("arrays nested more than {} deep", MAX_DEPTH)));
+        }
+        let mut items = vec![];
+        loop {
+            self.blank();
+            if self.chars.peek() == Some(&']') {
+                self.next();
+                return Ok(Value::Array(items));
+            }
+            self.depth += 1;
+            let item = self.value();
+            self.depth -= 1;
+            items.push(item?);
+            self.blank();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.expected("`,` or `]`")),
+            }
+        }
+    }
+
+    /// Are the next characters `"""` or `'''`? They're read if so.
+    fn triple(&mut self, quote: char) -> bool {
+        let mut ahead = self.chars.clone();
+        if ahead.next() == Some(quote) && ahead.next() == Some(quote) &&
+           ahead.next() == Some(quote) {
+            for _ in 0..3 {
+                self.next();
+            }
+            // A newline right after the opening quotes isn't part of
+            // the string
+            if self.chars.peek() == Some(&'\r') {
+                self.next();
+            }
+            if self.chars.peek() == Some(&'\n') {
+                self.next();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Is the string closed by the next characters? They're read if
+    /// so.
+    fn closes(&mut self, quote: char, multiline: bool) -> bool {
+        if !multiline {
+            return true;
+        }
+        let mut ahead = self.chars.clone();
+        if ahead.next() == Some(quote) && ahead.next() == Some(quote) {
+            self.next();
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        let multiline = self.triple('"');
+        if !multiline {
+            self.expect('"')?;
+        }
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') if self.closes('"', multiline) => return Ok(out),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('e') => '\u{1b}',
+                        Some('u') => self.unicode(4)?,
+                        Some('U') => self.unicode(8)?,
+                        Some(c @ '"') | Some(c @ '\\') => c,
+                        // A backslash ending a line of a multi-line
+                        // string joins it to the next text
+                        Some('\n') | Some('\r') if multiline => {
+                            while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+                                self.next();
+                            }
+                            continue;
+                        }
+                        _ => return Err(self.expected("an escape")),
+                    };
+                    out.push(c);
+                }
+                Some('\n') if !multiline => return Err(self.expected("`\"`")),
+                Some(c) => out.push(c),
+                None => return Err(self.expected("`\"`")),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        let multiline = self.triple('\'');
+        if !multiline {
+            self.expect('\'')?;
+        }
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('\'') if self.closes('\'', multiline) => return Ok(out),
+                Some('\n') if !multiline => return Err(self.expected("`'`")),
+                Some(c) => out.push(c),
+                None => return Err(self.expected("`'`")),
+            }
+        }
+    }
+
+    fn unicode(&mut self, digits: usize) -> Result<char, String> {
+        let mut n = 0;
+        for _ in 0..digits {
+            let d = self.next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.expected("a hex digit"))?;
+            n = n * 16 + d;
+        }
+        char::from_u32(n).ok_or_else(|| self.expected("a character"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(doc: &'a Value, key: &str) -> &'a Value {
+        doc.get(key).unwrap()
+    }
+
+    #[test]
+    fn reads_tables_and_arrays_of_tables() {
+        let doc = parse("title = \"Dates\" # a comment\n\
+                         [flags]\n\
+                         case = true\n\
+                         [[case]]\n\
+                         input = 'x'\n\
+                         [[case]]\n\
+                         input = \"y\"\n")
+            .unwrap();
+        assert_eq!(get(&doc, "title").as_str(), Some("Dates"));
+        assert_eq!(get(get(&doc, "flags"), "case").as_bool(), Some(true));
+        let cases = get(&doc, "case").as_array().unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(get(&cases[1], "input").as_str(), Some("y"));
+    }
+
+    #[test]
+    fn reads_strings_numbers_and_arrays() {
+        let doc = parse("a = \"tab\\there \\u00e9\"\n\
+                         b = 'C:\\path'\n\
+                         c = \"\"\"\nline one\nline two\"\"\"\n\
+                         d = 1_000\n\
+                         e = [1, [2, 3], \"four\",]\n")
+            .unwrap();
+        assert_eq!(get(&doc, "a").as_str(), Some("tab\there \u{e9}"));
+        assert_eq!(get(&doc, "b").as_str(), Some("C:\\path"));
+        assert_eq!(get(&doc, "c").as_str(), Some("line one\nline two"));
+        assert_eq!(get(&doc, "d").as_number(), Some(1000.0));
+        assert_eq!(get(&doc, "e").as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn unterminated_strings_are_errors() {
+        assert_eq!(parse("a = \"open\nb = 1\n"), Err("line 2: expected `\"`".to_owned()));
+        assert!(parse("a = 'open").is_err());
+        assert!(parse("a = \"\"\"never closed\n").is_err());
+    }
+
+    #[test]
+    fn bad_escapes_are_errors() {
+        assert!(parse("a = \"\\q\"").is_err());
+        assert!(parse("a = \"\\u12\"").is_err());
+        assert!(parse("a = \"\\UFFFFFFFF\"").is_err());
+        assert!(parse("a = \"\\").is_err());
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        assert_eq!(parse("a = 1\na = 2\n"), Err("line 2: `a` defined twice".to_owned()));
+        assert!(parse("a = 1 b = 2").is_err());
+        assert!(parse("= 1").is_err());
+        assert!(parse("a = ").is_err());
+        assert!(parse("a = [1, 2").is_err());
+        assert!(parse("[table").is_err());
+        assert!(parse("a = nope").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let deep = format!("a = {}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(parse(&deep).unwrap_err().contains("nested"));
+        let fine = format!("a = {}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&fine).is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Transforms applied to inputs before they're matched, to mimic
+//! how an application cleans up its data.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// A single input transform.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Transform {
+    /// Remove leading and trailing whitespace.
+    Trim,
+    /// Replace each run of whitespace with a single space.
+    Collapse,
+    Lowercase,
+    /// Remove ANSI terminal escape sequences.
+    StripAnsi,
+}
+
+/// Every transform and its name.
+pub const TRANSFORMS: &[(&str, Transform)] = &[("trim", Transform::Trim),
+                                               ("collapse", Transform::Collapse),
+                                               ("lower", Transform::Lowercase),
+                                               ("strip-ansi", Transform::StripAnsi)];
+
+impl Transform {
+    pub fn name(self) -> &'static str {
+        TRANSFORMS.iter().find(|&&(_, t)| t == self).unwrap().0
+    }
+
+    pub fn apply(self, input: &str) -> String {
+        match self {
+            Transform::Trim => input.trim().to_owned(),
+            Transform::Collapse => input.split_whitespace().collect::<Vec<_>>().join(" "),
+            Transform::Lowercase => input.to_lowercase(),
+            Transform::StripAnsi => strip_ansi(input),
+        }
+    }
+}
+
+/// Parse a list of transform names separated by commas or spaces.
+pub fn parse_list(list: &str) -> Result<Vec<Transform>, String> {
+    list.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            TRANSFORMS.iter()
+                .find(|&&(n, _)| n == name)
+                .map(|&(_, t)| t)
+                .ok_or_else(|| name.to_owned())
+        })
+        .collect()
+}
+
+/// The names of `transforms`, separated by commas.
+pub fn names(transforms: &[Transform]) -> String {
+    transforms.iter().map(|t| t.name()).collect::<Vec<_>>().join(",")
+}
+
+/// Run `input` through each of `transforms` in turn.
+pub fn apply_all(transforms: &[Transform], input: &str) -> String {
+    transforms.iter().fold(input.to_owned(), |s, t| t.apply(&s))
+}
+
+/// A Unicode normalization form.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Normalization {
+    None,
+    /// Canonical composition, so `e` + U+0301 becomes `é`.
+    Nfc,
+    /// Canonical decomposition, so `é` becomes `e` + U+0301.
+    Nfd,
+}
+
+impl Normalization {
+    pub fn from_name(name: &str) -> Option<Normalization> {
+        match &name.to_lowercase()[..] {
+            "none" => Some(Normalization::None),
+            "nfc" => Some(Normalization::Nfc),
+            "nfd" => Some(Normalization::Nfd),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Normalization::None => "none",
+            Normalization::Nfc => "nfc",
+            Normalization::Nfd => "nfd",
+        }
+    }
+
+    pub fn apply(self, input: &str) -> String {
+        match self {
+            Normalization::None => input.to_owned(),
+            Normalization::Nfc => input.nfc().collect(),
+            Normalization::Nfd => input.nfd().collect(),
+        }
+    }
+}
+
+/// Remove CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or
+/// `ESC ] ... ESC \`) and other `ESC ... final` sequences.
+fn strip_ansi(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                // Parameters and intermediates, then a final byte
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Intermediate bytes, like the `(` of `ESC ( B`, then a
+            // final byte
+            Some(c) if (' '..='/').contains(&c) => {
+                for c in chars.by_ref() {
+                    if !(' '..='/').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lists() {
+        assert_eq!(parse_list("trim, lower strip-ansi").unwrap(),
+                   vec![Transform::Trim, Transform::Lowercase, Transform::StripAnsi]);
+        assert!(parse_list("").unwrap().is_empty());
+        assert_eq!(parse_list("trim,upper").unwrap_err(), "upper");
+        assert_eq!(names(&parse_list("collapse trim").unwrap()), "collapse,trim");
+    }
+
+    #[test]
+    fn applies_in_order() {
+        let transforms = [Transform::Collapse, Transform::Lowercase];
+        assert_eq!(apply_all(&transforms, "  Hello \t\n World "), "hello world");
+        assert_eq!(Transform::Trim.apply(" a  b\n"), "a  b");
+    }
+
+    #[test]
+    fn strips_ansi_sequences() {
+        assert_eq!(strip_ansi("\x1b[1;31mred\x1b[0m"), "red");
+        assert_eq!(strip_ansi("\x1b]0;title\x07text"), "text");
+        assert_eq!(strip_ansi("\x1b]8;;http://x\x1b\\link"), "link");
+        assert_eq!(strip_ansi("\x1b(Bplain"), "plain");
+        // Unterminated sequences run to the end
+        assert_eq!(strip_ansi("ok\x1b[12"), "ok");
+        assert_eq!(strip_ansi("ok\x1b"), "ok");
+    }
+
+    #[test]
+    fn normalizes() {
+        assert_eq!(Normalization::from_name("NFD"), Some(Normalization::Nfd));
+        assert_eq!(Normalization::from_name("nfkc"), None);
+        assert_eq!(Normalization::Nfc.apply("e\u{301}"), "\u{e9}");
+        assert_eq!(Normalization::Nfd.apply("\u{e9}"), "e\u{301}");
+    }
+}
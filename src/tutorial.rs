@@ -0,0 +1,331 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! A guided sequence of lessons. Each lesson has sample inputs
+//! that the user's pattern must match and others it must not.
+//!
+//! Besides the lessons built in, exercise packs can be loaded from
+//! TOML or JSON files, one `exercise` per lesson:
+//!
+//! ```text
+//! title = "Log files"
+//!
+//! [[exercise]]
+//! title = "Timestamps"
+//! text = "Find every time of day in the log."
+//! sample = """
+//! 09:15 start
+//! 17:42 stop
+//! """
+//! find = ["09:15", "17:42"]
+//! match = ["00:00"]
+//! nomatch = ["9:15"]
+//! hints = ["Two digits, a colon, two digits."]
+//! solution = '\d{2}:\d{2}'
+//! ```
+//!
+//! With a `sample`, `find` lists every match the pattern must find
+//! in it, in order.
+
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+
+use regex::Regex;
+use rustyline::Editor;
+
+use console;
+use history::History;
+use json::{self, Value};
+use toml;
+
+/// A single exercise.
+pub struct Lesson {
+    pub title: String,
+    /// What the lesson teaches and what to do.
+    pub text: String,
+    /// Inputs the pattern must match.
+    pub matches: Vec<String>,
+    /// Inputs the pattern must not match.
+    pub rejects: Vec<String>,
+    /// Text shown with the lesson to search, empty if there's none.
+    pub sample: String,
+    /// Every match the pattern must find in `sample`, if given.
+    pub finds: Option<Vec<String>>,
+    /// Given out one at a time on `:hint`.
+    pub hints: Vec<String>,
+    pub solution: String,
+}
+
+impl Lesson {
+    fn new(title: &str,
+           text: &str,
+           matches: &[&str],
+           rejects: &[&str],
+           hints: &[&str],
+           solution: &str)
+           -> Lesson {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        Lesson {
+            title: title.to_owned(),
+            text: text.to_owned(),
+            matches: owned(matches),
+            rejects: owned(rejects),
+            sample: String::new(),
+            finds: None,
+            hints: owned(hints),
+            solution: solution.to_owned(),
+        }
+    }
+
+    /// Check `reg` against every sample, returning the inputs it
+    /// got wrong along with whether they should have matched.
+    pub fn check(&self, reg: &Regex) -> Vec<(&str, bool)> {
+        let wrong_matches = self.matches
+            .iter()
+            .filter(|s| !reg.is_match(s))
+            .map(|s| (&s[..], true));
+        let wrong_rejects = self.rejects
+            .iter()
+            .filter(|s| reg.is_match(s))
+            .map(|s| (&s[..], false));
+        wrong_matches.chain(wrong_rejects).collect()
+    }
+
+    /// What `reg` finds in the sample, if it isn't what it should.
+    pub fn check_finds(&self, reg: &Regex) -> Option<Vec<String>> {
+        let finds = self.finds.as_ref()?;
+        let found: Vec<String> =
+            reg.find_iter(&self.sample).map(|m| m.as_str().to_owned()).collect();
+        if found == *finds { None } else { Some(found) }
+    }
+
+    /// Read a lesson from an `exercise` of a pack.
+    fn from_value(value: &Value) -> Result<Lesson, String> {
+        let string = |key: &str, v: &Value, what: &str| {
+            v.as_str().map(str::to_owned).ok_or_else(|| format!("{} should {}", key, what))
+        };
+        let text = |key: &str| match value.get(key) {
+            None => Ok(String::new()),
+            Some(v) => string(key, v, "be a string"),
+        };
+        let list = |key: &str| -> Result<Option<Vec<String>>, String> {
+            let items = match value.get(key) {
+                None => return Ok(None),
+                Some(v) => v.as_array().ok_or_else(|| format!("{} should be an array", key))?,
+            };
+            items.iter()
+                .map(|i| string(key, i, "only hold strings"))
+                .collect::<Result<_, _>>()
+                .map(Some)
+        };
+        let lesson = Lesson {
+            title: text("title")?,
+            text: text("text")?,
+            matches: list("match")?.unwrap_or_default(),
+            rejects: list("nomatch")?.unwrap_or_default(),
+            sample: text("sample")?,
+            finds: list("find")?,
+            hints: list("hints")?.unwrap_or_default(),
+            solution: text("solution")?,
+        };
+        if lesson.matches.is_empty() && lesson.rejects.is_empty() && lesson.finds.is_none() {
+            return Err(format!("{:?} has no match, nomatch or find goals", lesson.title));
+        }
+        Ok(lesson)
+    }
+}
+
+/// An exercise pack loaded from a file.
+pub struct Pack {
+    pub title: String,
+    pub lessons: Vec<Lesson>,
+}
+
+impl Pack {
+    /// Read the pack at `path`, as JSON if it ends in `.json` and
+    /// TOML otherwise.
+    pub fn load(path: &str) -> Result<Pack, String> {
+        let mut text = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut text))
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let doc = if path.ends_with(".json") { json::parse(&text) } else { toml::parse(&text) };
+        Pack::from_value(&doc.map_err(|e| format!("{}: {}", path, e))?)
+            .map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn from_value(doc: &Value) -> Result<Pack, String> {
+        let exercises = doc.get("exercise")
+            .and_then(Value::as_array)
+            .ok_or("no [[exercise]] in the pack")?;
+        Ok(Pack {
+            title: doc.get("title").and_then(Value::as_str).unwrap_or("").to_owned(),
+            lessons: exercises.iter().map(Lesson::from_value).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// The lessons shipped with regtest.
+pub fn builtin_lessons() -> Vec<Lesson> {
+    vec![Lesson::new("Literals",
+                     "Most characters match themselves. Write a pattern matching the \
+                      word \"cat\".",
+                     &["cat", "concatenate", "a cat sat"],
+                     &["dog", "c a t", "CAT"],
+                     &["Just type the letters you want to find."],
+                     "cat"),
+         Lesson::new("Character classes",
+                     "[abc] matches any one of a, b or c. Match \"gray\" and \"grey\" \
+                      but not \"groy\".",
+                     &["gray", "grey"],
+                     &["groy", "gry", "graey"],
+                     &["Only the third letter changes.", "Put both vowels in a class: [ae]"],
+                     "gr[ae]y"),
+         Lesson::new("Ranges and shorthands",
+                     "[0-9] matches a digit; \\d is a shorthand for digits. Match a \
+                      three digit number like \"042\".",
+                     &["042", "room 101", "999"],
+                     &["42", "4 2", "abc"],
+                     &["You need exactly three digits in a row.",
+                       "Repeat \\d three times, or use \\d{3}."],
+                     "\\d{3}"),
+         Lesson::new("Quantifiers",
+                     "? means optional, * means zero or more and + means one or \
+                      more. Match \"color\" and \"colour\".",
+                     &["color", "colour", "watercolor"],
+                     &["colr", "colouur"],
+                     &["The u may or may not be there.", "Make the u optional with u?"],
+                     "colou?r"),
+         Lesson::new("Anchors",
+                     "^ matches at the start and $ at the end of the input. Match \
+                      inputs consisting only of digits.",
+                     &["12345", "7"],
+                     &["123a", "a123", "", "12 34"],
+                     &["Without anchors a digit anywhere would do.",
+                       "Anchor both ends and allow one or more digits: ^...+$"],
+                     "^\\d+$"),
+         Lesson::new("Alternation",
+                     "| matches either the expression on its left or its right. \
+                      Match \"cat\" or \"dog\", but not \"cow\".",
+                     &["cat", "dog", "hotdog"],
+                     &["cow", "bird"],
+                     &["Write both words separated by |."],
+                     "cat|dog"),
+         Lesson::new("Groups",
+                     "Parentheses group expressions so quantifiers and | apply to \
+                      the whole group. Match one or more repetitions of \"ha\" \
+                      forming the whole input.",
+                     &["ha", "haha", "hahaha"],
+                     &["hah", "aha", "h"],
+                     &["Group ha with parentheses and repeat the group.",
+                       "Don't forget to anchor both ends."],
+                     "^(ha)+$"),
+         Lesson::new("Word boundaries",
+                     "\\b matches between a word character and a non-word character. \
+                      Match \"in\" only as a whole word.",
+                     &["in", "log in now", "in."],
+                     &["inside", "login", "bin"],
+                     &["Put a \\b on each side of the word."],
+                     "\\bin\\b")]
+}
+
+/// Run through `lessons`, returning once the last one is done or
+/// the user quits.
+pub fn run(editor: &mut Editor<()>, history: &mut History, lessons: &[Lesson]) {
+    let mut stderr = io::stderr();
+    writeln!(stderr, "{}", msg!(TutorialWelcome));
+
+    for (n, lesson) in lessons.iter().enumerate() {
+        writeln!(stderr);
+        writeln!(stderr, "{}", msg!(TutorialLesson, n + 1, lessons.len(), lesson.title));
+        writeln!(stderr, "{}", lesson.text);
+        if !lesson.sample.is_empty() {
+            writeln!(stderr, "{}", msg!(TutorialSample));
+            writeln!(stderr, "{}", lesson.sample.trim_end_matches('\n'));
+        }
+        if let Some(ref finds) = lesson.finds {
+            writeln!(stderr, "{}", msg!(TutorialFindList, format!("{:?}", finds)));
+        }
+        if !lesson.matches.is_empty() {
+            writeln!(stderr, "{}", msg!(TutorialShouldMatchList, format!("{:?}", lesson.matches)));
+        }
+        if !lesson.rejects.is_empty() {
+            writeln!(stderr,
+                     "{}",
+                     msg!(TutorialShouldNotMatchList, format!("{:?}", lesson.rejects)));
+        }
+
+        let prompt = msg!(TutorialPrompt, n + 1);
+        let mut hints = lesson.hints.iter();
+        loop {
+            let line = match console::line(editor.readline(&prompt)) {
+                Ok(l) => l,
+                Err(_) => return,
+            };
+            history.add(editor, &line);
+
+            match line.trim() {
+                ":q" => return,
+                ":skip" => break,
+                ":h" | ":?" => {
+                    writeln!(stderr, "{}", msg!(TutorialHelp));
+                    continue;
+                }
+                ":hint" => {
+                    match hints.next() {
+                        Some(h) => writeln!(stderr, "{}", msg!(TutorialHint, h)),
+                        None => writeln!(stderr, "{}", msg!(TutorialNoMoreHints)),
+                    };
+                    continue;
+                }
+                ":solution" => {
+                    writeln!(stderr, "{}", msg!(TutorialSolution, lesson.solution));
+                    continue;
+                }
+                _ => {}
+            }
+
+            let reg = match Regex::new(&line) {
+                Ok(r) => r,
+                Err(e) => {
+                    writeln!(stderr, "{}", msg!(ErrorCompiling, e));
+                    continue;
+                }
+            };
+            let wrong = lesson.check(&reg);
+            let found = lesson.check_finds(&reg);
+            if wrong.is_empty() && found.is_none() {
+                writeln!(stderr, "{}", msg!(TutorialCorrect));
+                break;
+            }
+            for (input, should_match) in wrong {
+                if should_match {
+                    writeln!(stderr, "{}", msg!(TutorialShouldMatch, format!("{:?}", input)));
+                } else {
+                    writeln!(stderr, "{}", msg!(TutorialShouldNotMatch, format!("{:?}", input)));
+                }
+            }
+            if let Some(found) = found {
+                writeln!(stderr, "{}", msg!(TutorialFound, format!("{:?}", found)));
+            }
+            writeln!(stderr, "{}", msg!(TutorialNotQuite));
+        }
+    }
+    writeln!(stderr);
+    writeln!(stderr, "{}", msg!(TutorialDone));
+}
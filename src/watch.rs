@@ -0,0 +1,287 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Noticing when a file changes, by checking its size and time of
+//! last change a few times a second, to run the regex on it again
+//! or read the lines added to it.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use regex::Regex;
+
+/// How often the file is checked.
+pub const INTERVAL: Duration = Duration::from_millis(250);
+
+/// What tells one version of a file from the next.
+#[derive(Clone, Debug, PartialEq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn stamp(path: &Path) -> io::Result<Stamp> {
+    let meta = fs::metadata(path)?;
+    Ok(Stamp {
+        modified: meta.modified().ok(),
+        len: meta.len(),
+    })
+}
+
+/// The lines of `text` that `reg` matches, numbered from 1.
+pub fn matching(reg: &Regex, text: &str) -> Vec<(usize, String)> {
+    text.lines()
+        .enumerate()
+        .filter(|&(_, l)| reg.is_match(l))
+        .map(|(n, l)| (n + 1, l.to_owned()))
+        .collect()
+}
+
+/// Call `changed` with what's in the file at `path`, then again
+/// every time it changes, until `stop` is set. Errors reading it are
+/// passed on once, until it can be read again.
+pub fn watch<F>(path: &Path, stop: &AtomicBool, mut changed: F)
+    where F: FnMut(io::Result<String>)
+{
+    // The stamp last seen, or `None` inside if the file couldn't be
+    // read then
+    let mut last: Option<Option<Stamp>> = None;
+    while !stop.load(Ordering::SeqCst) {
+        let now = stamp(path);
+        let key = now.as_ref().ok().cloned();
+        if last.as_ref() != Some(&key) {
+            debug!("{} changed: {:?}", path.display(), key);
+            let mut text = String::new();
+            changed(now.and_then(|_| File::open(path)?.read_to_string(&mut text)).map(|_| text));
+            last = Some(key);
+        }
+        thread::sleep(INTERVAL);
+    }
+}
+
+/// Call `added` with the lines added to the file `follower` opened,
+/// each time there are some, until `stop` is set. Lines over the
+/// limit come as `None`.
+pub fn follow<F>(mut follower: Follower, stop: &AtomicBool, mut added: F) -> io::Result<()>
+    where F: FnMut(Vec<(usize, Option<String>)>)
+{
+    while !stop.load(Ordering::SeqCst) {
+        let lines = follower.lines()?;
+        if !lines.is_empty() {
+            added(lines);
+        }
+        if !follower.behind() {
+            thread::sleep(INTERVAL);
+        }
+    }
+    Ok(())
+}
+
+/// Bytes read from the file at a time.
+const CHUNK: usize = 64 * 1024;
+
+/// The most read for one call of `Follower::lines`, so a lot added
+/// at once is taken a piece at a time.
+const MAX_READ: u64 = 1 << 20;
+
+/// Reads the lines added to the end of a file, like `tail -f`.
+pub struct Follower {
+    file: File,
+    /// How far the file has been read, in bytes.
+    read: u64,
+    /// The number of the last whole line read.
+    line: usize,
+    /// The start of a line not ended yet.
+    partial: Vec<u8>,
+    /// The longest line kept, in bytes.
+    limit: Option<u64>,
+    /// Whether the line not ended yet is over the limit, so what's
+    /// read of it is dropped.
+    too_long: bool,
+    /// Whether the last read stopped short of the end.
+    behind: bool,
+}
+
+impl Follower {
+    /// Start following the file at `path`, past the whole lines in it
+    /// now. They're counted a chunk at a time rather than kept, so
+    /// a big file isn't read into memory. Lines longer than `limit`
+    /// are skipped.
+    pub fn open(path: &Path, limit: Option<u64>) -> io::Result<Follower> {
+        let mut file = File::open(path)?;
+        let mut buf = vec![0; CHUNK];
+        let (mut offset, mut line, mut start) = (0, 0, 0);
+        loop {
+            let n = match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            for i in buf[..n].iter().enumerate().filter(|&(_, &b)| b == b'\n').map(|(i, _)| i) {
+                line += 1;
+                start = offset + i as u64 + 1;
+            }
+            offset += n as u64;
+        }
+        Ok(Follower {
+            file,
+            read: start,
+            line,
+            partial: vec![],
+            limit,
+            too_long: false,
+            behind: false,
+        })
+    }
+
+    /// The whole lines added since the last call, numbered from 1,
+    /// with `None` for those over the limit. If the file got shorter
+    /// it's read again from the start.
+    pub fn lines(&mut self) -> io::Result<Vec<(usize, Option<String>)>> {
+        let len = self.file.metadata()?.len();
+        if len < self.read {
+            debug!("file shrank from {} to {} bytes, reading it from the start", self.read, len);
+            self.read = 0;
+            self.line = 0;
+            self.partial.clear();
+            self.too_long = false;
+        }
+        self.file.seek(SeekFrom::Start(self.read))?;
+        let mut bytes = vec![];
+        let read = Read::by_ref(&mut self.file).take(MAX_READ).read_to_end(&mut bytes)? as u64;
+        self.read += read;
+        self.behind = self.read < len;
+        self.partial.extend(bytes);
+
+        let mut lines = vec![];
+        let mut start = 0;
+        while let Some(end) = self.partial[start..].iter().position(|&b| b == b'\n') {
+            let line = &self.partial[start..start + end];
+            self.line += 1;
+            if self.too_long || self.over_limit(line.len()) {
+                lines.push((self.line, None));
+            } else {
+                let line = String::from_utf8_lossy(line);
+                lines.push((self.line, Some(line.trim_end_matches('\r').to_owned())));
+            }
+            self.too_long = false;
+            start += end + 1;
+        }
+        self.partial.drain(..start);
+        if self.over_limit(self.partial.len()) {
+            self.too_long = true;
+            self.partial.clear();
+        }
+        Ok(lines)
+    }
+
+    fn over_limit(&self, len: usize) -> bool {
+        self.limit.is_some_and(|l| len as u64 > l)
+    }
+
+    /// Is there more to read right away, so there's no need to wait
+    /// before calling `lines` again?
+    pub fn behind(&self) -> bool {
+        self.behind
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+
+    fn temp(name: &str, text: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("regtest-watch-{}-{}", process::id(), name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn append(path: &Path, text: &str) {
+        OpenOptions::new().append(true).open(path).unwrap().write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn follows_lines_added() {
+        let path = temp("follow", "one\ntwo\nhalf");
+        let mut follower = Follower::open(&path, None).unwrap();
+        // The unfinished line is read once it's ended
+        assert!(follower.lines().unwrap().is_empty());
+        append(&path, " done\r\nfour\nfi");
+        assert_eq!(follower.lines().unwrap(),
+                   vec![(3, Some("half done".to_owned())), (4, Some("four".to_owned()))]);
+        append(&path, "ve\n");
+        assert_eq!(follower.lines().unwrap(), vec![(5, Some("five".to_owned()))]);
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn counts_lines_across_chunks() {
+        let text = "x\n".repeat(CHUNK) + "last";
+        let path = temp("chunks", &text);
+        let mut follower = Follower::open(&path, None).unwrap();
+        append(&path, "\n");
+        assert_eq!(follower.lines().unwrap(), vec![(CHUNK + 1, Some("last".to_owned()))]);
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reads_a_lot_added_a_piece_at_a_time() {
+        let path = temp("behind", "");
+        let mut follower = Follower::open(&path, None).unwrap();
+        append(&path, &"0123456789abcde\n".repeat(MAX_READ as usize / 8));
+        assert_eq!(follower.lines().unwrap().len(), MAX_READ as usize / 16);
+        assert!(follower.behind());
+        assert_eq!(follower.lines().unwrap().len(), MAX_READ as usize / 16);
+        assert!(!follower.behind());
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn starts_over_when_the_file_shrinks() {
+        let path = temp("shrink", "a\nb\nc\n");
+        let mut follower = Follower::open(&path, None).unwrap();
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!(follower.lines().unwrap(), vec![(1, Some("new".to_owned()))]);
+        fs::remove_file(&path);
+    }
+
+    #[test]
+    fn skips_lines_over_the_limit() {
+        let path = temp("limit", "");
+        let mut follower = Follower::open(&path, Some(4)).unwrap();
+        append(&path, "abcd\nabcdefg");
+        assert_eq!(follower.lines().unwrap(), vec![(1, Some("abcd".to_owned()))]);
+        // What's read of a long line isn't kept while waiting for
+        // the rest
+        assert!(follower.partial.is_empty());
+        append(&path, "hij\nab");
+        assert_eq!(follower.lines().unwrap(), vec![(2, None)]);
+        append(&path, "\n");
+        assert_eq!(follower.lines().unwrap(), vec![(3, Some("ab".to_owned()))]);
+        fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,148 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Explain a failed match by finding the longest prefix of the
+//! pattern that still matches the input.
+
+use regex_syntax::ast::{Ast, Span};
+
+use flags::Flags;
+
+/// Where matching stopped making progress.
+#[derive(Debug)]
+pub struct Divergence {
+    /// The longest leading part of the pattern that matches.
+    pub prefix: String,
+    /// Where `prefix` matched in the input, if it's not empty.
+    pub matched: Option<(usize, usize)>,
+    /// The piece of the pattern that could not be matched next.
+    pub next: String,
+    /// Byte offset of `next` in the pattern.
+    pub offset: usize,
+}
+
+/// Returns `Ok(None)` when `pattern`, compiled with `flags`, matches
+/// `input`.
+pub fn explain(pattern: &str, flags: Flags, input: &str) -> Result<Option<Divergence>, String> {
+    if flags.build(pattern).map_err(|e| e.to_string())?.is_match(input) {
+        return Ok(None);
+    }
+    let ast = flags.ast_parser().parse(pattern).map_err(|e| e.to_string())?;
+
+    // Try each branch of a top level alternation and keep the one
+    // that gets furthest.
+    let branches: Vec<&Ast> = match *unwrap_group(&ast) {
+        Ast::Alternation(ref alt) => alt.asts.iter().collect(),
+        ref other => vec![other],
+    };
+    let mut best: Option<(usize, Divergence)> = None;
+    for branch in branches {
+        let (progress, d) = explain_branch(pattern, flags, branch, input);
+        if best.as_ref().is_none_or(|b| progress > b.0) {
+            best = Some((progress, d));
+        }
+    }
+    Ok(best.map(|b| b.1))
+}
+
+/// Look through a group wrapping the whole expression.
+fn unwrap_group(ast: &Ast) -> &Ast {
+    match *ast {
+        Ast::Group(ref g) => unwrap_group(&g.ast),
+        ref other => other,
+    }
+}
+
+/// Returns the number of pieces matched along with the divergence.
+fn explain_branch(pattern: &str, flags: Flags, ast: &Ast, input: &str) -> (usize, Divergence) {
+    let ast = unwrap_group(ast);
+    let pieces: Vec<&Span> = match *ast {
+        Ast::Concat(ref c) => c.asts.iter().map(|a| a.span()).collect(),
+        ref other => vec![other.span()],
+    };
+
+    // Drop pieces from the end until what's left matches.
+    for k in (1..pieces.len()).rev() {
+        let prefix = &pattern[pieces[0].start.offset..pieces[k - 1].end.offset];
+        let m = match flags.build(prefix) {
+            Ok(r) => r.find(input),
+            // A prefix that splits inline flags may not compile
+            Err(_) => continue,
+        };
+        if let Some(m) = m {
+            return (k,
+                    Divergence {
+                        prefix: prefix.to_owned(),
+                        matched: Some((m.start(), m.end())),
+                        next: text(pattern, pieces[k]).to_owned(),
+                        offset: pieces[k].start.offset,
+                    });
+        }
+    }
+    (0,
+     Divergence {
+         prefix: String::new(),
+         matched: None,
+         next: text(pattern, pieces[0]).to_owned(),
+         offset: pieces[0].start.offset,
+     })
+}
+
+fn text<'a>(pattern: &'a str, span: &Span) -> &'a str {
+    &pattern[span.start.offset..span.end.offset]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_to_explain_when_it_matches() {
+        assert!(explain("a+b", Flags::default(), "xaab").unwrap().is_none());
+        assert!(explain("(", Flags::default(), "x").is_err());
+    }
+
+    #[test]
+    fn finds_where_matching_stops() {
+        let d = explain(r"\d+-\d+", Flags::default(), "12-ab").unwrap().unwrap();
+        assert_eq!(d.prefix, r"\d+-");
+        assert_eq!(d.matched, Some((0, 3)));
+        assert_eq!((&d.next[..], d.offset), (r"\d+", 4));
+
+        let d = explain("xyz", Flags::default(), "abc").unwrap().unwrap();
+        assert_eq!((&d.prefix[..], d.matched), ("", None));
+        assert_eq!((&d.next[..], d.offset), ("x", 0));
+    }
+
+    #[test]
+    fn keeps_the_branch_that_gets_furthest() {
+        let d = explain("ab|cde", Flags::default(), "cdx").unwrap().unwrap();
+        assert_eq!((&d.prefix[..], &d.next[..]), ("cd", "e"));
+    }
+
+    #[test]
+    fn uses_the_flags() {
+        let insensitive = Flags::default() | Flags::CASE_INSENSITIVE;
+        assert!(explain("abc", insensitive, "ABC").unwrap().is_none());
+        let d = explain("abd", insensitive, "ABC").unwrap().unwrap();
+        assert_eq!((&d.prefix[..], d.matched), ("ab", Some((0, 2))));
+
+        let verbose = Flags::default() | Flags::IGNORE_WHITESPACE;
+        let d = explain("a b c", verbose, "abx").unwrap().unwrap();
+        assert_eq!((&d.next[..], d.offset), ("c", 4));
+    }
+}
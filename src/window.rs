@@ -0,0 +1,139 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Showing only the part of a long line around each match, like
+//! `…before [MATCH] after…`.
+
+/// Marks text left out at either end of a window.
+const ELLIPSIS: &str = "…";
+
+/// Part of a line around one or more matches.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Window {
+    /// The column of the first match in the window, counting
+    /// characters from 1.
+    pub column: usize,
+    /// The window's text, with each match in brackets.
+    pub text: String,
+}
+
+/// A window as a range of characters, and the byte ranges of the
+/// matches in it.
+struct Range {
+    start: usize,
+    end: usize,
+    spans: Vec<(usize, usize)>,
+}
+
+/// Windows of `context` characters either side of each of `spans`
+/// in `line`, given as byte ranges in order. With `merge`, windows
+/// that overlap or touch are joined into one.
+pub fn windows(line: &str, spans: &[(usize, usize)], context: usize, merge: bool) -> Vec<Window> {
+    // Byte offset of every character, and of the end
+    let mut bounds: Vec<usize> = line.char_indices().map(|(i, _)| i).collect();
+    bounds.push(line.len());
+    let char_at = |byte: usize| bounds.binary_search(&byte).unwrap_or_else(|i| i);
+
+    let mut ranges: Vec<Range> = vec![];
+    for &(s, e) in spans {
+        let (cs, ce) = (char_at(s), char_at(e));
+        let start = cs.saturating_sub(context);
+        let end = (ce + context).min(bounds.len() - 1);
+        match ranges.last_mut() {
+            Some(last) if merge && start <= last.end => {
+                last.end = last.end.max(end);
+                last.spans.push((s, e));
+            }
+            _ => {
+                ranges.push(Range {
+                    start,
+                    end,
+                    spans: vec![(s, e)],
+                })
+            }
+        }
+    }
+
+    ranges.into_iter()
+        .map(|Range { start, end, spans }| {
+            let mut text = String::new();
+            if start > 0 {
+                text.push_str(ELLIPSIS);
+            }
+            let mut at = bounds[start];
+            for &(s, e) in &spans {
+                text.push_str(&line[at..s]);
+                text.push('[');
+                text.push_str(&line[s..e]);
+                text.push(']');
+                at = e;
+            }
+            text.push_str(&line[at..bounds[end]]);
+            if end < bounds.len() - 1 {
+                text.push_str(ELLIPSIS);
+            }
+            Window {
+                column: char_at(spans[0].0) + 1,
+                text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(windows: Vec<Window>) -> Vec<String> {
+        windows.into_iter().map(|w| w.text).collect()
+    }
+
+    #[test]
+    fn shows_context_around_each_match() {
+        let line = "the quick brown fox jumps";
+        let found = windows(line, &[(10, 15)], 3, false);
+        assert_eq!(found,
+                   [Window {
+                        column: 11,
+                        text: "…ck [brown] fo…".to_owned(),
+                    }]);
+        // No ellipsis at the ends of the line
+        assert_eq!(texts(windows(line, &[(0, 3), (20, 25)], 2, false)),
+                   ["[the] q…", "…x [jumps]"]);
+    }
+
+    #[test]
+    fn merges_windows_that_overlap() {
+        let line = "a1 b2 c3";
+        let spans = [(1, 2), (4, 5)];
+        assert_eq!(texts(windows(line, &spans, 1, false)), ["a[1] …", "…b[2] …"]);
+        assert_eq!(windows(line, &spans, 1, true),
+                   [Window {
+                        column: 2,
+                        text: "a[1] b[2] …".to_owned(),
+                    }]);
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        let line = "ééé x ééé";
+        let found = windows(line, &[(7, 8)], 2, false);
+        assert_eq!(found[0].column, 5);
+        assert_eq!(found[0].text, "…é [x] é…");
+        assert_eq!(texts(windows("ab", &[(2, 2)], 1, false)), ["…b[]"]);
+    }
+}
@@ -0,0 +1,134 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Turning a list of words into one alternation, with the prefixes
+//! words share written once, as in `foo(?:bar|d)?`.
+
+use std::collections::BTreeMap;
+
+use regex;
+
+/// A trie of the words, one node per character.
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<char, Node>,
+    /// Whether a word ends here.
+    end: bool,
+}
+
+impl Node {
+    fn insert(&mut self, word: &str) {
+        let mut node = self;
+        for c in word.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        node.end = true;
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// The pattern matching what follows this node in each word.
+    fn pattern(&self) -> String {
+        // Characters that end words with nothing after them are
+        // gathered into one class
+        let leaves: Vec<char> = self.children
+            .iter()
+            .filter(|&(_, n)| n.is_leaf())
+            .map(|(&c, _)| c)
+            .collect();
+        let mut alts: Vec<String> = self.children
+            .iter()
+            .filter(|&(c, n)| !n.is_leaf() || leaves.len() == 1 && leaves[0] == *c)
+            .map(|(&c, n)| format!("{}{}", escape(c), n.pattern()))
+            .collect();
+        if leaves.len() > 1 {
+            let class: String = leaves.iter().map(|&c| escape(c)).collect();
+            alts.push(format!("[{}]", class));
+        }
+
+        let single = alts.len() == 1;
+        let body = if single {
+            alts.pop().unwrap()
+        } else {
+            format!("(?:{})", alts.join("|"))
+        };
+        match (self.end, self.is_leaf()) {
+            (_, true) => String::new(),
+            (false, false) => body,
+            (true, false) if single && atom(&body) => format!("{}?", body),
+            (true, false) if single => format!("(?:{})?", body),
+            (true, false) => format!("{}?", body),
+        }
+    }
+}
+
+fn escape(c: char) -> String {
+    regex::escape(&c.to_string())
+}
+
+/// Does `pattern` stand for one character, so a quantifier applies
+/// to all of it?
+fn atom(pattern: &str) -> bool {
+    pattern.chars().count() == 1 || pattern.starts_with('\\') && pattern.chars().count() == 2 ||
+    pattern.starts_with('[') && pattern.ends_with(']') && !pattern[1..].contains('[')
+}
+
+/// An alternation matching each of `words`, longer words tried
+/// before their prefixes. Empty words are left out.
+pub fn alternation(words: &[String]) -> String {
+    let mut root = Node::default();
+    for w in words.iter().filter(|w| !w.is_empty()) {
+        root.insert(w);
+    }
+    root.pattern()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn shares_prefixes() {
+        assert_eq!(alternation(&words(&["food", "foobar", "foo"])), "foo(?:bar|d)?");
+        assert_eq!(alternation(&words(&["cat", "car", "cab"])), "ca[brt]");
+        assert_eq!(alternation(&words(&["a", "ab", ""])), "ab?");
+        assert_eq!(alternation(&words(&["ab", "abcd"])), "ab(?:cd)?");
+        assert_eq!(alternation(&[]), "");
+    }
+
+    #[test]
+    fn matches_exactly_the_words() {
+        let list = words(&["a.b", "a", "a[", "ab", "(x)", "été", "étés", "-", "--"]);
+        let reg = Regex::new(&format!("^(?:{})$", alternation(&list))).unwrap();
+        for w in &list {
+            assert!(reg.is_match(w), "{}", w);
+        }
+        for w in &["", "axb", "a.", "x", "ét", "---", "a[b"] {
+            assert!(!reg.is_match(w), "{}", w);
+        }
+        // Longer words are tried first
+        let reg = Regex::new(&alternation(&words(&["in", "int", "integer"]))).unwrap();
+        assert_eq!(reg.find("integers").unwrap().as_str(), "integer");
+    }
+}
@@ -0,0 +1,350 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! Named workspaces, each a directory keeping the patterns,
+//! expectations and options of a session, along with any suites
+//! saved there:
+//!
+//! ```text
+//! options        name = value, as in the config file
+//! patterns       flags<TAB>pattern, one per line, oldest first
+//! current        the pattern being tested
+//! expectations   match <input> or nomatch <input>, one per line
+//! *.suite        suites saved with :expect save
+//! ```
+//!
+//! A workspace can also be bundled into one JSON file to share:
+//!
+//! ```text
+//! {"regtest-workspace": 1, "name": "logs",
+//!  "options": {"capture-groups": "on"},
+//!  "patterns": [{"pattern": "ab+", "flags": "i"}], "current": "ab+",
+//!  "expectations": [{"input": "abb", "match": true}],
+//!  "suites": {"dates.suite": "pattern ...\n"}}
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flags::Flags;
+use json::{self, Value};
+use output::quote;
+use settings;
+use suite::{self, Expectation};
+
+/// What a workspace keeps.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contents {
+    pub options: Vec<(String, String)>,
+    pub patterns: Vec<(String, Flags)>,
+    pub current: Option<String>,
+    pub expectations: Vec<Expectation>,
+}
+
+/// Can `name` be used as a directory name on its own?
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') &&
+    name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// Write `contents` to the workspace in `dir`, creating it if need
+/// be.
+pub fn save(dir: &Path, contents: &Contents) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut options = String::new();
+    for (name, value) in &contents.options {
+        options.push_str(&format!("{} = {}\n", name, value));
+    }
+    let mut patterns = String::new();
+    for &(ref p, flags) in &contents.patterns {
+        patterns.push_str(&format!("{}\t{}\n", flags.name(), p));
+    }
+    let mut expectations = String::new();
+    for e in &contents.expectations {
+        expectations.push_str(&format!("{} {}\n", e.directive(), e.input));
+    }
+    let current = contents.current.as_ref().map(|c| format!("{}\n", c)).unwrap_or_default();
+
+    write(&dir.join("options"), &options)?;
+    write(&dir.join("patterns"), &patterns)?;
+    write(&dir.join("expectations"), &expectations)?;
+    write(&dir.join("current"), &current)
+}
+
+fn write(path: &Path, text: &str) -> io::Result<()> {
+    File::create(path)?.write_all(text.as_bytes())
+}
+
+/// Read the workspace in `dir`. Missing files are taken as empty.
+pub fn load(dir: &Path) -> Result<Contents, String> {
+    let options = settings::load(&dir.join("options"))?
+        .into_iter()
+        .map(|e| (e.name, e.value))
+        .collect();
+    let mut patterns = vec![];
+    for line in read(&dir.join("patterns"))?.lines() {
+        let (flags, pattern) = match line.find('\t') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => continue,
+        };
+        if let Ok(flags) = Flags::from_name(flags) {
+            patterns.push((pattern.to_owned(), flags));
+        }
+    }
+    let expectations = read(&dir.join("expectations"))?
+        .lines()
+        .filter_map(Expectation::parse)
+        .collect();
+    let current = read(&dir.join("current"))?.lines().next().map(|l| l.to_owned());
+    Ok(Contents {
+        options,
+        patterns,
+        current,
+        expectations,
+    })
+}
+
+/// The text of `path`, or nothing if it doesn't exist.
+fn read(path: &Path) -> Result<String, String> {
+    let mut text = String::new();
+    match File::open(path) {
+        Ok(mut f) => {
+            f.read_to_string(&mut text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    }
+    Ok(text)
+}
+
+/// The suite files saved in the workspace in `dir`.
+pub fn suites(dir: &Path) -> Vec<PathBuf> {
+    suite::find(dir).unwrap_or_default()
+}
+
+/// The version of the bundle format written.
+const BUNDLE_VERSION: f64 = 1.0;
+
+/// A workspace as shared in one file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bundle {
+    pub name: String,
+    pub contents: Contents,
+    /// The file name and text of each suite.
+    pub suites: Vec<(String, String)>,
+}
+
+/// `bundle` as a JSON document.
+pub fn to_json(bundle: &Bundle) -> String {
+    let contents = &bundle.contents;
+    let options: Vec<String> = contents.options
+        .iter()
+        .map(|(n, v)| format!("    {}: {}", quote(n), quote(v)))
+        .collect();
+    let patterns: Vec<String> = contents.patterns
+        .iter()
+        .map(|(p, f)| format!("    {{\"pattern\": {}, \"flags\": {}}}", quote(p), quote(&f.name())))
+        .collect();
+    let expectations: Vec<String> = contents.expectations
+        .iter()
+        .map(|e| format!("    {{\"input\": {}, \"match\": {}}}", quote(&e.input), e.should_match))
+        .collect();
+    let suites: Vec<String> = bundle.suites
+        .iter()
+        .map(|(n, text)| format!("    {}: {}", quote(n), quote(text)))
+        .collect();
+    let current = contents.current.as_ref().map_or("null".to_owned(), |c| quote(c));
+    format!("{{\n  \"regtest-workspace\": {},\n  \"name\": {},\n  \"options\": {},\n  \
+             \"patterns\": {},\n  \"current\": {},\n  \"expectations\": {},\n  \
+             \"suites\": {}\n}}\n",
+            BUNDLE_VERSION,
+            quote(&bundle.name),
+            block('{', &options, '}'),
+            block('[', &patterns, ']'),
+            current,
+            block('[', &expectations, ']'),
+            block('{', &suites, '}'))
+}
+
+/// `items` between `open` and `close`, one per line.
+fn block(open: char, items: &[String], close: char) -> String {
+    if items.is_empty() {
+        format!("{}{}", open, close)
+    } else {
+        format!("{}\n{}\n  {}", open, items.join(",\n"), close)
+    }
+}
+
+/// Read a bundle written by `to_json`.
+pub fn from_json(text: &str) -> Result<Bundle, String> {
+    let doc = json::parse(text)?;
+    let bad = |what: &str| format!("not a regtest workspace: bad {}", what);
+    match doc.get("regtest-workspace") {
+        Some(&Value::Number(v)) if v <= BUNDLE_VERSION => {}
+        _ => return Err(bad("regtest-workspace version")),
+    }
+    let name = doc.get("name").and_then(Value::as_str).ok_or_else(|| bad("name"))?.to_owned();
+
+    let strings = |key: &str| -> Result<Vec<(String, String)>, String> {
+        let members = match doc.get(key) {
+            Some(v) => v.as_object().ok_or_else(|| bad(key))?,
+            None => return Ok(vec![]),
+        };
+        members.iter()
+            .map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_owned())).ok_or_else(|| bad(key)))
+            .collect()
+    };
+    let items = |key: &str| -> Result<&[Value], String> {
+        match doc.get(key) {
+            Some(v) => v.as_array().ok_or_else(|| bad(key)),
+            None => Ok(&[]),
+        }
+    };
+
+    let mut patterns = vec![];
+    for p in items("patterns")? {
+        let pattern = p.get("pattern").and_then(Value::as_str).ok_or_else(|| bad("patterns"))?;
+        let flags = p.get("flags").and_then(Value::as_str).unwrap_or("none");
+        let flags = Flags::from_name(flags).map_err(|_| bad("flags"))?;
+        patterns.push((pattern.to_owned(), flags));
+    }
+    let mut expectations = vec![];
+    for e in items("expectations")? {
+        expectations.push(Expectation {
+            input: e.get("input").and_then(Value::as_str).ok_or_else(|| bad("expectations"))?.to_owned(),
+            should_match: e.get("match").and_then(Value::as_bool).ok_or_else(|| bad("expectations"))?,
+        });
+    }
+    let suites = strings("suites")?;
+    if suites.iter().any(|(n, _)| !valid_name(n)) {
+        return Err(bad("suite name"));
+    }
+    let contents = Contents {
+        options: strings("options")?,
+        patterns,
+        current: doc.get("current").and_then(Value::as_str).map(|c| c.to_owned()),
+        expectations,
+    };
+    Ok(Bundle {
+        name,
+        contents,
+        suites,
+    })
+}
+
+/// Write each of `suites` into `dir`.
+pub fn write_suites(dir: &Path, suites: &[(String, String)]) -> io::Result<()> {
+    for (name, text) in suites {
+        write(&dir.join(name), text)?;
+    }
+    Ok(())
+}
+
+/// The file name and text of each suite in `dir`.
+pub fn read_suites(dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let mut out = vec![];
+    for path in suites(dir) {
+        // Suites in subdirectories are left out of bundles
+        if path.parent() != Some(dir) {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        out.push((name, read(&path)?));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn contents() -> Contents {
+        Contents {
+            options: vec![("capture-groups".to_owned(), "on".to_owned())],
+            patterns: vec![("ab+".to_owned(), Flags::default() | Flags::CASE_INSENSITIVE),
+                           ("a\tb".to_owned(), Flags::default())],
+            current: Some("ab+".to_owned()),
+            expectations: vec![Expectation {
+                                   input: "abb".to_owned(),
+                                   should_match: true,
+                               },
+                               Expectation {
+                                   input: "x y".to_owned(),
+                                   should_match: false,
+                               }],
+        }
+    }
+
+    #[test]
+    fn checks_names() {
+        assert!(valid_name("logs-2024_v1.2"));
+        assert!(!valid_name(""));
+        assert!(!valid_name(".hidden"));
+        assert!(!valid_name("a/b"));
+        assert!(!valid_name(".."));
+    }
+
+    #[test]
+    fn saves_and_loads_a_workspace() {
+        let dir = env::temp_dir().join(format!("regtest-workspace-{}", process::id()));
+        assert_eq!(load(&dir).unwrap(), Contents::default());
+
+        save(&dir, &contents()).unwrap();
+        assert_eq!(load(&dir).unwrap(), contents());
+        assert_eq!(fs::read_to_string(dir.join("patterns")).unwrap(), "i\tab+\nnone\ta\tb\n");
+
+        let suites = vec![("dates.suite".to_owned(), "pattern \\d\nmatch 1\n".to_owned())];
+        write_suites(&dir, &suites).unwrap();
+        assert_eq!(read_suites(&dir).unwrap(), suites);
+        fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn bundles_round_trip() {
+        let bundle = Bundle {
+            name: "logs".to_owned(),
+            contents: contents(),
+            suites: vec![("a.suite".to_owned(), "pattern \"x\"\n".to_owned())],
+        };
+        let json = to_json(&bundle);
+        assert!(json.starts_with("{\n  \"regtest-workspace\": 1,\n  \"name\": \"logs\",\n"));
+        assert_eq!(from_json(&json).unwrap(), bundle);
+
+        let empty = Bundle {
+            name: "empty".to_owned(),
+            contents: Contents::default(),
+            suites: vec![],
+        };
+        assert_eq!(from_json(&to_json(&empty)).unwrap(), empty);
+    }
+
+    #[test]
+    fn bad_bundles_are_errors() {
+        assert!(from_json("[]").is_err());
+        assert!(from_json(r#"{"regtest-workspace": 2, "name": "x"}"#).is_err());
+        assert!(from_json(r#"{"regtest-workspace": 1}"#).is_err());
+        assert!(from_json(r#"{"regtest-workspace": 1, "name": "x", "suites": {"../a": ""}}"#)
+            .is_err());
+        assert!(from_json(r#"{"regtest-workspace": 1, "name": "x",
+                              "patterns": [{"pattern": "a", "flags": "q"}]}"#)
+            .is_err());
+        assert!(from_json(r#"{"regtest-workspace": 1, "name": "x"}"#).is_ok());
+    }
+}
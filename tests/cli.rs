@@ -0,0 +1,167 @@
+// regtest - Interactive frontend for the regex crate
+// Copyright (C) 2016  Lucas Salibian
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See
+// the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+//! The exit status and output of the batch subcommands, run as a user
+//! would run them.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{self, Command, Output, Stdio};
+
+/// Run regtest with `args` and `stdin`, in English.
+fn regtest(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_regtest"))
+        .args(args)
+        .env("LANG", "C")
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands that fail early exit without reading their input
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).ok();
+    child.wait_with_output().unwrap()
+}
+
+/// Write `bytes` to a file called `name` in the temporary directory.
+fn temp(name: &str, bytes: &[u8]) -> String {
+    let path = env::temp_dir().join(format!("regtest-cli-{}-{}", process::id(), name));
+    fs::write(&path, bytes).unwrap();
+    path.display().to_string()
+}
+
+#[cfg(feature = "archives")]
+#[test]
+fn grep_in_a_corrupt_archive_is_an_error() {
+    let path = temp("junk.zip", b"this is not a zip archive at all");
+    let out = regtest(&["grep", "a", &path], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Can't open"));
+}
+
+#[test]
+fn grep_in_a_missing_file_is_an_error() {
+    let out = regtest(&["grep", "a", "/nonexistent/regtest.txt"], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn a_column_past_the_groups_is_an_error() {
+    let out = regtest(&["grep", "(a)(b)", "--output", "csv", "--columns", "match,group.3"], "ab\n");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+}
+
+#[test]
+fn batch_scripts_run_as_typed() {
+    let script = temp("script.txt", b"@echo off\n^(\\d+)$\n@echo Should match:\n123\nabc\n:q\n");
+    let out = regtest(&["--batch", &script], "");
+    assert_eq!(out.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let said = stderr.find("Should match:").unwrap();
+    let matched = stderr.find("Matched").unwrap();
+    assert!(said < matched);
+    assert!(stderr.contains("Failed to match"));
+    assert!(!stderr.contains("^(\\d+)$"));
+}
+
+#[test]
+fn batch_script_errors_name_the_line() {
+    let script = temp("bad-script.txt", b"abc\n\n@bad\n");
+    let out = regtest(&["--batch", &script], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains(":3: unknown directive @bad"));
+}
+
+#[test]
+fn grep_exits_by_whether_lines_matched() {
+    let path = temp("lines.txt", b"abc\nxyz\n");
+    let out = regtest(&["grep", "b", &path], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(out.stdout, b"abc\n");
+    assert_eq!(regtest(&["grep", "q", &path], "").status.code(), Some(1));
+    assert_eq!(regtest(&["grep", "(", &path], "").status.code(), Some(2));
+    // Standard input is read without files
+    let out = regtest(&["grep", "y"], "abc\nxyz\n");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(out.stdout, b"xyz\n");
+}
+
+#[test]
+fn match_exits_by_whether_the_input_matched() {
+    assert_eq!(regtest(&["match", "b", "abc"], "").status.code(), Some(0));
+    assert_eq!(regtest(&["match", "q", "abc"], "").status.code(), Some(1));
+    assert_eq!(regtest(&["match", "(", "abc"], "").status.code(), Some(2));
+}
+
+#[test]
+fn replace_rewrites_each_line() {
+    let path = temp("replace.txt", b"a1b22\nnone\n");
+    let out = regtest(&["replace", r"\d+", "#", &path], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(out.stdout, b"a#b#\nnone\n");
+    assert_eq!(regtest(&["replace", "(", "#", &path], "").status.code(), Some(2));
+}
+
+#[test]
+fn preview_shows_long_lines_whole_instead_of_diffing_them() {
+    let long = "x ".repeat(5000);
+    let path = temp("preview.txt", format!("a1b\n{}1\n", long).as_bytes());
+    let out = regtest(&["replace", "--preview", r"\d", "#", &path], "");
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("Replaced: [-a1b-]{+a#b+}\n"), "{}", stderr);
+    assert!(stderr.contains(&format!("Original: {}1\nReplaced: {}#\n", long, long)));
+}
+
+#[test]
+fn test_exits_by_whether_every_case_passed() {
+    let passing = temp("ok.suite", b"pattern ^\\d+$\nmatch 123\nnomatch 12a\n");
+    let out = regtest(&["test", &passing], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("2 of 2 cases passed"));
+
+    let failing = temp("bad.suite", b"pattern ^\\d+$\nmatch 12a\n");
+    let out = regtest(&["test", &failing], "");
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains(":2:"));
+
+    assert_eq!(regtest(&["test", "/nonexistent/regtest.suite"], "").status.code(), Some(2));
+}
+
+#[test]
+fn resuming_drops_output_written_after_the_checkpoint() {
+    let log = temp("resume.log", b"ERROR 1\nok\nERROR 2\n");
+    let first = format!("{}:ERROR 1\n", log);
+    // Killed after writing the second match but before saving again
+    let out = temp("resume.out", format!("{}{}:ERROR 2\n", first, log).as_bytes());
+    let checkpoint = format!("{{\"pattern\": \"ERROR\", \"done\": [], \"file\": \"{}\", \
+                              \"offset\": 8, \"line\": 1, \"lines\": 1, \"files\": 1, \
+                              \"output\": {}}}",
+                             log,
+                             first.len());
+    let checkpoint = temp("resume.json", checkpoint.as_bytes());
+    let run = regtest(&["grep", "ERROR", &log, "--recursive", "--checkpoint", &checkpoint,
+                        "--resume", "--output-file", &out],
+                      "");
+    assert_eq!(run.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&out).unwrap(),
+               format!("{}{}:ERROR 2\n# regtest: complete, 2 matching lines in 1 files\n",
+                       first,
+                       log));
+}